@@ -0,0 +1,69 @@
+// Benchmarks of finding the sailings to show for a date, over a synthetic dataset much larger than the published one.
+// The front-end is only a binary, so the processor module is included directly, along with the modules it uses.
+#![allow(dead_code, unused_imports)]
+
+#[path = "../src/note_renderer.rs"]
+mod note_renderer;
+#[path = "../src/prelude.rs"]
+mod prelude;
+#[path = "../src/sailings_processor.rs"]
+mod sailings_processor;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ferrysched_shared::prelude::*;
+
+use crate::sailings_processor::*;
+
+// Schedules per terminal pair, each covering the quarter after the previous one
+const SCHEDULES_PER_TERMINAL_PAIR: i64 = 8;
+const ITEMS_PER_SCHEDULE: i64 = 60;
+const FIRST_DATE: Date = date!(2024 - 01 - 01);
+
+fn synthetic_schedule(terminal_pair: TerminalPair, index: i64) -> Schedule {
+    let from = FIRST_DATE + Duration::days(91 * index);
+    let date_range = DateRange { from, to: from + Duration::days(90) };
+    // Every item has exceptions and a note on some dates, like the busiest published schedules
+    let some_dates =
+        |offset: i64| -> HashSet<Date> { date_range.iter_days().skip(offset as usize).step_by(9).collect() };
+    let items = (0..ITEMS_PER_SCHEDULE)
+        .map(|i| {
+            let depart_time = time!(05:00) + Duration::minutes(15 * i);
+            ScheduleItem {
+                sailing: Sailing { depart_time, arrive_time: depart_time + Duration::minutes(95), stops: vec![] },
+                weekdays: iter::successors(Some(Weekday::Monday), |weekday| Some(weekday.next()))
+                    .take(7)
+                    .map(|weekday| (weekday, DateRestriction::Except(some_dates(i % 9))))
+                    .collect(),
+                notes: HashMap::from([(Cow::from("Foot passengers only"), DateRestriction::Only(some_dates(i % 7)))]),
+                note_categories: HashMap::new(),
+                vehicle_restrictions: HashMap::new(),
+                conditional_stops: vec![],
+            }
+        })
+        .collect();
+    Schedule { date_range, items, ..Schedule::test_fixture(terminal_pair) }
+}
+
+fn synthetic_schedules_map() -> HashMap<TerminalPair, Vec<Schedule>> {
+    ALL_TERMINAL_PAIRS
+        .iter()
+        .map(|&tp| (tp, (0..SCHEDULES_PER_TERMINAL_PAIR).map(|i| synthetic_schedule(tp, i)).collect()))
+        .collect()
+}
+
+// Finding a day of one route's sailings should stay well within a frame, and takes about 0.02 ms natively.  Over 0.5 ms
+// (several times that in a phone's browser) is a regression worth looking into.  A whole day across every route, as
+// printed by the day pack, takes about 2 ms and should stay under 20 ms.
+fn bench_sailings_for_date(c: &mut Criterion) {
+    let schedules_map = synthetic_schedules_map();
+    let area_pair = AreaPair { from: Area::Victoria, to: Area::Galiano };
+    let date = FIRST_DATE + Duration::days(200);
+    let filter = SailingFilter::default().include_supplemental(true);
+    c.bench_function("area_sailings_for_date", |b| {
+        b.iter(|| area_sailings_for_date(area_pair, date, &schedules_map, &filter))
+    });
+    c.bench_function("all_sailings_for_date", |b| b.iter(|| all_sailings_for_date(date, &schedules_map, &filter)));
+}
+
+criterion_group!(benches, bench_sailings_for_date);
+criterion_main!(benches);
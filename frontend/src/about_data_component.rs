@@ -0,0 +1,63 @@
+use crate::date_format::*;
+use crate::prelude::*;
+use crate::types::*;
+use crate::utils::*;
+
+fn metadata_html(metadata: &DataMetadata) -> Html {
+    let scraped_at = metadata.scraped_at.to_timezone(timezones::db::america::VANCOUVER);
+    html! {
+        <dl class="row">
+            <dt class="col-sm-3">{ "Source" }</dt>
+            <dd class="col-sm-9">
+                <a class="link-dark" href={ metadata.source_url.clone() } target="_blank">{ &metadata.source_name }</a>
+            </dd>
+            <dt class="col-sm-3">{ "Attribution" }</dt>
+            <dd class="col-sm-9">{ &metadata.attribution }</dd>
+            <dt class="col-sm-3">{ "Disclaimer" }</dt>
+            <dd class="col-sm-9">{ &metadata.disclaimer }</dd>
+            <dt class="col-sm-3">{ "Collected" }</dt>
+            <dd class="col-sm-9">
+                <time datetime={ format_iso8601_date(scraped_at.date()) }>
+                    { format!("{} at {}", DATE_FORMATTER.long_date(scraped_at.date()), format_time(scraped_at.time())) }
+                </time>
+                { format!(" ({})", human_time(metadata.scraped_at)) }
+            </dd>
+            <dt class="col-sm-3">{ "Generator version" }</dt>
+            <dd class="col-sm-9">{ &metadata.generator_version }</dd>
+        </dl>
+    }
+}
+
+// Where the schedule data comes from, taken from the metadata the scraper embeds in the published data
+#[function_component(AboutData)]
+pub fn about_data_component() -> Html {
+    let schedules_state = use_context::<SchedulesState>().expect("Expect schedules state to be available");
+    let DataMetadataContext(metadata) =
+        use_context::<DataMetadataContext>().expect("Expect data metadata to be available");
+    html! { <>
+        <h1 class="display-6 mb-3 small">{ "About this data" }</h1>
+        { match (&schedules_state, metadata) {
+            (SchedulesState::Init | SchedulesState::Loading, _) => html! {
+                <div class="alert alert-light border text-center">
+                    <div class="spinner-border" role="status"/>
+                    <div>{ "Loading schedules..." }</div>
+                </div>
+            },
+            (SchedulesState::Failed, _) => html! {
+                <div class="alert alert-danger text-center" role="alert">
+                    { "There was a problem loading the ferry schedules; please refresh your browser to try again." }
+                </div>
+            },
+            (SchedulesState::Loaded(_), Some(metadata)) => metadata_html(&metadata),
+            (SchedulesState::Loaded(_), None) => html! {
+                <p>
+                    { "The schedules were published without information about where they came from. They are scraped from the " }
+                    <a class="link-dark" href={ ALL_SCHEDULES_URL } target="_blank">{ "official schedules" }</a>
+                    { " published by " }
+                    { DATA_SOURCE_NAME }
+                    { "." }
+                </p>
+            },
+        }}
+    </> }
+}
@@ -0,0 +1,64 @@
+use crate::prelude::*;
+
+// Decides when a changing value (such as the minutes until a sailing) is worth announcing to screen readers, so live
+// regions are only updated as thresholds are crossed rather than on every tick
+#[derive(Debug)]
+pub struct AnnouncementScheduler {
+    // In descending order
+    thresholds: &'static [i64],
+    last_value: Option<i64>,
+    message: String,
+}
+
+impl AnnouncementScheduler {
+    pub fn new(thresholds: &'static [i64]) -> AnnouncementScheduler {
+        AnnouncementScheduler { thresholds, last_value: None, message: String::new() }
+    }
+
+    // Returns the threshold crossed since the last update, if any.  An increasing value (such as counting down to the
+    // next sailing after one departs) starts over without announcing.
+    pub fn update(&mut self, value: i64) -> Option<i64> {
+        let crossed = match self.last_value {
+            Some(last_value) if value <= last_value => {
+                self.thresholds.iter().rev().copied().find(|&t| value <= t && t < last_value)
+            }
+            _ => None,
+        };
+        self.last_value = Some(value);
+        crossed
+    }
+
+    // Updates with the value, changing the message to announce only when a threshold is crossed
+    pub fn update_message<F: FnOnce(i64) -> String>(&mut self, value: i64, message: F) -> &str {
+        if let Some(threshold) = self.update(value) {
+            self.message = message(threshold);
+        }
+        &self.message
+    }
+}
+
+// Visually hidden live region; screen readers announce it whenever the message changes
+pub fn live_announcement_html(message: &str) -> Html {
+    html! {
+        <div class="visually-hidden" aria-live="polite" aria-atomic="true">{ message.to_string() }</div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_announcement_scheduler_update() -> Result<()> {
+        let mut scheduler = AnnouncementScheduler::new(&[30, 10, 5]);
+        assert_eq!(scheduler.update(45), None);
+        assert_eq!(scheduler.update(31), None);
+        assert_eq!(scheduler.update(30), Some(30));
+        assert_eq!(scheduler.update(29), None);
+        // Skipping past several thresholds at once only announces the latest
+        assert_eq!(scheduler.update(4), Some(5));
+        assert_eq!(scheduler.update(50), None);
+        assert_eq!(scheduler.update(10), Some(10));
+        Ok(())
+    }
+}
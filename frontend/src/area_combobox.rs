@@ -0,0 +1,166 @@
+use crate::prelude::*;
+use crate::types::*;
+
+#[derive(PartialEq, Properties)]
+pub struct AreaComboboxProps {
+    pub id: &'static str,
+    pub label: &'static str,
+    // Areas that can be selected, and the sailings query to navigate to when each is selected
+    pub options: Vec<(Area, SailingsQuery)>,
+    #[prop_or_default]
+    pub selected: Option<Area>,
+    // Extra content shown after each area's name in the list
+    #[prop_or_default]
+    pub option_suffix: Option<Callback<Area, Html>>,
+}
+
+// Areas matching the typed filter, in the order they are listed (by region, then name)
+fn matching_options<'a>(options: &'a [(Area, SailingsQuery)], filter: &str) -> Vec<&'a (Area, SailingsQuery)> {
+    let filter = filter.trim().to_lowercase();
+    options
+        .iter()
+        .filter(|(area, _)| area.long_name().to_lowercase().contains(&filter))
+        .sorted_by_key(|(area, _)| (area.region(), area.long_name()))
+        .collect()
+}
+
+fn option_id(id: &str, area: Area) -> String {
+    format!("{}-option-{}", id, area)
+}
+
+// Text input with a popup list of areas grouped by region, following the ARIA combobox pattern: typing filters the
+// list, up/down arrows move through it, enter selects, and escape closes it
+#[function_component(AreaCombobox)]
+pub fn area_combobox_component(props: &AreaComboboxProps) -> Html {
+    let history = use_history().expect("Expect history to be available");
+    let filter = use_state(String::new);
+    let expanded = use_state(|| false);
+    let active_index = use_state(|| None::<usize>);
+    let matches: Vec<_> = matching_options(&props.options, &filter).into_iter().cloned().collect();
+    let listbox_id = format!("{}-listbox", props.id);
+    let select = {
+        let (filter, expanded, active_index) = (filter.clone(), expanded.clone(), active_index.clone());
+        Callback::from(move |query: SailingsQuery| {
+            filter.set(String::new());
+            expanded.set(false);
+            active_index.set(None);
+            history.push_with_query(Route::Sailings, query).expect("Expect history to push");
+        })
+    };
+    let oninput = {
+        let (filter, expanded, active_index) = (filter.clone(), expanded.clone(), active_index.clone());
+        let options = props.options.clone();
+        Callback::from(move |e: InputEvent| {
+            let value = e.target_unchecked_into::<HtmlInputElement>().value();
+            active_index.set((!matching_options(&options, &value).is_empty()).then_some(0));
+            filter.set(value);
+            expanded.set(true);
+        })
+    };
+    let onkeydown = {
+        let (expanded, active_index, select) = (expanded.clone(), active_index.clone(), select.clone());
+        let matches = matches.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            let count = matches.len();
+            match e.key().as_str() {
+                "ArrowDown" | "ArrowUp" if count > 0 => {
+                    e.prevent_default();
+                    let next = match (*active_index, e.key() == "ArrowDown") {
+                        (None, true) => 0,
+                        (None, false) => count - 1,
+                        (Some(index), true) => (index + 1) % count,
+                        (Some(index), false) => (index + count - 1) % count,
+                    };
+                    expanded.set(true);
+                    active_index.set(Some(next));
+                }
+                "Enter" => {
+                    if let Some((_, query)) =
+                        (*expanded).then_some(*active_index).flatten().and_then(|i| matches.get(i))
+                    {
+                        e.prevent_default();
+                        select.emit(query.clone());
+                    }
+                }
+                "Escape" => {
+                    expanded.set(false);
+                    active_index.set(None);
+                }
+                _ => {}
+            }
+        })
+    };
+    let onclick = {
+        let expanded = expanded.clone();
+        Callback::from(move |_: MouseEvent| expanded.set(!*expanded))
+    };
+    let onblur = {
+        let (expanded, active_index) = (expanded.clone(), active_index.clone());
+        Callback::from(move |_: FocusEvent| {
+            expanded.set(false);
+            active_index.set(None);
+        })
+    };
+    let value = if filter.is_empty() {
+        props.selected.map(|a| a.long_name().to_string()).unwrap_or_default()
+    } else {
+        (*filter).clone()
+    };
+    let active_descendant = (*expanded).then_some(*active_index).flatten().and_then(|i| matches.get(i));
+    let region_groups: Vec<(Region, Vec<(usize, &(Area, SailingsQuery))>)> = matches
+        .iter()
+        .enumerate()
+        .group_by(|(_, (area, _))| area.region())
+        .into_iter()
+        .map(|(region, group)| (region, group.collect()))
+        .collect();
+    let option_html = |index: usize, (area, query): &(Area, SailingsQuery)| {
+        let is_active = *active_index == Some(index);
+        let onmousedown = {
+            let (select, query) = (select.clone(), query.clone());
+            // Mouse down rather than click, so the option is selected before the input loses focus and closes the list
+            Callback::from(move |e: MouseEvent| {
+                e.prevent_default();
+                select.emit(query.clone());
+            })
+        };
+        html! {
+            <li id={ option_id(props.id, *area) } role="option" aria-selected={ (props.selected == Some(*area)).to_string() }
+                class={ classes!("area-combobox-option", "px-3", "py-1", is_active.then_some("active")) } {onmousedown}>
+                { area.long_name() }
+                { for props.option_suffix.as_ref().map(|suffix| suffix.emit(*area)) }
+            </li>
+        }
+    };
+    html! {
+        <div class="area-combobox position-relative mb-3 d-print-none">
+            <label for={ props.id } class="form-label">{ props.label }</label>
+            <input id={ props.id } type="text" class="form-control" role="combobox" autocomplete="off"
+                aria-autocomplete="list" aria-expanded={ expanded.to_string() } aria-controls={ listbox_id.clone() }
+                aria-activedescendant={ active_descendant.map(|(area, _)| option_id(props.id, *area)) }
+                placeholder="Type or select an area" {value} {oninput} {onkeydown} {onclick} {onblur}/>
+            { if *expanded { html! {
+                <ul id={ listbox_id } role="listbox" aria-label={ props.label }
+                    class="area-combobox-listbox list-unstyled mb-0 py-1 bg-white border rounded shadow-sm">
+                    { if matches.is_empty() { html! {
+                        <li class="px-3 py-1 text-muted">{ "No matching areas" }</li>
+                    }} else { html! { <>
+                        { for region_groups.iter().map(|(region, group)| {
+                            let group_id = format!("{}-group-{:?}", props.id, region);
+                            html! {
+                                <li role="presentation">
+                                    <ul role="group" aria-labelledby={ group_id.clone() } class="list-unstyled">
+                                        <li id={ group_id } role="presentation" class="px-2 pt-1 small text-muted">{ region.name() }</li>
+                                        { for group.iter().map(|&(index, option)| option_html(index, option)) }
+                                    </ul>
+                                </li>
+                            }
+                        })}
+                    </> }}}
+                </ul>
+            }} else {
+                html! {}
+            }}
+        </div>
+    }
+}
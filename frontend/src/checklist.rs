@@ -0,0 +1,195 @@
+use crate::prelude::*;
+use crate::sailings_processor::*;
+use crate::travel_time::*;
+use crate::utils::*;
+
+// Minutes before departure of each deadline, which depend on how busy the departure terminal is
+struct TerminalDeadlines {
+    reserved_check_in: i64,
+    unreserved_arrival: i64,
+    ticket_sales_close: i64,
+}
+
+// Terminals with large lineups, where the earlier deadlines apply
+const MAJOR_TERMINALS: &[Terminal] = &[Terminal::SWB, Terminal::TSA];
+
+const MAJOR_TERMINAL_DEADLINES: TerminalDeadlines =
+    TerminalDeadlines { reserved_check_in: 30, unreserved_arrival: 60, ticket_sales_close: 10 };
+
+const MINOR_TERMINAL_DEADLINES: TerminalDeadlines =
+    TerminalDeadlines { reserved_check_in: 20, unreserved_arrival: 30, ticket_sales_close: 5 };
+
+const VEHICLE_DANGEROUS_GOODS_ITEM: &str = "Declare dangerous goods (such as propane tanks or spare fuel) at the \
+    ticket booth; some may only travel on dangerous goods sailings";
+
+const OVERSIZE_VEHICLE_ITEM: &str = "Oversize vehicles pay a higher fare and take up scarce deck space, so reserve if \
+    you can";
+
+const FOOT_PASSENGER_DANGEROUS_GOODS_ITEM: &str =
+    "Foot passengers may not bring dangerous goods (such as fuel containers or propane cylinders) aboard";
+
+fn terminal_deadlines(terminal: Terminal) -> &'static TerminalDeadlines {
+    if MAJOR_TERMINALS.contains(&terminal) {
+        &MAJOR_TERMINAL_DEADLINES
+    } else {
+        &MINOR_TERMINAL_DEADLINES
+    }
+}
+
+fn before_departure(sailing: &Sailing, minutes: i64) -> String {
+    format!("{} minutes before departure ({})", minutes, format_time(sailing.depart_time - Duration::minutes(minutes)))
+}
+
+// Leaving in time to reach the terminal by the earliest deadline for the traveller, if the drive from the city is known
+fn leave_by_item(
+    terminal: Terminal,
+    sailing: &Sailing,
+    home_city: HomeCity,
+    arrive_before_departure_minutes: i64,
+) -> Option<String> {
+    let drive_time = home_city.drive_time(terminal)?;
+    let leave_by = leave_by_time(sailing, Duration::minutes(arrive_before_departure_minutes), drive_time);
+    Some(format!(
+        "Leave {} by {} (about {} drive to {})",
+        home_city.name(),
+        format_time(leave_by),
+        format_duration(drive_time),
+        terminal.name()
+    ))
+}
+
+// When to leave home to drive on without a reservation, for summaries that have no room for the whole checklist
+pub fn leave_by_suggestion(terminal: Terminal, sailing: &Sailing, home_city: HomeCity) -> Option<String> {
+    leave_by_item(terminal, sailing, home_city, terminal_deadlines(terminal).unreserved_arrival)
+}
+
+// Things to do before departure for the sailing, so a traveller knows when to leave and what to bring
+pub fn checklist_items(
+    terminal_pair: TerminalPair,
+    sailing: &SailingWithNotes,
+    traveller_type: TravellerType,
+    home_city: Option<HomeCity>,
+) -> Vec<String> {
+    let deadlines = terminal_deadlines(terminal_pair.from);
+    let earliest_deadline = match traveller_type {
+        TravellerType::Vehicle | TravellerType::OversizeVehicle => deadlines.unreserved_arrival,
+        TravellerType::FootPassenger => deadlines.ticket_sales_close,
+    };
+    let mut items: Vec<_> = home_city
+        .and_then(|city| leave_by_item(terminal_pair.from, &sailing.sailing, city, earliest_deadline))
+        .into_iter()
+        .collect();
+    items.extend(match traveller_type {
+        TravellerType::Vehicle | TravellerType::OversizeVehicle => vec![
+            format!(
+                "With a reservation, check in at least {}, or the reservation may be cancelled",
+                before_departure(&sailing.sailing, deadlines.reserved_check_in)
+            ),
+            format!(
+                "Without a reservation, arrive at least {}; vehicles board in the order they arrive",
+                before_departure(&sailing.sailing, deadlines.unreserved_arrival)
+            ),
+            VEHICLE_DANGEROUS_GOODS_ITEM.to_string(),
+        ],
+        TravellerType::FootPassenger => vec![
+            format!(
+                "Buy your ticket at least {}, when ticket sales close",
+                before_departure(&sailing.sailing, deadlines.ticket_sales_close)
+            ),
+            FOOT_PASSENGER_DANGEROUS_GOODS_ITEM.to_string(),
+        ],
+    });
+    if traveller_type == TravellerType::OversizeVehicle {
+        items.push(OVERSIZE_VEHICLE_ITEM.to_string());
+    }
+    items.extend(
+        sailing
+            .sailing
+            .stops
+            .iter()
+            .filter(|s| s.type_ == StopType::Transfer)
+            .map(|stop| format!("Transfer to another vessel at {}", stop.terminal.name())),
+    );
+    items.extend(
+        sailing
+            .sailing
+            .stops
+            .iter()
+            .filter(|s| s.type_ == StopType::Thrufare && is_thrufare_eligible(terminal_pair, s.terminal))
+            .map(|stop| {
+                format!(
+                    "Thru-fare connection at {}: keep your ticket receipt for the connecting sailing",
+                    stop.terminal.name()
+                )
+            }),
+    );
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checklist_items() -> Result<()> {
+        let sailing = SailingWithNotes {
+            sailing: Sailing { depart_time: time!(10:00), arrive_time: time!(11:00), stops: vec![] },
+            notes: vec![SailingNote {
+                text: FOOT_PASSENGERS_ONLY_NOTE.to_string(),
+                category: NoteCategory::FootPassengersOnly,
+                sources: vec![NoteSource::Annotation],
+                only_dates: vec![],
+                vehicle_restriction: None,
+            }],
+            conditional_stop_dates: vec![],
+            departs_next_day: false,
+        };
+        let major_items = checklist_items(
+            TerminalPair { from: Terminal::TSA, to: Terminal::PSB },
+            &sailing,
+            TravellerType::Vehicle,
+            None,
+        );
+        assert!(major_items[1].contains("60 minutes before departure (9:00 am)"));
+        let minor_items = checklist_items(
+            TerminalPair { from: Terminal::PSB, to: Terminal::TSA },
+            &sailing,
+            TravellerType::FootPassenger,
+            None,
+        );
+        assert!(minor_items[0].contains("5 minutes before departure (9:55 am)"));
+        // Leaving in time to arrive an hour early, without a reservation
+        let home_items = checklist_items(
+            TerminalPair { from: Terminal::TSA, to: Terminal::PSB },
+            &sailing,
+            TravellerType::Vehicle,
+            Some(HomeCity::Richmond),
+        );
+        assert_eq!(home_items[0], "Leave Richmond by 8:35 am (about 25m drive to Tsawwassen)");
+        assert_eq!(home_items.len(), major_items.len() + 1);
+        // No drive to a terminal on another island
+        let island_items = checklist_items(
+            TerminalPair { from: Terminal::PSB, to: Terminal::TSA },
+            &sailing,
+            TravellerType::FootPassenger,
+            Some(HomeCity::Richmond),
+        );
+        assert_eq!(island_items.len(), minor_items.len());
+        assert!(!is_available_to_traveller_type(&sailing, TravellerType::Vehicle));
+        assert!(is_available_to_traveller_type(&sailing, TravellerType::FootPassenger));
+        let height_restriction = VehicleRestriction { dimension: VehicleDimension::Height, max_feet: 7 };
+        let restricted_sailing = SailingWithNotes {
+            notes: vec![SailingNote {
+                text: height_restriction.note(),
+                category: NoteCategory::VehicleRestriction,
+                sources: vec![NoteSource::Annotation],
+                only_dates: vec![],
+                vehicle_restriction: Some(height_restriction),
+            }],
+            ..sailing
+        };
+        assert!(is_available_to_traveller_type(&restricted_sailing, TravellerType::Vehicle));
+        assert!(!is_available_to_traveller_type(&restricted_sailing, TravellerType::OversizeVehicle));
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use gloo_timers::callback::Interval;
+
+use crate::announcer::*;
+use crate::prelude::*;
+use crate::utils::*;
+use crate::wake_lock::*;
+
+// Minutes before departure at which screen readers are told how long until the next sailing
+const COUNTDOWN_ANNOUNCEMENT_MINUTES: &[i64] = &[30, 10, 5];
+
+const COUNTDOWN_TICK_MILLIS: u32 = 15_000;
+
+#[derive(PartialEq, Properties)]
+pub struct NextSailingCountdownProps {
+    // Today's departure times, in order
+    pub depart_times: Vec<Time>,
+    // Shown in the large display, which fills the screen and so has no other context
+    pub route_name: String,
+}
+
+fn minutes_text(minutes: i64) -> String {
+    match minutes {
+        0 => "now".to_string(),
+        1 => "in 1 minute".to_string(),
+        _ => format!("in {} minutes", minutes),
+    }
+}
+
+#[function_component(NextSailingCountdown)]
+pub fn next_sailing_countdown_component(props: &NextSailingCountdownProps) -> Html {
+    let now_state = use_state(|| now_vancouver().time());
+    let scheduler = use_mut_ref(|| AnnouncementScheduler::new(COUNTDOWN_ANNOUNCEMENT_MINUTES));
+    let large_display_state = use_state(|| false);
+    let next_depart_time = props.depart_times.iter().copied().find(|&t| t >= *now_state);
+    // Glancing at the large display while driving to the terminal should not need a tap to wake the screen
+    use_screen_wake_lock(*large_display_state && next_depart_time.is_some());
+    {
+        let now_state = now_state.clone();
+        use_effect_with_deps(
+            move |_| {
+                let interval = Interval::new(COUNTDOWN_TICK_MILLIS, move || now_state.set(now_vancouver().time()));
+                move || drop(interval)
+            },
+            (),
+        );
+    }
+    let now = *now_state;
+    let next_depart_time = match next_depart_time {
+        Some(depart_time) => depart_time,
+        None => return html! {},
+    };
+    // Round up, so "in 0 minutes" is only shown once the sailing is due
+    let minutes = ((next_depart_time - now).whole_seconds() + 59) / 60;
+    let mut scheduler = scheduler.borrow_mut();
+    let announcement = scheduler.update_message(minutes, |threshold| {
+        format!("Next sailing at {} departs in {} minutes", format_time(next_depart_time), threshold)
+    });
+    let onclick_large_display = {
+        let large_display_state = large_display_state.clone();
+        Callback::from(move |_| large_display_state.set(!*large_display_state))
+    };
+    html! {
+        <div class="mb-2 text-muted">
+            <i class="bi bi-clock"/>
+            { format!(" Next sailing at {} departs {}", format_time(next_depart_time), minutes_text(minutes)) }
+            <button
+                type="button"
+                class="btn btn-link btn-sm py-0 d-print-none"
+                title="Show in large type"
+                onclick={ onclick_large_display.clone() }
+            >
+                <i class="bi bi-arrows-fullscreen"/>
+            </button>
+            { live_announcement_html(announcement) }
+            { if *large_display_state { html! {
+                <div
+                    class="large-departure d-flex flex-column justify-content-center text-center text-white bg-dark"
+                    role="dialog"
+                    aria-label="Next departure"
+                    onclick={ onclick_large_display }
+                >
+                    <div class="large-departure-route">{ &props.route_name }</div>
+                    <div class="large-departure-time">{ format_time(next_depart_time) }</div>
+                    <div class="large-departure-minutes">{ format!("Departs {}", minutes_text(minutes)) }</div>
+                    <div class="small text-white-50 mt-4">{ "Tap anywhere to close" }</div>
+                </div>
+            }} else { html! {} }}
+        </div>
+    }
+}
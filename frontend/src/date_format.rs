@@ -0,0 +1,102 @@
+use js_sys::{Object, Reflect};
+use wasm_bindgen::JsValue;
+
+use crate::prelude::*;
+use crate::storage::*;
+
+// Used when the browser does not report a language
+const DEFAULT_LOCALE: &str = "en-CA";
+
+// Regions where weeks conventionally start on Sunday; elsewhere they start on Monday
+const SUNDAY_FIRST_REGIONS: &[&str] = &["BR", "CA", "IL", "JP", "KR", "MX", "PH", "US", "ZA"];
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct DatePreferences {
+    // `None` to use the default for the locale
+    pub first_day_of_week: Option<Weekday>,
+}
+
+// Formats dates for display according to the user's locale and preferences, so that components don't hard-code
+// format strings
+pub struct DateFormatter {
+    locale: String,
+    first_day_of_week: Weekday,
+}
+
+pub static DATE_FORMATTER: Lazy<DateFormatter> = Lazy::new(|| {
+    let locale = web_sys::window().and_then(|w| w.navigator().language()).unwrap_or_else(|| DEFAULT_LOCALE.to_string());
+    let preferences: DatePreferences = load_or_default(&*default_storage());
+    DateFormatter::new(locale, preferences)
+});
+
+impl Stored for DatePreferences {
+    const KEY: &'static str = "date-preferences";
+    const VERSION: u32 = 1;
+}
+
+pub fn locale_first_day_of_week(locale: &str) -> Weekday {
+    let mut subtags = locale.split(['-', '_']);
+    let language = subtags.next().unwrap_or_default();
+    // Region subtags are the two-letter ones after the language (script subtags have four letters)
+    match subtags.find(|s| s.len() == 2) {
+        Some(region) if SUNDAY_FIRST_REGIONS.contains(&region.to_ascii_uppercase().as_str()) => Weekday::Sunday,
+        Some(_) => Weekday::Monday,
+        None if language.eq_ignore_ascii_case("en") => Weekday::Sunday,
+        None => Weekday::Monday,
+    }
+}
+
+impl DateFormatter {
+    pub fn new(locale: String, preferences: DatePreferences) -> DateFormatter {
+        let first_day_of_week = preferences.first_day_of_week.unwrap_or_else(|| locale_first_day_of_week(&locale));
+        DateFormatter { locale, first_day_of_week }
+    }
+
+    fn format(&self, date: Date, options: &[(&str, &str)]) -> String {
+        let js_options = Object::new();
+        for (key, value) in options {
+            Reflect::set(&js_options, &JsValue::from_str(key), &JsValue::from_str(value))
+                .expect("Expect date format option to set");
+        }
+        js_sys::Date::new_with_year_month_day(
+            date.year() as u32,
+            i32::from(u8::from(date.month())) - 1,
+            date.day().into(),
+        )
+        .to_locale_date_string(&self.locale, &js_options)
+        .into()
+    }
+
+    // For example, "Saturday, 28 May 2022" (ordering and names depend on the locale)
+    pub fn long_date(&self, date: Date) -> String {
+        self.format(date, &[("weekday", "long"), ("day", "numeric"), ("month", "long"), ("year", "numeric")])
+    }
+
+    // For example, "Sat May 28" (ordering and names depend on the locale)
+    pub fn short_date(&self, date: Date) -> String {
+        self.format(date, &[("weekday", "short"), ("day", "numeric"), ("month", "short")])
+    }
+
+    pub fn is_first_day_of_week(&self, date: Date) -> bool {
+        date.weekday() == self.first_day_of_week
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_first_day_of_week() -> Result<()> {
+        assert_eq!(locale_first_day_of_week("en-CA"), Weekday::Sunday);
+        assert_eq!(locale_first_day_of_week("en"), Weekday::Sunday);
+        assert_eq!(locale_first_day_of_week("en-GB"), Weekday::Monday);
+        assert_eq!(locale_first_day_of_week("fr"), Weekday::Monday);
+        assert_eq!(locale_first_day_of_week("de-Latn-DE"), Weekday::Monday);
+        assert_eq!(locale_first_day_of_week("pt_BR"), Weekday::Sunday);
+        let preferences = DatePreferences { first_day_of_week: Some(Weekday::Monday) };
+        let formatter = DateFormatter::new("en-US".to_string(), preferences);
+        assert!(formatter.is_first_day_of_week(date!(2022 - 05 - 30)));
+        Ok(())
+    }
+}
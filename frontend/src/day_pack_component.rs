@@ -0,0 +1,156 @@
+use crate::date_format::*;
+use crate::prelude::*;
+use crate::sailings_component::*;
+use crate::sailings_processor::*;
+use crate::types::*;
+
+fn onchange_date_input_callback(history: AnyHistory, today: Date, to: Option<Date>) -> Callback<Event> {
+    Callback::once(move |e: Event| {
+        let date = parse_iso8601_date(e.target_unchecked_into::<HtmlInputElement>().value().trim())
+            .ok()
+            .filter(|date| *date > today);
+        history.push_with_query(Route::DayPack, DayPackQuery { date, to }).expect("Expect history to push");
+    })
+}
+
+fn onchange_to_date_input_callback(history: AnyHistory, date: Date) -> Callback<Event> {
+    Callback::once(move |e: Event| {
+        let to = parse_iso8601_date(e.target_unchecked_into::<HtmlInputElement>().value().trim())
+            .ok()
+            .filter(|to| *to > date);
+        history.push_with_query(Route::DayPack, DayPackQuery { date: Some(date), to }).expect("Expect history to push");
+    })
+}
+
+fn onclick_print_button_callback() -> Callback<MouseEvent> {
+    Callback::from(|_| {
+        if let Some(window) = web_sys::window() {
+            window.print().unwrap_or_else(|err| error!("Failed to print: {:?}", err));
+        }
+    })
+}
+
+fn day_pack_dates_html(
+    date_range: &DateRange,
+    requested_to: Option<Date>,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+) -> Html {
+    let periods = split_at_schedule_periods(date_range, schedules_map);
+    html! { <>
+        { if requested_to.map(|to| to > date_range.to).unwrap_or(false) { html! {
+            <div class="alert alert-info d-print-none" role="alert">
+                { format!(
+                    "Schedules are only available until {}, so the dates end there.",
+                    DATE_FORMATTER.long_date(date_range.to)
+                ) }
+            </div>
+        }} else { html! {} }}
+        { for periods.iter().map(|period| html! { <>
+            { if periods.len() > 1 { html! {
+                <h5 class="day-pack-period">
+                    { format!(
+                        "Schedules for {} to {}",
+                        DATE_FORMATTER.short_date(period.from),
+                        DATE_FORMATTER.short_date(period.to)
+                    ) }
+                </h5>
+            }} else { html! {} }}
+            { for period.iter_days().map(|date| html! { <>
+                        { day_pack_sections_html(date, schedules_map) }
+            </> }) }
+        </> }) }
+    </> }
+}
+
+fn day_pack_sections_html(date: Date, schedules_map: &HashMap<TerminalPair, Vec<Schedule>>) -> Html {
+    let schedules_sailings = all_sailings_for_date(date, schedules_map, &SailingFilter::default());
+    if schedules_sailings.is_empty() {
+        return html! {
+            <div class="alert alert-warning text-center" role="alert">
+                { "There are no sailings available for this date yet; please check back later!" }
+            </div>
+        };
+    }
+    html! { <>
+        { for schedules_sailings.iter().map(|(schedule, sailings)| html! {
+            <section class="mb-4 day-pack-section">
+                <h5>
+                    { schedule.terminal_pair.from.area().long_name() }
+                    { " to " }
+                    { schedule.terminal_pair.to.area().long_name() }
+                </h5>
+                <table class="table table-light mb-0">
+                    { schedule_sailings_rows_html(true, true, date, schedule, sailings) }
+                </table>
+            </section>
+        })}
+    </> }
+}
+
+#[function_component(DayPack)]
+pub fn day_pack_component() -> Html {
+    let location = use_location();
+    let history = use_history().expect("Expect history to be available");
+    let schedules_state = use_context::<SchedulesState>().expect("Expect schedules state to be available");
+    let today = today_vancouver();
+    let query = location
+        .and_then(|l| l.query::<DayPackQuery>().map_err(|e| error!("Invalid day pack query: {}", e)).ok())
+        .unwrap_or(DayPackQuery { date: None, to: None });
+    let date = query.date.filter(|date| *date >= today).unwrap_or(today);
+    let requested_to = query.to.filter(|to| *to > date);
+    // A range can't run past the schedules that include its first date
+    let to_limit = match &schedules_state {
+        SchedulesState::Loaded(schedules_map) => printable_range_limit(date, schedules_map),
+        _ => None,
+    };
+    let date_range = DateRange {
+        from: date,
+        to: requested_to.map(|to| to_limit.map(|limit| min(to, limit)).unwrap_or(to)).unwrap_or(date),
+    };
+    html! { <>
+        <h1 class="display-6 mb-3 small">
+            { "Travel day pack" }
+            <small class="text-muted">{ " all routes by day" }</small>
+        </h1>
+        <div class="d-print-none row mb-3">
+            <label for="day-pack-date-input" class="col-2 col-md-1 col-form-label">{ "Date" }</label>
+            <div class="col-10 col-md-7 col-lg-5 d-flex">
+                <input
+                    id="day-pack-date-input"
+                    type="date"
+                    class="form-control align-self-center date-input"
+                    value={ format_iso8601_date(date) }
+                    min={ format_iso8601_date(today) }
+                    onchange={ onchange_date_input_callback(history.clone(), today, requested_to) }/>
+                <label for="day-pack-to-date-input" class="mx-2 align-self-center">{ "to" }</label>
+                <input
+                    id="day-pack-to-date-input"
+                    type="date"
+                    class="form-control align-self-center date-input"
+                    value={ (date_range.to > date).then(|| format_iso8601_date(date_range.to)).unwrap_or_default() }
+                    min={ format_iso8601_date(date) }
+                    max={ to_limit.map(format_iso8601_date) }
+                    title="Last date to include, up to the end of the schedules"
+                    onchange={ onchange_to_date_input_callback(history, date) }/>
+                <span class="me-auto"/>
+                <button type="button" class="btn btn-outline-secondary btn-sm" title="Print" onclick={ onclick_print_button_callback() }>
+                    <i class="bi bi-printer"/>
+                </button>
+            </div>
+        </div>
+        { match &schedules_state {
+            SchedulesState::Init | SchedulesState::Loading => html! {
+                <div class="alert alert-light border text-center">
+                    <div class="spinner-border" role="status"/>
+                    <div>{ "Loading schedules..." }</div>
+                </div>
+            },
+            SchedulesState::Failed => html! {
+                <div class="alert alert-danger text-center" role="alert">
+                    { "There was a problem loading the ferry schedules; please refresh your browser to try again." }
+                </div>
+            },
+            SchedulesState::Loaded(schedules_map) => day_pack_dates_html(&date_range, requested_to, schedules_map),
+        }}
+    </> }
+}
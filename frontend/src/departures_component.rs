@@ -0,0 +1,128 @@
+use gloo_timers::callback::Interval;
+
+use crate::date_format::*;
+use crate::prelude::*;
+use crate::sailings_processor::*;
+use crate::types::*;
+use crate::utils::*;
+
+// Often enough for departures to change status soon after their time, as on the board at the terminal
+const DEPARTURE_BOARD_TICK_MILLIS: u32 = 30_000;
+
+#[derive(PartialEq, Properties)]
+pub struct DepartureBoardProps {
+    pub terminal: Terminal,
+}
+
+fn live_departures_url(terminal: Terminal) -> String {
+    format!("{}?terminalCode={}", ALL_DEPARTURES_URL, terminal)
+}
+
+fn departure_row_html(departure: &TerminalDeparture, now: Time, is_next: bool) -> Html {
+    let has_departed = departure.depart_time < now;
+    let (status_class, status) = if has_departed {
+        ("text-muted", "Departed")
+    } else if is_next {
+        ("fw-bold", "Next")
+    } else {
+        ("", "Scheduled")
+    };
+    html! {
+        <tr class={ classes!(has_departed.then_some("text-muted")) }>
+            <td class="text-nowrap">{ format_time(departure.depart_time) }</td>
+            <td>
+                <ul class="list-unstyled mb-0">
+                    { for departure.destinations.iter().map(|destination| html! {
+                        <li>
+                            <span class="text-nowrap">{ destination.terminal.area().long_name() }</span>
+                            <small class="text-muted text-nowrap">
+                                { format!(" {}, arrives {}", destination.terminal.name(), format_time(destination.arrive_time)) }
+                            </small>
+                        </li>
+                    }) }
+                </ul>
+                { if departure.notes.is_empty() { html! {} } else { html! {
+                    <ul class="list-unstyled small text-muted mb-0">
+                        { for departure.notes.iter().map(|note| html! { <li>{ note }</li> }) }
+                    </ul>
+                }}}
+            </td>
+            <td class={ classes!("text-nowrap", status_class) }>{ status }</td>
+        </tr>
+    }
+}
+
+fn departures_html(departures: &[TerminalDeparture], now: Time) -> Html {
+    if departures.is_empty() {
+        return html! {
+            <div class="alert alert-warning text-center" role="alert">
+                { "There are no sailings from this terminal today." }
+            </div>
+        };
+    }
+    let next_index = departures.iter().position(|departure| departure.depart_time >= now);
+    html! {
+        <table class="table table-sm">
+            <thead>
+                <tr>
+                    <th>{ "Departs" }</th>
+                    <th>{ "Destinations" }</th>
+                    <th>{ "Status" }</th>
+                </tr>
+            </thead>
+            <tbody>
+                { for departures
+                    .iter()
+                    .enumerate()
+                    .map(|(index, departure)| departure_row_html(departure, now, Some(index) == next_index)) }
+            </tbody>
+        </table>
+    }
+}
+
+// Every departure from a terminal today across all of its destinations, like the board at the terminal.  The status
+// comes from the schedule and the time of day, so delays and cancellations are only on BC Ferries' live departures.
+#[function_component(DepartureBoard)]
+pub fn departure_board_component(props: &DepartureBoardProps) -> Html {
+    let terminal = props.terminal;
+    let schedules_state = use_context::<SchedulesState>().expect("Expect schedules state to be available");
+    let now_state = use_state(now_vancouver);
+    {
+        let now_state = now_state.clone();
+        use_effect_with_deps(
+            move |_| {
+                let interval = Interval::new(DEPARTURE_BOARD_TICK_MILLIS, move || now_state.set(now_vancouver()));
+                move || drop(interval)
+            },
+            (),
+        );
+    }
+    let now = *now_state;
+    html! { <>
+        <h1 class="display-6 mb-3 small">
+            { format!("Departures from {}", terminal.name()) }
+            <small class="text-muted">{ format!(" {}", DATE_FORMATTER.long_date(now.date())) }</small>
+        </h1>
+        <p class="small d-print-none">
+            { "Times are as scheduled. Check " }
+            <a href={ live_departures_url(terminal) } target="_blank">{ "BC Ferries' live departures" }</a>
+            { " for delays and cancellations." }
+        </p>
+        { match &schedules_state {
+            SchedulesState::Init | SchedulesState::Loading => html! {
+                <div class="alert alert-light border text-center">
+                    <div class="spinner-border" role="status"/>
+                    <div>{ "Loading schedules..." }</div>
+                </div>
+            },
+            SchedulesState::Failed => html! {
+                <div class="alert alert-danger text-center" role="alert">
+                    { "There was a problem loading the ferry schedules; please refresh your browser to try again." }
+                </div>
+            },
+            SchedulesState::Loaded(schedules_map) => {
+                departures_html(&terminal_departures(terminal, now.date(), schedules_map), now.time())
+            }
+        }}
+    </> }
+}
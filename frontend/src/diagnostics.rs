@@ -0,0 +1,199 @@
+use reqwasm::http;
+
+use crate::prelude::*;
+use crate::storage::*;
+
+const DIAGNOSTICS_URL: &str = "/api/diagnostics";
+
+// Limits how many reports one browser sends, so a widespread data problem does not flood the endpoint
+const MAX_REPORTS_PER_DAY: usize = 5;
+
+// Kinds of impossible data states that the scraper should never produce
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum DataIssueKind {
+    InvertedDateRange,
+    WeekdayDateOutsideRange,
+    NoteDateOutsideRange,
+    EmptySchedule,
+}
+
+// Deliberately contains only which schedule has the problem: nothing about the user, their device, or what they
+// were looking at
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct DiagnosticReport {
+    pub app_version: String,
+    pub kind: DataIssueKind,
+    pub terminal_pair: TerminalPair,
+    pub schedule_from: Date,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct DiagnosticsOptIn {
+    pub enabled: bool,
+}
+
+// Reports already sent, so each problem is reported at most once and the daily limit can be enforced
+#[derive(Default, Deserialize, Serialize)]
+pub struct ReportLog {
+    date: Option<Date>,
+    sent_today: usize,
+    sent: Vec<DiagnosticReport>,
+}
+
+impl Stored for DiagnosticsOptIn {
+    const KEY: &'static str = "diagnostics-opt-in";
+    const VERSION: u32 = 1;
+}
+
+impl Stored for ReportLog {
+    const KEY: &'static str = "diagnostics-report-log";
+    const VERSION: u32 = 1;
+}
+
+impl DiagnosticReport {
+    fn new(kind: DataIssueKind, schedule: &Schedule) -> DiagnosticReport {
+        DiagnosticReport {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            kind,
+            terminal_pair: schedule.terminal_pair,
+            schedule_from: schedule.date_range.from,
+        }
+    }
+}
+
+impl ReportLog {
+    // Removes reports that were already sent or exceed the daily limit, and records the rest as sent
+    pub fn take_allowed(&mut self, today: Date, reports: Vec<DiagnosticReport>) -> Vec<DiagnosticReport> {
+        if self.date != Some(today) {
+            self.date = Some(today);
+            self.sent_today = 0;
+        }
+        let allowed: Vec<_> = reports
+            .into_iter()
+            .unique()
+            .filter(|r| !self.sent.contains(r))
+            .take(MAX_REPORTS_PER_DAY.saturating_sub(self.sent_today))
+            .collect();
+        self.sent_today += allowed.len();
+        self.sent.extend(allowed.iter().cloned());
+        allowed
+    }
+}
+
+fn date_restriction_dates(date_restriction: &DateRestriction) -> Option<&HashSet<Date>> {
+    match date_restriction {
+        DateRestriction::All => None,
+        DateRestriction::Only(dates) | DateRestriction::Except(dates) => Some(dates),
+    }
+}
+
+fn schedule_data_issues(schedule: &Schedule) -> Vec<DataIssueKind> {
+    let mut issues = Vec::new();
+    if schedule.date_range.from > schedule.date_range.to {
+        issues.push(DataIssueKind::InvertedDateRange);
+    }
+    if schedule.items.is_empty() && schedule.alerts.is_empty() {
+        issues.push(DataIssueKind::EmptySchedule);
+    }
+    let is_outside_range = |dates: Option<&HashSet<Date>>| {
+        dates.into_iter().flatten().any(|&date| !schedule.date_range.includes_date_inclusive(date))
+    };
+    if schedule.items.iter().any(|i| i.weekdays.values().any(|dr| is_outside_range(date_restriction_dates(dr)))) {
+        issues.push(DataIssueKind::WeekdayDateOutsideRange);
+    }
+    if schedule.items.iter().any(|i| i.notes.values().any(|dr| is_outside_range(date_restriction_dates(dr)))) {
+        issues.push(DataIssueKind::NoteDateOutsideRange);
+    }
+    issues
+}
+
+pub fn find_data_issues(schedules_map: &HashMap<TerminalPair, Vec<Schedule>>) -> Vec<DiagnosticReport> {
+    schedules_map
+        .values()
+        .flatten()
+        .flat_map(|s| schedule_data_issues(s).into_iter().map(|kind| DiagnosticReport::new(kind, s)))
+        .collect()
+}
+
+async fn send_reports(reports: &[DiagnosticReport]) -> Result<()> {
+    let body = serde_json::to_string(reports)?;
+    let response =
+        http::Request::post(DIAGNOSTICS_URL).header("Content-Type", "application/json").body(body).send().await?;
+    ensure!(response.ok(), "Failed to send diagnostics: HTTP status {}", response.status());
+    Ok(())
+}
+
+// Sends reports about impossible data states, but only if the user has opted in
+pub fn report_data_issues(schedules_map: &HashMap<TerminalPair, Vec<Schedule>>) {
+    let storage = default_storage();
+    if !load_or_default::<DiagnosticsOptIn>(&*storage).enabled {
+        return;
+    }
+    let reports = find_data_issues(schedules_map);
+    reports.iter().for_each(|r| warn!("Schedule data issue: {:?}", r));
+    let mut report_log: ReportLog = load_or_default(&*storage);
+    let allowed_reports = report_log.take_allowed(today_vancouver(), reports);
+    if allowed_reports.is_empty() {
+        return;
+    }
+    save(&*storage, &report_log).unwrap_or_else(|err| warn!("{:?}", err));
+    wasm_bindgen_futures::spawn_local(async move {
+        send_reports(&allowed_reports).await.unwrap_or_else(|err| warn!("{:?}", err));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(date_range: DateRange, note_dates: &[Date]) -> Schedule {
+        Schedule {
+            date_range,
+            items: vec![ScheduleItem {
+                sailing: Sailing { depart_time: time!(10:00), arrive_time: time!(11:00), stops: vec![] },
+                weekdays: HashMap::from([(Weekday::Monday, DateRestriction::All)]),
+                notes: HashMap::from([(
+                    Cow::from("Note"),
+                    DateRestriction::Only(note_dates.iter().copied().collect()),
+                )]),
+                note_categories: HashMap::new(),
+                vehicle_restrictions: HashMap::new(),
+                conditional_stops: vec![],
+            }],
+            ..Schedule::test_fixture(TerminalPair { from: Terminal::SWB, to: Terminal::PSB })
+        }
+    }
+
+    #[test]
+    fn test_schedule_data_issues() -> Result<()> {
+        let date_range = DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 30) };
+        assert_eq!(schedule_data_issues(&schedule(date_range, &[date!(2022 - 06 - 15)])), vec![]);
+        assert_eq!(
+            schedule_data_issues(&schedule(date_range, &[date!(2022 - 07 - 01)])),
+            vec![DataIssueKind::NoteDateOutsideRange]
+        );
+        let inverted_date_range = DateRange { from: date!(2022 - 06 - 30), to: date!(2022 - 06 - 01) };
+        assert_eq!(schedule_data_issues(&schedule(inverted_date_range, &[])), vec![DataIssueKind::InvertedDateRange]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_log_take_allowed() -> Result<()> {
+        let date_range = DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 30) };
+        let report = DiagnosticReport::new(DataIssueKind::EmptySchedule, &schedule(date_range, &[]));
+        let other_reports: Vec<_> = date_range
+            .iter_days()
+            .map(|schedule_from| DiagnosticReport {
+                schedule_from,
+                kind: DataIssueKind::InvertedDateRange,
+                ..report.clone()
+            })
+            .collect();
+        let mut log = ReportLog::default();
+        assert_eq!(log.take_allowed(date!(2022 - 06 - 01), vec![report.clone(), report.clone()]).len(), 1);
+        assert_eq!(log.take_allowed(date!(2022 - 06 - 01), vec![report.clone()]).len(), 0);
+        assert_eq!(log.take_allowed(date!(2022 - 06 - 01), other_reports.clone()).len(), MAX_REPORTS_PER_DAY - 1);
+        assert_eq!(log.take_allowed(date!(2022 - 06 - 02), other_reports).len(), MAX_REPORTS_PER_DAY);
+        Ok(())
+    }
+}
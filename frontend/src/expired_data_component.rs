@@ -0,0 +1,98 @@
+use crate::date_format::*;
+use crate::prelude::*;
+use crate::schedules_loader::*;
+use crate::types::*;
+use crate::utils::*;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum UpdateCheck {
+    Checking,
+    NotFound,
+    Failed,
+}
+
+#[derive(PartialEq, Properties)]
+struct ExpiredDataProps {
+    last_date: Date,
+}
+
+// Checks once for newer schedules than the expired ones, skipping the browser's cached copy.  If there are some, they
+// replace the loaded schedules, which takes this page away.
+#[function_component(ExpiredData)]
+fn expired_data_component(props: &ExpiredDataProps) -> Html {
+    let SchedulesRevalidator(revalidate) =
+        use_context::<SchedulesRevalidator>().expect("Expect schedules revalidator to be available");
+    let update_check = use_state(|| UpdateCheck::Checking);
+    {
+        let update_check = update_check.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    match fetch_schedules(LoadFault::None, true, current_data_channel(), &[]).await {
+                        Ok(data) if !is_data_expired(&data.schedules_map, today_vancouver()) => revalidate.emit(()),
+                        Ok(_) => update_check.set(UpdateCheck::NotFound),
+                        Err(err) => {
+                            warn!("Failed to check for newer schedules: {}", err);
+                            update_check.set(UpdateCheck::Failed);
+                        }
+                    }
+                });
+                || ()
+            },
+            (),
+        );
+    }
+    html! { <>
+        <h1 class="display-6 mb-3 small">{ "These schedules are out of date" }</h1>
+        <div class="alert alert-warning" role="alert">
+            <p>
+                { format!(
+                    "The schedules on this site only go up to {}, and newer ones have not been collected yet. ",
+                    DATE_FORMATTER.long_date(props.last_date)
+                ) }
+                { "Please don't rely on this site for planning travel until they are." }
+            </p>
+            <p class="mb-0">
+                { "In the meantime, check the " }
+                <a class="link-dark" href={ ALL_SCHEDULES_URL } target="_blank">{ "official schedules" }</a>
+                { " published by " }
+                { DATA_SOURCE_NAME }
+                { "." }
+            </p>
+        </div>
+        <p class="text-muted">
+            { match *update_check {
+                UpdateCheck::Checking => "Checking for newer schedules...",
+                UpdateCheck::NotFound => "No newer schedules have been published yet.",
+                UpdateCheck::Failed => "Newer schedules could not be checked for; please refresh your browser to try again.",
+            }}
+            { " " }
+            <Link<Route> classes="link-dark" to={Route::AboutData}>{ "About this data" }</Link<Route>>
+        </p>
+    </> }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ExpiredDataGateProps {
+    pub children: Children,
+}
+
+// Shown instead of any page once every schedule has ended, rather than each page saying that no schedule is available
+// yet.  The about and help pages don't depend on the schedules, so they are still shown.
+#[function_component(ExpiredDataGate)]
+pub fn expired_data_gate_component(props: &ExpiredDataGateProps) -> Html {
+    let route: Route = use_route().unwrap_or_default();
+    let schedules_state = use_context::<SchedulesState>().expect("Expect schedules state to be available");
+    let last_date = match &schedules_state {
+        SchedulesState::Loaded(schedules_map) if is_data_expired(schedules_map, today_vancouver()) => {
+            schedules_map.values().flatten().map(|schedule| schedule.date_range.to).max()
+        }
+        _ => None,
+    };
+    match last_date {
+        Some(last_date) if !matches!(route, Route::AboutData | Route::HelpIndex | Route::Help { .. }) => {
+            html! { <ExpiredData {last_date}/> }
+        }
+        _ => html! { <>{ for props.children.iter() }</> },
+    }
+}
@@ -0,0 +1,122 @@
+use crate::prelude::*;
+use crate::storage::*;
+
+// Features that the published manifest rolls out gradually, named in the manifest in kebab case.  A feature the
+// manifest doesn't list uses its default, so features released before they had a flag stay on unless held back.
+#[derive(Copy, Clone, Debug, Display, Eq, PartialEq)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Feature {
+    JourneyGapExplanations,
+}
+
+impl Feature {
+    fn enabled_by_default(self) -> bool {
+        match self {
+            Feature::JourneyGapExplanations => true,
+        }
+    }
+}
+
+// Random number for this browser, only used to decide which rollouts it is in, and never sent anywhere
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ClientId(u32);
+
+impl Stored for ClientId {
+    const KEY: &'static str = "client-id";
+    const VERSION: u32 = 1;
+}
+
+// The feature flags from the manifest as they apply to this browser
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeatureFlags {
+    client_id: Option<ClientId>,
+    flags: Rc<Vec<FeatureFlag>>,
+}
+
+// FNV-1a rather than the standard library's hasher, which may change between Rust versions and so move clients in and
+// out of rollouts with each build
+fn rollout_bucket(client_id: ClientId, flag_name: &str) -> u8 {
+    let ClientId(id) = client_id;
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in id.to_le_bytes().iter().chain(flag_name.as_bytes()) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    (hash % 100) as u8
+}
+
+impl FeatureFlags {
+    pub fn new(client_id: ClientId, flags: Vec<FeatureFlag>) -> FeatureFlags {
+        FeatureFlags { client_id: Some(client_id), flags: Rc::new(flags) }
+    }
+
+    // Features limited to some routes are off where there is no route, such as on the home page
+    pub fn is_enabled(&self, feature: Feature, area_pair: Option<AreaPair>) -> bool {
+        let name = feature.to_string();
+        let flag = match self.flags.iter().find(|f| f.name == name) {
+            Some(flag) => flag,
+            None => return feature.enabled_by_default(),
+        };
+        let on_route = flag.routes.is_empty()
+            || area_pair.map_or(false, |ap| flag.routes.iter().any(|r| *r == ap || *r == ap.swapped()));
+        match self.client_id {
+            Some(client_id) => on_route && rollout_bucket(client_id, &flag.name) < flag.rollout_percent,
+            None => on_route && flag.rollout_percent >= 100,
+        }
+    }
+}
+
+// Created on first use.  If it can't be saved, such as when storage is full, this visit still gets a consistent ID,
+// but the next one may be in different rollouts.
+pub fn client_id(storage: &dyn Storage) -> ClientId {
+    match load(storage) {
+        Ok(Some(client_id)) => return client_id,
+        Ok(None) => {}
+        Err(err) => warn!("{:?}", err),
+    }
+    let client_id = ClientId((js_sys::Math::random() * f64::from(u32::MAX)) as u32);
+    if let Err(err) = save(storage, &client_id) {
+        warn!("Failed to save client ID: {:?}", err);
+    }
+    client_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_flags() -> Result<()> {
+        let route = AreaPair { from: Area::Vancouver, to: Area::Victoria };
+        let flag = |rollout_percent, routes| FeatureFlag {
+            name: "journey-gap-explanations".to_string(),
+            rollout_percent,
+            routes,
+        };
+        assert!(FeatureFlags::default().is_enabled(Feature::JourneyGapExplanations, None));
+        let clients: Vec<_> = (0..1000).map(ClientId).collect();
+        let enabled_count = |flags: Vec<FeatureFlag>, area_pair| {
+            clients
+                .iter()
+                .filter(|c| {
+                    FeatureFlags::new(**c, flags.clone()).is_enabled(Feature::JourneyGapExplanations, area_pair)
+                })
+                .count()
+        };
+        assert_eq!(enabled_count(vec![flag(0, vec![])], None), 0);
+        assert_eq!(enabled_count(vec![flag(100, vec![])], None), 1000);
+        let quarter = enabled_count(vec![flag(25, vec![])], None);
+        assert!((200..300).contains(&quarter), "Expected about a quarter of clients, but found {}", quarter);
+        // Raising the percentage only adds clients
+        for client_id in &clients {
+            let enabled = |percent| {
+                FeatureFlags::new(*client_id, vec![flag(percent, vec![])])
+                    .is_enabled(Feature::JourneyGapExplanations, None)
+            };
+            assert!(!enabled(25) || enabled(50));
+        }
+        assert_eq!(enabled_count(vec![flag(100, vec![route])], Some(route.swapped())), 1000);
+        assert_eq!(enabled_count(vec![flag(100, vec![route])], None), 0);
+        Ok(())
+    }
+}
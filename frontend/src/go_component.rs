@@ -0,0 +1,65 @@
+use crate::intents::*;
+use crate::navigation_history::*;
+use crate::prelude::*;
+use crate::storage::*;
+use crate::travel_time::*;
+use crate::types::*;
+use crate::utils::*;
+
+fn intent_error_html(error: &IntentError) -> Html {
+    html! {
+        <div class="alert alert-warning" role="alert">
+            <p>{ error.message() }</p>
+            <Link<Route> to={Route::Home}>{ "Choose a route instead" }</Link<Route>>
+        </div>
+    }
+}
+
+// Resolves a shortcut link and replaces it with the sailings page it leads to, so that going back skips it
+#[function_component(GoPage)]
+pub fn go_page_component() -> Html {
+    let location = use_location();
+    let history = use_history().expect("Expect history to be available");
+    let schedules_state = use_context::<SchedulesState>().expect("Expect schedules state to be available");
+    let navigation = use_context::<NavigationHistoryContext>().expect("Expect navigation history to be available");
+    let query = location
+        .and_then(|l| l.query::<GoQuery>().map_err(|e| error!("Invalid go query: {}", e)).ok())
+        .unwrap_or(GoQuery { from: None, to: None, when: None });
+    // The next sailing can't be found until the schedules load, but a failure to load still leads to the route
+    let schedules_map = match &schedules_state {
+        SchedulesState::Init | SchedulesState::Loading => {
+            return html! {
+                <div class="alert alert-light border text-center">
+                    <div class="spinner-border" role="status"/>
+                    <div>{ "Loading schedules..." }</div>
+                </div>
+            }
+        }
+        SchedulesState::Failed => None,
+        SchedulesState::Loaded(schedules_map) => Some(schedules_map),
+    };
+    let now = now_vancouver();
+    let home_city = load_or_default::<HomeCityPreference>(&*default_storage()).city;
+    let result = resolve_intent(
+        query.from.as_deref(),
+        query.to.as_deref(),
+        query.when.as_deref(),
+        home_city,
+        &navigation.history,
+        now,
+        schedules_map.map(|m| &**m),
+    );
+    match result {
+        Ok(target) => {
+            let date = (target.date != now.date()).then_some(target.date);
+            history
+                .replace_with_query(Route::Sailings, SailingsQuery { from: Some(target.from), to: target.to, date })
+                .expect("Expect history to replace");
+            if let Some((terminal_pair, depart_time)) = target.sailing {
+                replace_location_hash(&sailing_anchor_id(terminal_pair, depart_time));
+            }
+            html! {}
+        }
+        Err(error) => intent_error_html(&error),
+    }
+}
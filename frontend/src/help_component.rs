@@ -0,0 +1,58 @@
+use crate::help_content::*;
+use crate::prelude::*;
+use crate::types::*;
+
+#[derive(PartialEq, Properties)]
+pub struct HelpProps {
+    pub slug: String,
+}
+
+// The page body is rendered from bundled Markdown, so it is trusted to be inserted as HTML
+fn body_html(page: &HelpPage) -> Html {
+    let elem = web_sys::window()
+        .and_then(|w| w.document())
+        .expect("Expect document to be available")
+        .create_element("div")
+        .expect("Expect div element to be created");
+    elem.set_inner_html(&page.body_html());
+    Html::VRef(elem.into())
+}
+
+pub fn help_link_html(page: &HelpPage) -> Html {
+    html! {
+        <Link<Route> to={ Route::Help { slug: page.slug.clone() } }>{ &page.title }</Link<Route>>
+    }
+}
+
+#[function_component(HelpIndex)]
+pub fn help_index_component() -> Html {
+    html! { <>
+        <h1 class="display-6 mb-3 small">{ "Help" }</h1>
+        <dl>
+            { for HELP_PAGES.iter().map(|page| html! { <>
+                <dt>{ help_link_html(page) }</dt>
+                <dd>{ &page.summary }</dd>
+            </> }) }
+        </dl>
+    </> }
+}
+
+#[function_component(Help)]
+pub fn help_component(props: &HelpProps) -> Html {
+    match HelpPage::find(&props.slug) {
+        Some(page) => html! { <>
+            <h1 class="display-6 mb-3 small">{ &page.title }</h1>
+            <div class="help-body">{ body_html(page) }</div>
+            <p>
+                <Link<Route> to={ Route::HelpIndex }>{ "More help" }</Link<Route>>
+            </p>
+        </> },
+        None => html! {
+            <div class="alert alert-warning text-center" role="alert">
+                { "There is no help page here. " }
+                <Link<Route> to={ Route::HelpIndex }>{ "See all help pages" }</Link<Route>>
+                { "." }
+            </div>
+        },
+    }
+}
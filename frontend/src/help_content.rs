@@ -0,0 +1,69 @@
+use pulldown_cmark::{html, Parser};
+
+use crate::prelude::*;
+
+#[derive(Debug, Deserialize)]
+pub struct HelpPage {
+    pub slug: String,
+    pub title: String,
+    pub summary: String,
+    // Routes that include any of these terminals link to the page
+    pub terminals: HashSet<Terminal>,
+    // Notes in these categories link to the page
+    pub note_categories: Vec<NoteCategory>,
+    // Markdown
+    pub body: String,
+}
+
+#[derive(Deserialize)]
+struct HelpContent {
+    pages: Vec<HelpPage>,
+}
+
+// Bundled at build time, so help text is edited as content rather than in components
+pub static HELP_PAGES: Lazy<Vec<HelpPage>> = Lazy::new(|| {
+    toml::from_str::<HelpContent>(include_str!("../content/help.toml")).expect("Expect help content to parse").pages
+});
+
+impl HelpPage {
+    pub fn find(slug: &str) -> Option<&'static HelpPage> {
+        HELP_PAGES.iter().find(|page| page.slug == slug)
+    }
+
+    pub fn for_area_pair(area_pair: AreaPair) -> impl Iterator<Item = &'static HelpPage> {
+        HELP_PAGES.iter().filter(move |page| area_pair.includes_any_terminal(&page.terminals))
+    }
+
+    pub fn for_note_category(category: NoteCategory) -> Option<&'static HelpPage> {
+        HELP_PAGES.iter().find(|page| page.note_categories.contains(&category))
+    }
+
+    pub fn body_html(&self) -> String {
+        let mut body_html = String::new();
+        html::push_html(&mut body_html, Parser::new(&self.body));
+        body_html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_pages() -> Result<()> {
+        assert!(!HELP_PAGES.is_empty());
+        for page in HELP_PAGES.iter() {
+            assert_eq!(
+                HELP_PAGES.iter().filter(|p| p.slug == page.slug).count(),
+                1,
+                "Expect unique slug {:?}",
+                page.slug
+            );
+            assert!(page.body_html().starts_with("<p>"), "Expect {:?} body to render", page.slug);
+        }
+        assert!(HelpPage::find("thru-fares").is_some());
+        assert!(HelpPage::for_area_pair(AreaPair { from: Area::Victoria, to: Area::Vancouver })
+            .any(|p| p.slug == "reservations"));
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+use crate::navigation_history::*;
+use crate::prelude::*;
+use crate::sailings_processor::*;
+use crate::travel_time::*;
+
+// Links like `/go?from=home-terminal&when=next`, for phone shortcuts and widgets that open the sailings page in one tap.
+// They name places and times relative to the user, which are resolved against their preferences, the sailings pages
+// they have visited, and the schedules.
+//
+// - `from` and `to`: an area (e.g. `Victoria`) or terminal code (e.g. `SWB`), or `home-terminal` for the terminal
+//   nearest the home city.  Without `to`, the destination last viewed from `from` is used.
+// - `when`: `next` (the default) for the next sailing, `today`, `tomorrow`, or a date as `YYYY-MM-DD`.
+
+const HOME_TERMINAL: &str = "home-terminal";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum IntentPlace {
+    HomeTerminal,
+    Area(Area),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum IntentWhen {
+    Next,
+    Date(Date),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IntentError {
+    MissingFrom,
+    UnknownPlace(String),
+    UnknownWhen(String),
+    NoHomeCity,
+}
+
+// Where a link leads: the sailings page for the areas and date, at the sailing if there is one to jump to
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IntentTarget {
+    pub from: Area,
+    // Missing if there is no destination to go by, in which case the page asks for one
+    pub to: Option<Area>,
+    pub date: Date,
+    // The sailing's route and departure time, which together identify its row
+    pub sailing: Option<(TerminalPair, Time)>,
+}
+
+impl IntentError {
+    pub fn message(&self) -> String {
+        match self {
+            IntentError::MissingFrom => "The link does not say where to travel from.".to_string(),
+            IntentError::UnknownPlace(text) => format!("The link's place {:?} is not a terminal or area.", text),
+            IntentError::UnknownWhen(text) => format!("The link's time {:?} is not recognized.", text),
+            IntentError::NoHomeCity => {
+                "The link goes from your home terminal, but no home city is set. Choose one at the bottom of the page."
+                    .to_string()
+            }
+        }
+    }
+}
+
+fn parse_place(text: &str) -> StdResult<IntentPlace, IntentError> {
+    if text.eq_ignore_ascii_case(HOME_TERMINAL) {
+        return Ok(IntentPlace::HomeTerminal);
+    }
+    Area::iter()
+        .find(|area| area.to_string().eq_ignore_ascii_case(text))
+        .or_else(|| Terminal::iter().find(|terminal| terminal.to_string().eq_ignore_ascii_case(text)).map(|t| t.area()))
+        .map(IntentPlace::Area)
+        .ok_or_else(|| IntentError::UnknownPlace(text.to_string()))
+}
+
+fn parse_when(text: Option<&str>, today: Date) -> StdResult<IntentWhen, IntentError> {
+    match text.map(str::to_lowercase).as_deref() {
+        None | Some("next") => Ok(IntentWhen::Next),
+        Some("today") => Ok(IntentWhen::Date(today)),
+        Some("tomorrow") => {
+            today.next_day().map(IntentWhen::Date).ok_or_else(|| IntentError::UnknownWhen("tomorrow".into()))
+        }
+        Some(date_text) => {
+            parse_iso8601_date(date_text).map(IntentWhen::Date).map_err(|_| IntentError::UnknownWhen(date_text.into()))
+        }
+    }
+}
+
+// The area of the terminal with the shortest drive from the home city
+fn home_terminal_area(home_city: Option<HomeCity>) -> StdResult<Area, IntentError> {
+    let home_city = home_city.ok_or(IntentError::NoHomeCity)?;
+    Terminal::iter()
+        .filter_map(|terminal| home_city.drive_time(terminal).map(|drive_time| (terminal, drive_time)))
+        .min_by_key(|(_, drive_time)| *drive_time)
+        .map(|(terminal, _)| terminal.area())
+        .ok_or(IntentError::NoHomeCity)
+}
+
+fn resolve_place(place: IntentPlace, home_city: Option<HomeCity>) -> StdResult<Area, IntentError> {
+    match place {
+        IntentPlace::HomeTerminal => home_terminal_area(home_city),
+        IntentPlace::Area(area) => Ok(area),
+    }
+}
+
+// Sailings pages viewed from the area, most recent first
+fn recent_destination(from: Area, history: &NavigationHistory) -> Option<Area> {
+    history
+        .current()
+        .copied()
+        .into_iter()
+        .chain(history.recent_entries(usize::MAX))
+        .find(|entry| entry.area_pair.from == from)
+        .map(|entry| entry.area_pair.to)
+}
+
+// `now` is in Vancouver time.  Without schedules, such as when they failed to load, the next sailing is not known, so
+// the link goes to today's sailings.
+pub fn resolve_intent(
+    from: Option<&str>,
+    to: Option<&str>,
+    when: Option<&str>,
+    home_city: Option<HomeCity>,
+    history: &NavigationHistory,
+    now: OffsetDateTime,
+    schedules_map: Option<&HashMap<TerminalPair, Vec<Schedule>>>,
+) -> StdResult<IntentTarget, IntentError> {
+    let today = now.date();
+    let from = resolve_place(parse_place(from.ok_or(IntentError::MissingFrom)?)?, home_city)?;
+    let to = match to {
+        Some(text) => Some(resolve_place(parse_place(text)?, home_city)?),
+        None => recent_destination(from, history),
+    }
+    .filter(|&to| AreaPair { from, to }.validate().is_ok());
+    let date = match parse_when(when, today)? {
+        IntentWhen::Date(date) => return Ok(IntentTarget { from, to, date, sailing: None }),
+        IntentWhen::Next => today,
+    };
+    // After the last sailing of the day, the next one is tomorrow's first
+    let next_sailing = to.zip(schedules_map).and_then(|(to, schedules_map)| {
+        let area_pair = AreaPair { from, to };
+        next_sailing(area_pair, today, now.time(), schedules_map).map(|sailing| (today, sailing)).or_else(|| {
+            let tomorrow = today.next_day()?;
+            next_sailing(area_pair, tomorrow, Time::MIDNIGHT, schedules_map).map(|sailing| (tomorrow, sailing))
+        })
+    });
+    Ok(match next_sailing {
+        Some((date, sailing)) => IntentTarget { from, to, date, sailing: Some(sailing) },
+        None => IntentTarget { from, to, date, sailing: None },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(terminal_pair: TerminalPair, depart_times: &[Time]) -> Schedule {
+        Schedule {
+            items: depart_times
+                .iter()
+                .map(|&depart_time| ScheduleItem {
+                    sailing: Sailing { depart_time, arrive_time: depart_time + Duration::minutes(95), stops: vec![] },
+                    weekdays: HashMap::from([
+                        (Weekday::Wednesday, DateRestriction::All),
+                        (Weekday::Thursday, DateRestriction::All),
+                    ]),
+                    notes: HashMap::new(),
+                    note_categories: HashMap::new(),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                })
+                .collect(),
+            ..Schedule::test_fixture(terminal_pair)
+        }
+    }
+
+    #[test]
+    fn test_resolve_intent() -> Result<()> {
+        let to_vancouver = TerminalPair { from: Terminal::SWB, to: Terminal::TSA };
+        let schedules_map = HashMap::from([(to_vancouver, vec![schedule(to_vancouver, &[time!(9:00), time!(17:00)])])]);
+        let now = date!(2022 - 06 - 15).with_time(time!(12:00)).assume_utc();
+        let history = NavigationHistory::default().apply(NavigationAction::Visit(NavigationEntry {
+            area_pair: AreaPair { from: Area::Victoria, to: Area::Vancouver },
+            date: None,
+        }));
+        let resolve = |from: Option<&str>, to: Option<&str>, when: Option<&str>, home_city, now| {
+            resolve_intent(from, to, when, home_city, &history, now, Some(&schedules_map))
+        };
+        assert_eq!(
+            resolve(Some(HOME_TERMINAL), None, Some("next"), Some(HomeCity::Sidney), now),
+            Ok(IntentTarget {
+                from: Area::Victoria,
+                to: Some(Area::Vancouver),
+                date: date!(2022 - 06 - 15),
+                sailing: Some((to_vancouver, time!(17:00)))
+            })
+        );
+        assert_eq!(
+            resolve(
+                Some("swb"),
+                Some("Vancouver"),
+                None,
+                None,
+                date!(2022 - 06 - 15).with_time(time!(18:00)).assume_utc()
+            )
+            .map(|t| (t.date, t.sailing)),
+            Ok((date!(2022 - 06 - 16), Some((to_vancouver, time!(9:00)))))
+        );
+        assert_eq!(
+            resolve(Some("Victoria"), Some("TSA"), Some("tomorrow"), None, now).map(|t| (t.date, t.sailing)),
+            Ok((date!(2022 - 06 - 16), None))
+        );
+        // Without a recent destination, the page asks for one
+        assert_eq!(resolve(Some("Vancouver"), None, None, None, now).map(|t| t.to), Ok(None));
+        assert_eq!(resolve(Some(HOME_TERMINAL), None, None, None, now), Err(IntentError::NoHomeCity));
+        assert_eq!(resolve(None, None, None, None, now), Err(IntentError::MissingFrom));
+        assert_eq!(resolve(Some("Atlantis"), None, None, None, now), Err(IntentError::UnknownPlace("Atlantis".into())));
+        assert_eq!(
+            resolve(Some("Victoria"), None, Some("soon"), None, now),
+            Err(IntentError::UnknownWhen("soon".into()))
+        );
+        Ok(())
+    }
+}
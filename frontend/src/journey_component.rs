@@ -0,0 +1,249 @@
+use crate::date_format::*;
+use crate::feature_flags::*;
+use crate::prelude::*;
+use crate::sailings_processor::*;
+use crate::types::*;
+use crate::utils::*;
+
+fn journey_terminals() -> Vec<Terminal> {
+    Terminal::iter().sorted_by_key(|t| (t.area().long_name(), t.name())).collect()
+}
+
+fn terminal_select_html(id: &'static str, selected: Option<Terminal>, onchange: Callback<Option<Terminal>>) -> Html {
+    let onchange = Callback::from(move |e: Event| {
+        let index = e.target_unchecked_into::<web_sys::HtmlSelectElement>().selected_index();
+        // The first option is for no terminal
+        let terminal = usize::try_from(index)
+            .ok()
+            .and_then(|i| i.checked_sub(1))
+            .and_then(|i| journey_terminals().get(i).copied());
+        onchange.emit(terminal);
+    });
+    html! {
+        <select {id} class="form-select" {onchange}>
+            <option selected={ selected.is_none() }>{ "Select a terminal" }</option>
+            { for journey_terminals().into_iter().map(|terminal| html! {
+                <option selected={ Some(terminal) == selected }>
+                    { format!("{} ({})", terminal.area().long_name(), terminal.name()) }
+                </option>
+            })}
+        </select>
+    }
+}
+
+fn journey_leg_html(leg: &JourneyLeg, date: Date) -> Html {
+    let TerminalPair { from, to } = leg.terminal_pair;
+    html! {
+        <li>
+            <a href={ sailings_page_url(leg.terminal_pair.area_pair(), date) }>
+                { format!("{} {} to {}", format_time(leg.sailing.depart_time), from.name(), to.name()) }
+            </a>
+            { format!(", arriving {}", format_time(leg.sailing.arrive_time)) }
+        </li>
+    }
+}
+
+fn journey_connection_html(arrived: &JourneyLeg, next: &JourneyLeg) -> Html {
+    let wait = next.sailing.depart_time - arrived.sailing.arrive_time;
+    let text = match transfer_drive_time(arrived.terminal_pair.to, next.terminal_pair.from) {
+        Some(drive_time) => format!(
+            "Drive to {} (about {}), arriving {} before the next sailing",
+            next.terminal_pair.from.name(),
+            format_duration(drive_time),
+            format_duration(wait - drive_time)
+        ),
+        None => {
+            format!("Connect at {} with {} between sailings", arrived.terminal_pair.to.name(), format_duration(wait))
+        }
+    };
+    html! { <li class="text-muted list-unstyled small">{ text }</li> }
+}
+
+fn journey_html(journey: &Journey, date: Date) -> Html {
+    let duration = journey.arrive_time() - journey.depart_time();
+    html! {
+        <div class="card mb-3">
+            <div class="card-body">
+                <h5 class="card-title">
+                    { format!("{} to {}", format_time(journey.depart_time()), format_time(journey.arrive_time())) }
+                    <small class="text-muted">
+                        { format!(" {}, {} sailings", format_duration(duration), journey.legs.len()) }
+                    </small>
+                </h5>
+                <ol class="mb-0">
+                    { journey_leg_html(&journey.legs[0], date) }
+                    { for journey.legs.iter().tuple_windows().map(|(arrived, next)| html! { <>
+                        { journey_connection_html(arrived, next) }
+                        { journey_leg_html(next, date) }
+                    </> }) }
+                </ol>
+            </div>
+        </div>
+    }
+}
+
+fn route_link_html(terminal_pair: TerminalPair, date: Date) -> Html {
+    let TerminalPair { from, to } = terminal_pair;
+    html! {
+        <a href={ sailings_page_url(terminal_pair.area_pair(), date) }>{ format!("{} to {}", from.name(), to.name()) }</a>
+    }
+}
+
+fn journey_gap_html(gap: &JourneyGap, date: Date) -> Html {
+    match *gap {
+        JourneyGap::NoSchedule { terminal_pair, period_end, next_period_from } => {
+            let period_text = match (period_end, next_period_from) {
+                (Some(end), Some(from)) => format!(
+                    ": its schedule ends {} and the next one starts {}",
+                    DATE_FORMATTER.short_date(end),
+                    DATE_FORMATTER.short_date(from)
+                ),
+                (Some(end), None) => format!(
+                    ": its current schedule ends {}, and BC Ferries hasn't published the next one yet",
+                    DATE_FORMATTER.short_date(end)
+                ),
+                (None, Some(from)) => format!(": its schedule starts {}", DATE_FORMATTER.short_date(from)),
+                (None, None) => String::new(),
+            };
+            html! { <>
+                { route_link_html(terminal_pair, date) }
+                { format!(" has no schedule for this date{}.", period_text) }
+            </> }
+        }
+        JourneyGap::NoService { terminal_pair } => html! { <>
+            { route_link_html(terminal_pair, date) }
+            { " has no sailings on this date." }
+        </> },
+        JourneyGap::MissedConnection { arrived, next, earliest_arrive_time, last_depart_time } => html! { <>
+            { "The earliest arrival from " }
+            { route_link_html(arrived, date) }
+            { format!(" is {}, but the last sailing from ", format_time(earliest_arrive_time)) }
+            { route_link_html(next, date) }
+            { format!(" leaves at {}, too soon to connect.", format_time(last_depart_time)) }
+        </> },
+    }
+}
+
+fn journeys_html(
+    query: &JourneyQuery,
+    date: Date,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+    feature_flags: &FeatureFlags,
+) -> Html {
+    let (from, to) = match (query.from, query.to) {
+        (Some(from), Some(to)) => (from, to),
+        _ => return html! { <p>{ "Select the terminals to travel between." }</p> },
+    };
+    if from == to {
+        return html! { <p>{ "Select two different terminals." }</p> };
+    }
+    let journeys = find_journeys(from, to, date, schedules_map);
+    if journeys.is_empty() {
+        let area_pair = AreaPair { from: from.area(), to: to.area() };
+        let gaps = if feature_flags.is_enabled(Feature::JourneyGapExplanations, Some(area_pair)) {
+            explain_missing_journeys(from, to, date, schedules_map)
+        } else {
+            vec![]
+        };
+        return html! {
+            <div class="alert alert-warning" role="alert">
+                <p class={ classes!("text-center", gaps.is_empty().then(|| "mb-0")) }>
+                    { "There are no journeys between these terminals on this date that connect with at most three sailings." }
+                </p>
+                { if gaps.is_empty() { html! {} } else { html! {
+                    <ul class="mb-0">
+                        { for gaps.iter().map(|gap| html! { <li>{ journey_gap_html(gap, date) }</li> }) }
+                    </ul>
+                }}}
+            </div>
+        };
+    }
+    html! { <>
+        <p class="text-muted small">
+            { format!(
+                "Connections allow at least {} between sailings, plus the drive between terminals on the same island. \
+                Check each sailing before you travel, since a late first sailing can mean missing the next.",
+                format_duration(MIN_TRANSFER_TIME)
+            ) }
+        </p>
+        { for journeys.iter().map(|journey| journey_html(journey, date)) }
+    </> }
+}
+
+#[function_component(JourneyPlanner)]
+pub fn journey_planner_component() -> Html {
+    let location = use_location();
+    let history = use_history().expect("Expect history to be available");
+    let schedules_state = use_context::<SchedulesState>().expect("Expect schedules state to be available");
+    let feature_flags = use_context::<FeatureFlags>().expect("Expect feature flags to be available");
+    let today = today_vancouver();
+    let query = location
+        .and_then(|l| l.query::<JourneyQuery>().map_err(|e| error!("Invalid journey query: {}", e)).ok())
+        .unwrap_or(JourneyQuery { from: None, to: None, date: None });
+    let date = query.date.filter(|date| *date >= today).unwrap_or(today);
+    let push_query = move |query: JourneyQuery| {
+        history.push_with_query(Route::JourneyPlanner, query).expect("Expect history to push")
+    };
+    let onchange_from = {
+        let (push_query, query) = (push_query.clone(), query.clone());
+        Callback::from(move |from| push_query(JourneyQuery { from, ..query.clone() }))
+    };
+    let onchange_to = {
+        let (push_query, query) = (push_query.clone(), query.clone());
+        Callback::from(move |to| push_query(JourneyQuery { to, ..query.clone() }))
+    };
+    let onchange_date = {
+        let query = query.clone();
+        Callback::from(move |e: Event| {
+            let date = parse_iso8601_date(e.target_unchecked_into::<HtmlInputElement>().value().trim())
+                .ok()
+                .filter(|date| *date > today);
+            push_query(JourneyQuery { date, ..query.clone() })
+        })
+    };
+    html! { <>
+        <h1 class="display-6 mb-3 small">
+            { "Journey planner" }
+            <small class="text-muted">{ " connecting sailings across routes" }</small>
+        </h1>
+        <div class="row g-2 mb-3">
+            <div class="col-md-5">
+                <label for="journey-from" class="form-label">{ "From" }</label>
+                { terminal_select_html("journey-from", query.from, onchange_from) }
+            </div>
+            <div class="col-md-5">
+                <label for="journey-to" class="form-label">{ "To" }</label>
+                { terminal_select_html("journey-to", query.to, onchange_to) }
+            </div>
+            <div class="col-md-2">
+                <label for="journey-date" class="form-label">{ "Date" }</label>
+                <input
+                    id="journey-date"
+                    type="date"
+                    class="form-control date-input"
+                    value={ format_iso8601_date(date) }
+                    min={ format_iso8601_date(today) }
+                    onchange={ onchange_date }/>
+            </div>
+        </div>
+        { match &schedules_state {
+            SchedulesState::Init | SchedulesState::Loading => html! {
+                <div class="alert alert-light border text-center">
+                    <div class="spinner-border" role="status"/>
+                    <div>{ "Loading schedules..." }</div>
+                </div>
+            },
+            SchedulesState::Failed => html! {
+                <div class="alert alert-danger text-center" role="alert">
+                    { "There was a problem loading the ferry schedules; please refresh your browser to try again." }
+                </div>
+            },
+            SchedulesState::Loaded(schedules_map) => html! {
+                <>
+                    <h5>{ DATE_FORMATTER.long_date(date) }</h5>
+                    { journeys_html(&query, date, schedules_map, &feature_flags) }
+                </>
+            },
+        }}
+    </> }
+}
@@ -0,0 +1,78 @@
+use crate::prelude::*;
+
+// Data fetched in the background while a page is open.  Each source fails independently of the others, and none of
+// them are needed to show the schedules that already loaded.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LiveSource {
+    ScheduleUpdates,
+}
+
+impl LiveSource {
+    pub fn description(self) -> &'static str {
+        match self {
+            LiveSource::ScheduleUpdates => "schedule updates",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SourceHealth {
+    pub last_succeeded_at: Option<OffsetDateTime>,
+    // Reset by the next success, so one slow request does not keep the source marked unavailable
+    pub consecutive_failures: u32,
+}
+
+// How each live source has fared, so pages can say which data is unavailable while still showing what they have
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LiveDataHealth {
+    sources: HashMap<LiveSource, SourceHealth>,
+}
+
+impl LiveDataHealth {
+    pub fn succeeded(&self, source: LiveSource, at: OffsetDateTime) -> LiveDataHealth {
+        let mut sources = self.sources.clone();
+        sources.insert(source, SourceHealth { last_succeeded_at: Some(at), consecutive_failures: 0 });
+        LiveDataHealth { sources }
+    }
+
+    pub fn failed(&self, source: LiveSource) -> LiveDataHealth {
+        let mut sources = self.sources.clone();
+        sources.entry(source).or_default().consecutive_failures += 1;
+        LiveDataHealth { sources }
+    }
+
+    pub fn unavailable_sources(&self) -> Vec<(LiveSource, SourceHealth)> {
+        self.sources
+            .iter()
+            .filter(|(_, health)| health.consecutive_failures > 0)
+            .map(|(source, health)| (*source, *health))
+            .sorted_by_key(|(source, _)| source.description())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_data_health() -> Result<()> {
+        let loaded_at = OffsetDateTime::UNIX_EPOCH;
+        let health = LiveDataHealth::default().succeeded(LiveSource::ScheduleUpdates, loaded_at);
+        assert!(health.unavailable_sources().is_empty());
+        let health = health.failed(LiveSource::ScheduleUpdates).failed(LiveSource::ScheduleUpdates);
+        assert_eq!(
+            health.unavailable_sources(),
+            [(
+                LiveSource::ScheduleUpdates,
+                SourceHealth { last_succeeded_at: Some(loaded_at), consecutive_failures: 2 }
+            )]
+        );
+        let refreshed_at = loaded_at + Duration::minutes(30);
+        let health = health.succeeded(LiveSource::ScheduleUpdates, refreshed_at);
+        assert!(health.unavailable_sources().is_empty());
+        let health = health.failed(LiveSource::ScheduleUpdates);
+        assert_eq!(health.unavailable_sources()[0].1.last_succeeded_at, Some(refreshed_at));
+        Ok(())
+    }
+}
@@ -1,15 +1,71 @@
-mod imports;
+mod about_data_component;
+mod announcer;
+mod area_combobox;
+mod checklist;
+mod countdown_component;
+mod date_format;
+mod day_pack_component;
+mod departures_component;
+mod diagnostics;
+mod expired_data_component;
+mod feature_flags;
+mod go_component;
+mod help_component;
+mod help_content;
+mod intents;
+mod journey_component;
+mod live_data;
+mod navigation_history;
+mod note_renderer;
+mod offline_pin_component;
+mod pinned_routes;
+mod prelude;
+mod qr_code;
+mod reservation_hints;
+mod revalidation;
+mod route_map;
 mod sailings_component;
+mod sailings_model;
 mod sailings_processor;
+mod sailings_summary;
+mod saved_trips;
+mod schedules_compaction;
+mod schedules_loader;
+mod sparkline;
+mod storage;
+mod structured_data;
+mod travel_time;
+mod trip_digest_component;
 mod types;
 mod utils;
+mod view_state;
+mod wake_lock;
 
-use reqwasm::http;
-
-use crate::imports::*;
+use crate::about_data_component::*;
+use crate::area_combobox::*;
+use crate::date_format::*;
+use crate::day_pack_component::*;
+use crate::departures_component::*;
+use crate::diagnostics::*;
+use crate::expired_data_component::*;
+use crate::feature_flags::*;
+use crate::go_component::*;
+use crate::help_component::*;
+use crate::journey_component::*;
+use crate::live_data::*;
+use crate::navigation_history::*;
+use crate::pinned_routes::*;
+use crate::prelude::*;
 use crate::sailings_component::*;
+use crate::sailings_processor::*;
+use crate::schedules_loader::*;
+use crate::sparkline::*;
+use crate::storage::*;
+use crate::travel_time::*;
+use crate::trip_digest_component::*;
 use crate::types::*;
 use crate::utils::*;
+use crate::view_state::*;
 
 #[function_component(Navbar)]
 fn navbar_component() -> Html {
@@ -43,6 +99,11 @@ fn navbar_component() -> Html {
 }
 
 fn select_from_area_html(query: &SailingsQuery) -> Html {
+    // Only offer departure areas with a route to the arrival area, if one is already selected
+    let options: Vec<_> = Area::iter()
+        .filter(|&from| query.to.map(|to| AreaPair { from, to }.validate().is_ok()).unwrap_or(true))
+        .map(|from| (from, SailingsQuery { from: Some(from), ..*query }))
+        .collect();
     html! { <>
         <p class="mt-3">
             { if query.to.is_none() {
@@ -51,28 +112,60 @@ fn select_from_area_html(query: &SailingsQuery) -> Html {
                 "Select your departure area:"
             }}
         </p>
-        <ul>
-            { for Area::iter().map(|from| html!{
-                <li>
-                    <strong>{ area_link_html(from, SailingsQuery{from: Some(from), ..*query}) }</strong>
-                </li>
-            })}
-        </ul>
+        <AreaCombobox id="from-area" label="Depart from" {options}/>
     </> }
 }
 
+// Number of days shown in the sailing density sparkline for each route
+const SAILING_DENSITY_DAYS: usize = 14;
+
+#[derive(Eq, PartialEq, Properties)]
+struct SailingDensityProps {
+    area_pair: AreaPair,
+}
+
+#[function_component(SailingDensity)]
+fn sailing_density_component(props: &SailingDensityProps) -> Html {
+    // Counts are only computed once schedules are loaded, and only for the routes being listed
+    match use_context::<SchedulesState>().expect("Expect schedules state to be available") {
+        SchedulesState::Loaded(schedules_map) => {
+            let counts =
+                area_daily_sailing_counts(props.area_pair, today_vancouver(), SAILING_DENSITY_DAYS, &schedules_map);
+            let values: Vec<_> = counts
+                .into_iter()
+                .map(|(date, count)| SparklineValue {
+                    label: format!("{}: {} sailings", DATE_FORMATTER.short_date(date), count),
+                    value: count,
+                    starts_group: DATE_FORMATTER.is_first_day_of_week(date),
+                })
+                .collect();
+            html! {
+                <span class="ms-2 text-muted" title="Sailings per day over the next two weeks">
+                    { sparkline_html(&values, "sailing-density") }
+                </span>
+            }
+        }
+        _ => html! {},
+    }
+}
+
 fn select_to_area_html(from: Area, query: &SailingsQuery) -> Html {
-    let mut to_areas: Vec<Area> = ALL_AREA_PAIRS.iter().filter(|ap| ap.from == from).map(|ap| ap.to).collect();
-    to_areas.sort_unstable();
+    let from_options: Vec<_> = Area::iter()
+        .map(|from| {
+            let to = query.to.filter(|&to| AreaPair { from, to }.validate().is_ok());
+            (from, SailingsQuery { from: Some(from), to, ..*query })
+        })
+        .collect();
+    let to_options: Vec<_> = ALL_AREA_PAIRS
+        .iter()
+        .filter(|ap| ap.from == from)
+        .map(|ap| (ap.to, SailingsQuery { to: Some(ap.to), ..*query }))
+        .collect();
+    let density = Callback::from(move |to: Area| html! { <SailingDensity area_pair={AreaPair{from, to}}/> });
     html! { <>
+        <AreaCombobox id="from-area" label="Depart from" options={from_options} selected={from}/>
         <p class="mt-3">{ "Select your arrival area:" }</p>
-        <ul>
-            { for to_areas.iter().map(|&to| html! {
-                    <li>
-                        <strong>{ area_link_html(to, SailingsQuery{to: Some(to), ..*query}) }</strong>
-                    </li>
-            }) }
-        </ul>
+        <AreaCombobox id="to-area" label="Arrive at" options={to_options} option_suffix={density}/>
     </> }
 }
 
@@ -85,7 +178,15 @@ fn home_html() -> Html {
         <p class="lead">
             { "An easy to use and understand presentation of the BC Ferries schedules for the Southern Gulf Islands, Victoria, and Vancouver. Just select your locations and date, and you're shown the sailings for that day."}
         </p>
+        { trip_digest_banner_html() }
         { select_from_area_html(&SailingsQuery::new()) }
+        <p>
+            { "Travelling across several routes in one day? Print a " }
+            <Link<Route> to={Route::DayPack}>{ "travel day pack" }</Link<Route>>
+            { " with every route's sailings, or " }
+            <Link<Route> to={Route::JourneyPlanner}>{ "plan a journey" }</Link<Route>>
+            { " that connects between them." }
+        </p>
         <div class="p-2 bg-light border rounded">
             <div><strong>{ "Do not rely on this site as your only source of schedule information!" }</strong></div>
             <div>
@@ -98,13 +199,53 @@ fn home_html() -> Html {
     </> }
 }
 
+// Number of nearby routes suggested when the selected areas are not a route
+const INVALID_ROUTE_ALTERNATIVES: usize = 3;
+
+fn invalid_route_html(area_pair: AreaPair, invalid_route: InvalidRoute, date: Option<Date>) -> Html {
+    let alternatives = area_pair.nearest_valid_alternatives(INVALID_ROUTE_ALTERNATIVES);
+    html! {
+        <div class="alert alert-warning" role="alert">
+            { format!(
+                "There are no sailings from {} to {} because {}.",
+                area_pair.from.long_name(),
+                area_pair.to.long_name(),
+                invalid_route
+            ) }
+            { if alternatives.is_empty() { html! {} } else { html! {
+                <div class="mt-1">
+                    { "Nearby routes: " }
+                    { for alternatives.into_iter().enumerate().map(|(index, ap)| html! { <>
+                        { if index > 0 { ", " } else { "" } }
+                        <Link<Route, SailingsQuery>
+                            to={Route::Sailings}
+                            query={SailingsQuery { from: Some(ap.from), to: Some(ap.to), date }}
+                        >
+                            { format!("{} to {}", ap.from.long_name(), ap.to.long_name()) }
+                        </Link<Route, SailingsQuery>>
+                    </> }) }
+                </div>
+            }}}
+        </div>
+    }
+}
+
 #[function_component(SailingsPage)]
 fn sailings_page_component() -> Html {
     let location = use_location();
     let history = use_history().expect("Expect history to be available");
-    let query = location
-        .and_then(|l| l.query().map_err(|e| error!("Invalid sailings query: {}", e)).ok())
-        .unwrap_or_else(SailingsQuery::new);
+    let view_state = location
+        .as_ref()
+        .and_then(|l| l.query::<ViewQuery>().ok())
+        .and_then(|q| q.view)
+        .and_then(|view| ViewState::decode(&view).map_err(|e| error!("{:?}", e)).ok());
+    let query = match view_state {
+        Some(view_state) => view_state.sailings_query(),
+        None => location
+            .and_then(|l| l.query().map_err(|e| error!("Invalid sailings query: {}", e)).ok())
+            .unwrap_or_else(SailingsQuery::new),
+    };
+    let modes = view_state.map(|view_state| view_state.modes);
     if query.is_empty() {
         history.replace(Route::Home);
         return html! {};
@@ -123,7 +264,7 @@ fn sailings_page_component() -> Html {
                 html! {}
             }}
             { match query.to {
-                Some(to) if query.from.map(|from| ALL_AREA_PAIRS.contains(&AreaPair{ from, to })).unwrap_or(true) => html! {
+                Some(to) if query.from.map(|from| AreaPair{ from, to }.validate().is_ok()).unwrap_or(true) => html! {
                     <div>
                         { "To " }
                         <strong>{ to.long_name() }</strong>
@@ -135,12 +276,14 @@ fn sailings_page_component() -> Html {
         { match query {
             SailingsQuery { from: None, .. } => select_from_area_html(&query),
             SailingsQuery { from: Some(from), to: None, .. } => select_to_area_html(from, &query),
-            SailingsQuery { from: Some(from), to: Some(to), date } => {
-                if ALL_AREA_PAIRS.contains(&AreaPair { from, to }) { html! {
-                    <Sailings area_pair={AreaPair{from, to}} {date}/>
-                }} else {
-                    select_to_area_html(from, &query)
-                }
+            SailingsQuery { from: Some(from), to: Some(to), date } => match (AreaPair { from, to }).validate() {
+                Ok(()) => html! {
+                    <Sailings area_pair={AreaPair{from, to}} {date} {modes}/>
+                },
+                Err(invalid_route) => html! { <>
+                    { invalid_route_html(AreaPair { from, to }, invalid_route, date) }
+                    { select_to_area_html(from, &query) }
+                </> },
             }
         }}
     </> }
@@ -160,6 +303,14 @@ fn switch_route(route: &Route) -> Html {
     match route {
         Route::Home => home_html(),
         Route::Sailings => html! { <SailingsPage/> },
+        Route::DayPack => html! { <DayPack/> },
+        Route::JourneyPlanner => html! { <JourneyPlanner/> },
+        Route::Departures { terminal } => html! { <DepartureBoard terminal={ *terminal }/> },
+        Route::AboutData => html! { <AboutData/> },
+        Route::TripDigest => html! { <TripDigest/> },
+        Route::Go => html! { <GoPage/> },
+        Route::HelpIndex => html! { <HelpIndex/> },
+        Route::Help { slug } => html! { <Help slug={ slug.clone() }/> },
         Route::NotFound => not_found_html(),
     }
 }
@@ -177,30 +328,305 @@ fn footer_html() -> Html {
                 { ". " }
                 { "Send feedback to " }
                 <a class="link-dark" href="mailto:emanuel@borsboom.io" target="_blank">{ "emanuel@borsboom.io" }</a>
+                { ". " }
+                <Link<Route> classes="link-dark" to={Route::AboutData}>{ "About this data" }</Link<Route>>
+                { ". " }
+                <Link<Route> classes="link-dark" to={Route::HelpIndex}>{ "Help" }</Link<Route>>
                 { "." }
             </div>
             <div class="text-muted">{ "This site is independently operated and is not affiliated with British Columbia Ferry Services Inc." }</div>
+            { data_channel_notice_html() }
+            { first_day_of_week_html() }
+            { home_city_html() }
+            { diagnostics_opt_in_html() }
+            { if cfg!(debug_assertions) { debug_menu_html() } else { html! {} }}
         </div>
     }
 }
 
-async fn fetch_json<T: DeserializeOwned>(url: &str) -> Result<T> {
-    let response = http::Request::get(url).send().await?;
-    let json = response.json::<T>().await?;
-    Ok(json)
+// Only shown on the preview channel, so nobody stays on it by accident
+fn data_channel_notice_html() -> Html {
+    let channel = current_data_channel();
+    if channel == DataChannel::Stable {
+        return html! {};
+    }
+    html! {
+        <div class="mt-1 d-print-none text-danger">
+            { format!("Showing {} schedule data, which may have mistakes. ", channel) }
+            <a class="link-danger" href={ format!("?data-channel={}", DataChannel::Stable) }>{ "Switch back to stable data" }</a>
+            { "." }
+        </div>
+    }
+}
+
+fn first_day_of_week_html() -> Html {
+    let preferences: DatePreferences = load_or_default(&*default_storage());
+    let options = [(None, "Default"), (Some(Weekday::Sunday), "Sunday"), (Some(Weekday::Monday), "Monday")];
+    let onchange = Callback::from(move |e: Event| {
+        let index = e.target_unchecked_into::<web_sys::HtmlSelectElement>().selected_index();
+        let first_day_of_week = usize::try_from(index).ok().and_then(|i| options.get(i)).and_then(|(day, _)| *day);
+        save(&*default_storage(), &DatePreferences { first_day_of_week }).unwrap_or_else(|err| error!("{:?}", err));
+        // The date formatter is only initialized once, so reload to apply the change
+        if let Some(window) = web_sys::window() {
+            window.location().reload().unwrap_or_else(|err| error!("Failed to reload: {:?}", err));
+        }
+    });
+    html! {
+        <div class="mt-1 d-print-none text-muted">
+            <label for="first-day-of-week">{ "Weeks start on " }</label>
+            <select id="first-day-of-week" class="form-select form-select-sm d-inline-block w-auto" {onchange}>
+                { for options.iter().map(|(day, name)| html! {
+                    <option selected={ *day == preferences.first_day_of_week }>{ name }</option>
+                })}
+            </select>
+        </div>
+    }
 }
 
-fn load_schedules_state(schedules_state: UseStateHandle<SchedulesState>) {
-    schedules_state.set(SchedulesState::Loading);
+fn home_city_html() -> Html {
+    let preferences: HomeCityPreference = load_or_default(&*default_storage());
+    let onchange = Callback::from(move |e: Event| {
+        let index = e.target_unchecked_into::<web_sys::HtmlSelectElement>().selected_index();
+        // The first option is for no home city
+        let city = usize::try_from(index).ok().and_then(|i| i.checked_sub(1)).and_then(|i| HomeCity::iter().nth(i));
+        save(&*default_storage(), &HomeCityPreference { city }).unwrap_or_else(|err| error!("{:?}", err));
+        // Sailings only check the preference when rendered, so reload to apply the change
+        if let Some(window) = web_sys::window() {
+            window.location().reload().unwrap_or_else(|err| error!("Failed to reload: {:?}", err));
+        }
+    });
+    html! {
+        <div class="mt-1 d-print-none text-muted">
+            <label for="home-city">{ "Suggest when to leave from " }</label>
+            <select id="home-city" class="form-select form-select-sm d-inline-block w-auto" {onchange}>
+                <option selected={ preferences.city.is_none() }>{ "Nowhere" }</option>
+                { for HomeCity::iter().map(|city| html! {
+                    <option selected={ Some(city) == preferences.city }>{ city.name() }</option>
+                })}
+            </select>
+        </div>
+    }
+}
+
+fn diagnostics_opt_in_html() -> Html {
+    let enabled = load_or_default::<DiagnosticsOptIn>(&*default_storage()).enabled;
+    let onchange = Callback::from(|e: Event| {
+        let enabled = e.target_unchecked_into::<HtmlInputElement>().checked();
+        save(&*default_storage(), &DiagnosticsOptIn { enabled }).unwrap_or_else(|err| error!("{:?}", err));
+    });
+    html! {
+        <div class="form-check mt-1 d-print-none text-muted">
+            <input class="form-check-input" type="checkbox" id="diagnostics-opt-in" checked={ enabled } {onchange}/>
+            <label class="form-check-label" for="diagnostics-opt-in">
+                { "Help find schedule data problems by sending anonymous reports when this site notices impossible schedule data" }
+            </label>
+        </div>
+    }
+}
+
+// Only shown in debug builds, for simulating failures while loading schedules, and for reminding maintainers of
+// hand-maintained content that is overdue for review
+fn debug_menu_html() -> Html {
+    let current_fault = current_load_fault();
+    let onchange = Callback::from(|e: Event| {
+        let index = e.target_unchecked_into::<web_sys::HtmlSelectElement>().selected_index();
+        let fault = usize::try_from(index).ok().and_then(|i| LoadFault::iter().nth(i)).unwrap_or_default();
+        save(&*default_storage(), &fault).unwrap_or_else(|err| error!("{:?}", err));
+        if let Some(window) = web_sys::window() {
+            window.location().reload().unwrap_or_else(|err| error!("Failed to reload: {:?}", err));
+        }
+    });
+    html! {
+        <div class="mt-2 d-print-none">
+            <label for="debug-load-fault" class="text-muted">{ "Debug: simulate schedules loading failure " }</label>
+            <select id="debug-load-fault" class="form-select form-select-sm d-inline-block w-auto" {onchange}>
+                { for LoadFault::iter().map(|fault| html! {
+                    <option selected={ fault == current_fault }>{ fault.description() }</option>
+                })}
+            </select>
+            <div class="text-muted">
+                { match time_provider() {
+                    TimeProvider::System => "Debug: add ?today=YYYY-MM-DD to the URL to simulate another day".to_string(),
+                    TimeProvider::Simulated { .. } => format!("Debug: simulating today as {}", format_iso8601_date(today_vancouver())),
+                }}
+            </div>
+            { for overdue_content_reviews(today_vancouver()).map(|review| html! {
+                <div class="text-danger">{ format!("Debug: {}", review.warning()) }</div>
+            }) }
+        </div>
+    }
+}
+
+// Delays before retrying schedules partitions that failed to load, in seconds; the last one repeats
+const PARTITION_RETRY_DELAYS_SECONDS: &[u32] = &[10, 30, 60, 300];
+
+// After this, the partitions are left for the next revalidation to pick up
+const PARTITION_RETRY_ATTEMPTS: usize = 8;
+
+// Each retried partition is dispatched on its own, so that it is merged onto whichever schedules are loaded by the time
+// it arrives
+fn retry_missing_partitions(
+    schedules_state: UseReducerHandle<SchedulesState>,
+    missing_partitions_state: UseReducerHandle<MissingPartitionsContext>,
+    missing_partitions: Vec<DataPartition>,
+) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let mut missing_partitions = missing_partitions;
+        for attempt in 0..PARTITION_RETRY_ATTEMPTS {
+            if missing_partitions.is_empty() {
+                break;
+            }
+            let delay_seconds = PARTITION_RETRY_DELAYS_SECONDS[min(attempt, PARTITION_RETRY_DELAYS_SECONDS.len() - 1)];
+            gloo_timers::future::TimeoutFuture::new(delay_seconds * 1000).await;
+            let mut still_missing = Vec::new();
+            for partition in missing_partitions {
+                match fetch_partition(current_data_channel(), &partition, true).await {
+                    Ok(partition_map) => {
+                        schedules_state.dispatch(SchedulesEvent::PartitionsLoaded(Rc::new(partition_map)));
+                        missing_partitions_state.dispatch(MissingPartitionsEvent::Retried(partition));
+                    }
+                    Err(err) => {
+                        warn!("Failed to retry schedules partition {:?}: {}", partition.file, err);
+                        still_missing.push(partition);
+                    }
+                }
+            }
+            missing_partitions = still_missing;
+        }
+    });
+}
+
+// The routes of the sailings page being opened, whose schedules are loaded before the rest
+fn viewed_terminal_pairs() -> Vec<TerminalPair> {
+    let pathname = web_sys::window().and_then(|window| window.location().pathname().ok()).unwrap_or_default();
+    let area = |name| query_param(name).and_then(|value| value.parse::<Area>().ok());
+    match (Route::recognize(&pathname), area("from"), area("to")) {
+        (Some(Route::Sailings), Some(from), Some(to)) => {
+            AREA_PAIR_TERMINAL_PAIRS.get(&AreaPair { from, to }).into_iter().flatten().copied().collect()
+        }
+        _ => vec![],
+    }
+}
+
+// Diagnostics and pinned routes need every route, so they wait until the whole schedules are loaded
+fn complete_schedules_map(schedules_map: &mut HashMap<TerminalPair, Vec<Schedule>>) {
+    report_data_issues(schedules_map);
+    apply_pinned_routes(&*default_storage(), schedules_map, today_vancouver(), now_utc());
+}
+
+// Loads the routes that weren't viewed when the page was opened, so that other pages have them by the time they are
+// visited.  If they fail to load, the whole schedules file is loaded instead.
+fn load_pending_route_files(
+    schedules_state: UseReducerHandle<SchedulesState>,
+    live_data_state: UseStateHandle<LiveDataHealth>,
+    viewed_schedules_map: Rc<HashMap<TerminalPair, Vec<Schedule>>>,
+    pending_route_files: Vec<DataRouteFile>,
+) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let channel = current_data_channel();
+        let result = match fetch_route_files(channel, &pending_route_files, LoadFault::None, false).await {
+            Ok(data) => {
+                let mut merged_map = (*viewed_schedules_map).clone();
+                merge_schedules(&mut merged_map, data.schedules_map);
+                Ok(merged_map)
+            }
+            Err(err) => {
+                warn!("Failed to load the rest of the routes, so loading all schedules: {}", err);
+                fetch_schedules(LoadFault::None, false, channel, &[]).await.map(|data| data.schedules_map)
+            }
+        };
+        match result {
+            Ok(mut schedules_map) => {
+                complete_schedules_map(&mut schedules_map);
+                // Ignored if revalidated schedules have replaced the viewed routes' in the meantime
+                schedules_state.dispatch(SchedulesEvent::RoutesLoaded {
+                    viewed: viewed_schedules_map,
+                    schedules: Rc::new(schedules_map),
+                });
+            }
+            // The viewed routes are still usable, and the next revalidation loads the rest
+            Err(err) => {
+                warn!("Failed to load all schedules: {}", err);
+                live_data_state.set(live_data_state.failed(LiveSource::ScheduleUpdates));
+            }
+        }
+    });
+}
+
+fn load_schedules_state(
+    schedules_state: UseReducerHandle<SchedulesState>,
+    metadata_state: UseStateHandle<DataMetadataContext>,
+    missing_partitions_state: UseReducerHandle<MissingPartitionsContext>,
+    live_data_state: UseStateHandle<LiveDataHealth>,
+    feature_flags_state: UseStateHandle<FeatureFlags>,
+) {
+    schedules_state.dispatch(SchedulesEvent::Load);
     wasm_bindgen_futures::spawn_local(async move {
-        match fetch_json::<Vec<Schedule>>("/data/schedules.json").await {
-            Ok(schedules) => {
-                schedules_state
-                    .set(SchedulesState::Loaded(Rc::new(into_vec_group_map(schedules, |i| i.terminal_pair))));
+        let fetch_result =
+            fetch_schedules(current_load_fault(), false, current_data_channel(), &viewed_terminal_pairs()).await;
+        let event = match fetch_result {
+            Ok(LoadedData { mut schedules_map, metadata, missing_partitions, feature_flags, pending_route_files }) => {
+                live_data_state.set(live_data_state.succeeded(LiveSource::ScheduleUpdates, now_utc()));
+                metadata_state.set(DataMetadataContext(metadata.map(Rc::new)));
+                // Only taken from the initial load, so that features don't come and go while the page is open
+                feature_flags_state.set(FeatureFlags::new(client_id(&*default_storage()), feature_flags));
+                if pending_route_files.is_empty() {
+                    complete_schedules_map(&mut schedules_map);
+                }
+                let schedules_map = Rc::new(schedules_map);
+                if !missing_partitions.is_empty() {
+                    missing_partitions_state.dispatch(MissingPartitionsEvent::Loaded(missing_partitions.clone()));
+                    retry_missing_partitions(schedules_state.clone(), missing_partitions_state, missing_partitions);
+                }
+                if !pending_route_files.is_empty() {
+                    load_pending_route_files(
+                        schedules_state.clone(),
+                        live_data_state,
+                        schedules_map.clone(),
+                        pending_route_files,
+                    );
+                }
+                SchedulesEvent::LoadSucceeded(schedules_map)
             }
             Err(err) => {
                 error!("{}", err);
-                schedules_state.set(SchedulesState::Failed);
+                // Pinned routes are still usable without a connection, flagged as possibly out of date
+                match offline_schedules_map(&*default_storage()) {
+                    Some(schedules_map) => {
+                        live_data_state.set(live_data_state.failed(LiveSource::ScheduleUpdates));
+                        SchedulesEvent::LoadSucceeded(Rc::new(schedules_map))
+                    }
+                    None => SchedulesEvent::LoadFailed,
+                }
+            }
+        };
+        schedules_state.dispatch(event);
+    });
+}
+
+fn revalidate_schedules_state(
+    schedules_state: UseReducerHandle<SchedulesState>,
+    metadata_state: UseStateHandle<DataMetadataContext>,
+    missing_partitions_state: UseReducerHandle<MissingPartitionsContext>,
+    live_data_state: UseStateHandle<LiveDataHealth>,
+) {
+    wasm_bindgen_futures::spawn_local(async move {
+        match fetch_schedules(LoadFault::None, true, current_data_channel(), &[]).await {
+            Ok(LoadedData { mut schedules_map, metadata, missing_partitions, .. }) => {
+                live_data_state.set(live_data_state.succeeded(LiveSource::ScheduleUpdates, now_utc()));
+                apply_pinned_routes(&*default_storage(), &mut schedules_map, today_vancouver(), now_utc());
+                // Only replaces schedules that had loaded; otherwise the initial load is still in charge
+                if let SchedulesState::Loaded(_) = *schedules_state {
+                    metadata_state.set(DataMetadataContext(metadata.map(Rc::new)));
+                    missing_partitions_state.dispatch(MissingPartitionsEvent::Loaded(missing_partitions));
+                }
+                schedules_state.dispatch(SchedulesEvent::Revalidated(Rc::new(schedules_map)));
+            }
+            // The schedules already shown are still usable, so flag them as possibly stale and wait for the next
+            // revalidation
+            Err(err) => {
+                warn!("Failed to revalidate schedules: {}", err);
+                live_data_state.set(live_data_state.failed(LiveSource::ScheduleUpdates));
             }
         }
     });
@@ -208,24 +634,105 @@ fn load_schedules_state(schedules_state: UseStateHandle<SchedulesState>) {
 
 #[function_component(App)]
 fn app() -> Html {
-    let schedules_state = use_state(|| SchedulesState::Init);
+    let schedules_state = use_reducer_eq(|| SchedulesState::Init);
+    let metadata_state = use_state(DataMetadataContext::default);
+    let missing_partitions_state = use_reducer_eq(MissingPartitionsContext::default);
+    let live_data_state = use_state(LiveDataHealth::default);
+    let feature_flags_state = use_state(FeatureFlags::default);
     if let SchedulesState::Init = *schedules_state {
-        load_schedules_state(schedules_state.clone());
+        load_schedules_state(
+            schedules_state.clone(),
+            metadata_state.clone(),
+            missing_partitions_state.clone(),
+            live_data_state.clone(),
+            feature_flags_state.clone(),
+        );
     }
+    let navigation_state = use_state(|| Rc::new(load_or_default::<NavigationHistory>(&*default_storage())));
+    let navigation = {
+        let navigation_state = navigation_state.clone();
+        NavigationHistoryContext {
+            history: (*navigation_state).clone(),
+            dispatch: Callback::from(move |action| {
+                let history = navigation_state.apply(action);
+                if let Err(err) = save(&*default_storage(), &history) {
+                    warn!("Failed to save navigation history: {:?}", err);
+                }
+                navigation_state.set(Rc::new(history));
+            }),
+        }
+    };
+    let revalidator = {
+        let (schedules_state, metadata_state, missing_partitions_state, live_data_state) = (
+            schedules_state.clone(),
+            metadata_state.clone(),
+            missing_partitions_state.clone(),
+            live_data_state.clone(),
+        );
+        SchedulesRevalidator(Callback::from(move |_| {
+            revalidate_schedules_state(
+                schedules_state.clone(),
+                metadata_state.clone(),
+                missing_partitions_state.clone(),
+                live_data_state.clone(),
+            )
+        }))
+    };
     html! {
         <ContextProvider<SchedulesState> context={(*schedules_state).clone()}>
-            <BrowserRouter>
-                <div class="container">
-                    <Navbar/>
-                    <Switch<Route> render={Switch::render(switch_route)}/>
-                    { footer_html() }
-                </div>
-            </BrowserRouter>
+            <ContextProvider<DataMetadataContext> context={(*metadata_state).clone()}>
+                <ContextProvider<MissingPartitionsContext> context={(*missing_partitions_state).clone()}>
+                    <ContextProvider<SchedulesRevalidator> context={revalidator}>
+                        <ContextProvider<LiveDataHealth> context={(*live_data_state).clone()}>
+                            <ContextProvider<FeatureFlags> context={(*feature_flags_state).clone()}>
+                                <ContextProvider<NavigationHistoryContext> context={navigation}>
+                                    <BrowserRouter>
+                                        <div class="container">
+                                            <Navbar/>
+                                            <ExpiredDataGate>
+                                                <Switch<Route> render={Switch::render(switch_route)}/>
+                                            </ExpiredDataGate>
+                                            { footer_html() }
+                                        </div>
+                                    </BrowserRouter>
+                                </ContextProvider<NavigationHistoryContext>>
+                            </ContextProvider<FeatureFlags>>
+                        </ContextProvider<LiveDataHealth>>
+                    </ContextProvider<SchedulesRevalidator>>
+                </ContextProvider<MissingPartitionsContext>>
+            </ContextProvider<DataMetadataContext>>
         </ContextProvider<SchedulesState>>
     }
 }
 
+fn query_param(name: &str) -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    let prefix = format!("{}=", name);
+    search.trim_start_matches('?').split('&').find_map(|param| param.strip_prefix(&prefix)).map(str::to_string)
+}
+
+// Date from the `today` query parameter (e.g. `?today=2021-03-31`), which debug builds use to simulate another day
+fn simulated_today_query() -> Option<Date> {
+    let value = query_param("today")?;
+    parse_iso8601_date(&value).map_err(|err| error!("Invalid simulated today: {:?}", err)).ok()
+}
+
+// Channel from the `data-channel` query parameter (e.g. `?data-channel=preview`), which is remembered until switched
+// back with `?data-channel=stable`
+fn data_channel_query() -> Option<DataChannel> {
+    let value = query_param("data-channel")?;
+    value.parse().map_err(|err| error!("Invalid data channel {:?}: {:?}", value, err)).ok()
+}
+
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
+    if let Some(channel) = data_channel_query() {
+        save(&*default_storage(), &channel).unwrap_or_else(|err| error!("{:?}", err));
+    }
+    if cfg!(debug_assertions) {
+        if let Some(today) = simulated_today_query() {
+            set_time_provider(TimeProvider::simulating_today(today)).unwrap_or_else(|err| error!("{:?}", err));
+        }
+    }
     yew::start_app::<App>();
 }
@@ -0,0 +1,130 @@
+use crate::prelude::*;
+use crate::storage::*;
+
+// Oldest entries are dropped past this, so the stored history stays small
+const MAX_ENTRIES: usize = 50;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct NavigationEntry {
+    pub area_pair: AreaPair,
+    // Missing for today, like the sailings page query
+    pub date: Option<Date>,
+}
+
+// Sailings pages viewed in the app, with back and forward like a browser's.  Mobile browser gestures often leave an
+// installed app instead of going back, so the app keeps its own history.  It is stored, so reopening the app keeps it.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct NavigationHistory {
+    entries: Vec<NavigationEntry>,
+    // Index of the entry being viewed
+    position: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum NavigationAction {
+    Visit(NavigationEntry),
+    Back,
+    Forward,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct NavigationHistoryContext {
+    pub history: Rc<NavigationHistory>,
+    pub dispatch: Callback<NavigationAction>,
+}
+
+impl Stored for NavigationHistory {
+    const KEY: &'static str = "navigation-history";
+    const VERSION: u32 = 1;
+}
+
+impl NavigationHistory {
+    pub fn current(&self) -> Option<&NavigationEntry> {
+        self.entries.get(self.position)
+    }
+
+    pub fn back_entry(&self) -> Option<&NavigationEntry> {
+        self.position.checked_sub(1).and_then(|position| self.entries.get(position))
+    }
+
+    pub fn forward_entry(&self) -> Option<&NavigationEntry> {
+        self.entries.get(self.position + 1)
+    }
+
+    // Like a browser, visiting a page after going back drops the pages that were ahead.  Going back or forward also
+    // visits the page it goes to, which is already the current entry by then.
+    pub fn apply(&self, action: NavigationAction) -> NavigationHistory {
+        let mut history = self.clone();
+        match action {
+            NavigationAction::Visit(entry) if self.current() == Some(&entry) => {}
+            NavigationAction::Visit(entry) => {
+                history.entries.truncate(min(self.position + 1, self.entries.len()));
+                history.entries.push(entry);
+                let excess = history.entries.len().saturating_sub(MAX_ENTRIES);
+                history.entries.drain(..excess);
+                history.position = history.entries.len() - 1;
+            }
+            NavigationAction::Back => history.position = self.position.saturating_sub(1),
+            NavigationAction::Forward => {
+                history.position = min(self.position + 1, self.entries.len().saturating_sub(1))
+            }
+        }
+        history
+    }
+
+    // Distinct pages, most recently visited first, not counting the current one
+    pub fn recent_entries(&self, count: usize) -> Vec<NavigationEntry> {
+        self.entries[..min(self.position + 1, self.entries.len())]
+            .iter()
+            .rev()
+            .filter(|&entry| Some(entry) != self.current())
+            .unique()
+            .take(count)
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(day: u8) -> NavigationEntry {
+        NavigationEntry {
+            area_pair: AreaPair { from: Area::Victoria, to: Area::Galiano },
+            date: Some(Date::from_calendar_date(2022, Month::June, day).expect("Expect valid test date")),
+        }
+    }
+
+    #[test]
+    fn test_navigation_history_apply() -> Result<()> {
+        let history = NavigationHistory::default()
+            .apply(NavigationAction::Visit(entry(1)))
+            .apply(NavigationAction::Visit(entry(2)))
+            .apply(NavigationAction::Visit(entry(3)));
+        let back = history.apply(NavigationAction::Back).apply(NavigationAction::Back);
+        assert_eq!(back.current(), Some(&entry(1)));
+        assert_eq!(back.back_entry(), None);
+        // Arriving at the page gone back to is not a new visit
+        assert_eq!(back.apply(NavigationAction::Visit(entry(1))), back);
+        assert_eq!(back.apply(NavigationAction::Forward).current(), Some(&entry(2)));
+        let branched = back.apply(NavigationAction::Visit(entry(4)));
+        assert_eq!(branched.back_entry(), Some(&entry(1)));
+        assert_eq!(branched.forward_entry(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_navigation_history_recent_entries() -> Result<()> {
+        let history = [1, 2, 1, 3, 2]
+            .iter()
+            .fold(NavigationHistory::default(), |history, &day| history.apply(NavigationAction::Visit(entry(day))));
+        assert_eq!(history.recent_entries(5), [entry(3), entry(1)]);
+        let capped = (0..MAX_ENTRIES + 5).fold(NavigationHistory::default(), |history, i| {
+            history.apply(NavigationAction::Visit(entry(i as u8 % 28 + 1)))
+        });
+        assert_eq!(capped.entries.len(), MAX_ENTRIES);
+        assert_eq!(capped.current(), capped.entries.last());
+        Ok(())
+    }
+}
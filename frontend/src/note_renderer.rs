@@ -0,0 +1,127 @@
+use crate::prelude::*;
+use crate::sailings_processor::*;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NoteSeverity {
+    Info,
+    // Restricts who can take the sailing
+    Restriction,
+    // The sailing may not run as scheduled
+    Warning,
+    // The schedules contradict each other, so the sailing cannot be relied on
+    DataError,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NoteRenderer {
+    // Bootstrap icon name
+    pub icon: &'static str,
+    pub severity: NoteSeverity,
+    // Label for places without room for the whole note, such as descriptions in exported data
+    short_text: Option<&'static str>,
+    // Bootstrap background classes for notes shown as a badge, which stand out from the sailing's other notes
+    pub badge_class: Option<&'static str>,
+    // Shown instead of the note's own wording, which is then left for the tooltip
+    text: Option<&'static str>,
+}
+
+impl NoteSeverity {
+    pub fn text_class(&self) -> &'static str {
+        match self {
+            NoteSeverity::Info => "text-secondary",
+            NoteSeverity::Restriction => "text-danger",
+            NoteSeverity::Warning => "text-warning",
+            NoteSeverity::DataError => "text-danger",
+        }
+    }
+
+    // Conditions that may stop the sailing from running, and sailings that can't be taken as listed, stand out
+    fn badge_class(&self) -> Option<&'static str> {
+        match self {
+            NoteSeverity::Info | NoteSeverity::Restriction => None,
+            NoteSeverity::Warning => Some("bg-warning text-dark"),
+            NoteSeverity::DataError => Some("bg-danger"),
+        }
+    }
+}
+
+impl NoteRenderer {
+    pub fn of(category: NoteCategory) -> NoteRenderer {
+        let renderer = |icon, severity: NoteSeverity, short_text| NoteRenderer {
+            icon,
+            severity,
+            short_text: Some(short_text),
+            badge_class: severity.badge_class(),
+            text: None,
+        };
+        match category {
+            NoteCategory::FootPassengersOnly => {
+                renderer("person-walking", NoteSeverity::Restriction, "Foot passengers only")
+            }
+            // A badge, since it stops drivers from taking a sailing they may have planned on
+            NoteCategory::PassengerOnlyVessel => NoteRenderer {
+                badge_class: Some("bg-danger"),
+                text: Some("No vehicles on this sailing"),
+                ..renderer("sign-stop", NoteSeverity::Restriction, "No vehicles")
+            },
+            NoteCategory::VehicleRestriction => renderer("rulers", NoteSeverity::Restriction, "Vehicle size limit"),
+            NoteCategory::VehiclePriority => {
+                renderer("sign-turn-right", NoteSeverity::Info, "Saturna vehicle priority")
+            }
+            NoteCategory::NearMidnight => renderer("moon-stars", NoteSeverity::Info, "Near midnight"),
+            NoteCategory::DaylightLoading => renderer("sunset", NoteSeverity::Warning, "Daylight loading only"),
+            NoteCategory::Tide => renderer("water", NoteSeverity::Warning, "Tide dependent"),
+            NoteCategory::NextCalendarDay => renderer("calendar-event", NoteSeverity::Info, "Next day"),
+            NoteCategory::ImpossibleConnection => {
+                renderer("exclamation-octagon", NoteSeverity::DataError, "Connection not possible")
+            }
+            // Shown as plain text, since there's nothing known about them
+            NoteCategory::Other => NoteRenderer { short_text: None, ..renderer("info-circle", NoteSeverity::Info, "") },
+        }
+    }
+
+    pub fn short_text<'a>(&self, note_text: &'a str) -> &'a str {
+        self.short_text.unwrap_or(note_text)
+    }
+
+    pub fn text<'a>(&self, note_text: &'a str) -> &'a str {
+        self.text.unwrap_or(note_text)
+    }
+}
+
+impl SailingNote {
+    pub fn renderer(&self) -> NoteRenderer {
+        NoteRenderer::of(self.category)
+    }
+}
+
+impl SailingWithNotes {
+    pub fn has_note_category(&self, category: NoteCategory) -> bool {
+        self.notes.iter().any(|n| n.category == category)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_renderer_of() -> Result<()> {
+        assert_eq!(NoteRenderer::of(NoteCategory::Tide).severity, NoteSeverity::Warning);
+        assert_eq!(NoteRenderer::of(NoteCategory::PassengerOnlyVessel).severity, NoteSeverity::Restriction);
+        assert_eq!(
+            NoteRenderer::of(NoteCategory::FootPassengersOnly).short_text("Foot passengers only on this sailing"),
+            "Foot passengers only"
+        );
+        let passenger_only = NoteRenderer::of(NoteCategory::PassengerOnlyVessel);
+        assert_eq!(passenger_only.badge_class, Some("bg-danger"));
+        assert_eq!(passenger_only.text("Passenger only sailing"), "No vehicles on this sailing");
+        assert_eq!(NoteRenderer::of(NoteCategory::Tide).badge_class, Some("bg-warning text-dark"));
+        let other = NoteRenderer::of(NoteCategory::Other);
+        assert_eq!(other.severity, NoteSeverity::Info);
+        assert_eq!(other.badge_class, None);
+        assert_eq!(other.text("Bicycles must be walked aboard"), "Bicycles must be walked aboard");
+        assert_eq!(other.short_text("Bicycles must be walked aboard"), "Bicycles must be walked aboard");
+        Ok(())
+    }
+}
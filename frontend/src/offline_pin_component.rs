@@ -0,0 +1,105 @@
+use crate::date_format::*;
+use crate::pinned_routes::*;
+use crate::prelude::*;
+use crate::storage::*;
+use crate::types::*;
+
+#[derive(PartialEq, Properties)]
+pub struct OfflinePinProps {
+    pub area_pair: AreaPair,
+    pub view_date: Date,
+}
+
+fn storage_size_text(bytes: usize) -> String {
+    format!("{} KB", (bytes + 1023) / 1024)
+}
+
+// Pins the route's schedules for the period including the view date, so they are still there without a connection
+#[function_component(OfflinePin)]
+pub fn offline_pin_component(props: &OfflinePinProps) -> Html {
+    let OfflinePinProps { area_pair, view_date } = *props;
+    let schedules_state = use_context::<SchedulesState>().expect("Expect schedules state to be available");
+    let pinned_routes_state = use_state(|| load_or_default::<PinnedRoutes>(&*default_storage()));
+    let error_state = use_state(|| None as Option<&'static str>);
+    let save_pinned_routes = {
+        let (pinned_routes_state, error_state) = (pinned_routes_state.clone(), error_state.clone());
+        move |pinned_routes: PinnedRoutes| match save(&*default_storage(), &pinned_routes) {
+            Ok(()) => {
+                pinned_routes_state.set(pinned_routes);
+                error_state.set(None);
+            }
+            Err(err) if is_quota_exceeded(&err) => {
+                error_state.set(Some("There isn't enough storage on this device; unpin another route first."))
+            }
+            Err(err) => {
+                warn!("{:?}", err);
+                error_state.set(Some("Saving for offline use failed."));
+            }
+        }
+    };
+    let pinned_bytes = stored_bytes::<PinnedRoutes>(&*default_storage());
+    let error_html = match *error_state {
+        Some(error) => html! { <div class="text-danger">{ error }</div> },
+        None => html! {},
+    };
+    let pin_html = match pinned_routes_state.find(area_pair, view_date) {
+        Some(pin) => {
+            let date_range = pin.date_range;
+            let onclick = {
+                let (pinned_routes_state, save_pinned_routes) = (pinned_routes_state.clone(), save_pinned_routes);
+                Callback::from(move |_| {
+                    save_pinned_routes((*pinned_routes_state).clone().unpinned(area_pair, date_range))
+                })
+            };
+            html! {
+                <span class="text-muted">
+                    <i class="bi bi-pin-angle-fill"/>
+                    { format!(
+                        " Saved for offline use through {}; pinned routes use {} on this device. ",
+                        DATE_FORMATTER.short_date(date_range.to),
+                        storage_size_text(pinned_bytes)
+                    ) }
+                    <button type="button" class="btn btn-link btn-sm p-0 align-baseline" {onclick}>{ "Unpin" }</button>
+                </span>
+            }
+        }
+        None => {
+            let schedules_map = match &schedules_state {
+                SchedulesState::Loaded(schedules_map) => schedules_map.clone(),
+                _ => return html! {},
+            };
+            let date_range = match pinnable_period(area_pair, view_date, &schedules_map) {
+                Some(date_range) => date_range,
+                None => return html! {},
+            };
+            let onclick = {
+                let pinned_routes_state = pinned_routes_state.clone();
+                Callback::from(move |_| {
+                    let pin = route_snapshot(area_pair, date_range, &schedules_map, now_utc());
+                    save_pinned_routes((*pinned_routes_state).clone().pinned(pin))
+                })
+            };
+            let title = format!(
+                "Keep this route's schedules through {} on this device, for travelling without a connection",
+                DATE_FORMATTER.short_date(date_range.to)
+            );
+            html! {
+                <button type="button" class="btn btn-outline-secondary btn-sm" {title} {onclick}>
+                    <i class="bi bi-pin-angle"/>
+                    { " Save for offline use" }
+                    { if pinned_routes_state.is_empty() { html! {} } else { html! {
+                        <small class="text-muted">{ format!(" (pinned routes use {})", storage_size_text(pinned_bytes)) }</small>
+                    }}}
+                </button>
+            }
+        }
+    };
+    html! {
+        <div class="row mb-3 d-print-none">
+            <div class="offset-2 offset-md-1 col-10 col-md-7 col-lg-5 small">
+                { pin_html }
+                { error_html }
+            </div>
+        </div>
+    }
+}
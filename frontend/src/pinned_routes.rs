@@ -0,0 +1,207 @@
+use crate::prelude::*;
+use crate::storage::*;
+
+// A route's schedules in both directions for one schedule period, kept in local storage so they are available without
+// a connection, such as when heading somewhere without coverage.  Unlike the browser's cache, which may be cleared at
+// any time, pinned schedules are only removed by unpinning them or once their period is over.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PinnedRoute {
+    pub area_pair: AreaPair,
+    pub date_range: DateRange,
+    pub pinned_at: OffsetDateTime,
+    pub schedules: Vec<Schedule>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PinnedRoutes {
+    routes: Vec<PinnedRoute>,
+}
+
+impl Stored for PinnedRoutes {
+    const KEY: &'static str = "pinned-routes";
+    const VERSION: u32 = 1;
+}
+
+fn route_terminal_pairs(area_pair: AreaPair) -> Vec<TerminalPair> {
+    [area_pair, area_pair.swapped()]
+        .iter()
+        .flat_map(|ap| AREA_PAIR_TERMINAL_PAIRS.get(ap).into_iter().flatten().copied())
+        .collect()
+}
+
+fn same_route(a: AreaPair, b: AreaPair) -> bool {
+    a == b || a == b.swapped()
+}
+
+fn overlaps(a: &DateRange, b: &DateRange) -> bool {
+    a.from <= b.to && b.from <= a.to
+}
+
+// The BC Ferries schedule period in effect on the date, across the route's terminals in both directions
+pub fn pinnable_period(
+    area_pair: AreaPair,
+    date: Date,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+) -> Option<DateRange> {
+    let date_ranges: Vec<_> = route_terminal_pairs(area_pair)
+        .into_iter()
+        .flat_map(|tp| schedules_map.get(&tp).into_iter().flatten())
+        .filter(|s| s.operator == Operator::BcFerries && s.date_range.includes_date_inclusive(date))
+        .map(|s| s.date_range)
+        .collect();
+    Some(DateRange { from: date_ranges.iter().map(|dr| dr.from).min()?, to: date_ranges.iter().map(|dr| dr.to).max()? })
+}
+
+pub fn route_snapshot(
+    area_pair: AreaPair,
+    date_range: DateRange,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+    now: OffsetDateTime,
+) -> PinnedRoute {
+    let schedules = route_terminal_pairs(area_pair)
+        .into_iter()
+        .flat_map(|tp| schedules_map.get(&tp).into_iter().flatten())
+        .filter(|s| overlaps(&s.date_range, &date_range))
+        .cloned()
+        .collect();
+    PinnedRoute { area_pair, date_range, pinned_at: now, schedules }
+}
+
+impl PinnedRoutes {
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    pub fn routes(&self) -> &[PinnedRoute] {
+        &self.routes
+    }
+
+    pub fn find(&self, area_pair: AreaPair, date: Date) -> Option<&PinnedRoute> {
+        self.routes.iter().find(|r| same_route(r.area_pair, area_pair) && r.date_range.includes_date_inclusive(date))
+    }
+
+    // Replaces any pin of the same route and period, so pinning again refreshes it
+    pub fn pinned(mut self, route: PinnedRoute) -> PinnedRoutes {
+        self.routes
+            .retain(|r| !(same_route(r.area_pair, route.area_pair) && overlaps(&r.date_range, &route.date_range)));
+        self.routes.push(route);
+        self
+    }
+
+    pub fn unpinned(mut self, area_pair: AreaPair, date_range: DateRange) -> PinnedRoutes {
+        self.routes.retain(|r| !(same_route(r.area_pair, area_pair) && r.date_range == date_range));
+        self
+    }
+
+    // Takes newly loaded schedules into the pins, so they are as current as the last time the app was online, and drops
+    // pins whose period is over
+    pub fn refreshed(
+        self,
+        schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+        today: Date,
+        now: OffsetDateTime,
+    ) -> PinnedRoutes {
+        let routes = self
+            .routes
+            .into_iter()
+            .filter(|r| r.date_range.to >= today)
+            .map(|r| {
+                let snapshot = route_snapshot(r.area_pair, r.date_range, schedules_map, now);
+                // Loaded data missing the period, such as from a partition that failed, doesn't replace the pin
+                if snapshot.schedules.is_empty() {
+                    r
+                } else {
+                    snapshot
+                }
+            })
+            .collect();
+        PinnedRoutes { routes }
+    }
+
+    // Adds pinned schedules that the loaded data is missing, such as when offline or a partition failed to load
+    pub fn merge_into(&self, schedules_map: &mut HashMap<TerminalPair, Vec<Schedule>>) {
+        for schedule in self.routes.iter().flat_map(|r| &r.schedules) {
+            let schedules = schedules_map.entry(schedule.terminal_pair).or_default();
+            if !schedules.iter().any(|s| s.operator == schedule.operator && s.date_range == schedule.date_range) {
+                schedules.push(schedule.clone());
+            }
+        }
+    }
+}
+
+// Refreshes the stored pins from freshly loaded schedules, then fills in whatever the load is missing from them
+pub fn apply_pinned_routes(
+    storage: &dyn Storage,
+    schedules_map: &mut HashMap<TerminalPair, Vec<Schedule>>,
+    today: Date,
+    now: OffsetDateTime,
+) {
+    let pinned_routes = load_or_default::<PinnedRoutes>(storage);
+    if pinned_routes.is_empty() {
+        return;
+    }
+    let pinned_routes = pinned_routes.refreshed(schedules_map, today, now);
+    if let Err(err) = save(storage, &pinned_routes) {
+        warn!("Failed to save refreshed pinned routes: {:?}", err);
+    }
+    pinned_routes.merge_into(schedules_map);
+}
+
+// Schedules to show when loading failed, if any routes are pinned
+pub fn offline_schedules_map(storage: &dyn Storage) -> Option<HashMap<TerminalPair, Vec<Schedule>>> {
+    let pinned_routes = load_or_default::<PinnedRoutes>(storage);
+    if pinned_routes.is_empty() {
+        return None;
+    }
+    let mut schedules_map = HashMap::new();
+    pinned_routes.merge_into(&mut schedules_map);
+    Some(schedules_map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(terminal_pair: TerminalPair, date_range: DateRange) -> Schedule {
+        Schedule { date_range, ..Schedule::test_fixture(terminal_pair) }
+    }
+
+    #[test]
+    fn test_pinned_routes() -> Result<()> {
+        let to_victoria = TerminalPair { from: Terminal::TSA, to: Terminal::SWB };
+        let to_vancouver = to_victoria.swapped();
+        let summer = DateRange { from: date!(2022 - 06 - 30), to: date!(2022 - 09 - 05) };
+        let fall = DateRange { from: date!(2022 - 09 - 06), to: date!(2022 - 10 - 10) };
+        let schedules_map = HashMap::from([
+            (to_victoria, vec![schedule(to_victoria, summer), schedule(to_victoria, fall)]),
+            (to_vancouver, vec![schedule(to_vancouver, summer)]),
+        ]);
+        let area_pair = to_victoria.area_pair();
+        assert_eq!(pinnable_period(area_pair, date!(2022 - 07 - 15), &schedules_map), Some(summer));
+        assert_eq!(pinnable_period(area_pair, date!(2022 - 12 - 15), &schedules_map), None);
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let pins = PinnedRoutes::default().pinned(route_snapshot(area_pair, summer, &schedules_map, now));
+        let pin = pins.find(area_pair.swapped(), date!(2022 - 07 - 15)).expect("Expect pin for both directions");
+        assert_eq!(
+            pin.schedules.iter().map(|s| s.terminal_pair).collect::<HashSet<_>>(),
+            [to_victoria, to_vancouver].into()
+        );
+
+        // Offline, only the pinned period is available
+        let mut offline_map = HashMap::new();
+        pins.merge_into(&mut offline_map);
+        assert_eq!(offline_map[&to_victoria].len(), 1);
+        let mut online_map = schedules_map;
+        pins.merge_into(&mut online_map);
+        assert_eq!(online_map[&to_victoria].len(), 2);
+
+        // Pins survive a load that is missing their period, and are dropped once it is over
+        let refreshed = pins.refreshed(&HashMap::new(), date!(2022 - 09 - 05), now);
+        assert_eq!(refreshed.routes().len(), 1);
+        assert_eq!(refreshed.routes()[0].schedules.len(), 2);
+        assert!(refreshed.refreshed(&HashMap::new(), date!(2022 - 09 - 06), now).is_empty());
+        let pins = PinnedRoutes::default().pinned(route_snapshot(area_pair, summer, &online_map, now));
+        assert!(pins.unpinned(area_pair.swapped(), summer).is_empty());
+        Ok(())
+    }
+}
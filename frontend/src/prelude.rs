@@ -4,7 +4,4 @@ pub use web_sys::HtmlInputElement;
 pub use yew::prelude::*;
 pub use yew_router::prelude::*;
 
-pub use ferrysched_shared::constants::*;
-pub use ferrysched_shared::imports::*;
-pub use ferrysched_shared::types::*;
-pub use ferrysched_shared::utils::*;
+pub use ferrysched_shared::prelude::*;
@@ -0,0 +1,72 @@
+use qrcode::{Color, QrCode};
+
+use crate::prelude::*;
+
+// Number of light modules around the code, as required by the QR code specification
+const QUIET_ZONE: usize = 4;
+
+#[derive(PartialEq, Properties)]
+pub struct QrCodeDetailsProps {
+    pub data: String,
+    pub title: &'static str,
+}
+
+// Horizontal runs of dark modules as (x, y, length), so the SVG needs far fewer elements than one per module
+fn dark_runs(colors: &[Color], width: usize) -> Vec<(usize, usize, usize)> {
+    let mut runs = Vec::new();
+    for (y, row) in colors.chunks(width).enumerate() {
+        let mut run_start = None;
+        for (x, color) in row.iter().chain(iter::once(&Color::Light)).enumerate() {
+            match (color, run_start) {
+                (Color::Dark, None) => run_start = Some(x),
+                (Color::Light, Some(start)) => {
+                    runs.push((start, y, x - start));
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    runs
+}
+
+pub fn qr_code_html(data: &str, class: &'static str) -> Html {
+    match QrCode::new(data) {
+        Ok(code) => {
+            let width = code.width();
+            let view_box = format!("0 0 {0} {0}", width + QUIET_ZONE * 2);
+            html! {
+                <svg class={ class } viewBox={ view_box } xmlns="http://www.w3.org/2000/svg" shape-rendering="crispEdges">
+                    <rect width="100%" height="100%" fill="white"/>
+                    { for dark_runs(&code.to_colors(), width).into_iter().map(|(x, y, length)| html! {
+                        <rect x={ (x + QUIET_ZONE).to_string() } y={ (y + QUIET_ZONE).to_string() } width={ length.to_string() } height="1"/>
+                    })}
+                </svg>
+            }
+        }
+        Err(err) => {
+            error!("Failed to generate QR code for {:?}: {}", data, err);
+            html! {}
+        }
+    }
+}
+
+// Only generated once first opened, since a sailings page has a QR code for every sailing but few are ever looked at
+#[function_component(QrCodeDetails)]
+pub fn qr_code_details_component(props: &QrCodeDetailsProps) -> Html {
+    let opened_state = use_state(|| false);
+    let ontoggle = {
+        let opened_state = opened_state.clone();
+        Callback::from(move |e: Event| {
+            if e.target_unchecked_into::<web_sys::HtmlDetailsElement>().open() {
+                opened_state.set(true);
+            }
+        })
+    };
+    html! {
+        <details class="d-print-none sailing-qr-code" {ontoggle}>
+            <summary title={ props.title }><i class="bi bi-qr-code"/></summary>
+            { if *opened_state { qr_code_html(&props.data, "qr-code") } else { html! {} }}
+        </details>
+    }
+}
@@ -0,0 +1,63 @@
+use crate::prelude::*;
+
+// When bookings typically open for a schedule period, for travel between any of the `from` terminals and any of the
+// `to` terminals (in either direction).  BC Ferries opens bookings for a whole schedule period at once, some time before
+// the period starts.
+struct ReservationOpening {
+    from: &'static [Terminal],
+    to: &'static [Terminal],
+    days_before_period: i64,
+}
+
+// Maintained by hand from BC Ferries' announcements of when bookings open, so these are typical rather than exact
+const RESERVATION_OPENINGS: &[ReservationOpening] = &[
+    // Route 1 (Tsawwassen/Swartz Bay)
+    ReservationOpening { from: &[Terminal::TSA], to: &[Terminal::SWB], days_before_period: 90 },
+    // Route 9 (Tsawwassen/Southern Gulf Islands)
+    ReservationOpening {
+        from: &[Terminal::TSA],
+        to: &[Terminal::PLH, Terminal::POB, Terminal::PSB, Terminal::PST, Terminal::PVB],
+        days_before_period: 60,
+    },
+];
+
+impl ReservationOpening {
+    fn applies_to(&self, terminal_pair: TerminalPair) -> bool {
+        let includes =
+            |a: &[Terminal], b: &[Terminal]| a.contains(&terminal_pair.from) && b.contains(&terminal_pair.to);
+        includes(self.from, self.to) || includes(self.to, self.from)
+    }
+}
+
+// Date that bookings for the schedule's period typically open, if that is after today
+pub fn reservations_open_date(schedule: &Schedule, today: Date) -> Option<Date> {
+    RESERVATION_OPENINGS
+        .iter()
+        .find(|opening| opening.applies_to(schedule.terminal_pair))
+        .map(|opening| schedule.date_range.from - Duration::days(opening.days_before_period))
+        .filter(|&opens_on| opens_on > today)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(terminal_pair: TerminalPair) -> Schedule {
+        Schedule {
+            date_range: DateRange { from: date!(2022 - 06 - 30), to: date!(2022 - 09 - 05) },
+            ..Schedule::test_fixture(terminal_pair)
+        }
+    }
+
+    #[test]
+    fn test_reservations_open_date() -> Result<()> {
+        let route1 = schedule(TerminalPair { from: Terminal::SWB, to: Terminal::TSA });
+        assert_eq!(reservations_open_date(&route1, date!(2022 - 03 - 01)), Some(date!(2022 - 04 - 01)));
+        // Not shown once bookings are open
+        assert_eq!(reservations_open_date(&route1, date!(2022 - 04 - 01)), None);
+        // Routes without reservations have no opening date
+        let route4 = schedule(TerminalPair { from: Terminal::FUL, to: Terminal::SWB });
+        assert_eq!(reservations_open_date(&route4, date!(2022 - 03 - 01)), None);
+        Ok(())
+    }
+}
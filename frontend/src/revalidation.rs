@@ -0,0 +1,97 @@
+use gloo_timers::callback::Interval;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::VisibilityState;
+
+use crate::prelude::*;
+
+// How often a due refresh is checked for; the refresh interval itself is usually much longer
+const REVALIDATION_TICK_MILLIS: u32 = 60_000;
+
+// Decides when to refresh data that can change during the day, so it is only fetched while someone is looking at
+// today's sailings, and no more often than the interval
+#[derive(Debug)]
+pub struct RevalidationScheduler {
+    interval: Duration,
+    last_refreshed_at: OffsetDateTime,
+}
+
+impl RevalidationScheduler {
+    // The data was just loaded, so the first refresh is a whole interval away
+    pub fn new(interval: Duration, loaded_at: OffsetDateTime) -> RevalidationScheduler {
+        RevalidationScheduler { interval, last_refreshed_at: loaded_at }
+    }
+
+    // Returns whether to refresh now, and if so assumes the refresh happens
+    pub fn poll(&mut self, now: OffsetDateTime, is_visible: bool, is_viewing_today: bool) -> bool {
+        let is_due = is_visible && is_viewing_today && now - self.last_refreshed_at >= self.interval;
+        if is_due {
+            self.last_refreshed_at = now;
+        }
+        is_due
+    }
+}
+
+fn is_page_visible() -> bool {
+    web_sys::window()
+        .and_then(|w| w.document())
+        .map(|d| d.visibility_state() == VisibilityState::Visible)
+        .unwrap_or(true)
+}
+
+// Calls `refresh` every `interval` while the page is visible and today is being viewed.  Hidden tabs are not
+// refreshed, but returning to one refreshes it right away if it is due.
+pub fn use_revalidation(interval: Duration, is_viewing_today: bool, refresh: Callback<()>) {
+    let scheduler = use_mut_ref(|| RevalidationScheduler::new(interval, now_utc()));
+    // The effect outlives renders, so it calls whichever callback was rendered last
+    let latest_refresh = use_mut_ref(|| refresh.clone());
+    *latest_refresh.borrow_mut() = refresh;
+    use_effect_with_deps(
+        move |&is_viewing_today| {
+            let poll = Rc::new(move || {
+                if scheduler.borrow_mut().poll(now_utc(), is_page_visible(), is_viewing_today) {
+                    latest_refresh.borrow().emit(());
+                }
+            });
+            let interval = {
+                let poll = poll.clone();
+                Interval::new(REVALIDATION_TICK_MILLIS, move || poll())
+            };
+            let document = web_sys::window().and_then(|w| w.document());
+            let listener = Closure::<dyn Fn()>::wrap(Box::new(move || poll()));
+            if let Some(document) = &document {
+                document
+                    .add_event_listener_with_callback("visibilitychange", listener.as_ref().unchecked_ref())
+                    .unwrap_or_else(|err| error!("Failed to listen for visibility changes: {:?}", err));
+            }
+            move || {
+                drop(interval);
+                if let Some(document) = &document {
+                    document
+                        .remove_event_listener_with_callback("visibilitychange", listener.as_ref().unchecked_ref())
+                        .unwrap_or_else(|err| error!("Failed to stop listening for visibility changes: {:?}", err));
+                }
+            }
+        },
+        is_viewing_today,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revalidation_scheduler_poll() -> Result<()> {
+        let loaded_at = date!(2022 - 06 - 01).with_time(time!(08:00)).assume_utc();
+        let mut scheduler = RevalidationScheduler::new(Duration::minutes(30), loaded_at);
+        assert!(!scheduler.poll(loaded_at + Duration::minutes(29), true, true));
+        // Hidden tabs and other days are not refreshed, however overdue
+        assert!(!scheduler.poll(loaded_at + Duration::minutes(45), false, true));
+        assert!(!scheduler.poll(loaded_at + Duration::minutes(45), true, false));
+        assert!(scheduler.poll(loaded_at + Duration::minutes(45), true, true));
+        assert!(!scheduler.poll(loaded_at + Duration::minutes(46), true, true));
+        assert!(scheduler.poll(loaded_at + Duration::minutes(75), true, true));
+        Ok(())
+    }
+}
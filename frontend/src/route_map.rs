@@ -0,0 +1,124 @@
+use crate::prelude::*;
+
+// Map width in SVG user units; the height follows from the area covered by the terminals
+const MAP_WIDTH: f64 = 160.0;
+const MAP_MARGIN: f64 = 8.0;
+const OPENSTREETMAP_URL: &str = "https://www.openstreetmap.org/";
+
+// Extent of every terminal on the same part of the coast, so the map always covers the same area and the selected route
+// is shown in context
+struct MapBounds {
+    min: Coordinates,
+    max: Coordinates,
+}
+
+impl MapBounds {
+    fn of(coordinates: impl Iterator<Item = Coordinates>) -> MapBounds {
+        let coordinates: Vec<_> = coordinates.collect();
+        let fold = |f: fn(f64, f64) -> f64, get: fn(&Coordinates) -> f64| {
+            coordinates.iter().map(get).reduce(f).expect("Expect map to have terminals")
+        };
+        MapBounds {
+            min: Coordinates { latitude: fold(f64::min, |c| c.latitude), longitude: fold(f64::min, |c| c.longitude) },
+            max: Coordinates { latitude: fold(f64::max, |c| c.latitude), longitude: fold(f64::max, |c| c.longitude) },
+        }
+    }
+
+    // A degree of longitude is shorter than a degree of latitude this far north, so scale it to keep the shapes right
+    fn longitude_scale(&self) -> f64 {
+        ((self.min.latitude + self.max.latitude) / 2.0).to_radians().cos()
+    }
+
+    fn scale(&self) -> f64 {
+        (MAP_WIDTH - 2.0 * MAP_MARGIN) / ((self.max.longitude - self.min.longitude) * self.longitude_scale())
+    }
+
+    fn height(&self) -> f64 {
+        (self.max.latitude - self.min.latitude) * self.scale() + 2.0 * MAP_MARGIN
+    }
+
+    fn project(&self, coordinates: Coordinates) -> (f64, f64) {
+        (
+            MAP_MARGIN + (coordinates.longitude - self.min.longitude) * self.longitude_scale() * self.scale(),
+            MAP_MARGIN + (self.max.latitude - coordinates.latitude) * self.scale(),
+        )
+    }
+}
+
+fn openstreetmap_url(terminals: &[Terminal]) -> String {
+    let bounds = MapBounds::of(terminals.iter().map(Terminal::coordinates));
+    format!(
+        "{}?bbox={:.4},{:.4},{:.4},{:.4}",
+        OPENSTREETMAP_URL, bounds.min.longitude, bounds.min.latitude, bounds.max.longitude, bounds.max.latitude
+    )
+}
+
+// Small static map of the route's terminals and the paths between them, drawn from the terminal coordinates
+pub fn route_map_html(area_pair: AreaPair) -> Html {
+    let terminal_pairs: Vec<_> =
+        AREA_PAIR_TERMINAL_PAIRS.get(&area_pair).into_iter().flatten().copied().sorted().collect();
+    if terminal_pairs.is_empty() {
+        return html! {};
+    }
+    let route_terminals: Vec<_> = terminal_pairs.iter().flat_map(|tp| [tp.from, tp.to]).unique().sorted().collect();
+    // The northern routes are too far from the others to show both at a useful scale
+    let is_north_coast = |terminal: &Terminal| terminal.area().region() == Region::NorthCoast;
+    let is_north_coast_route = route_terminals.iter().any(is_north_coast);
+    let map_terminals: Vec<_> = Terminal::iter().filter(|t| is_north_coast(t) == is_north_coast_route).collect();
+    let bounds = MapBounds::of(map_terminals.iter().map(Terminal::coordinates));
+    let view_box = format!("0 0 {} {:.1}", MAP_WIDTH, bounds.height());
+    let label = format!("Map of the route from {} to {}", area_pair.from.long_name(), area_pair.to.long_name());
+    html! {
+        <figure class="route-map d-print-none">
+            <svg viewBox={ view_box } role="img" aria-label={ label } xmlns="http://www.w3.org/2000/svg">
+                { for terminal_pairs.iter().filter(|tp| tp.from < tp.to || !terminal_pairs.contains(&tp.swapped())).map(|tp| {
+                    let (x1, y1) = bounds.project(tp.from.coordinates());
+                    let (x2, y2) = bounds.project(tp.to.coordinates());
+                    html! {
+                        <line class="route-map-path" x1={ format!("{:.1}", x1) } y1={ format!("{:.1}", y1) }
+                            x2={ format!("{:.1}", x2) } y2={ format!("{:.1}", y2) }/>
+                    }
+                })}
+                { for map_terminals.iter().map(|&terminal| {
+                    let (x, y) = bounds.project(terminal.coordinates());
+                    let is_on_route = route_terminals.contains(&terminal);
+                    html! {
+                        <circle class={ if is_on_route { "route-map-terminal" } else { "route-map-other-terminal" } }
+                            cx={ format!("{:.1}", x) } cy={ format!("{:.1}", y) } r={ if is_on_route { "2.5" } else { "1.5" } }>
+                            <title>{ terminal.name() }</title>
+                        </circle>
+                    }
+                })}
+            </svg>
+            <figcaption class="small">
+                <a class="link-secondary" href={ openstreetmap_url(&route_terminals) } target="_blank">{ "View full map" }</a>
+            </figcaption>
+        </figure>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_bounds_project() -> Result<()> {
+        let bounds = MapBounds::of([Terminal::TSA, Terminal::SWB].iter().map(Terminal::coordinates));
+        let is_near = |(x, y): (f64, f64), (expected_x, expected_y): (f64, f64)| {
+            (x - expected_x).abs() < 1e-9 && (y - expected_y).abs() < 1e-9
+        };
+        // Tsawwassen is the north-east corner and Swartz Bay the south-west corner
+        assert!(is_near(bounds.project(Terminal::TSA.coordinates()), (MAP_WIDTH - MAP_MARGIN, MAP_MARGIN)));
+        assert!(is_near(bounds.project(Terminal::SWB.coordinates()), (MAP_MARGIN, bounds.height() - MAP_MARGIN)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_openstreetmap_url() -> Result<()> {
+        assert_eq!(
+            openstreetmap_url(&[Terminal::SWB, Terminal::TSA]),
+            "https://www.openstreetmap.org/?bbox=-123.4108,48.6889,-123.1303,49.0072"
+        );
+        Ok(())
+    }
+}
@@ -1,43 +1,110 @@
-use crate::imports::*;
+use crate::checklist::*;
+use crate::countdown_component::*;
+use crate::date_format::*;
+use crate::help_component::*;
+use crate::help_content::*;
+use crate::live_data::*;
+use crate::navigation_history::*;
+use crate::note_renderer::*;
+use crate::offline_pin_component::*;
+use crate::prelude::*;
+use crate::qr_code::*;
+use crate::reservation_hints::*;
+use crate::revalidation::*;
+use crate::route_map::*;
+use crate::sailings_model::*;
 use crate::sailings_processor::*;
+use crate::sailings_summary::*;
+use crate::saved_trips::*;
+use crate::storage::*;
+use crate::structured_data::*;
+use crate::travel_time::*;
+use crate::trip_digest_component::*;
 use crate::types::*;
 use crate::utils::*;
+use crate::view_state::*;
+
+// Small enough not to get in the way, since the schedules already shown are still usable without the live data
+fn live_data_chip_html(live_data: &LiveDataHealth) -> Html {
+    let unavailable_sources = live_data.unavailable_sources();
+    if unavailable_sources.is_empty() {
+        return html! {};
+    }
+    let title = unavailable_sources
+        .iter()
+        .map(|(source, health)| match health.last_succeeded_at {
+            Some(at) => format!(
+                "Couldn't refresh {}; last updated {} ago",
+                source.description(),
+                format_duration(now_utc() - at)
+            ),
+            None => format!("Couldn't load {}", source.description()),
+        })
+        .join("\n");
+    html! {
+        <div class="d-print-none text-end">
+            <span class="badge rounded-pill bg-light text-muted border" {title}>
+                <i class="bi bi-cloud-slash"/>
+                { " Live data unavailable" }
+            </span>
+        </div>
+    }
+}
 
 #[derive(Eq, PartialEq, Properties)]
 pub struct SailingsProps {
     pub area_pair: AreaPair,
     pub date: Option<Date>,
+    // Modes from a shared view link, which take the place of remembered preferences without replacing them
+    #[prop_or_default]
+    pub modes: Option<ViewModes>,
 }
 
+// How often today's sailings are fetched again while they are being viewed, to pick up same-day schedule changes
+const SCHEDULES_REVALIDATION_INTERVAL: Duration = Duration::minutes(30);
+
+// Number of pages listed in the recently viewed menu
+const RECENT_NAVIGATION_ENTRIES: usize = 8;
+
+// Number of consecutive sailings listed for group travel: the selected sailing and the following two
+const GROUP_TRAVEL_SAILINGS: usize = 3;
+
 struct DateInputState {
     input: String,
     value: StdResult<Date, &'static str>,
 }
 
-enum SailingsStateModel<'a> {
-    InvalidDate(String),
-    LoadingSchedules,
-    LoadSchedulesFailed,
-    NoSchedule,
-    NoSailings,
-    Sailings(Vec<(&'a Schedule, Vec<SailingWithNotes>)>),
+// Last known maximum schedule date of each area pair, so the date input has the right bounds before schedules load
+#[derive(Default, Deserialize, Serialize)]
+struct KnownMaxDates {
+    entries: Vec<(AreaPair, Date)>,
 }
 
-struct SailingsModel<'a> {
-    sailings_state_model: SailingsStateModel<'a>,
-    area_pair: AreaPair,
-    view_date: Date,
-    max_date: Date,
+// Whether to show sailings in both directions side by side, remembered for day-trippers who always want both
+#[derive(Default, Deserialize, Serialize)]
+struct BothDirectionsPreference {
+    enabled: bool,
+}
+
+// Whether to list sailings just after midnight with the evening before, as part of that day's service
+#[derive(Default, Deserialize, Serialize)]
+struct ServiceDayPreference {
+    enabled: bool,
 }
 
 struct FormModel {
     history: AnyHistory,
+    navigation: NavigationHistoryContext,
     date_input_state: UseStateHandle<DateInputState>,
+    include_supplemental_state: UseStateHandle<bool>,
+    both_directions_state: UseStateHandle<bool>,
+    service_day_state: UseStateHandle<bool>,
     area_pair: AreaPair,
     query_date: Option<Date>,
     today: Date,
     view_date: Date,
     max_date: Date,
+    has_supplemental_schedules: bool,
 }
 
 struct InformationUrlsModel<'a> {
@@ -46,16 +113,52 @@ struct InformationUrlsModel<'a> {
     service_notices_url: &'a str,
 }
 
-fn stop_html(stop: &Stop) -> Html {
+// Stops with a route to or from them open links to sailings for that part of the journey.  Stops the sailing only makes
+// on some dates list those dates, like "Stop Pender on Jul 1, 8".
+fn stop_html(terminal_pair: TerminalPair, date: Date, stop: &Stop, only_dates: Option<&[Date]>) -> Html {
+    let area_pairs = stop_area_pairs(terminal_pair, stop.terminal);
+    let area_html = if area_pairs.is_empty() {
+        html! { stop.terminal.area().short_name() }
+    } else {
+        html! {
+            <details class="sailing-popover">
+                <summary title="Show sailings for part of this journey">{ stop.terminal.area().short_name() }</summary>
+                <div class="sailing-popover-body bg-light border rounded p-2 small">
+                    <ul class="list-unstyled mb-0">
+                        { for area_pairs.into_iter().map(|area_pair| html! {
+                            <li>
+                                <Link<Route, SailingsQuery>
+                                    to={Route::Sailings}
+                                    query={SailingsQuery { from: Some(area_pair.from), to: Some(area_pair.to), date: Some(date) }}
+                                >
+                                    { format!("{} to {}", area_pair.from.short_name(), area_pair.to.short_name()) }
+                                </Link<Route, SailingsQuery>>
+                            </li>
+                        })}
+                    </ul>
+                </div>
+            </details>
+        }
+    };
     html! {
         <li>
-        { match stop.type_ {
-            StopType::Stop => "Stop",
-            StopType::Transfer => "Transfer",
-            StopType::Thrufare => "Thru-fare",
+        { match (stop.type_, HelpPage::find("thru-fares")) {
+            (StopType::Stop, _) => html! { "Stop" },
+            (StopType::Transfer, Some(page)) => html! {
+                <Link<Route> classes="link-dark" to={ Route::Help { slug: page.slug.clone() } }>
+                    <span title="Transfer to another vessel">{ "Transfer" }</span>
+                </Link<Route>>
+            },
+            (StopType::Transfer, None) => html! { <span title="Transfer to another vessel">{ "Transfer" }</span> },
+            (StopType::Thrufare, _) => html! { "Thru-fare" },
         }}
         { " " }
-        { stop.terminal.area().short_name() }
+        { area_html }
+        { for only_dates.map(|dates| html! {
+            <small class="text-muted">
+                { format!(" on {}", dates.iter().map(|&date| DATE_FORMATTER.short_date(date)).join(", ")) }
+            </small>
+        }) }
         </li>
     }
 }
@@ -77,19 +180,371 @@ fn alert_row_html(alert: &Alert) -> Html {
     }
 }
 
-fn sailing_row_html(sailing: &SailingWithNotes) -> Html {
+fn alert_dates_text(alert: &Alert) -> String {
+    if alert.relevant_date_ranges.is_empty() {
+        return "All dates".to_string();
+    }
+    alert
+        .relevant_date_ranges
+        .iter()
+        .map(|&DateRange { from, to }| {
+            if from == to {
+                DATE_FORMATTER.short_date(from)
+            } else {
+                format!("{} to {}", DATE_FORMATTER.short_date(from), DATE_FORMATTER.short_date(to))
+            }
+        })
+        .join(", ")
+}
+
+// Notices that don't apply to the viewed date are only listed here, so they don't crowd out the ones that do
+fn all_notices_row_html(alerts: &[Alert]) -> Html {
+    html! {
+        <tr class="d-print-none">
+            <td colspan="3" class="border-bottom-0">
+                <details class="small">
+                    <summary>{ format!("All notices for this route ({})", alerts.len()) }</summary>
+                    <ul class="list-unstyled mb-0 mt-1">
+                        { for alerts.iter().map(|alert| html! {
+                            <li class="mb-1">
+                                <span class="text-muted">{ format!("{}: ", alert_dates_text(alert)) }</span>
+                                { &alert.message }
+                            </li>
+                        })}
+                    </ul>
+                </details>
+            </td>
+        </tr>
+    }
+}
+
+fn switchover_row_html(switchover_time: Time) -> Html {
+    html! {
+        <tr>
+            <td colspan="3" class="border-bottom-0">
+                <div class="alert alert-info mb-0 py-1 small">
+                    { "New schedule in effect from " }
+                    { format_time(switchover_time) }
+                </div>
+            </td>
+        </tr>
+    }
+}
+
+fn operator_disclaimer_row_html(disclaimer: &str) -> Html {
+    html! {
+        <tr>
+            <td colspan="3" class="border-bottom-0">
+                <div class="alert alert-secondary mb-0 small">
+                    { disclaimer }
+                </div>
+            </td>
+        </tr>
+    }
+}
+
+fn provisional_row_html(basis: DateRange) -> Html {
+    html! {
+        <tr>
+            <td colspan="3" class="border-bottom-0">
+                <div class="alert alert-warning mb-0">
+                    <strong>{ "Provisional — based on last year's schedule." }</strong>
+                    { format!(
+                        " BC Ferries has not published the schedule for these dates yet, so these are the weekly sailings \
+                        from {} to {} last year. Expect changes, and check again before travelling.",
+                        DATE_FORMATTER.short_date(basis.from),
+                        DATE_FORMATTER.short_date(basis.to)
+                    ) }
+                </div>
+            </td>
+        </tr>
+    }
+}
+
+fn route_notes_row_html(notes: Vec<&str>) -> Html {
+    if notes.is_empty() {
+        return html! {};
+    }
+    html! {
+        <tr>
+            <td colspan="3" class="border-bottom-0 small text-muted">
+                <ul class="list-unstyled mb-0">
+                    { for notes.into_iter().map(|note| html! {
+                        <li><i class="bi bi-info-circle"/>{ " " }{ note }</li>
+                    })}
+                </ul>
+            </td>
+        </tr>
+    }
+}
+
+fn last_light_row_html(date: Date, terminal_pair: TerminalPair, sailings: &[SailingWithNotes]) -> Html {
+    let text = match last_sailing_last_light(date, terminal_pair, sailings) {
+        Some(LastLight::AfterDark { dusk }) => {
+            format!(
+                "The last sailing arrives at {} after dark (last light {})",
+                terminal_pair.to.name(),
+                format_time(dusk)
+            )
+        }
+        Some(LastLight::AfterSunset { sunset, dusk }) => format!(
+            "The last sailing arrives at {} after sunset ({}), with last light at {}",
+            terminal_pair.to.name(),
+            format_time(sunset),
+            format_time(dusk)
+        ),
+        None => return html! {},
+    };
+    html! {
+        <tr>
+            <td colspan="3" class="border-bottom-0 small text-muted">
+                <i class="bi bi-moon"/>{ " " }{ text }
+            </td>
+        </tr>
+    }
+}
+
+fn sailing_times_text(sailing: &Sailing) -> String {
+    format!("{} to {}", format_time(sailing.depart_time), format_time(sailing.arrive_time))
+}
+
+fn schedule_changes_row_html(changes: &SailingChanges) -> Html {
+    html! {
+        <tr>
+            <td colspan="3" class="border-bottom-0">
+                <div class="alert alert-warning mb-0 py-1 small">
+                    { "This schedule was changed since it was first published" }
+                    { if changes.removed.is_empty() { html! {} } else { html! {
+                        <div>
+                            { "No longer listed: " }
+                            { changes.removed.iter().map(sailing_times_text).join(", ") }
+                        </div>
+                    }}}
+                </div>
+            </td>
+        </tr>
+    }
+}
+
+fn changed_sailing_html(changes: &SailingChanges) -> Html {
+    html! {
+        <details class="d-print-none sailing-popover">
+            <summary title="Show what changed from the previously published schedule">
+                <span class="badge bg-warning text-dark">{ "changed" }</span>
+            </summary>
+            <div class="sailing-popover-body bg-light border rounded p-2 small">
+                <strong>{ "Changed from previously published schedule" }</strong>
+                { if changes.removed.is_empty() { html! {
+                    <div>{ "This sailing was added" }</div>
+                }} else { html! { <>
+                    <div>{ "Previously published sailings no longer listed:" }</div>
+                    <ul class="mb-1">
+                        { for changes.removed.iter().map(|s| html! { <li>{ sailing_times_text(s) }</li> }) }
+                    </ul>
+                </> }}}
+            </div>
+        </details>
+    }
+}
+
+fn checklist_html(terminal_pair: TerminalPair, sailing: &SailingWithNotes, home_city: Option<HomeCity>) -> Html {
+    html! {
+        <details class="d-print-none sailing-popover">
+            <summary title="Show pre-departure checklist for this sailing"><i class="bi bi-list-check"/></summary>
+            <div class="sailing-popover-body bg-light border rounded p-2 small">
+                { for TravellerType::iter().filter(|&tt| is_available_to_traveller_type(sailing, tt)).map(|traveller_type| html! { <>
+                    <strong>{ traveller_type.name() }</strong>
+                    <ul class="mb-1">
+                        { for checklist_items(terminal_pair, sailing, traveller_type, home_city).into_iter().map(|item| html! {
+                            <li>{ item }</li>
+                        })}
+                    </ul>
+                </> })}
+            </div>
+        </details>
+    }
+}
+
+fn group_booking_html(group_sailings: &[SailingWithNotes]) -> Html {
+    let (first, last) = match group_sailings {
+        [first, .., last] => (first, last),
+        _ => return html! {},
+    };
+    let span = match last.sailing.arrive_time - first.sailing.depart_time {
+        // Arrives after midnight
+        span if span.is_negative() => span + Duration::DAY,
+        span => span,
+    };
+    html! {
+        <details class="d-print-none sailing-popover">
+            <summary title="Show this and the following sailings, for groups that may not fit on one sailing"><i class="bi bi-people"/></summary>
+            <div class="sailing-popover-body bg-light border rounded p-2 small">
+                <strong>{ "Group travel" }</strong>
+                <ul class="mb-1">
+                    { for group_sailings.iter().map(|s| html! {
+                        <li>
+                            { format_time(s.sailing.depart_time) }
+                            { " to " }
+                            { format_time(s.sailing.arrive_time) }
+                        </li>
+                    })}
+                </ul>
+                <div>{ format!("All arrive within {} of the first departure", format_duration(span)) }</div>
+            </div>
+        </details>
+    }
+}
+
+fn help_pages_html(area_pair: AreaPair) -> Html {
+    let pages: Vec<_> = HelpPage::for_area_pair(area_pair).collect();
+    if pages.is_empty() {
+        return html! {};
+    }
+    html! {
+        <div class="mt-3 d-print-none">
+            <small>
+                { "Help for this route: " }
+                { for pages.into_iter().enumerate().map(|(index, page)| html! { <>
+                    { if index > 0 { ", " } else { "" } }
+                    { help_link_html(page) }
+                </> }) }
+            </small>
+        </div>
+    }
+}
+
+fn alternatives_html(alternatives: &[(AreaPair, Time)], view_date: Date) -> Html {
+    if alternatives.is_empty() {
+        return html! {};
+    }
+    html! {
+        <div class="mt-3 d-print-none">
+            <small>
+                { "Also consider: " }
+                { for alternatives.iter().enumerate().map(|(index, &(area_pair, depart_time))| html! { <>
+                    { if index > 0 { ", " } else { "" } }
+                    <Link<Route, SailingsQuery>
+                        to={ Route::Sailings }
+                        query={ SailingsQuery {
+                            from: Some(area_pair.from),
+                            to: Some(area_pair.to),
+                            date: Some(view_date),
+                        } }
+                    >
+                        { format!("{} to {}", area_pair.from.long_name(), area_pair.to.long_name()) }
+                    </Link<Route, SailingsQuery>>
+                    { format!(" (next sailing {})", format_time(depart_time)) }
+                </> }) }
+            </small>
+        </div>
+    }
+}
+
+fn sailing_note_html(note: &SailingNote) -> Html {
+    let renderer = note.renderer();
+    let text = renderer.text(&note.text);
+    let icon_class = classes!("bi", format!("bi-{}", renderer.icon));
+    let content = match renderer.badge_class {
+        Some(badge_class) => {
+            let badge_text = if note.only_dates.is_empty() {
+                text.to_string()
+            } else {
+                format!(
+                    "{} (on {})",
+                    text,
+                    note.only_dates.iter().map(|&date| DATE_FORMATTER.short_date(date)).join(", ")
+                )
+            };
+            html! {
+                <span class={ classes!("badge", badge_class, "text-wrap", "text-start") } title={ (text != note.text).then(|| note.text.clone()) }>
+                    <i class={ icon_class }/>
+                    { " " }
+                    { badge_text }
+                </span>
+            }
+        }
+        None => html! { <>
+            <i class={ classes!(icon_class, renderer.severity.text_class()) }/>
+            { " " }
+            { text }
+        </> },
+    };
+    html! {
+        <li>
+            { content }
+            { for HelpPage::for_note_category(note.category).map(|page| html! { <>
+                { " " }
+                <Link<Route> classes="link-secondary" to={ Route::Help { slug: page.slug.clone() } }>
+                    <i class="bi bi-question-circle" title={ page.title.clone() }/>
+                </Link<Route>>
+            </> }) }
+            { if cfg!(debug_assertions) { html! {
+                <span class="text-muted">{ format!(" [{}]", note.sources.iter().map(|s| format!("{:?}", s)).join(", ")) }</span>
+            }} else {
+                html! {}
+            }}
+        </li>
+    }
+}
+
+// What the sailing rows of a schedule on a date have in common
+#[derive(Clone, Copy)]
+struct SailingRowsModel<'a> {
+    terminal_pair: TerminalPair,
+    date: Date,
+    page_url: &'a str,
+    changes: Option<&'a SailingChanges>,
+    home_city: Option<HomeCity>,
+}
+
+fn sailing_row_html(rows_model: SailingRowsModel, sailings: &[SailingWithNotes], index: usize) -> Html {
+    let SailingRowsModel { terminal_pair, date, page_url, changes, home_city } = rows_model;
+    let sailing = &sailings[index];
+    let group_sailings = consecutive_sailings(sailings, index, GROUP_TRAVEL_SAILINGS);
     let main_td_class = (!sailing.notes.is_empty()).then_some("border-bottom-0");
-    let all_td_class = sailing.sailing.is_thrufare().then_some("text-muted");
+    let all_td_class = sailing.sailing.is_thrufare(terminal_pair).then_some("text-muted");
+    let time_class =
+        sailing.has_note_category(NoteCategory::ImpossibleConnection).then_some("text-decoration-line-through");
+    let anchor_id = sailing_anchor_id(terminal_pair, sailing.sailing.depart_time);
+    let sailing_url = format!("{}#{}", page_url, anchor_id);
+    let (depart_datetime, arrive_datetime) = sailing_datetime_attributes(sailing.depart_date(date), &sailing.sailing);
     html! { <>
-        <tr>
-            <td class={ classes!(all_td_class, main_td_class) }>{ format_time(sailing.sailing.depart_time) }</td>
-            <td class={ classes!(all_td_class, main_td_class) }>{ format_time(sailing.sailing.arrive_time) }</td>
+        <tr id={ anchor_id }>
+            <td class={ classes!(all_td_class, main_td_class) }>
+                <time class={ classes!(time_class) } datetime={ depart_datetime }>
+                    { format_time(sailing.sailing.depart_time) }
+                </time>
+                { for changes.filter(|c| c.added.contains(&sailing.sailing)).map(changed_sailing_html) }
+                <SaveTripButton
+                    trip={ SavedTrip { terminal_pair, date: sailing.depart_date(date), depart_time: sailing.sailing.depart_time } }
+                />
+                <QrCodeDetails data={ sailing_url } title="Show QR code for this sailing"/>
+                { checklist_html(terminal_pair, sailing, home_city) }
+                { group_booking_html(group_sailings) }
+            </td>
+            <td class={ classes!(all_td_class, main_td_class) }>
+                <time class={ classes!(time_class) } datetime={ arrive_datetime }>
+                    { format_time(sailing.sailing.arrive_time) }
+                </time>
+                { if sailing.sailing.arrive_time < sailing.sailing.depart_time { html! {
+                    <div class="small text-muted">{ "next day" }</div>
+                }} else {
+                    html! {}
+                }}
+            </td>
             <td class={ classes!("text-nowrap", all_td_class, main_td_class) }>
                 { if sailing.sailing.stops.is_empty() { html! {
                     <span class="text-muted">{ "non-stop" }</span>
                 }} else { html! {
                     <ul class="list-unstyled mb-0">
-                        { for sailing.sailing.stops.iter().map(stop_html) }
+                        { for sailing.sailing.stops.iter().map(|stop| {
+                            let only_dates = sailing
+                                .conditional_stop_dates
+                                .iter()
+                                .find(|(terminal, _)| *terminal == stop.terminal)
+                                .map(|(_, dates)| dates.as_slice());
+                            stop_html(terminal_pair, date, stop, only_dates)
+                        }) }
                     </ul>
                 }}}
             </td>
@@ -97,10 +552,8 @@ fn sailing_row_html(sailing: &SailingWithNotes) -> Html {
         { if !sailing.notes.is_empty() { html! {
             <tr>
                 <td colspan="3" class={ classes!("small", "pt-0", all_td_class) }>
-                    <ul class="mb-0">
-                        { for sailing.notes.iter().map(|note| { html! {
-                            <li>{ note }</li>
-                        }})}
+                    <ul class="list-unstyled mb-0">
+                        { for sailing.notes.iter().map(sailing_note_html) }
                     </ul>
                 </td>
             </tr>
@@ -114,8 +567,18 @@ fn schedule_sailings_header_row_html(schedule: &Schedule) -> Html {
     html! {
         <tr>
             <th class="bg-heading">
+                { if schedule.operator.is_supplemental() { html! {
+                    <div><span class="badge bg-light text-dark">{ schedule.operator.name() }</span></div>
+                }} else {
+                    html! {}
+                }}
                 <span class="fw-normal">{ "Depart " }</span>
-                <span class="text-nowrap">{ schedule.terminal_pair.from.name() }</span>
+                <Link<Route>
+                    classes="text-nowrap link-dark"
+                    to={ Route::Departures { terminal: schedule.terminal_pair.from } }
+                >
+                    { schedule.terminal_pair.from.name() }
+                </Link<Route>>
             </th>
             <th class="bg-heading">
                 <span class="fw-normal">{ "Arrive " }</span>
@@ -128,8 +591,32 @@ fn schedule_sailings_header_row_html(schedule: &Schedule) -> Html {
     }
 }
 
-fn schedule_sailings_rows_html(first: bool, last: bool, schedule: &Schedule, sailings: &[SailingWithNotes]) -> Html {
+pub fn schedule_sailings_rows_html(
+    first: bool,
+    last: bool,
+    date: Date,
+    schedule: &Schedule,
+    sailings: &[SailingWithNotes],
+) -> Html {
     let bottom_class = (!last).then_some("pb-3");
+    let page_url = sailings_page_url(schedule.terminal_pair.area_pair(), date);
+    let switchover_time = schedule.switchover_time(date);
+    let (before_switchover_sailings, after_switchover_sailings) = sailings.split_at(
+        switchover_time
+            .map(|time| sailings.iter().position(|s| s.sailing.depart_time >= time).unwrap_or(sailings.len()))
+            .unwrap_or(0),
+    );
+    let changes = schedule_sailing_changes(schedule, date, sailings);
+    let has_other_date_alerts = schedule.alerts.iter().any(|a| !a.is_relevant_on(date));
+    let home_city = load_or_default::<HomeCityPreference>(&*default_storage()).city;
+    let rows_model = SailingRowsModel {
+        terminal_pair: schedule.terminal_pair,
+        date,
+        page_url: &page_url,
+        changes: changes.as_ref(),
+        home_city,
+    };
+    let row_html = |index: usize| sailing_row_html(rows_model, sailings, index);
     html! { <>
         { if first {
             html! {
@@ -145,8 +632,17 @@ fn schedule_sailings_rows_html(first: bool, last: bool, schedule: &Schedule, sai
             }
         }}
         <tbody>
-        { for schedule.alerts.iter().map(alert_row_html) }
-        { for sailings.iter().map(sailing_row_html) }
+        { route_notes_row_html(schedule.route_notes()) }
+        { for schedule.operator.disclaimer().map(operator_disclaimer_row_html) }
+        { for schedule.provisional_basis.map(provisional_row_html) }
+        { for schedule.alerts.iter().filter(|a| a.is_relevant_on(date)).map(alert_row_html) }
+        { if has_other_date_alerts { all_notices_row_html(&schedule.alerts) } else { html! {} }}
+        { for changes.as_ref().map(schedule_changes_row_html) }
+        { for before_switchover_sailings.iter().enumerate().map(|(i, _)| row_html(i)) }
+        { for switchover_time.map(switchover_row_html) }
+        { for after_switchover_sailings.iter().enumerate().map(|(i, _)| row_html(before_switchover_sailings.len() + i)) }
+        { last_light_row_html(date, schedule.terminal_pair, sailings) }
+        { schedule_json_ld_html(date, schedule, sailings) }
         </tbody>
         <tbody>
             <tr>
@@ -165,69 +661,59 @@ fn schedule_sailings_rows_html(first: bool, last: bool, schedule: &Schedule, sai
     </> }
 }
 
+impl Stored for KnownMaxDates {
+    const KEY: &'static str = "known-max-dates";
+    const VERSION: u32 = 1;
+}
+
+impl Stored for BothDirectionsPreference {
+    const KEY: &'static str = "both-directions-preference";
+    const VERSION: u32 = 1;
+}
+
+impl Stored for ServiceDayPreference {
+    const KEY: &'static str = "service-day-preference";
+    const VERSION: u32 = 1;
+}
+
+impl KnownMaxDates {
+    fn get(&self, area_pair: AreaPair) -> Option<Date> {
+        self.entries.iter().find(|(ap, _)| *ap == area_pair).map(|(_, date)| *date)
+    }
+
+    fn set(&mut self, area_pair: AreaPair, date: Date) {
+        self.entries.retain(|(ap, _)| *ap != area_pair);
+        self.entries.push((area_pair, date));
+    }
+}
+
+fn save_known_max_date(area_pair: AreaPair, max_date: Date) {
+    let storage = default_storage();
+    let mut known_max_dates: KnownMaxDates = load_or_default(&*storage);
+    if known_max_dates.get(area_pair) != Some(max_date) {
+        known_max_dates.set(area_pair, max_date);
+        save(&*storage, &known_max_dates).unwrap_or_else(|err| warn!("{:?}", err));
+    }
+}
+
+fn schedule_sailings_summary_text(schedule_sailings: &[(&Schedule, Vec<SailingWithNotes>)]) -> String {
+    let sailings: Vec<_> =
+        schedule_sailings.iter().flat_map(|(_, sailings)| sailings.iter().map(|s| &s.sailing)).collect();
+    sailings_summary_text(&sailings)
+}
+
 impl<'a> SailingsModel<'a> {
-    fn new(
-        schedules_state: &'a SchedulesState,
-        date_input_state: &DateInputState,
-        area_pair: AreaPair,
-        query_date_or_today: Date,
-    ) -> SailingsModel<'a> {
-        let base = SailingsModel {
-            sailings_state_model: SailingsStateModel::NoSailings,
-            area_pair,
-            view_date: query_date_or_today,
-            max_date: query_date_or_today,
-        };
-        match (date_input_state.value, schedules_state) {
-            (Err(err), _) => {
-                SailingsModel { sailings_state_model: SailingsStateModel::InvalidDate(err.to_string()), ..base }
-            }
-            (Ok(view_date), SchedulesState::Init) | (Ok(view_date), SchedulesState::Loading) => SailingsModel {
-                sailings_state_model: SailingsStateModel::LoadingSchedules,
-                view_date,
-                max_date: view_date,
-                ..base
-            },
-            (Ok(view_date), SchedulesState::Failed) => SailingsModel {
-                sailings_state_model: SailingsStateModel::LoadSchedulesFailed,
-                view_date,
-                max_date: view_date,
-                ..base
-            },
-            (Ok(view_date), SchedulesState::Loaded(schedules_map)) => {
-                let max_date = max(
-                    view_date,
-                    AREA_PAIR_TERMINAL_PAIRS
-                        .get(&area_pair)
-                        .and_then(|tps| {
-                            tps.iter()
-                                .flat_map(|tp| {
-                                    schedules_map.get(tp).and_then(|ss| ss.iter().map(|s| s.date_range.to).max())
-                                })
-                                .max()
-                        })
-                        .unwrap_or(view_date),
-                );
-                if let Some(schedules_sailings) = area_sailings_for_date(area_pair, view_date, schedules_map) {
-                    if schedules_sailings.is_empty() {
-                        SailingsModel {
-                            sailings_state_model: SailingsStateModel::NoSailings,
-                            view_date,
-                            max_date,
-                            ..base
-                        }
-                    } else {
-                        SailingsModel {
-                            sailings_state_model: SailingsStateModel::Sailings(schedules_sailings),
-                            view_date,
-                            max_date,
-                            ..base
-                        }
-                    }
-                } else {
-                    SailingsModel { sailings_state_model: SailingsStateModel::NoSchedule, view_date, max_date, ..base }
-                }
-            }
+    // Description of the page for link previews, if there are sailings to describe
+    fn description_text(&self) -> Option<String> {
+        match &self.sailings_state_model {
+            SailingsStateModel::Sailings(schedule_sailings) => Some(format!(
+                "Ferry sailings from {} to {} on {}: {}",
+                self.area_pair.from.long_name(),
+                self.area_pair.to.long_name(),
+                DATE_FORMATTER.long_date(self.view_date),
+                schedule_sailings_summary_text(schedule_sailings)
+            )),
+            _ => None,
         }
     }
 
@@ -235,13 +721,23 @@ impl<'a> SailingsModel<'a> {
         let last_schedule_index = schedule_sailings.len() - 1;
         html! { <>
             <div>
-                <h6>{ self.view_date.format(format_description!("[weekday], [day padding:none] [month repr:long], [year]")).expect("Expect friendly date to format") }</h6>
+                <h6>
+                    { DATE_FORMATTER.long_date(self.view_date) }
+                    { for bc_holiday_name(self.view_date).map(|name| html! {
+                        <span class="badge bg-info text-dark ms-2">{ name }</span>
+                    }) }
+                </h6>
             </div>
+            <p class="visually-hidden">{ schedule_sailings_summary_text(schedule_sailings) }</p>
             <table class="table table-light mb-0">
                 { for schedule_sailings.iter().enumerate().map(|(index, (schedule, sailings))|
-                    schedule_sailings_rows_html(index == 0, index == last_schedule_index, schedule, sailings)
+                    schedule_sailings_rows_html(index == 0, index == last_schedule_index, self.view_date, schedule, sailings)
                 ) }
             </table>
+            <div class="d-none d-print-block mt-3 text-center">
+                { qr_code_html(&sailings_page_url(self.area_pair, self.view_date), "qr-code") }
+                <div class="small text-muted">{ "Scan to view these sailings on your phone" }</div>
+            </div>
         </> }
     }
 
@@ -261,6 +757,8 @@ impl<'a> SailingsModel<'a> {
                     { "There was a problem loading the ferry schedules; please refresh your browser to try again." }
                 </div>
             },
+            // The missing partition warning already explains why
+            SailingsStateModel::NoSchedule if self.missing_date_range.is_some() => html! {},
             SailingsStateModel::NoSchedule => html! {
                 <div class="alert alert-warning text-center" role="alert">
                     { "There is no schedule available for this date yet; please check back later!" }
@@ -271,11 +769,60 @@ impl<'a> SailingsModel<'a> {
                     { "There are no sailings between the these terminals on the specified date." }
                 </div>
             },
+            SailingsStateModel::Suspended(suspension) => html! {
+                <div class="alert alert-warning text-center" role="alert">
+                    { format!(
+                        "Service on this route is suspended until {}.",
+                        DATE_FORMATTER.short_date(suspension.to)
+                    ) }
+                </div>
+            },
             SailingsStateModel::Sailings(schedule_sailings) => self.sailings_table_html(schedule_sailings),
         }
     }
 
-    fn html(self) -> Html {
+    fn pane_heading_html(&self) -> Html {
+        html! {
+            <h5>
+                { self.area_pair.from.long_name() }
+                <i class="bi bi-arrow-right mx-2" aria-label="to"/>
+                { self.area_pair.to.long_name() }
+            </h5>
+        }
+    }
+
+    // Countdown and sailings for one direction of travel
+    fn pane_html(&self) -> Html {
+        let today_depart_times = match &self.sailings_state_model {
+            SailingsStateModel::Sailings(schedule_sailings) if self.view_date == today_vancouver() => Some(
+                schedule_sailings
+                    .iter()
+                    .flat_map(|(_, sailings)| sailings.iter().map(|s| s.sailing.depart_time))
+                    .sorted()
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        };
+        html! { <>
+            { for self.missing_date_range.map(|date_range| html! {
+                <div class="alert alert-warning py-2" role="status">
+                    { format!(
+                        "Schedules for {} to {} could not be loaded yet, so some sailings may be missing. Retrying in the background.",
+                        DATE_FORMATTER.short_date(date_range.from),
+                        DATE_FORMATTER.short_date(date_range.to)
+                    ) }
+                </div>
+            }) }
+            { for today_depart_times.map(|depart_times| {
+                let route_name =
+                    format!("{} to {}", self.area_pair.from.short_name(), self.area_pair.to.short_name());
+                html! { <NextSailingCountdown {depart_times} {route_name}/> }
+            }) }
+            { self.sailings_html() }
+        </> }
+    }
+
+    fn html(self, day_navigation_html: Html, return_model: Option<SailingsModel>) -> Html {
         let info_urls = if self.area_pair.includes_terminal(Terminal::SWB)
             && self.area_pair.includes_any_terminal(&*ROUTE_5_AND_9_GULF_ISLAND_TERMINALS)
         {
@@ -302,33 +849,64 @@ impl<'a> SailingsModel<'a> {
         let is_reservable = self.area_pair.is_reservable();
         let has_thrufares = match &self.sailings_state_model {
             SailingsStateModel::Sailings(schedule_sailings) => {
-                schedule_sailings.iter().any(|(_, a)| a.iter().any(|b| b.sailing.is_thrufare()))
+                schedule_sailings.iter().any(|(s, a)| a.iter().any(|b| b.sailing.is_thrufare(s.terminal_pair)))
             }
             _ => false,
         };
+        let has_sailings = matches!(self.sailings_state_model, SailingsStateModel::Sailings(_));
+        let reservations_open_on = match &self.sailings_state_model {
+            SailingsStateModel::Sailings(schedule_sailings) => schedule_sailings
+                .iter()
+                .filter(|(s, _)| s.operator.is_bc_ferries())
+                .filter_map(|(s, _)| reservations_open_date(s, today_vancouver()))
+                .min(),
+            _ => None,
+        };
         html! { <>
             <div class="row mt-4">
-                <div class="col-12 col-md-8 col-lg-6">
-                    { self.sailings_html() }
-                </div>
+                { if let Some(return_model) = &return_model { html! { <>
+                    <div class="col-12 col-lg-6">
+                        { self.pane_heading_html() }
+                        { self.pane_html() }
+                    </div>
+                    <div class="col-12 col-lg-6 mt-3 mt-lg-0">
+                        { return_model.pane_heading_html() }
+                        { return_model.pane_html() }
+                    </div>
+                    <div class="col-12">
+                        { if has_sailings { day_navigation_html } else { html! {} }}
+                        { route_map_html(self.area_pair) }
+                    </div>
+                </> }} else { html! { <>
+                    <div class="col-12 col-md-8 col-lg-6">
+                        { self.pane_html() }
+                        { if has_sailings { day_navigation_html } else { html! {} }}
+                    </div>
+                    <div class="col-12 col-md-4 col-lg-3 mt-3 mt-md-0">
+                        { route_map_html(self.area_pair) }
+                    </div>
+                </> }}}
             </div>
-            { if is_reservable || has_thrufares { html! { <>
+            { if is_reservable || has_thrufares || reservations_open_on.is_some() { html! { <>
                 <div class="mt-3">
                     <small>
-                        { if is_reservable { html! {
+                        { if is_reservable || reservations_open_on.is_some() { html! {
                             <span class="text-nowrap">
                                 <a href={ BCFERRIES_HOME_URL } target="_blank">{ "Reservations" }</a>
-                                { if has_thrufares {
-                                    " are recommended for direct sailings."
-                                } else {
-                                    " are recommended."
+                                { match reservations_open_on {
+                                    Some(opens_on) => format!(
+                                        " for this period usually open on {}.",
+                                        DATE_FORMATTER.short_date(opens_on)
+                                    ),
+                                    None if has_thrufares => " are recommended for direct sailings.".to_string(),
+                                    None => " are recommended.".to_string(),
                                 }}
                             </span>
                         }} else {
                             html! {}
                         }}
                         { if has_thrufares { html! { <>
-                            { if is_reservable { " " } else { "" }}
+                            { if is_reservable || reservations_open_on.is_some() { " " } else { "" }}
                             <span class="text-nowrap">
                                 { "See here for more " }
                                 <a href={ THRU_FARE_INFORMATION_URL } target="_blank">{ "information about thru-fares" }</a>
@@ -342,6 +920,8 @@ impl<'a> SailingsModel<'a> {
             </> }} else {
                 html! {}
             }}
+            { alternatives_html(&self.alternatives, self.view_date) }
+            { help_pages_html(self.area_pair) }
             <div class="mt-3 text-muted">
                 <small>
                     <div><strong>{ "BC Ferries may adjust schedules at any time and without notice." }</strong></div>
@@ -370,6 +950,13 @@ impl<'a> SailingsModel<'a> {
     }
 }
 
+fn day_navigation_date_text(date: Date) -> String {
+    match bc_holiday_name(date) {
+        Some(name) => format!("{} ({})", DATE_FORMATTER.short_date(date), name),
+        None => DATE_FORMATTER.short_date(date),
+    }
+}
+
 impl FormModel {
     fn onchange_date_input_callback(&self) -> Callback<Event> {
         let date_input_state = self.date_input_state.clone();
@@ -411,6 +998,106 @@ impl FormModel {
         })
     }
 
+    // Goes to a page from the navigation history, updating the date input like the other date controls do
+    fn navigate_to_entry(&self, entry: NavigationEntry, action: Option<NavigationAction>) -> impl FnOnce() {
+        let date_input_state = self.date_input_state.clone();
+        let history = self.history.clone();
+        let dispatch = self.navigation.dispatch.clone();
+        let today = self.today;
+        move || {
+            let date = entry.date.filter(|&date| date >= today).unwrap_or(today);
+            date_input_state.set(DateInputState { input: format_iso8601_date(date), value: Ok(date) });
+            if let Some(action) = action {
+                dispatch.emit(action);
+            }
+            history
+                .push_with_query(
+                    Route::Sailings,
+                    SailingsQuery { from: Some(entry.area_pair.from), to: Some(entry.area_pair.to), date: entry.date },
+                )
+                .expect("Expect history to push");
+        }
+    }
+
+    fn onclick_navigation_button_callback(&self, action: NavigationAction) -> Callback<MouseEvent> {
+        let entry = match action {
+            NavigationAction::Back => self.navigation.history.back_entry(),
+            NavigationAction::Forward => self.navigation.history.forward_entry(),
+            NavigationAction::Visit(ref entry) => Some(entry),
+        };
+        match entry {
+            Some(&entry) => {
+                let navigate = self.navigate_to_entry(entry, Some(action));
+                Callback::once(move |_| navigate())
+            }
+            None => Callback::noop(),
+        }
+    }
+
+    fn onchange_recent_entries_select_callback(&self, recent_entries: Vec<NavigationEntry>) -> Callback<Event> {
+        let navigate_callbacks: Vec<_> = recent_entries.into_iter().map(|e| self.navigate_to_entry(e, None)).collect();
+        Callback::once(move |e: Event| {
+            let index = e.target_unchecked_into::<web_sys::HtmlSelectElement>().selected_index();
+            // The first option is the menu's label
+            if let Some(navigate) = usize::try_from(index - 1).ok().and_then(|i| navigate_callbacks.into_iter().nth(i))
+            {
+                navigate();
+            }
+        })
+    }
+
+    fn navigation_html(&self) -> Html {
+        let history = &self.navigation.history;
+        let today = self.today;
+        let recent_entries: Vec<_> = history
+            .recent_entries(RECENT_NAVIGATION_ENTRIES)
+            .into_iter()
+            .filter(|entry| entry.date.map(|date| date >= today).unwrap_or(true))
+            .collect();
+        let entry_label = |entry: &NavigationEntry| {
+            format!(
+                "{} to {}, {}",
+                entry.area_pair.from.short_name(),
+                entry.area_pair.to.short_name(),
+                entry.date.map(|date| DATE_FORMATTER.short_date(date)).unwrap_or_else(|| "today".to_string())
+            )
+        };
+        html! {
+            <nav class="d-flex align-items-center mb-2" aria-label="Navigation history">
+                <button
+                    type="button"
+                    class="btn btn-outline-secondary btn-sm border-0"
+                    title="Back"
+                    disabled={ history.back_entry().is_none() }
+                    onclick={ self.onclick_navigation_button_callback(NavigationAction::Back) }
+                >
+                    <i class="bi bi-arrow-left"/>
+                </button>
+                <button
+                    type="button"
+                    class="btn btn-outline-secondary btn-sm border-0"
+                    title="Forward"
+                    disabled={ history.forward_entry().is_none() }
+                    onclick={ self.onclick_navigation_button_callback(NavigationAction::Forward) }
+                >
+                    <i class="bi bi-arrow-right"/>
+                </button>
+                { if recent_entries.is_empty() {
+                    html! {}
+                } else { html! {
+                    <select
+                        class="form-select form-select-sm w-auto ms-2"
+                        aria-label="Recently viewed"
+                        onchange={ self.onchange_recent_entries_select_callback(recent_entries.clone()) }
+                    >
+                        <option selected={ true } disabled={ true }>{ "Recently viewed" }</option>
+                        { for recent_entries.iter().map(|entry| html! { <option>{ entry_label(entry) }</option> }) }
+                    </select>
+                }}}
+            </nav>
+        }
+    }
+
     fn onclick_adjust_date_button_callback(&self, opt_new_date: Option<Date>) -> Callback<MouseEvent> {
         let date_input_state = self.date_input_state.clone();
         let history = self.history.clone();
@@ -428,6 +1115,46 @@ impl FormModel {
         })
     }
 
+    fn onchange_include_supplemental_input_callback(&self) -> Callback<Event> {
+        let include_supplemental_state = self.include_supplemental_state.clone();
+        Callback::from(move |e: Event| {
+            include_supplemental_state.set(e.target_unchecked_into::<HtmlInputElement>().checked());
+        })
+    }
+
+    // The whole view, including its modes, for sharing as one link
+    fn view_query(&self) -> ViewQuery {
+        let view_state = ViewState {
+            from: Some(self.area_pair.from),
+            to: Some(self.area_pair.to),
+            date: self.query_date,
+            modes: ViewModes {
+                include_supplemental: *self.include_supplemental_state,
+                by_service_day: *self.service_day_state,
+                both_directions: *self.both_directions_state,
+            },
+        };
+        ViewQuery { view: Some(view_state.encode()) }
+    }
+
+    fn onchange_both_directions_input_callback(&self) -> Callback<Event> {
+        let both_directions_state = self.both_directions_state.clone();
+        Callback::from(move |e: Event| {
+            let enabled = e.target_unchecked_into::<HtmlInputElement>().checked();
+            save(&*default_storage(), &BothDirectionsPreference { enabled }).unwrap_or_else(|err| warn!("{:?}", err));
+            both_directions_state.set(enabled);
+        })
+    }
+
+    fn onchange_service_day_input_callback(&self) -> Callback<Event> {
+        let service_day_state = self.service_day_state.clone();
+        Callback::from(move |e: Event| {
+            let enabled = e.target_unchecked_into::<HtmlInputElement>().checked();
+            save(&*default_storage(), &ServiceDayPreference { enabled }).unwrap_or_else(|err| warn!("{:?}", err));
+            service_day_state.set(enabled);
+        })
+    }
+
     fn onclick_swap_terminals_button_callback(&self) -> Callback<MouseEvent> {
         let history = self.history.clone();
         let area_pair = self.area_pair.swapped();
@@ -442,9 +1169,43 @@ impl FormModel {
         })
     }
 
+    fn adjust_date_button_html(&self, opt_date: Option<Date>, content: Html) -> Html {
+        if let Some(date) = opt_date {
+            html! {
+                <button
+                    type="button"
+                    class="btn btn-link btn-sm link-secondary text-decoration-none"
+                    onclick={ self.onclick_adjust_date_button_callback(Some(date)) }
+                >
+                    { content }
+                </button>
+            }
+        } else {
+            html! { <span/> }
+        }
+    }
+
+    fn day_navigation_html(&self) -> Html {
+        let previous_date = self.view_date.previous_day().filter(|d| *d >= self.today);
+        let next_date = self.view_date.next_day().filter(|d| *d <= self.max_date);
+        html! {
+            <nav class="d-flex justify-content-between mt-1 d-print-none" aria-label="Day navigation">
+                { self.adjust_date_button_html(previous_date, html! { <>
+                    <i class="bi bi-caret-left-fill"/>
+                    { previous_date.map(day_navigation_date_text).unwrap_or_default() }
+                </> }) }
+                { self.adjust_date_button_html(next_date, html! { <>
+                    { next_date.map(day_navigation_date_text).unwrap_or_default() }
+                    <i class="bi bi-caret-right-fill"/>
+                </> }) }
+            </nav>
+        }
+    }
+
     fn html(self) -> Html {
         html! {
             <div class="d-print-none">
+                { self.navigation_html() }
                 <div class="row mb-1">
                     <label class="col-2 col-md-1 col-form-label">{ "From" }</label>
                     <div class="col-10 col-md-7 col-lg-5">
@@ -483,6 +1244,7 @@ impl FormModel {
                             value={ self.date_input_state.input.to_owned() }
                             min={ format_iso8601_date(self.today) }
                             max={ format_iso8601_date(self.max_date) }
+                            title={ bc_holiday_name(self.view_date).map(|name| format!("{} is a holiday", name)).unwrap_or_default() }
                             onchange={ self.onchange_date_input_callback() }/>
                         <button
                             type="button"
@@ -522,6 +1284,65 @@ impl FormModel {
                         </button>
                     </div>
                 </div>
+                { if self.has_supplemental_schedules { html! {
+                    <div class="row mb-3">
+                        <div class="offset-2 offset-md-1 col-10 col-md-7 col-lg-5">
+                            <div class="form-check">
+                                <input
+                                    id="include-supplemental-input"
+                                    type="checkbox"
+                                    class="form-check-input"
+                                    checked={ *self.include_supplemental_state }
+                                    onchange={ self.onchange_include_supplemental_input_callback() }/>
+                                <label for="include-supplemental-input" class="form-check-label">
+                                    { "Include water taxis not operated by BC Ferries" }
+                                </label>
+                            </div>
+                        </div>
+                    </div>
+                }} else {
+                    html! {}
+                }}
+                <div class="row mb-3">
+                    <div class="offset-2 offset-md-1 col-10 col-md-7 col-lg-5">
+                        <div class="form-check">
+                            <input
+                                id="both-directions-input"
+                                type="checkbox"
+                                class="form-check-input"
+                                checked={ *self.both_directions_state }
+                                onchange={ self.onchange_both_directions_input_callback() }/>
+                            <label for="both-directions-input" class="form-check-label">
+                                { "Show both directions side by side" }
+                            </label>
+                        </div>
+                    </div>
+                </div>
+                <div class="row mb-3">
+                    <div class="offset-2 offset-md-1 col-10 col-md-7 col-lg-5">
+                        <div class="form-check">
+                            <input
+                                id="service-day-input"
+                                type="checkbox"
+                                class="form-check-input"
+                                checked={ *self.service_day_state }
+                                onchange={ self.onchange_service_day_input_callback() }/>
+                            <label for="service-day-input" class="form-check-label">
+                                { "List sailings just after midnight with the evening before" }
+                            </label>
+                        </div>
+                    </div>
+                </div>
+                <div class="row mb-3 d-print-none">
+                    <div class="offset-2 offset-md-1 col-10 col-md-7 col-lg-5">
+                        <small>
+                            <Link<Route, ViewQuery> to={ Route::Sailings } query={ self.view_query() }>
+                                <i class="bi bi-link-45deg"/>
+                                { " Link to this view" }
+                            </Link<Route, ViewQuery>>
+                        </small>
+                    </div>
+                </div>
             </div>
         }
     }
@@ -543,18 +1364,108 @@ pub fn sailings_component(props: &SailingsProps) -> Html {
         input: format_iso8601_date(query_date_or_today),
         value: Ok(query_date_or_today),
     });
-    let sailings_model = SailingsModel::new(&schedules_state, &date_input_state, area_pair, query_date_or_today);
+    let modes = props.modes;
+    let include_supplemental_state = use_state(|| modes.map(|m| m.include_supplemental).unwrap_or(false));
+    let both_directions_state = use_state(|| match modes {
+        Some(modes) => modes.both_directions,
+        None => load_or_default::<BothDirectionsPreference>(&*default_storage()).enabled,
+    });
+    let service_day_state = use_state(|| match modes {
+        Some(modes) => modes.by_service_day,
+        None => load_or_default::<ServiceDayPreference>(&*default_storage()).enabled,
+    });
+    let known_max_dates: KnownMaxDates = load_or_default(&*default_storage());
+    let known_max_date = known_max_dates.get(area_pair);
+    let MissingPartitionsContext(missing_partitions) =
+        use_context::<MissingPartitionsContext>().expect("Expect missing partitions to be available");
+    let options =
+        SailingsOptions { include_supplemental: *include_supplemental_state, by_service_day: *service_day_state };
+    let sailings_model = SailingsModel::new(
+        &schedules_state,
+        date_input_state.value,
+        area_pair,
+        query_date_or_today,
+        options,
+        known_max_date,
+        &missing_partitions,
+    );
+    // Both directions share the date controls, which are bounded by the outbound sailings
+    let return_model = (*both_directions_state).then(|| {
+        SailingsModel::new(
+            &schedules_state,
+            date_input_state.value,
+            area_pair.swapped(),
+            query_date_or_today,
+            options,
+            known_max_dates.get(area_pair.swapped()),
+            &missing_partitions,
+        )
+    });
+    let navigation = use_context::<NavigationHistoryContext>().expect("Expect navigation history to be available");
+    use_effect_with_deps(
+        {
+            let dispatch = navigation.dispatch.clone();
+            move |&entry| {
+                dispatch.emit(NavigationAction::Visit(entry));
+                || ()
+            }
+        },
+        NavigationEntry { area_pair, date: query_date },
+    );
+    let SchedulesRevalidator(revalidate) =
+        use_context::<SchedulesRevalidator>().expect("Expect schedules revalidator to be available");
+    use_revalidation(SCHEDULES_REVALIDATION_INTERVAL, sailings_model.view_date == today, revalidate);
+    use_effect_with_deps(
+        |&(area_pair, fresh_max_date)| {
+            if let Some(max_date) = fresh_max_date {
+                save_known_max_date(area_pair, max_date);
+            }
+            || ()
+        },
+        (area_pair, sailings_model.fresh_max_date),
+    );
+    // Shortcut links go to a sailing, which isn't on the page until the schedules have loaded
+    use_effect_with_deps(
+        |&loaded| {
+            if loaded {
+                scroll_to_location_hash();
+            }
+            || ()
+        },
+        matches!(schedules_state, SchedulesState::Loaded(_)),
+    );
+    use_effect_with_deps(
+        |description: &Option<String>| {
+            let previous = description.as_deref().and_then(replace_meta_description);
+            move || {
+                if let Some(previous) = previous {
+                    replace_meta_description(&previous);
+                }
+            }
+        },
+        sailings_model.description_text(),
+    );
     let form_model = FormModel {
         history,
+        navigation,
         date_input_state,
+        include_supplemental_state,
+        both_directions_state,
+        service_day_state,
         area_pair,
         query_date,
         today,
         view_date: sailings_model.view_date,
         max_date: sailings_model.max_date,
+        has_supplemental_schedules: sailings_model.has_supplemental_schedules,
     };
+    let live_data = use_context::<LiveDataHealth>().expect("Expect live data health to be available");
+    let day_navigation_html = form_model.day_navigation_html();
+    let view_date = sailings_model.view_date;
     html! { <>
+        { live_data_chip_html(&live_data) }
         { form_model.html() }
-        { sailings_model.html() }
+        <OfflinePin {area_pair} {view_date}/>
+        { sailings_model.html(day_navigation_html, return_model) }
     </> }
 }
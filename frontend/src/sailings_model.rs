@@ -0,0 +1,352 @@
+use crate::prelude::*;
+use crate::sailings_processor::*;
+use crate::types::*;
+
+pub enum SailingsStateModel<'a> {
+    InvalidDate(String),
+    LoadingSchedules,
+    LoadSchedulesFailed,
+    NoSchedule,
+    NoSailings,
+    Suspended(DateRange),
+    Sailings(Vec<(&'a Schedule, Vec<SailingWithNotes>)>),
+}
+
+// How the user asked for sailings to be listed
+#[derive(Clone, Copy, Default)]
+pub struct SailingsOptions {
+    pub include_supplemental: bool,
+    // List sailings just after midnight with the evening before
+    pub by_service_day: bool,
+}
+
+impl SailingsOptions {
+    pub fn filter(&self) -> SailingFilter {
+        SailingFilter::default().include_supplemental(self.include_supplemental)
+    }
+}
+
+pub struct SailingsModel<'a> {
+    pub sailings_state_model: SailingsStateModel<'a>,
+    pub area_pair: AreaPair,
+    pub view_date: Date,
+    pub max_date: Date,
+    // Set only when `max_date` comes from loaded schedules rather than a remembered value
+    pub fresh_max_date: Option<Date>,
+    pub has_supplemental_schedules: bool,
+    // Dates of a schedules partition that failed to load, if it includes the view date
+    pub missing_date_range: Option<DateRange>,
+    // Other area pairs for the same journey, with the departure time of their next sailing on the view date
+    pub alternatives: Vec<(AreaPair, Time)>,
+}
+
+impl<'a> SailingsModel<'a> {
+    // Derives what the sailings page shows from its inputs, without depending on Yew so it can be unit tested
+    pub fn new(
+        schedules_state: &'a SchedulesState,
+        date_value: StdResult<Date, &'static str>,
+        area_pair: AreaPair,
+        query_date_or_today: Date,
+        options: SailingsOptions,
+        known_max_date: Option<Date>,
+        missing_partitions: &[DataPartition],
+    ) -> SailingsModel<'a> {
+        let view_date = match date_value {
+            Err(err) => {
+                let sailings_state_model = SailingsStateModel::InvalidDate(err.to_string());
+                return SailingsModel::without_schedules(
+                    sailings_state_model,
+                    area_pair,
+                    query_date_or_today,
+                    known_max_date,
+                );
+            }
+            Ok(view_date) => view_date,
+        };
+        let schedules_map = match schedules_state {
+            SchedulesState::Init | SchedulesState::Loading => {
+                return SailingsModel::without_schedules(
+                    SailingsStateModel::LoadingSchedules,
+                    area_pair,
+                    view_date,
+                    known_max_date,
+                )
+            }
+            SchedulesState::Failed => {
+                return SailingsModel::without_schedules(
+                    SailingsStateModel::LoadSchedulesFailed,
+                    area_pair,
+                    view_date,
+                    known_max_date,
+                )
+            }
+            SchedulesState::Loaded(schedules_map) => schedules_map,
+        };
+        let fresh_max_date = AREA_PAIR_TERMINAL_PAIRS.get(&area_pair).and_then(|tps| {
+            tps.iter()
+                .flat_map(|tp| schedules_map.get(tp).and_then(|ss| ss.iter().map(|s| s.date_range.to).max()))
+                .max()
+        });
+        let max_date = max(view_date, fresh_max_date.unwrap_or(view_date));
+        let suspension = AREA_PAIR_TERMINAL_PAIRS.get(&area_pair).and_then(|tps| {
+            tps.iter()
+                .flat_map(|tp| schedules_map.get(tp).into_iter().flatten())
+                .flat_map(|s| s.suspensions.iter())
+                .find(|suspension| suspension.includes_date_inclusive(view_date))
+        });
+        let area_sailings = if options.by_service_day { area_sailings_for_service_day } else { area_sailings_for_date };
+        let sailings_state_model = match suspension {
+            Some(&suspension) => SailingsStateModel::Suspended(suspension),
+            None => match area_sailings(area_pair, view_date, schedules_map, &options.filter()) {
+                None => SailingsStateModel::NoSchedule,
+                Some(schedules_sailings) if schedules_sailings.is_empty() => SailingsStateModel::NoSailings,
+                Some(schedules_sailings) => SailingsStateModel::Sailings(schedules_sailings),
+            },
+        };
+        let after_time = if view_date == today_vancouver() { now_vancouver().time() } else { Time::MIDNIGHT };
+        let alternatives = ALTERNATE_AREA_PAIRS
+            .get(&area_pair)
+            .into_iter()
+            .flatten()
+            .filter_map(|&ap| next_sailing(ap, view_date, after_time, schedules_map).map(|(_, time)| (ap, time)))
+            .collect();
+        SailingsModel {
+            sailings_state_model,
+            area_pair,
+            view_date,
+            max_date,
+            fresh_max_date,
+            has_supplemental_schedules: area_has_supplemental_schedules(area_pair, schedules_map),
+            missing_date_range: missing_partitions
+                .iter()
+                .map(|partition| partition.date_range)
+                .find(|date_range| date_range.includes_date_inclusive(view_date)),
+            alternatives,
+        }
+    }
+
+    fn without_schedules(
+        sailings_state_model: SailingsStateModel<'a>,
+        area_pair: AreaPair,
+        view_date: Date,
+        known_max_date: Option<Date>,
+    ) -> SailingsModel<'a> {
+        SailingsModel {
+            sailings_state_model,
+            area_pair,
+            view_date,
+            max_date: max(view_date, known_max_date.unwrap_or(view_date)),
+            fresh_max_date: None,
+            has_supplemental_schedules: false,
+            missing_date_range: None,
+            alternatives: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA_PAIR: AreaPair = AreaPair { from: Area::Victoria, to: Area::Galiano };
+    const DATE: Date = date!(2022 - 06 - 15);
+    const KNOWN_MAX_DATE: Date = date!(2022 - 07 - 31);
+
+    fn schedule(items: Vec<ScheduleItem>, operator: Operator) -> Schedule {
+        Schedule {
+            items,
+            refreshed_at: date!(2022 - 06 - 01).midnight().assume_utc(),
+            operator,
+            ..Schedule::test_fixture(TerminalPair { from: Terminal::SWB, to: Terminal::PSB })
+        }
+    }
+
+    fn daily_item() -> ScheduleItem {
+        ScheduleItem {
+            sailing: Sailing { depart_time: time!(09:00), arrive_time: time!(09:50), stops: vec![] },
+            weekdays: iter::successors(Some(Weekday::Monday), |w| Some(w.next()))
+                .take(7)
+                .map(|w| (w, DateRestriction::All))
+                .collect(),
+            notes: HashMap::new(),
+            note_categories: HashMap::new(),
+            vehicle_restrictions: HashMap::new(),
+            conditional_stops: vec![],
+        }
+    }
+
+    fn loaded(schedules: Vec<Schedule>) -> SchedulesState {
+        SchedulesState::Loaded(Rc::new(into_vec_group_map(schedules, |s| s.terminal_pair)))
+    }
+
+    fn model_for<'a>(state: &'a SchedulesState, date_value: StdResult<Date, &'static str>) -> SailingsModel<'a> {
+        SailingsModel::new(state, date_value, AREA_PAIR, DATE, SailingsOptions::default(), Some(KNOWN_MAX_DATE), &[])
+    }
+
+    #[test]
+    fn test_sailings_model_invalid_date() -> Result<()> {
+        for state in [SchedulesState::Loading, loaded(vec![schedule(vec![daily_item()], Operator::BcFerries)])] {
+            let model = model_for(&state, Err("Invalid date"));
+            assert!(
+                matches!(&model.sailings_state_model, SailingsStateModel::InvalidDate(err) if err == "Invalid date")
+            );
+            assert_eq!(model.view_date, DATE);
+            assert_eq!(model.max_date, KNOWN_MAX_DATE);
+            assert_eq!(model.fresh_max_date, None);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sailings_model_schedules_not_loaded() -> Result<()> {
+        for state in [SchedulesState::Init, SchedulesState::Loading] {
+            let model = model_for(&state, Ok(DATE));
+            assert!(matches!(model.sailings_state_model, SailingsStateModel::LoadingSchedules));
+            assert_eq!(model.max_date, KNOWN_MAX_DATE);
+        }
+        let model = model_for(&SchedulesState::Failed, Ok(DATE));
+        assert!(matches!(model.sailings_state_model, SailingsStateModel::LoadSchedulesFailed));
+        assert!(!model.has_supplemental_schedules);
+        // Without a remembered maximum date, the view date is the maximum
+        let model = SailingsModel::new(
+            &SchedulesState::Loading,
+            Ok(DATE),
+            AREA_PAIR,
+            DATE,
+            SailingsOptions::default(),
+            None,
+            &[],
+        );
+        assert_eq!(model.max_date, DATE);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sailings_model_no_schedule() -> Result<()> {
+        let state = loaded(vec![]);
+        let model = model_for(&state, Ok(DATE));
+        assert!(matches!(model.sailings_state_model, SailingsStateModel::NoSchedule));
+        assert_eq!(model.fresh_max_date, None);
+        assert_eq!(model.max_date, DATE);
+        // A date outside the schedule's date range also has no schedule
+        let state = loaded(vec![schedule(vec![daily_item()], Operator::BcFerries)]);
+        let later_date = date!(2022 - 07 - 10);
+        let model = model_for(&state, Ok(later_date));
+        assert!(matches!(model.sailings_state_model, SailingsStateModel::NoSchedule));
+        assert_eq!(model.max_date, later_date);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sailings_model_no_sailings() -> Result<()> {
+        let state = loaded(vec![schedule(vec![], Operator::BcFerries)]);
+        let model = model_for(&state, Ok(DATE));
+        assert!(matches!(model.sailings_state_model, SailingsStateModel::NoSailings));
+        assert_eq!(model.fresh_max_date, Some(date!(2022 - 06 - 30)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sailings_model_missing_partition() -> Result<()> {
+        let state = loaded(vec![schedule(vec![daily_item()], Operator::BcFerries)]);
+        let date_range = DateRange { from: date!(2022 - 07 - 01), to: date!(2022 - 09 - 30) };
+        let partitions = [DataPartition { file: "schedules-2.json".to_string(), bytes: 0, date_range }];
+        let later_date = date!(2022 - 07 - 10);
+        let model =
+            SailingsModel::new(&state, Ok(later_date), AREA_PAIR, DATE, SailingsOptions::default(), None, &partitions);
+        assert!(matches!(model.sailings_state_model, SailingsStateModel::NoSchedule));
+        assert_eq!(model.missing_date_range, Some(date_range));
+        // Dates in the partitions that loaded are unaffected
+        let model =
+            SailingsModel::new(&state, Ok(DATE), AREA_PAIR, DATE, SailingsOptions::default(), None, &partitions);
+        assert!(matches!(model.sailings_state_model, SailingsStateModel::Sailings(_)));
+        assert_eq!(model.missing_date_range, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sailings_model_suspended() -> Result<()> {
+        let suspension = DateRange { from: date!(2022 - 07 - 01), to: date!(2022 - 07 - 15) };
+        let state = loaded(vec![Schedule {
+            suspensions: vec![suspension],
+            ..schedule(vec![daily_item()], Operator::BcFerries)
+        }]);
+        // The suspension applies even past the end of the schedule that announced it
+        let model = model_for(&state, Ok(date!(2022 - 07 - 10)));
+        assert!(matches!(model.sailings_state_model, SailingsStateModel::Suspended(s) if s == suspension));
+        let model = model_for(&state, Ok(DATE));
+        assert!(matches!(model.sailings_state_model, SailingsStateModel::Sailings(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sailings_model_sailings() -> Result<()> {
+        let state = loaded(vec![
+            schedule(vec![daily_item()], Operator::BcFerries),
+            schedule(vec![daily_item()], Operator::GulfIslandsWaterTaxi),
+        ]);
+        let model = model_for(&state, Ok(DATE));
+        match &model.sailings_state_model {
+            SailingsStateModel::Sailings(schedule_sailings) => {
+                assert_eq!(schedule_sailings.len(), 1);
+                assert_eq!(schedule_sailings[0].1.len(), 1);
+            }
+            _ => panic!("Expect sailings"),
+        }
+        // Loaded schedules take precedence over the remembered maximum date
+        assert_eq!(model.fresh_max_date, Some(date!(2022 - 06 - 30)));
+        assert_eq!(model.max_date, date!(2022 - 06 - 30));
+        assert!(model.has_supplemental_schedules);
+        let options = SailingsOptions { include_supplemental: true, ..SailingsOptions::default() };
+        let with_supplemental = SailingsModel::new(&state, Ok(DATE), AREA_PAIR, DATE, options, None, &[]);
+        assert!(matches!(&with_supplemental.sailings_state_model, SailingsStateModel::Sailings(s) if s.len() == 2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sailings_model_service_day() -> Result<()> {
+        let after_midnight_item = ScheduleItem {
+            sailing: Sailing { depart_time: time!(00:30), arrive_time: time!(01:20), stops: vec![] },
+            ..daily_item()
+        };
+        let state = loaded(vec![schedule(vec![daily_item(), after_midnight_item.clone()], Operator::BcFerries)]);
+        let depart_times = |model: &SailingsModel| match &model.sailings_state_model {
+            SailingsStateModel::Sailings(schedule_sailings) => schedule_sailings[0]
+                .1
+                .iter()
+                .map(|s| (s.sailing.depart_time, s.depart_date(model.view_date)))
+                .collect::<Vec<_>>(),
+            _ => panic!("Expect sailings"),
+        };
+        let model = model_for(&state, Ok(DATE));
+        assert_eq!(depart_times(&model), [(time!(00:30), DATE), (time!(09:00), DATE)]);
+        // The sailing just after midnight is listed with the evening before
+        let options = SailingsOptions { by_service_day: true, ..SailingsOptions::default() };
+        let model = SailingsModel::new(&state, Ok(DATE), AREA_PAIR, DATE, options, None, &[]);
+        let next_date = date!(2022 - 06 - 16);
+        assert_eq!(depart_times(&model), [(time!(09:00), DATE), (time!(00:30), next_date)]);
+        match &model.sailings_state_model {
+            SailingsStateModel::Sailings(schedule_sailings) => {
+                assert_eq!(schedule_sailings[0].1[1].notes[0].text, "Technically departs on Thu, Jun 16")
+            }
+            _ => panic!("Expect sailings"),
+        }
+        // The first day of a schedule still lists its sailing just after midnight with the evening before
+        let next_day_schedule = Schedule {
+            date_range: DateRange { from: next_date, to: KNOWN_MAX_DATE },
+            ..schedule(vec![daily_item(), after_midnight_item.clone()], Operator::BcFerries)
+        };
+        let state = loaded(vec![next_day_schedule]);
+        let model = SailingsModel::new(&state, Ok(DATE), AREA_PAIR, DATE, options, None, &[]);
+        assert_eq!(depart_times(&model), [(time!(00:30), next_date)]);
+        // A schedule whose only sailing on the date belongs to the evening before is left out
+        let last_day_schedule = Schedule {
+            date_range: DateRange { from: date!(2022 - 06 - 01), to: DATE },
+            ..schedule(vec![after_midnight_item], Operator::BcFerries)
+        };
+        let state = loaded(vec![last_day_schedule]);
+        let model = SailingsModel::new(&state, Ok(DATE), AREA_PAIR, DATE, options, None, &[]);
+        assert!(matches!(model.sailings_state_model, SailingsStateModel::NoSailings));
+        Ok(())
+    }
+}
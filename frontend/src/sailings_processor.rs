@@ -1,46 +1,183 @@
-use ferrysched_shared::constants::*;
-use ferrysched_shared::imports::*;
-use ferrysched_shared::types::*;
+use ferrysched_shared::prelude::*;
+
+// Where a sailing note came from, shown in the debug build so maintainers can trace duplicates back to the data
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum NoteSource {
+    Annotation,
+    ServiceDay,
+    Connection,
+}
+
+#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct SailingNote {
+    pub text: String,
+    pub category: NoteCategory,
+    pub sources: Vec<NoteSource>,
+    // Every date the note applies to, if it is only for some dates
+    pub only_dates: Vec<Date>,
+    // Size limit behind the note, if it is a `NoteCategory::VehicleRestriction`
+    pub vehicle_restriction: Option<VehicleRestriction>,
+}
 
 #[derive(Eq, Ord, PartialEq, PartialOrd)]
 pub struct SailingWithNotes {
     pub sailing: Sailing,
-    pub notes: Vec<String>,
+    pub notes: Vec<SailingNote>,
+    // Every date the sailing makes each of its stops that it only makes on some dates
+    pub conditional_stop_dates: Vec<(Terminal, Vec<Date>)>,
+    // Whether the sailing departs on the calendar day after the date it is listed under, when grouped by service day
+    pub departs_next_day: bool,
 }
 
-fn schedule_sailings_for_date(schedule: &Schedule, date: Date) -> Vec<SailingWithNotes> {
+impl SailingWithNotes {
+    // Calendar date that the sailing departs on, when it is listed under the date
+    pub fn depart_date(&self, listed_date: Date) -> Date {
+        if self.departs_next_day {
+            listed_date.next_day().unwrap_or(listed_date)
+        } else {
+            listed_date
+        }
+    }
+}
+
+// Notes that differ only in case, spacing, or punctuation are the same note
+pub fn note_key(text: &str) -> String {
+    text.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+fn merge_notes(notes: impl IntoIterator<Item = (String, NoteCategory, NoteSource)>) -> Vec<SailingNote> {
+    let mut merged: Vec<SailingNote> = Vec::new();
+    for (text, category, source) in notes {
+        let key = note_key(&text);
+        match merged.iter_mut().find(|n| note_key(&n.text) == key) {
+            Some(note) if note.sources.contains(&source) => {}
+            Some(note) => note.sources.push(source),
+            None => merged.push(SailingNote {
+                text,
+                category,
+                sources: vec![source],
+                only_dates: vec![],
+                vehicle_restriction: None,
+            }),
+        }
+    }
+    merged
+}
+
+// Transfers are listed in the Stops column, so annotations that only repeat one aren't listed again as notes
+fn is_transfer_stop_note(sailing: &Sailing, text: &str) -> bool {
+    let key = note_key(text);
+    sailing
+        .stops
+        .iter()
+        .filter(|stop| stop.type_ == StopType::Transfer)
+        .any(|stop| key == note_key(&format!("Transfer to another vessel at {}", stop.terminal.area().short_name())))
+}
+
+// Differences from the previously published schedule, if the schedule was re-issued with changes
+pub struct SailingChanges {
+    pub added: Vec<Sailing>,
+    pub removed: Vec<Sailing>,
+}
+
+fn items_sailings_for_date(items: &[ScheduleItem], date: Date) -> Vec<SailingWithNotes> {
     let mut sailings = Vec::new();
-    for item in &schedule.items {
-        if let Some(weekday_dr) = item.weekdays.get(&date.weekday()) {
-            if weekday_dr.includes_date(date) {
-                let notes = item
-                    .notes
-                    .iter()
-                    .filter_map(|(a, dr)| dr.includes_date(date).then(|| a.as_ref()))
-                    .map(String::from)
-                    .collect();
-                sailings.push(SailingWithNotes { sailing: item.sailing.clone(), notes });
+    for item in items.iter().filter(|item| item.is_on_date(date)) {
+        let sailing = Sailing { stops: item.stops_on_date(date), ..item.sailing.clone() };
+        let annotation_notes = item
+            .notes
+            .iter()
+            .filter_map(|(a, dr)| dr.includes_date(date).then(|| a.as_ref()))
+            .filter(|a| !is_transfer_stop_note(&sailing, a))
+            .sorted()
+            .map(|a| (String::from(a), item.note_category(a), NoteSource::Annotation));
+        let mut notes = merge_notes(annotation_notes);
+        for note in &mut notes {
+            let key = note_key(&note.text);
+            if let Some((_, DateRestriction::Only(dates))) = item.notes.iter().find(|(a, _)| note_key(a) == key) {
+                note.only_dates = dates.iter().copied().sorted().collect();
             }
+            note.vehicle_restriction = item.vehicle_restrictions.get(note.text.as_str()).copied();
         }
+        let conditional_stop_dates = item
+            .conditional_stops
+            .iter()
+            .filter_map(|cs| match &cs.dates {
+                DateRestriction::Only(dates) if dates.contains(&date) => {
+                    Some((cs.stop.terminal, dates.iter().copied().sorted().collect()))
+                }
+                _ => None,
+            })
+            .collect();
+        sailings.push(SailingWithNotes { sailing, notes, conditional_stop_dates, departs_next_day: false });
     }
     sailings
 }
 
-fn schedules_sailings_for_date(schedules: &[Schedule], date: Date) -> Option<(&Schedule, Vec<SailingWithNotes>)> {
-    schedules
+fn schedule_sailings_for_date(schedule: &Schedule, date: Date) -> Vec<SailingWithNotes> {
+    items_sailings_for_date(&schedule.items, date)
+}
+
+pub fn schedule_sailing_changes(
+    schedule: &Schedule,
+    date: Date,
+    sailings: &[SailingWithNotes],
+) -> Option<SailingChanges> {
+    if schedule.previous_items.is_empty() {
+        return None;
+    }
+    let previous_sailings: Vec<_> = items_sailings_for_date(&schedule.previous_items, date)
+        .into_iter()
+        .map(|s| s.sailing)
+        .filter(|s| schedule.is_effective_at(date, s.depart_time))
+        .sorted()
+        .collect();
+    let added: Vec<_> = sailings
         .iter()
-        .filter(|sched| sched.date_range.includes_date_inclusive(date))
-        .map(|sched| (sched, schedule_sailings_for_date(sched, date)))
-        .next()
+        .map(|s| &s.sailing)
+        .filter(|s| schedule.is_effective_at(date, s.depart_time) && !previous_sailings.contains(s))
+        .cloned()
+        .collect();
+    let removed: Vec<_> = previous_sailings.into_iter().filter(|p| !sailings.iter().any(|s| s.sailing == *p)).collect();
+    (!added.is_empty() || !removed.is_empty()).then(|| SailingChanges { added, removed })
+}
+
+fn schedules_sailings_for_date(
+    schedules: &[Schedule],
+    operator: Operator,
+    date: Date,
+) -> Option<(&Schedule, Vec<SailingWithNotes>)> {
+    let mut date_schedules =
+        schedules.iter().filter(|sched| sched.operator == operator && sched.date_range.includes_date_inclusive(date));
+    match date_schedules.clone().find_map(|sched| sched.switchover_time(date).map(|time| (sched, time))) {
+        None => date_schedules.next().map(|sched| (sched, schedule_sailings_for_date(sched, date))),
+        Some((new_schedule, switchover_time)) => {
+            // Sailings before the switchover time still follow the schedule being replaced
+            let mut sailings: Vec<_> = date_schedules
+                .find(|sched| !std::ptr::eq(*sched, new_schedule))
+                .map(|old_schedule| schedule_sailings_for_date(old_schedule, date))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|s| s.sailing.depart_time < switchover_time)
+                .collect();
+            sailings.extend(
+                schedule_sailings_for_date(new_schedule, date)
+                    .into_iter()
+                    .filter(|s| new_schedule.is_effective_at(date, s.sailing.depart_time)),
+            );
+            Some((new_schedule, sailings))
+        }
+    }
 }
 
 fn terminal_pair_sailings_for_date(
     terminal_pair: TerminalPair,
+    operator: Operator,
     date: Date,
     schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
 ) -> Option<(&Schedule, Vec<SailingWithNotes>)> {
     if let Some((schedule, mut sailings)) =
-        schedules_map.get(&terminal_pair).and_then(|schedules| schedules_sailings_for_date(schedules, date))
+        schedules_map.get(&terminal_pair).and_then(|schedules| schedules_sailings_for_date(schedules, operator, date))
     {
         sailings.sort_unstable();
         Some((schedule, sailings))
@@ -49,19 +186,1124 @@ fn terminal_pair_sailings_for_date(
     }
 }
 
-pub fn area_sailings_for_date(
+// The sailing at the index followed by up to `count - 1` of the next sailings, for planning groups that might not all
+// fit on one sailing
+pub fn consecutive_sailings(sailings: &[SailingWithNotes], index: usize, count: usize) -> &[SailingWithNotes] {
+    &sailings[index..min(index + count, sailings.len())]
+}
+
+// When to set out to reach the terminal the given time before the sailing departs; may be the evening before for sailings
+// just after midnight
+pub fn leave_by_time(sailing: &Sailing, arrive_before_departure: Duration, travel_time: Duration) -> Time {
+    sailing.depart_time - arrive_before_departure - travel_time
+}
+
+// When the last sailing of the day arrives at a terminal that mostly operates in daylight
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LastLight {
+    AfterSunset { sunset: Time, dusk: Time },
+    AfterDark { dusk: Time },
+}
+
+// Only the arrival terminal matters, since that is where travellers have to find their way on in the dark
+pub fn last_sailing_last_light(
+    date: Date,
+    terminal_pair: TerminalPair,
+    sailings: &[SailingWithNotes],
+) -> Option<LastLight> {
+    if terminal_pair.to.has_night_operations() {
+        return None;
+    }
+    let last_sailing = &sailings.last()?.sailing;
+    let coordinates = terminal_pair.to.coordinates();
+    let sunset = sunset_vancouver(date, coordinates)?;
+    let dusk = nautical_dusk_vancouver(date, coordinates)?;
+    // A sailing that arrives before it departs arrives after midnight
+    let arrives_after =
+        |time: Time| last_sailing.arrive_time < last_sailing.depart_time || last_sailing.arrive_time > time;
+    if arrives_after(dusk) {
+        Some(LastLight::AfterDark { dusk })
+    } else if arrives_after(sunset) {
+        Some(LastLight::AfterSunset { sunset, dusk })
+    } else {
+        None
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, Eq, PartialEq)]
+pub enum TravellerType {
+    Vehicle,
+    // Such as a tall camper, which some sailings have no room for
+    OversizeVehicle,
+    FootPassenger,
+}
+
+impl TravellerType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TravellerType::Vehicle => "Driving",
+            TravellerType::OversizeVehicle => "Driving an oversize vehicle",
+            TravellerType::FootPassenger => "Walking on",
+        }
+    }
+
+    pub fn vehicle_class(&self) -> Option<VehicleClass> {
+        match self {
+            TravellerType::Vehicle => Some(VehicleClass::Standard),
+            TravellerType::OversizeVehicle => Some(VehicleClass::Oversize),
+            TravellerType::FootPassenger => None,
+        }
+    }
+}
+
+pub fn is_available_to_traveller_type(sailing: &SailingWithNotes, traveller_type: TravellerType) -> bool {
+    let vehicle_class = match traveller_type.vehicle_class() {
+        Some(vehicle_class) => vehicle_class,
+        None => return true,
+    };
+    !(sailing.has_note_category(NoteCategory::FootPassengersOnly)
+        || sailing.has_note_category(NoteCategory::PassengerOnlyVessel)
+        || sailing
+            .notes
+            .iter()
+            .filter_map(|note| note.vehicle_restriction)
+            .any(|restriction| restriction.applies_to(vehicle_class)))
+}
+
+// Shorter trips to and from one of the sailing's stops, for exploring part of the journey; only trips between areas that
+// have a route are included
+pub fn stop_area_pairs(terminal_pair: TerminalPair, stop: Terminal) -> Vec<AreaPair> {
+    [TerminalPair { from: terminal_pair.from, to: stop }, TerminalPair { from: stop, to: terminal_pair.to }]
+        .iter()
+        .map(TerminalPair::area_pair)
+        .filter(|area_pair| ALL_AREA_PAIRS.contains(area_pair))
+        .collect()
+}
+
+pub fn area_has_supplemental_schedules(
     area_pair: AreaPair,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+) -> bool {
+    AREA_PAIR_TERMINAL_PAIRS
+        .get(&area_pair)
+        .map(|tps| tps.iter().flat_map(|tp| schedules_map.get(tp)).flatten().any(|s| s.operator.is_supplemental()))
+        .unwrap_or(false)
+}
+
+// Thru-fare sailings whose connecting sailing leaves before they arrive cannot actually be taken, which means the
+// schedules disagree with each other
+fn add_connection_notes(
+    schedule: &Schedule,
+    sailings: &mut [SailingWithNotes],
     date: Date,
     schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
-) -> Option<Vec<(&Schedule, Vec<SailingWithNotes>)>> {
+) {
+    let leg_sailings = |terminal_pair| {
+        terminal_pair_sailings_for_date(terminal_pair, schedule.operator, date, schedules_map)
+            .map(|(_, sailings)| sailings)
+            .unwrap_or_default()
+    };
+    for sailing in sailings {
+        let via = match sailing.sailing.thrufare_via(schedule.terminal_pair) {
+            Some(via) => via,
+            None => continue,
+        };
+        let first_leg_sailings = leg_sailings(TerminalPair { from: schedule.terminal_pair.from, to: via });
+        let second_leg_sailings = leg_sailings(TerminalPair { from: via, to: schedule.terminal_pair.to });
+        if is_impossible_connection(
+            &sailing.sailing,
+            first_leg_sailings.iter().map(|s| &s.sailing),
+            second_leg_sailings.iter().map(|s| &s.sailing),
+        ) {
+            sailing.notes.insert(
+                0,
+                SailingNote {
+                    text: format!(
+                        "Connection not possible at {}: the connecting sailing leaves before this one arrives",
+                        via.area().short_name()
+                    ),
+                    category: NoteCategory::ImpossibleConnection,
+                    sources: vec![NoteSource::Connection],
+                    only_dates: vec![],
+                    vehicle_restriction: None,
+                },
+            );
+        }
+    }
+}
+
+// Which sailings to list.  Every page that lists or counts sailings builds one of these rather than filtering on its
+// own, so the table, the printed day pack, the counts, and the departure board agree on what they leave out.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SailingFilter {
+    include_supplemental: bool,
+    exclude_thrufares: bool,
+    traveller_type: Option<TravellerType>,
+    // Departure times at or after `depart_from` and before `depart_before`
+    depart_from: Option<Time>,
+    depart_before: Option<Time>,
+    excluded_note_categories: Vec<NoteCategory>,
+}
+
+impl SailingFilter {
+    // Includes sailings by operators other than BC Ferries
+    pub fn include_supplemental(mut self, include_supplemental: bool) -> SailingFilter {
+        self.include_supplemental = include_supplemental;
+        self
+    }
+
+    // Leaves out sailings that only reach the destination by connecting to another sailing
+    pub fn exclude_thrufares(mut self) -> SailingFilter {
+        self.exclude_thrufares = true;
+        self
+    }
+
+    // Leaves out sailings that the traveller cannot take, such as foot-passenger-only sailings for drivers
+    pub fn traveller_type(mut self, traveller_type: TravellerType) -> SailingFilter {
+        self.traveller_type = Some(traveller_type);
+        self
+    }
+
+    pub fn depart_from(mut self, time: Time) -> SailingFilter {
+        self.depart_from = Some(time);
+        self
+    }
+
+    pub fn depart_before(mut self, time: Time) -> SailingFilter {
+        self.depart_before = Some(time);
+        self
+    }
+
+    pub fn exclude_note_category(mut self, category: NoteCategory) -> SailingFilter {
+        if !self.excluded_note_categories.contains(&category) {
+            self.excluded_note_categories.push(category);
+        }
+        self
+    }
+
+    pub fn includes_operator(&self, operator: Operator) -> bool {
+        self.include_supplemental || !operator.is_supplemental()
+    }
+
+    pub fn includes(&self, terminal_pair: TerminalPair, sailing: &SailingWithNotes) -> bool {
+        let depart_time = sailing.sailing.depart_time;
+        !(self.exclude_thrufares && sailing.sailing.is_thrufare(terminal_pair))
+            && self.traveller_type.map(|tt| is_available_to_traveller_type(sailing, tt)).unwrap_or(true)
+            && self.depart_from.map(|from| depart_time >= from).unwrap_or(true)
+            && self.depart_before.map(|before| depart_time < before).unwrap_or(true)
+            && !self.excluded_note_categories.iter().any(|&category| sailing.has_note_category(category))
+    }
+
+    fn sailings_for_date<'a>(
+        &self,
+        terminal_pair: TerminalPair,
+        operator: Operator,
+        date: Date,
+        schedules_map: &'a HashMap<TerminalPair, Vec<Schedule>>,
+    ) -> Option<(&'a Schedule, Vec<SailingWithNotes>)> {
+        if !self.includes_operator(operator) {
+            return None;
+        }
+        let (schedule, mut sailings) = terminal_pair_sailings_for_date(terminal_pair, operator, date, schedules_map)?;
+        add_connection_notes(schedule, &mut sailings, date, schedules_map);
+        sailings.retain(|s| self.includes(terminal_pair, s));
+        Some((schedule, sailings))
+    }
+}
+
+pub fn area_sailings_for_date<'a>(
+    area_pair: AreaPair,
+    date: Date,
+    schedules_map: &'a HashMap<TerminalPair, Vec<Schedule>>,
+    filter: &SailingFilter,
+) -> Option<Vec<(&'a Schedule, Vec<SailingWithNotes>)>> {
     let mut area_schedules_vec = AREA_PAIR_TERMINAL_PAIRS
         .get(&area_pair)
-        .map(|tps| tps.iter().filter_map(|&tp| terminal_pair_sailings_for_date(tp, date, schedules_map)).collect())
+        .map(|tps| {
+            tps.iter()
+                .cartesian_product(Operator::iter().collect::<Vec<_>>())
+                .filter_map(|(&tp, operator)| filter.sailings_for_date(tp, operator, date, schedules_map))
+                .collect()
+        })
         .unwrap_or_else(Vec::new);
     (!area_schedules_vec.is_empty()).then(|| {
         area_schedules_vec.sort_unstable_by(|(sa, va), (sb, vb)| {
-            va.len().cmp(&vb.len()).reverse().then_with(|| sa.terminal_pair.cmp(&sb.terminal_pair))
+            sa.operator
+                .is_supplemental()
+                .cmp(&sb.operator.is_supplemental())
+                .then_with(|| va.len().cmp(&vb.len()).reverse())
+                .then_with(|| sa.terminal_pair.cmp(&sb.terminal_pair))
         });
-        area_schedules_vec.into_iter().filter(|(s, v)| !v.is_empty() || !s.alerts.is_empty()).collect()
+        area_schedules_vec
+            .into_iter()
+            .filter(|(s, v)| !v.is_empty() || s.alerts.iter().any(|a| a.is_relevant_on(date)))
+            .collect()
     })
 }
+
+// Like `area_sailings_for_date`, but for the day of service rather than the calendar date: sailings departing just after
+// midnight are listed with the evening before, noting the date they technically depart on
+pub fn area_sailings_for_service_day<'a>(
+    area_pair: AreaPair,
+    date: Date,
+    schedules_map: &'a HashMap<TerminalPair, Vec<Schedule>>,
+    filter: &SailingFilter,
+) -> Option<Vec<(&'a Schedule, Vec<SailingWithNotes>)>> {
+    // The date may have no schedule while the next one does, and its sailings just after midnight still belong here
+    let date_sailings = area_sailings_for_date(area_pair, date, schedules_map, filter);
+    let is_date_scheduled = date_sailings.is_some();
+    let mut schedules_sailings = date_sailings.unwrap_or_default();
+    for (_, sailings) in &mut schedules_sailings {
+        sailings.retain(|s| s.sailing.service_date(date) == date);
+    }
+    schedules_sailings.retain(|(s, v)| !v.is_empty() || s.alerts.iter().any(|a| a.is_relevant_on(date)));
+    let next_date = match date.next_day() {
+        Some(next_date) => next_date,
+        None => return Some(schedules_sailings).filter(|_| is_date_scheduled),
+    };
+    let next_date_text = next_date
+        .format(format_description!("[weekday repr:short], [month repr:short] [day padding:none]"))
+        .expect("Expect service day date to format");
+    for (next_schedule, next_sailings) in
+        area_sailings_for_date(area_pair, next_date, schedules_map, filter).unwrap_or_default()
+    {
+        let early_sailings: Vec<_> = next_sailings
+            .into_iter()
+            .filter(|s| s.sailing.service_date(next_date) == date)
+            .map(|mut s| {
+                s.notes.insert(
+                    0,
+                    SailingNote {
+                        text: format!("Technically departs on {}", next_date_text),
+                        category: NoteCategory::NextCalendarDay,
+                        sources: vec![NoteSource::ServiceDay],
+                        only_dates: vec![],
+                        vehicle_restriction: None,
+                    },
+                );
+                s.departs_next_day = true;
+                s
+            })
+            .collect();
+        if early_sailings.is_empty() {
+            continue;
+        }
+        let same_route = schedules_sailings.iter_mut().find(|(schedule, _)| {
+            schedule.terminal_pair == next_schedule.terminal_pair && schedule.operator == next_schedule.operator
+        });
+        match same_route {
+            Some((_, sailings)) => sailings.extend(early_sailings),
+            None => schedules_sailings.push((next_schedule, early_sailings)),
+        }
+    }
+    Some(schedules_sailings).filter(|ss| is_date_scheduled || !ss.is_empty())
+}
+
+// Sailings for every route on the date, for printing a whole day of travel across routes
+pub fn all_sailings_for_date<'a>(
+    date: Date,
+    schedules_map: &'a HashMap<TerminalPair, Vec<Schedule>>,
+    filter: &SailingFilter,
+) -> Vec<(&'a Schedule, Vec<SailingWithNotes>)> {
+    ALL_TERMINAL_PAIRS
+        .iter()
+        .sorted_by_key(|tp| (tp.from.area(), tp.to.area(), **tp))
+        .filter_map(|&tp| filter.sailings_for_date(tp, Operator::BcFerries, date, schedules_map))
+        .filter(|(_, sailings)| !sailings.is_empty())
+        .collect()
+}
+
+fn bc_ferries_schedules_for_date(
+    date: Date,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+) -> impl Iterator<Item = &Schedule> {
+    schedules_map
+        .values()
+        .flatten()
+        .filter(move |s| s.operator == Operator::BcFerries && s.date_range.includes_date_inclusive(date))
+}
+
+// Last date that a printed range starting on the date can run to: the end of the longest schedule period that
+// includes it, so a range never runs past the schedules that are available
+pub fn printable_range_limit(from: Date, schedules_map: &HashMap<TerminalPair, Vec<Schedule>>) -> Option<Date> {
+    bc_ferries_schedules_for_date(from, schedules_map).map(|s| s.date_range.to).max()
+}
+
+// Splits the range wherever the schedule periods in effect change, so each part of a printed range follows a single
+// set of schedules
+pub fn split_at_schedule_periods(
+    date_range: &DateRange,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+) -> Vec<DateRange> {
+    let periods_key = |date| {
+        bc_ferries_schedules_for_date(date, schedules_map)
+            .map(|s| (s.date_range.from, s.date_range.to))
+            .sorted()
+            .dedup()
+            .collect::<Vec<_>>()
+    };
+    date_range
+        .iter_days()
+        .group_by(|&date| periods_key(date))
+        .into_iter()
+        .filter_map(|(_, mut dates)| {
+            let from = dates.next()?;
+            Some(DateRange { from, to: dates.last().unwrap_or(from) })
+        })
+        .collect()
+}
+
+// Number of BC Ferries sailings per day starting at the date, to show how frequent service is on a route
+pub fn area_daily_sailing_counts(
+    area_pair: AreaPair,
+    start_date: Date,
+    days: usize,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+) -> Vec<(Date, usize)> {
+    iter::successors(Some(start_date), |date| date.next_day())
+        .take(days)
+        .map(|date| {
+            let count = area_sailings_for_date(area_pair, date, schedules_map, &SailingFilter::default())
+                .map(|schedules_sailings| schedules_sailings.iter().map(|(_, sailings)| sailings.len()).sum())
+                .unwrap_or(0);
+            (date, count)
+        })
+        .collect()
+}
+
+// Departure time of the first sailing between the areas on the date at or after `after`
+// The route and departure time of the first sailing between the areas on the date at or after the time
+pub fn next_sailing(
+    area_pair: AreaPair,
+    date: Date,
+    after: Time,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+) -> Option<(TerminalPair, Time)> {
+    area_sailings_for_date(area_pair, date, schedules_map, &SailingFilter::default().depart_from(after))?
+        .iter()
+        .flat_map(|(schedule, sailings)| sailings.iter().map(|s| (schedule.terminal_pair, s.sailing.depart_time)))
+        .min_by_key(|(_, depart_time)| *depart_time)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct DepartureDestination {
+    pub terminal: Terminal,
+    pub arrive_time: Time,
+}
+
+// Sailings leaving a terminal at the same time, which are usually one vessel calling at each destination in turn
+#[derive(Debug, Eq, PartialEq)]
+pub struct TerminalDeparture {
+    pub depart_time: Time,
+    // In order of arrival
+    pub destinations: Vec<DepartureDestination>,
+    pub notes: Vec<String>,
+}
+
+// Every departure from the terminal on the date across all of its routes, for a departure board.  Thru-fare sailings
+// are left out, since the vessel only goes as far as the connection, which is already a departure of its own.
+pub fn terminal_departures(
+    terminal: Terminal,
+    date: Date,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+) -> Vec<TerminalDeparture> {
+    let filter = SailingFilter::default().exclude_thrufares();
+    let sailings = ALL_TERMINAL_PAIRS
+        .iter()
+        .filter(|tp| tp.from == terminal)
+        .filter_map(|&tp| filter.sailings_for_date(tp, Operator::BcFerries, date, schedules_map))
+        .flat_map(|(schedule, sailings)| sailings.into_iter().map(move |s| (schedule.terminal_pair, s)));
+    into_vec_group_map(sailings, |(_, s)| s.sailing.depart_time)
+        .into_iter()
+        .map(|(depart_time, sailings)| TerminalDeparture {
+            depart_time,
+            destinations: sailings
+                .iter()
+                .map(|(terminal_pair, s)| DepartureDestination {
+                    terminal: terminal_pair.to,
+                    arrive_time: s.sailing.arrive_time,
+                })
+                .sorted_by_key(|d| (d.arrive_time < depart_time, d.arrive_time, d.terminal))
+                .collect(),
+            notes: sailings
+                .iter()
+                .flat_map(|(_, s)| s.notes.iter().map(|note| note.text.clone()))
+                .unique_by(|text| note_key(text))
+                .collect(),
+        })
+        .sorted_by_key(|departure| departure.depart_time)
+        .collect()
+}
+
+// Most sailings in a suggested journey, which reaches every terminal from every other without long detours
+const MAX_JOURNEY_LEGS: usize = 3;
+
+// Time to allow between arriving on one sailing and departing on the next, to get off and back in line
+pub const MIN_TRANSFER_TIME: Duration = Duration::minutes(15);
+
+// Typical drive times in minutes between terminals in the same area, so journeys can connect across an island
+static TRANSFER_DRIVE_MINUTES: &[(Terminal, Terminal, i64)] =
+    &[(Terminal::FUL, Terminal::VES, 30), (Terminal::FUL, Terminal::PLH, 25), (Terminal::PLH, Terminal::VES, 15)];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JourneyLeg {
+    pub terminal_pair: TerminalPair,
+    pub sailing: Sailing,
+}
+
+// Sailings that connect one terminal to another on a date, in the order they are taken
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Journey {
+    pub legs: Vec<JourneyLeg>,
+}
+
+impl Journey {
+    pub fn depart_time(&self) -> Time {
+        self.legs[0].sailing.depart_time
+    }
+
+    pub fn arrive_time(&self) -> Time {
+        self.legs[self.legs.len() - 1].sailing.arrive_time
+    }
+}
+
+// Drive between terminals to make a connection, if they are different terminals in the same area
+pub fn transfer_drive_time(from: Terminal, to: Terminal) -> Option<Duration> {
+    TRANSFER_DRIVE_MINUTES
+        .iter()
+        .find(|(a, b, _)| (*a == from && *b == to) || (*a == to && *b == from))
+        .map(|(_, _, minutes)| Duration::minutes(*minutes))
+}
+
+// Time needed between arriving at one terminal and departing from the other, if a connection can be made between them
+fn connection_time(arrive_terminal: Terminal, depart_terminal: Terminal) -> Option<Duration> {
+    if arrive_terminal == depart_terminal {
+        Some(MIN_TRANSFER_TIME)
+    } else {
+        transfer_drive_time(arrive_terminal, depart_terminal).map(|drive_time| drive_time + MIN_TRANSFER_TIME)
+    }
+}
+
+// Continues the journey towards the destination with the earliest connecting sailing on each route that does not
+// return to an area already visited
+fn extend_journeys(
+    journey: Journey,
+    to: Terminal,
+    visited_areas: &[Area],
+    sailings_map: &HashMap<TerminalPair, Vec<&Sailing>>,
+    journeys: &mut Vec<Journey>,
+) {
+    let last_leg = &journey.legs[journey.legs.len() - 1];
+    if last_leg.terminal_pair.to == to {
+        journeys.push(journey);
+        return;
+    }
+    if journey.legs.len() == MAX_JOURNEY_LEGS {
+        return;
+    }
+    for (&terminal_pair, sailings) in sailings_map {
+        if visited_areas.contains(&terminal_pair.to.area()) {
+            continue;
+        }
+        let min_connection_time = match connection_time(last_leg.terminal_pair.to, terminal_pair.from) {
+            Some(min_connection_time) => min_connection_time,
+            None => continue,
+        };
+        let next_sailing = sailings
+            .iter()
+            .filter(|s| s.depart_time - last_leg.sailing.arrive_time >= min_connection_time)
+            .min_by_key(|s| s.depart_time);
+        if let Some(&next_sailing) = next_sailing {
+            let mut next_journey = journey.clone();
+            next_journey.legs.push(JourneyLeg { terminal_pair, sailing: next_sailing.clone() });
+            let mut next_visited_areas = visited_areas.to_vec();
+            next_visited_areas.push(terminal_pair.to.area());
+            extend_journeys(next_journey, to, &next_visited_areas, sailings_map, journeys);
+        }
+    }
+}
+
+// Sailings that arrive after midnight cannot be connected to on the same date
+fn journey_sailings_map<'a>(
+    schedules_sailings: &'a [(&Schedule, Vec<SailingWithNotes>)],
+) -> HashMap<TerminalPair, Vec<&'a Sailing>> {
+    schedules_sailings
+        .iter()
+        .map(|(schedule, sailings)| {
+            let same_day_sailings =
+                sailings.iter().map(|s| &s.sailing).filter(|s| s.arrive_time >= s.depart_time).collect();
+            (schedule.terminal_pair, same_day_sailings)
+        })
+        .collect()
+}
+
+// Journeys from one terminal to another on the date, across routes when there is no direct sailing.  Each journey
+// takes the earliest connections, and journeys that leave earlier but arrive no sooner than another are left out.
+pub fn find_journeys(
+    from: Terminal,
+    to: Terminal,
+    date: Date,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+) -> Vec<Journey> {
+    let schedules_sailings = all_sailings_for_date(date, schedules_map, &SailingFilter::default());
+    let sailings_map = journey_sailings_map(&schedules_sailings);
+    let mut journeys = Vec::new();
+    for (&terminal_pair, sailings) in sailings_map.iter().filter(|(tp, _)| tp.from == from) {
+        for &sailing in sailings {
+            let journey = Journey { legs: vec![JourneyLeg { terminal_pair, sailing: sailing.clone() }] };
+            extend_journeys(journey, to, &[from.area(), terminal_pair.to.area()], &sailings_map, &mut journeys);
+        }
+    }
+    // Leaves no earlier and arrives no later, and is better in at least one of those or takes fewer sailings
+    let is_better = |a: &Journey, b: &Journey| {
+        a.depart_time() >= b.depart_time()
+            && a.arrive_time() <= b.arrive_time()
+            && (a.depart_time() > b.depart_time() || a.arrive_time() < b.arrive_time() || a.legs.len() < b.legs.len())
+    };
+    let mut best_journeys: Vec<_> =
+        journeys.iter().filter(|j| !journeys.iter().any(|other| is_better(other, j))).cloned().collect();
+    best_journeys.sort_unstable_by_key(|j| (j.depart_time(), j.arrive_time(), j.legs.len()));
+    best_journeys.dedup_by_key(|j| (j.depart_time(), j.arrive_time(), j.legs.len()));
+    best_journeys
+}
+
+// Why a route that could take part in a journey doesn't on the date
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum JourneyGap {
+    // No schedule has been published for the date, usually because it is past the end of the route's current
+    // schedule period
+    NoSchedule { terminal_pair: TerminalPair, period_end: Option<Date>, next_period_from: Option<Date> },
+    // The route's schedule covers the date, but there are no sailings on it
+    NoService { terminal_pair: TerminalPair },
+    // The route's last sailing of the day leaves before the earliest arrival from the previous route can connect
+    MissedConnection { arrived: TerminalPair, next: TerminalPair, earliest_arrive_time: Time, last_depart_time: Time },
+}
+
+// Sequences of routes from one terminal to another with the fewest sailings, regardless of whether they run
+fn journey_routes(from: Terminal, to: Terminal) -> Vec<Vec<TerminalPair>> {
+    let mut routes: Vec<Vec<TerminalPair>> =
+        ALL_TERMINAL_PAIRS.iter().filter(|tp| tp.from == from).sorted().map(|&tp| vec![tp]).collect();
+    for _ in 0..MAX_JOURNEY_LEGS {
+        let complete: Vec<_> = routes.iter().filter(|r| r[r.len() - 1].to == to).cloned().collect();
+        if !complete.is_empty() {
+            return complete;
+        }
+        routes = routes
+            .iter()
+            .flat_map(|route| {
+                let last = route[route.len() - 1];
+                ALL_TERMINAL_PAIRS
+                    .iter()
+                    .filter(move |tp| {
+                        connection_time(last.to, tp.from).is_some()
+                            && tp.to.area() != from.area()
+                            && !route.iter().any(|r| r.to.area() == tp.to.area())
+                    })
+                    .sorted()
+                    .map(move |&tp| route.iter().copied().chain([tp]).collect())
+            })
+            .collect();
+    }
+    vec![]
+}
+
+// Routes without any schedules loaded aren't running at all, such as seasonal routes out of season, so they aren't gaps
+fn route_gap(
+    terminal_pair: TerminalPair,
+    date: Date,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+) -> Option<JourneyGap> {
+    let schedules: Vec<_> =
+        schedules_map.get(&terminal_pair).into_iter().flatten().filter(|s| s.operator == Operator::BcFerries).collect();
+    if schedules.is_empty() {
+        None
+    } else if schedules.iter().any(|s| s.date_range.includes_date_inclusive(date)) {
+        Some(JourneyGap::NoService { terminal_pair })
+    } else {
+        Some(JourneyGap::NoSchedule {
+            terminal_pair,
+            period_end: schedules.iter().map(|s| s.date_range.to).filter(|&to| to < date).max(),
+            next_period_from: schedules.iter().map(|s| s.date_range.from).filter(|&from| from > date).min(),
+        })
+    }
+}
+
+// When there are no journeys on the date, explains where each of the shortest sequences of routes breaks down, such as
+// a connecting route whose schedule period ends before the date, so the gap can be shown rather than just no journeys
+pub fn explain_missing_journeys(
+    from: Terminal,
+    to: Terminal,
+    date: Date,
+    schedules_map: &HashMap<TerminalPair, Vec<Schedule>>,
+) -> Vec<JourneyGap> {
+    let schedules_sailings = all_sailings_for_date(date, schedules_map, &SailingFilter::default());
+    let sailings_map = journey_sailings_map(&schedules_sailings);
+    let mut gaps = Vec::new();
+    for route in journey_routes(from, to) {
+        let mut arrived: Option<(TerminalPair, Time)> = None;
+        for &terminal_pair in &route {
+            let sailings = sailings_map.get(&terminal_pair).map(Vec::as_slice).unwrap_or_default();
+            if sailings.is_empty() {
+                gaps.extend(route_gap(terminal_pair, date, schedules_map));
+                break;
+            }
+            let earliest_depart_time = arrived.and_then(|(arrived_pair, arrive_time)| {
+                connection_time(arrived_pair.to, terminal_pair.from)
+                    .map(|min_connection_time| arrive_time + min_connection_time)
+            });
+            let next_sailing = sailings
+                .iter()
+                .filter(|s| earliest_depart_time.map_or(true, |t| s.depart_time >= t))
+                .min_by_key(|s| s.arrive_time);
+            match (next_sailing, arrived) {
+                (Some(sailing), _) => arrived = Some((terminal_pair, sailing.arrive_time)),
+                (None, Some((arrived_pair, earliest_arrive_time))) => {
+                    gaps.push(JourneyGap::MissedConnection {
+                        arrived: arrived_pair,
+                        next: terminal_pair,
+                        earliest_arrive_time,
+                        last_depart_time: sailings.iter().map(|s| s.depart_time).max().expect("Expect sailings"),
+                    });
+                    break;
+                }
+                (None, None) => break,
+            }
+        }
+    }
+    gaps.into_iter().unique().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_notes() -> Result<()> {
+        let notes = merge_notes([
+            ("Bicycles must be walked aboard.".to_string(), NoteCategory::Other, NoteSource::Annotation),
+            ("Foot passengers only".to_string(), NoteCategory::FootPassengersOnly, NoteSource::Annotation),
+            ("Bicycles must be walked aboard".to_string(), NoteCategory::Other, NoteSource::Annotation),
+            ("bicycles must be walked  aboard".to_string(), NoteCategory::Other, NoteSource::Connection),
+        ]);
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].text, "Bicycles must be walked aboard.");
+        assert_eq!(notes[0].sources, vec![NoteSource::Annotation, NoteSource::Connection]);
+        assert_eq!(notes[1].sources, vec![NoteSource::Annotation]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_transfer_stop_notes() -> Result<()> {
+        let item = ScheduleItem {
+            sailing: Sailing {
+                depart_time: time!(9:00),
+                arrive_time: time!(10:35),
+                stops: vec![Stop { type_: StopType::Transfer, terminal: Terminal::PVB }],
+            },
+            weekdays: HashMap::from([(Weekday::Wednesday, DateRestriction::All)]),
+            notes: HashMap::from([
+                (Cow::from("Transfer to another vessel at Mayne."), DateRestriction::All),
+                (Cow::from(FOOT_PASSENGERS_ONLY_NOTE), DateRestriction::All),
+            ]),
+            note_categories: HashMap::new(),
+            vehicle_restrictions: HashMap::new(),
+            conditional_stops: vec![],
+        };
+        // The transfer is only listed with the stops
+        let sailings = items_sailings_for_date(&[item], date!(2023 - 07 - 05));
+        assert_eq!(sailings[0].notes.iter().map(|n| n.text.as_str()).collect::<Vec<_>>(), [FOOT_PASSENGERS_ONLY_NOTE]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_vehicle_restriction_notes() -> Result<()> {
+        let restriction = VehicleRestriction { dimension: VehicleDimension::Height, max_feet: 7 };
+        let item = ScheduleItem {
+            sailing: Sailing { depart_time: time!(9:00), arrive_time: time!(10:35), stops: vec![] },
+            weekdays: HashMap::from([(Weekday::Wednesday, DateRestriction::All)]),
+            notes: HashMap::from([(Cow::from(restriction.note()), DateRestriction::All)]),
+            note_categories: HashMap::from([(Cow::from(restriction.note()), NoteCategory::VehicleRestriction)]),
+            vehicle_restrictions: HashMap::from([(Cow::from(restriction.note()), restriction)]),
+            conditional_stops: vec![],
+        };
+        let sailings = items_sailings_for_date(&[item], date!(2023 - 07 - 05));
+        assert_eq!(sailings[0].notes[0].vehicle_restriction, Some(restriction));
+        assert!(is_available_to_traveller_type(&sailings[0], TravellerType::Vehicle));
+        assert!(!is_available_to_traveller_type(&sailings[0], TravellerType::OversizeVehicle));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stop_area_pairs() -> Result<()> {
+        assert_eq!(
+            stop_area_pairs(TerminalPair { from: Terminal::PSB, to: Terminal::TSA }, Terminal::PVB),
+            [AreaPair { from: Area::Galiano, to: Area::Mayne }, AreaPair { from: Area::Mayne, to: Area::Vancouver }]
+        );
+        // No routes between Chemainus and the Southern Gulf Islands or Tsawwassen
+        assert_eq!(stop_area_pairs(TerminalPair { from: Terminal::PSB, to: Terminal::TSA }, Terminal::CHM), []);
+        Ok(())
+    }
+
+    fn schedule(terminal_pair: TerminalPair, date_range: DateRange) -> Schedule {
+        Schedule { date_range, ..Schedule::test_fixture(terminal_pair) }
+    }
+
+    #[test]
+    fn test_sailing_filter() -> Result<()> {
+        let terminal_pair = TerminalPair { from: Terminal::SWB, to: Terminal::PST };
+        let sailing = |depart_time, stops: &[Stop], notes: &[(&str, NoteCategory)]| SailingWithNotes {
+            sailing: Sailing { depart_time, arrive_time: depart_time + Duration::hours(2), stops: stops.to_vec() },
+            notes: notes
+                .iter()
+                .map(|&(text, category)| SailingNote {
+                    text: text.to_string(),
+                    category,
+                    sources: vec![NoteSource::Annotation],
+                    only_dates: vec![],
+                    vehicle_restriction: None,
+                })
+                .collect(),
+            conditional_stop_dates: vec![],
+            departs_next_day: false,
+        };
+        let direct = sailing(time!(7:00), &[], &[]);
+        let thrufare = sailing(time!(9:00), &[Stop { type_: StopType::Thrufare, terminal: Terminal::PVB }], &[]);
+        let foot_passengers_only =
+            sailing(time!(15:00), &[], &[(FOOT_PASSENGERS_ONLY_NOTE, NoteCategory::FootPassengersOnly)]);
+        assert!([&direct, &thrufare, &foot_passengers_only]
+            .iter()
+            .all(|s| SailingFilter::default().includes(terminal_pair, s)));
+        assert!(!SailingFilter::default().exclude_thrufares().includes(terminal_pair, &thrufare));
+        assert!(!SailingFilter::default()
+            .traveller_type(TravellerType::Vehicle)
+            .includes(terminal_pair, &foot_passengers_only));
+        assert!(SailingFilter::default()
+            .traveller_type(TravellerType::FootPassenger)
+            .includes(terminal_pair, &foot_passengers_only));
+        let morning = SailingFilter::default().depart_from(time!(8:00)).depart_before(time!(12:00));
+        assert_eq!(
+            [&direct, &thrufare, &foot_passengers_only].map(|s| morning.includes(terminal_pair, s)),
+            [false, true, false]
+        );
+        assert!(!SailingFilter::default()
+            .exclude_note_category(NoteCategory::FootPassengersOnly)
+            .includes(terminal_pair, &foot_passengers_only));
+        assert!(!SailingFilter::default().includes_operator(Operator::GulfIslandsWaterTaxi));
+        assert!(SailingFilter::default().include_supplemental(true).includes_operator(Operator::GulfIslandsWaterTaxi));
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_at_schedule_periods() -> Result<()> {
+        let route1 = TerminalPair { from: Terminal::TSA, to: Terminal::SWB };
+        let route2 = TerminalPair { from: Terminal::CHM, to: Terminal::THT };
+        let schedules_map = HashMap::from([
+            (
+                route1,
+                vec![
+                    schedule(route1, DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 29) }),
+                    schedule(route1, DateRange { from: date!(2022 - 06 - 30), to: date!(2022 - 09 - 05) }),
+                ],
+            ),
+            (route2, vec![schedule(route2, DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 07 - 10) })]),
+        ]);
+        assert_eq!(printable_range_limit(date!(2022 - 06 - 28), &schedules_map), Some(date!(2022 - 07 - 10)));
+        assert_eq!(printable_range_limit(date!(2022 - 09 - 06), &schedules_map), None);
+        assert_eq!(
+            split_at_schedule_periods(
+                &DateRange { from: date!(2022 - 06 - 28), to: date!(2022 - 07 - 12) },
+                &schedules_map
+            ),
+            [
+                DateRange { from: date!(2022 - 06 - 28), to: date!(2022 - 06 - 29) },
+                DateRange { from: date!(2022 - 06 - 30), to: date!(2022 - 07 - 10) },
+                DateRange { from: date!(2022 - 07 - 11), to: date!(2022 - 07 - 12) },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_conditional_stops() -> Result<()> {
+        let via_pender = Stop { type_: StopType::Stop, terminal: Terminal::POB };
+        let item = ScheduleItem {
+            sailing: Sailing { depart_time: time!(9:00), arrive_time: time!(10:35), stops: vec![] },
+            weekdays: HashMap::from([(Weekday::Saturday, DateRestriction::All)]),
+            notes: HashMap::new(),
+            note_categories: HashMap::new(),
+            vehicle_restrictions: HashMap::new(),
+            conditional_stops: vec![ConditionalStop {
+                stop: via_pender,
+                dates: DateRestriction::Only(HashSet::from([date!(2023 - 07 - 01), date!(2023 - 07 - 08)])),
+            }],
+        };
+        let items = [item];
+        let on_date = items_sailings_for_date(&items, date!(2023 - 07 - 01));
+        assert_eq!(on_date[0].sailing.stops, [via_pender]);
+        assert_eq!(
+            on_date[0].conditional_stop_dates,
+            [(Terminal::POB, vec![date!(2023 - 07 - 01), date!(2023 - 07 - 08)])]
+        );
+        let other_date = items_sailings_for_date(&items, date!(2023 - 07 - 15));
+        assert!(other_date[0].sailing.stops.is_empty());
+        assert!(other_date[0].conditional_stop_dates.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_sailing() -> Result<()> {
+        let route6 = TerminalPair { from: Terminal::CFT, to: Terminal::VES };
+        let mut crofton_schedule =
+            schedule(route6, DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 30) });
+        crofton_schedule.items = [time!(7:00), time!(10:15)]
+            .into_iter()
+            .map(|depart_time| {
+                ScheduleItem::test_fixture(
+                    Sailing { depart_time, arrive_time: depart_time + Duration::minutes(20), stops: vec![] },
+                    &[Weekday::Wednesday],
+                )
+            })
+            .collect();
+        let schedules_map = HashMap::from([(route6, vec![crofton_schedule])]);
+        let area_pair = AreaPair { from: Area::Crofton, to: Area::SaltSpring };
+        assert_eq!(
+            next_sailing(area_pair, date!(2022 - 06 - 15), time!(8:00), &schedules_map),
+            Some((route6, time!(10:15)))
+        );
+        assert_eq!(next_sailing(area_pair, date!(2022 - 06 - 15), time!(11:00), &schedules_map), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_last_sailing_last_light() -> Result<()> {
+        let sailings = |depart_time: Time| {
+            vec![SailingWithNotes {
+                sailing: Sailing { depart_time, arrive_time: depart_time + Duration::minutes(35), stops: vec![] },
+                notes: vec![],
+                conditional_stop_dates: vec![],
+                departs_next_day: false,
+            }]
+        };
+        let date = date!(2022 - 12 - 15);
+        let swb_ful = TerminalPair { from: Terminal::SWB, to: Terminal::FUL };
+        assert!(matches!(
+            last_sailing_last_light(date, swb_ful, &sailings(time!(21:00))),
+            Some(LastLight::AfterDark { .. })
+        ));
+        assert_eq!(last_sailing_last_light(date, swb_ful, &sailings(time!(10:00))), None);
+        // Arriving at a terminal that operates at night is fine, even leaving from one that doesn't
+        assert_eq!(last_sailing_last_light(date, swb_ful.swapped(), &sailings(time!(21:00))), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_journeys() -> Result<()> {
+        let date_range = DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 30) };
+        let schedule_with_sailings = |terminal_pair, sailings: &[(Time, Time)]| {
+            let mut schedule = schedule(terminal_pair, date_range);
+            schedule.items = sailings
+                .iter()
+                .map(|&(depart_time, arrive_time)| {
+                    ScheduleItem::test_fixture(
+                        Sailing { depart_time, arrive_time, stops: vec![] },
+                        &[Weekday::Wednesday],
+                    )
+                })
+                .collect();
+            (terminal_pair, vec![schedule])
+        };
+        let route4 = TerminalPair { from: Terminal::SWB, to: Terminal::FUL };
+        let route6 = TerminalPair { from: Terminal::VES, to: Terminal::CFT };
+        let schedules_map = HashMap::from([
+            schedule_with_sailings(route4, &[(time!(7:00), time!(7:35)), (time!(9:00), time!(9:35))]),
+            schedule_with_sailings(route6, &[(time!(8:00), time!(8:20)), (time!(8:30), time!(8:50))]),
+        ]);
+        let journeys = find_journeys(Terminal::SWB, Terminal::CFT, date!(2022 - 06 - 15), &schedules_map);
+        // Driving across Salt Spring Island from Fulford Harbour to Vesuvius Bay takes too long to make the 8:00 am
+        // sailing, and the later sailing from Swartz Bay misses the last one
+        assert_eq!(
+            journeys,
+            [Journey {
+                legs: vec![
+                    JourneyLeg {
+                        terminal_pair: route4,
+                        sailing: Sailing { depart_time: time!(7:00), arrive_time: time!(7:35), stops: vec![] },
+                    },
+                    JourneyLeg {
+                        terminal_pair: route6,
+                        sailing: Sailing { depart_time: time!(8:30), arrive_time: time!(8:50), stops: vec![] },
+                    },
+                ],
+            }]
+        );
+        assert!(find_journeys(Terminal::CFT, Terminal::SWB, date!(2022 - 06 - 15), &schedules_map).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_missing_journeys() -> Result<()> {
+        let route4 = TerminalPair { from: Terminal::SWB, to: Terminal::FUL };
+        let route6 = TerminalPair { from: Terminal::VES, to: Terminal::CFT };
+        let schedule_with_sailing = |terminal_pair, date_range, depart_time, arrive_time| {
+            let mut schedule = schedule(terminal_pair, date_range);
+            schedule.items = vec![ScheduleItem::test_fixture(
+                Sailing { depart_time, arrive_time, stops: vec![] },
+                &[Weekday::Wednesday],
+            )];
+            schedule
+        };
+        let june = DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 30) };
+        let july = DateRange { from: date!(2022 - 07 - 01), to: date!(2022 - 07 - 31) };
+        let schedules_map = HashMap::from([
+            (
+                route4,
+                vec![
+                    schedule_with_sailing(route4, june, time!(9:00), time!(9:35)),
+                    schedule_with_sailing(route4, july, time!(9:00), time!(9:35)),
+                ],
+            ),
+            (route6, vec![schedule_with_sailing(route6, june, time!(9:30), time!(9:50))]),
+        ]);
+        // The connecting route's last sailing leaves before the first can connect with it
+        assert_eq!(
+            explain_missing_journeys(Terminal::SWB, Terminal::CFT, date!(2022 - 06 - 15), &schedules_map),
+            [JourneyGap::MissedConnection {
+                arrived: route4,
+                next: route6,
+                earliest_arrive_time: time!(9:35),
+                last_depart_time: time!(9:30),
+            }]
+        );
+        // The connecting route's schedule period is over
+        assert_eq!(
+            explain_missing_journeys(Terminal::SWB, Terminal::CFT, date!(2022 - 07 - 13), &schedules_map),
+            [JourneyGap::NoSchedule {
+                terminal_pair: route6,
+                period_end: Some(date!(2022 - 06 - 30)),
+                next_period_from: None
+            }]
+        );
+        // No sailings on Thursdays
+        assert_eq!(
+            explain_missing_journeys(Terminal::SWB, Terminal::CFT, date!(2022 - 06 - 16), &schedules_map),
+            [JourneyGap::NoService { terminal_pair: route4 }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_terminal_departures() -> Result<()> {
+        let date_range = DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 30) };
+        let schedule_with_sailing = |terminal_pair, depart_time, arrive_time, stops: &[Stop]| {
+            let mut schedule = schedule(terminal_pair, date_range);
+            schedule.items = vec![ScheduleItem::test_fixture(
+                Sailing { depart_time, arrive_time, stops: stops.to_vec() },
+                &[Weekday::Wednesday],
+            )];
+            (terminal_pair, vec![schedule])
+        };
+        let to_pender = TerminalPair { from: Terminal::SWB, to: Terminal::POB };
+        let to_mayne = TerminalPair { from: Terminal::SWB, to: Terminal::PVB };
+        let to_vancouver = TerminalPair { from: Terminal::SWB, to: Terminal::TSA };
+        let to_saturna = TerminalPair { from: Terminal::SWB, to: Terminal::PST };
+        let schedules_map = HashMap::from([
+            schedule_with_sailing(
+                to_mayne,
+                time!(9:00),
+                time!(10:10),
+                &[Stop { type_: StopType::Stop, terminal: Terminal::POB }],
+            ),
+            schedule_with_sailing(to_pender, time!(9:00), time!(9:40), &[]),
+            schedule_with_sailing(to_vancouver, time!(7:00), time!(8:35), &[]),
+            // Thru-fare via Mayne Island on the 9:00 am sailing
+            schedule_with_sailing(
+                to_saturna,
+                time!(9:00),
+                time!(11:00),
+                &[Stop { type_: StopType::Thrufare, terminal: Terminal::PVB }],
+            ),
+        ]);
+        let departures = terminal_departures(Terminal::SWB, date!(2022 - 06 - 15), &schedules_map);
+        assert_eq!(
+            departures,
+            [
+                TerminalDeparture {
+                    depart_time: time!(7:00),
+                    destinations: vec![DepartureDestination { terminal: Terminal::TSA, arrive_time: time!(8:35) }],
+                    notes: vec![],
+                },
+                TerminalDeparture {
+                    depart_time: time!(9:00),
+                    destinations: vec![
+                        DepartureDestination { terminal: Terminal::POB, arrive_time: time!(9:40) },
+                        DepartureDestination { terminal: Terminal::PVB, arrive_time: time!(10:10) },
+                    ],
+                    notes: vec![],
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_connection_notes() -> Result<()> {
+        let date_range = DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 30) };
+        let schedule_with_sailings = |terminal_pair, sailings: &[(Time, Time)], stops: &[Stop]| {
+            let mut schedule = schedule(terminal_pair, date_range);
+            schedule.items = sailings
+                .iter()
+                .map(|&(depart_time, arrive_time)| {
+                    ScheduleItem::test_fixture(
+                        Sailing { depart_time, arrive_time, stops: stops.to_vec() },
+                        &[Weekday::Wednesday],
+                    )
+                })
+                .collect();
+            schedule
+        };
+        let thrufare_pair = TerminalPair { from: Terminal::TSA, to: Terminal::POB };
+        let first_leg_pair = TerminalPair { from: Terminal::TSA, to: Terminal::SWB };
+        let second_leg_pair = TerminalPair { from: Terminal::SWB, to: Terminal::POB };
+        let via_swartz_bay = [Stop { type_: StopType::Thrufare, terminal: Terminal::SWB }];
+        let schedules_map = HashMap::from([
+            (
+                thrufare_pair,
+                vec![schedule_with_sailings(
+                    thrufare_pair,
+                    &[(time!(7:00), time!(10:15)), (time!(9:00), time!(12:15))],
+                    &via_swartz_bay,
+                )],
+            ),
+            (
+                first_leg_pair,
+                vec![schedule_with_sailings(
+                    first_leg_pair,
+                    &[(time!(7:00), time!(8:35)), (time!(9:00), time!(10:35))],
+                    &[],
+                )],
+            ),
+            (
+                second_leg_pair,
+                vec![schedule_with_sailings(
+                    second_leg_pair,
+                    &[(time!(9:00), time!(10:15)), (time!(10:30), time!(12:15))],
+                    &[],
+                )],
+            ),
+        ]);
+        let schedules_sailings = area_sailings_for_date(
+            thrufare_pair.area_pair(),
+            date!(2022 - 06 - 15),
+            &schedules_map,
+            &SailingFilter::default(),
+        )
+        .expect("Expect sailings");
+        let (_, sailings) = schedules_sailings
+            .iter()
+            .find(|(s, _)| s.terminal_pair == thrufare_pair)
+            .expect("Expect thru-fare schedule sailings");
+        assert!(sailings[0].notes.is_empty());
+        assert_eq!(sailings[1].notes[0].sources, vec![NoteSource::Connection]);
+        Ok(())
+    }
+}
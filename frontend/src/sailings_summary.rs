@@ -0,0 +1,109 @@
+use crate::prelude::*;
+use crate::utils::*;
+
+// Average gap between sailings, rounded the way someone would say it
+fn interval_text(average: Duration) -> String {
+    let minutes = average.whole_minutes();
+    if minutes < 60 {
+        return format!("every {} minutes", max(5, (minutes + 2) / 5 * 5));
+    }
+    let half_hours = (minutes + 15) / 30;
+    match (half_hours / 2, half_hours % 2) {
+        (1, 0) => "every hour".to_string(),
+        (1, _) => "every hour and a half".to_string(),
+        (hours, 0) => format!("every {} hours", hours),
+        (hours, _) => format!("every {} and a half hours", hours),
+    }
+}
+
+fn list_text(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.clone(),
+        [init @ .., last] => format!("{} and {}", init.join(", "), last),
+    }
+}
+
+// One sentence describing a day's sailings, such as "8 sailings from 6:15 am to 9:00 pm, roughly every 2 hours; the
+// 1:05 pm requires a transfer at Mayne Island.", for screen readers to read before the table and for link previews
+pub fn sailings_summary_text(sailings: &[&Sailing]) -> String {
+    let sailings: Vec<_> = sailings.iter().sorted_by_key(|s| s.depart_time).collect();
+    let (first, last) = match (sailings.first(), sailings.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return "No sailings.".to_string(),
+    };
+    let mut clauses = vec![if sailings.len() == 1 {
+        format!("1 sailing at {}", format_time(first.depart_time))
+    } else {
+        let gaps: Vec<_> = sailings.iter().tuple_windows().map(|(a, b)| b.depart_time - a.depart_time).collect();
+        let average = (last.depart_time - first.depart_time) / (gaps.len() as u32);
+        let longest_gap = gaps.iter().position_max().expect("Expect gaps between sailings");
+        let spacing_text = if gaps[longest_gap] <= average * 1.5 {
+            format!("roughly {}", interval_text(average))
+        } else {
+            format!(
+                "with the longest gap between {} and {}",
+                format_time(sailings[longest_gap].depart_time),
+                format_time(sailings[longest_gap + 1].depart_time)
+            )
+        };
+        format!(
+            "{} sailings from {} to {}, {}",
+            sailings.len(),
+            format_time(first.depart_time),
+            format_time(last.depart_time),
+            spacing_text
+        )
+    }];
+    let transfer_areas = sailings
+        .iter()
+        .flat_map(|s| {
+            s.stops.iter().filter(|stop| stop.type_ == StopType::Transfer).map(move |stop| (stop.terminal.area(), s))
+        })
+        .into_group_map();
+    for (area, transfer_sailings) in transfer_areas.into_iter().sorted_by_key(|(area, _)| area.long_name()) {
+        let times: Vec<_> = transfer_sailings.iter().map(|s| format_time(s.depart_time)).dedup().collect();
+        clauses.push(format!(
+            "the {} {} a transfer at {}",
+            list_text(&times),
+            if times.len() == 1 { "requires" } else { "require" },
+            area.long_name()
+        ));
+    }
+    format!("{}.", clauses.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sailing(depart_time: Time, transfer_at: Option<Terminal>) -> Sailing {
+        Sailing {
+            depart_time,
+            arrive_time: depart_time + Duration::minutes(90),
+            stops: transfer_at.map(|terminal| Stop { type_: StopType::Transfer, terminal }).into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn test_sailings_summary_text() -> Result<()> {
+        let regular: Vec<_> = [time!(6:15), time!(8:20), time!(10:30), time!(13:05), time!(15:00), time!(17:00)]
+            .into_iter()
+            .map(|t| sailing(t, (t == time!(13:05)).then_some(Terminal::PVB)))
+            .collect();
+        assert_eq!(
+            sailings_summary_text(&regular.iter().collect::<Vec<_>>()),
+            "6 sailings from 6:15 am to 5:00 pm, roughly every 2 hours; the 1:05 pm requires a transfer at Mayne Island."
+        );
+        let irregular = [sailing(time!(7:00), None), sailing(time!(7:40), None), sailing(time!(16:30), None)];
+        assert_eq!(
+            sailings_summary_text(&irregular.iter().collect::<Vec<_>>()),
+            "3 sailings from 7:00 am to 4:30 pm, with the longest gap between 7:40 am and 4:30 pm."
+        );
+        assert_eq!(sailings_summary_text(&[&irregular[0]]), "1 sailing at 7:00 am.");
+        assert_eq!(sailings_summary_text(&[]), "No sailings.");
+        assert_eq!(interval_text(Duration::minutes(43)), "every 45 minutes");
+        assert_eq!(interval_text(Duration::minutes(95)), "every hour and a half");
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use crate::prelude::*;
+use crate::sailings_processor::*;
+use crate::storage::*;
+
+// The digest of the next day's saved trips is offered from this time in the evening
+pub const TRIP_DIGEST_FROM_TIME: Time = time!(17:00);
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SavedTrip {
+    pub terminal_pair: TerminalPair,
+    // Calendar date the sailing departs on
+    pub date: Date,
+    pub depart_time: Time,
+}
+
+// Sailings the user plans to take, so the evening before they can check on them in one place
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SavedTrips {
+    trips: Vec<SavedTrip>,
+}
+
+impl Stored for SavedTrips {
+    const KEY: &'static str = "saved-trips";
+    const VERSION: u32 = 1;
+}
+
+pub struct TripDigestEntry<'a> {
+    pub trip: SavedTrip,
+    // Missing if the sailing is no longer scheduled on the trip's date
+    pub sailing: Option<SailingWithNotes>,
+    // Alerts on the trip's route that are relevant on its date
+    pub alerts: Vec<&'a Alert>,
+}
+
+impl SavedTrips {
+    pub fn contains(&self, trip: &SavedTrip) -> bool {
+        self.trips.contains(trip)
+    }
+
+    // Saves the trip, or removes it if it was already saved.  Trips from before today are dropped at the same time, so
+    // the stored trips do not grow forever.
+    pub fn toggled(&self, trip: SavedTrip, today: Date) -> SavedTrips {
+        let mut trips: Vec<_> = self.trips.iter().filter(|t| t.date >= today && **t != trip).copied().collect();
+        if !self.contains(&trip) {
+            trips.push(trip);
+        }
+        SavedTrips { trips }
+    }
+
+    pub fn on_date(&self, date: Date) -> Vec<SavedTrip> {
+        self.trips.iter().filter(|t| t.date == date).sorted_by_key(|t| t.depart_time).copied().collect()
+    }
+}
+
+// What to check the evening before the saved trips on the date: whether each sailing is still scheduled, and any
+// alerts on its route
+pub fn trip_digest<'a>(
+    saved_trips: &SavedTrips,
+    date: Date,
+    schedules_map: &'a HashMap<TerminalPair, Vec<Schedule>>,
+) -> Vec<TripDigestEntry<'a>> {
+    saved_trips
+        .on_date(date)
+        .into_iter()
+        .map(|trip| {
+            let schedule_sailings = area_sailings_for_date(
+                trip.terminal_pair.area_pair(),
+                date,
+                schedules_map,
+                &SailingFilter::default().include_supplemental(true),
+            )
+            .unwrap_or_default()
+            .into_iter()
+            .find(|(schedule, _)| schedule.terminal_pair == trip.terminal_pair);
+            match schedule_sailings {
+                Some((schedule, sailings)) => TripDigestEntry {
+                    trip,
+                    sailing: sailings.into_iter().find(|s| s.sailing.depart_time == trip.depart_time),
+                    alerts: schedule.alerts.iter().filter(|a| a.is_relevant_on(date)).collect(),
+                },
+                None => TripDigestEntry { trip, sailing: None, alerts: vec![] },
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TERMINAL_PAIR: TerminalPair = TerminalPair { from: Terminal::SWB, to: Terminal::PSB };
+
+    fn trip(date: Date, depart_time: Time) -> SavedTrip {
+        SavedTrip { terminal_pair: TERMINAL_PAIR, date, depart_time }
+    }
+
+    #[test]
+    fn test_saved_trips_toggled() -> Result<()> {
+        let today = date!(2022 - 06 - 15);
+        let saved_trips = SavedTrips::default()
+            .toggled(trip(date!(2022 - 06 - 14), time!(7:00)), date!(2022 - 06 - 14))
+            .toggled(trip(today, time!(9:00)), today)
+            .toggled(trip(today, time!(7:00)), today);
+        // Yesterday's trip was dropped when the later ones were saved
+        assert_eq!(saved_trips.on_date(today), [trip(today, time!(7:00)), trip(today, time!(9:00))]);
+        assert!(saved_trips.on_date(date!(2022 - 06 - 14)).is_empty());
+        assert!(!saved_trips.toggled(trip(today, time!(9:00)), today).contains(&trip(today, time!(9:00))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_trip_digest() -> Result<()> {
+        let date = date!(2022 - 06 - 15);
+        let schedule = Schedule {
+            items: vec![ScheduleItem {
+                sailing: Sailing { depart_time: time!(7:00), arrive_time: time!(8:05), stops: vec![] },
+                weekdays: HashMap::from([(Weekday::Wednesday, DateRestriction::All)]),
+                notes: HashMap::new(),
+                note_categories: HashMap::new(),
+                vehicle_restrictions: HashMap::new(),
+                conditional_stops: vec![],
+            }],
+            alerts: vec![Alert {
+                message: "Reduced service".to_string(),
+                level: AlertLevel::Warning,
+                severity: None,
+                relevant_date_ranges: vec![],
+            }],
+            ..Schedule::test_fixture(TERMINAL_PAIR)
+        };
+        let schedules_map = HashMap::from([(TERMINAL_PAIR, vec![schedule])]);
+        let saved_trips =
+            SavedTrips::default().toggled(trip(date, time!(7:00)), date).toggled(trip(date, time!(9:00)), date);
+        let digest = trip_digest(&saved_trips, date, &schedules_map);
+        assert_eq!(digest.len(), 2);
+        assert_eq!(digest[0].sailing.as_ref().map(|s| s.sailing.arrive_time), Some(time!(8:05)));
+        assert_eq!(digest[0].alerts.len(), 1);
+        // No longer scheduled, but the route's alerts still apply
+        assert!(digest[1].sailing.is_none());
+        assert_eq!(digest[1].alerts.len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+use std::mem;
+
+use crate::prelude::*;
+
+thread_local! {
+    // There are only a few hundred distinct note texts and they are needed until the app closes, so each is leaked once
+    // and shared by every sailing with the note, rather than each sailing having its own copy
+    static NOTE_TEXTS: RefCell<HashSet<&'static str>> = RefCell::new(HashSet::new());
+}
+
+fn intern_note_text(text: Cow<'static, str>) -> Cow<'static, str> {
+    match text {
+        Cow::Borrowed(_) => text,
+        Cow::Owned(owned) => NOTE_TEXTS.with(|texts| {
+            let mut texts = texts.borrow_mut();
+            let interned = match texts.get(owned.as_str()) {
+                Some(&interned) => interned,
+                None => {
+                    let leaked: &'static str = Box::leak(owned.into_boxed_str());
+                    texts.insert(leaked);
+                    leaked
+                }
+            };
+            Cow::Borrowed(interned)
+        }),
+    }
+}
+
+fn compact_date_restriction(date_restriction: &mut DateRestriction) {
+    match date_restriction {
+        DateRestriction::All => {}
+        DateRestriction::Only(dates) | DateRestriction::Except(dates) => dates.shrink_to_fit(),
+    }
+}
+
+fn compact_items(items: &mut Vec<ScheduleItem>) {
+    for item in items.iter_mut() {
+        item.weekdays.values_mut().for_each(compact_date_restriction);
+        item.notes = mem::take(&mut item.notes)
+            .into_iter()
+            .map(|(text, mut date_restriction)| {
+                compact_date_restriction(&mut date_restriction);
+                (intern_note_text(text), date_restriction)
+            })
+            .collect();
+        item.note_categories = mem::take(&mut item.note_categories)
+            .into_iter()
+            .map(|(text, category)| (intern_note_text(text), category))
+            .collect();
+        item.vehicle_restrictions = mem::take(&mut item.vehicle_restrictions)
+            .into_iter()
+            .map(|(text, restriction)| (intern_note_text(text), restriction))
+            .collect();
+        item.sailing.stops.shrink_to_fit();
+    }
+    items.shrink_to_fit();
+}
+
+// Reduces the heap that loaded schedules take, which matters on low-memory phones since the whole data set stays loaded
+// while the app is open
+pub fn compact_schedules(schedules_map: &mut HashMap<TerminalPair, Vec<Schedule>>) {
+    for schedules in schedules_map.values_mut() {
+        for schedule in schedules.iter_mut() {
+            compact_items(&mut schedule.items);
+            compact_items(&mut schedule.previous_items);
+            schedule.header_notes.shrink_to_fit();
+            schedule.alerts.shrink_to_fit();
+        }
+        schedules.shrink_to_fit();
+    }
+    schedules_map.shrink_to_fit();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(terminal_pair: TerminalPair, note: &str) -> Schedule {
+        let item = ScheduleItem {
+            sailing: Sailing { depart_time: time!(09:00), arrive_time: time!(09:50), stops: vec![] },
+            weekdays: HashMap::from([(Weekday::Monday, DateRestriction::All)]),
+            notes: HashMap::from([(Cow::Owned(note.to_string()), DateRestriction::All)]),
+            note_categories: HashMap::new(),
+            vehicle_restrictions: HashMap::new(),
+            conditional_stops: vec![],
+        };
+        Schedule { items: vec![item], ..Schedule::test_fixture(terminal_pair) }
+    }
+
+    #[test]
+    fn test_compact_schedules() -> Result<()> {
+        let terminal_pairs = [
+            TerminalPair { from: Terminal::SWB, to: Terminal::PSB },
+            TerminalPair { from: Terminal::PSB, to: Terminal::SWB },
+        ];
+        let mut schedules_map =
+            into_vec_group_map(terminal_pairs.iter().map(|&tp| schedule(tp, "Foot passengers only")), |s| {
+                s.terminal_pair
+            });
+        compact_schedules(&mut schedules_map);
+        for tp in &terminal_pairs {
+            assert_eq!(schedules_map[tp][0].items, schedule(*tp, "Foot passengers only").items);
+        }
+        // Every copy of the note shares one string
+        let note_texts: Vec<_> = terminal_pairs
+            .iter()
+            .map(|tp| match schedules_map[tp][0].items[0].notes.keys().next() {
+                Some(Cow::Borrowed(text)) => text.as_ptr(),
+                other => panic!("Expect interned note text, not {:?}", other),
+            })
+            .collect();
+        assert_eq!(note_texts[0], note_texts[1]);
+        Ok(())
+    }
+}
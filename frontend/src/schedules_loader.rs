@@ -0,0 +1,452 @@
+use reqwasm::http;
+use web_sys::RequestCache;
+
+use crate::prelude::*;
+use crate::schedules_compaction::*;
+use crate::storage::*;
+
+// Locations for the stable channel; other channels' files are in subdirectories
+const SCHEDULES_URL: &str = "/data/schedules.json";
+const MANIFEST_URL: &str = "/data/manifest.json";
+
+// How long a simulated slow response is delayed
+const SLOW_RESPONSE_DELAY_MILLIS: u32 = 10_000;
+
+// Failure modes that can be simulated from the debug menu, to check how the app behaves when loading schedules fails
+#[derive(Copy, Clone, Debug, Default, Deserialize, EnumIter, Eq, PartialEq, Serialize)]
+pub enum LoadFault {
+    #[default]
+    None,
+    PartialDownload,
+    CorruptJson,
+    VersionMismatch,
+    SlowResponse,
+    PartitionFailure,
+}
+
+impl LoadFault {
+    pub fn description(&self) -> &'static str {
+        match self {
+            LoadFault::None => "No simulated failure",
+            LoadFault::PartialDownload => "Partial download",
+            LoadFault::CorruptJson => "Corrupt JSON",
+            LoadFault::VersionMismatch => "Data version mismatch",
+            LoadFault::SlowResponse => "Slow response",
+            LoadFault::PartitionFailure => "Schedules partition failure",
+        }
+    }
+
+    fn apply(&self, body: String) -> String {
+        match self {
+            LoadFault::None | LoadFault::SlowResponse | LoadFault::PartitionFailure => body,
+            LoadFault::PartialDownload => String::from_utf8_lossy(&body.as_bytes()[..body.len() / 2]).into_owned(),
+            LoadFault::CorruptJson => body.replacen(':', ";", 1),
+            // Simulates data written by a newer scraper that wraps the data with a format version
+            LoadFault::VersionMismatch => format!(r#"{{"version":999,"schedules":{}}}"#, body),
+        }
+    }
+}
+
+impl Stored for LoadFault {
+    const KEY: &'static str = "debug-load-fault";
+    const VERSION: u32 = 1;
+}
+
+// Only changed by opening the site with a `data-channel` query parameter, since preview data is for checking scraper
+// changes before they are released
+impl Stored for DataChannel {
+    const KEY: &'static str = "data-channel";
+    const VERSION: u32 = 1;
+}
+
+#[derive(Default)]
+pub struct LoadedData {
+    pub schedules_map: HashMap<TerminalPair, Vec<Schedule>>,
+    // Missing if the data was published before metadata was embedded
+    pub metadata: Option<DataMetadata>,
+    // Partitions that failed to load, whose dates are missing from `schedules_map` until they are retried
+    pub missing_partitions: Vec<DataPartition>,
+    // Empty if the data was published before manifests were written
+    pub feature_flags: Vec<FeatureFlag>,
+    // Routes not in `schedules_map` yet, because only the routes being viewed were loaded first
+    pub pending_route_files: Vec<DataRouteFile>,
+}
+
+pub fn parse_schedules(body: &str) -> Result<LoadedData> {
+    let data: PublishedData = serde_json::from_str(body).context("Failed to parse schedules")?;
+    let metadata = data.metadata().cloned();
+    let mut schedules_map = into_vec_group_map(data.into_schedules(), |i| i.terminal_pair);
+    compact_schedules(&mut schedules_map);
+    Ok(LoadedData { schedules_map, metadata, ..LoadedData::default() })
+}
+
+// Schedules for periods that are already loaded are skipped, as when a partition is retried after revalidated
+// schedules have it
+pub fn merge_schedules(
+    schedules_map: &mut HashMap<TerminalPair, Vec<Schedule>>,
+    partition_map: HashMap<TerminalPair, Vec<Schedule>>,
+) {
+    for (terminal_pair, schedules) in partition_map {
+        let loaded_schedules = schedules_map.entry(terminal_pair).or_default();
+        for schedule in schedules {
+            if !loaded_schedules.iter().any(|loaded| loaded.date_range == schedule.date_range) {
+                loaded_schedules.push(schedule);
+            }
+        }
+    }
+}
+
+pub fn current_load_fault() -> LoadFault {
+    if cfg!(debug_assertions) {
+        load_or_default(&*default_storage())
+    } else {
+        LoadFault::None
+    }
+}
+
+pub fn current_data_channel() -> DataChannel {
+    load_or_default(&*default_storage())
+}
+
+fn manifest_file_url(channel: DataChannel, file: &str) -> String {
+    let manifest_url = channel.path(MANIFEST_URL);
+    let (manifest_dir, _) = manifest_url.rsplit_once('/').expect("Expect manifest URL to have a directory");
+    format!("{}/{}", manifest_dir, file)
+}
+
+fn manifest_schedules_url(channel: DataChannel, manifest: &DataManifest) -> Result<String> {
+    ensure!(manifest.channel == channel, "Expected {} data manifest, but found {}", channel, manifest.channel);
+    Ok(manifest_file_url(channel, &manifest.schedules_file))
+}
+
+// The pre-compressed copy of the schedules to fetch instead, if any.  Browsers only accept brotli over HTTPS, so it's
+// only preferred in a secure context.
+fn manifest_compressed_schedules_url(channel: DataChannel, manifest: &DataManifest, is_secure: bool) -> Option<String> {
+    let encoding = [ContentEncoding::Brotli, ContentEncoding::Gzip]
+        .into_iter()
+        .filter(|encoding| is_secure || *encoding != ContentEncoding::Brotli)
+        .find(|encoding| manifest.schedules_encodings.contains(encoding))?;
+    Some(manifest_file_url(channel, &encoding.file_name(&manifest.schedules_file)))
+}
+
+// Compressed copies come back still compressed if the host doesn't send their `Content-Encoding` header
+fn is_decoded_json(body: &str) -> bool {
+    matches!(body.trim_start().chars().next(), Some('[' | '{'))
+}
+
+async fn fetch_schedules_body(url: &str, cache: RequestCache) -> Result<String> {
+    let response = http::Request::get(url).cache(cache).send().await?;
+    ensure!(response.ok(), "Failed to fetch schedules: HTTP status {}", response.status());
+    Ok(response.text().await?)
+}
+
+pub async fn fetch_partition(
+    channel: DataChannel,
+    partition: &DataPartition,
+    revalidate: bool,
+) -> Result<HashMap<TerminalPair, Vec<Schedule>>> {
+    let cache = if revalidate { RequestCache::NoCache } else { RequestCache::Default };
+    let response = http::Request::get(&manifest_file_url(channel, &partition.file)).cache(cache).send().await?;
+    ensure!(
+        response.ok(),
+        "Failed to fetch schedules partition {:?}: HTTP status {}",
+        partition.file,
+        response.status()
+    );
+    Ok(parse_schedules(&response.text().await?)?.schedules_map)
+}
+
+// The route files to load instead of the schedules file, and the rest of them to load afterwards, if the manifest has
+// files for any of the routes being viewed.  Data split into date partitions is always loaded whole.
+fn split_route_files(
+    manifest: &DataManifest,
+    viewed_terminal_pairs: &[TerminalPair],
+) -> Option<(Vec<DataRouteFile>, Vec<DataRouteFile>)> {
+    if !manifest.partitions.is_empty() {
+        return None;
+    }
+    let (viewed, pending): (Vec<_>, Vec<_>) =
+        manifest.route_files.iter().cloned().partition(|f| viewed_terminal_pairs.contains(&f.terminal_pair));
+    (!viewed.is_empty()).then_some((viewed, pending))
+}
+
+pub async fn fetch_route_files(
+    channel: DataChannel,
+    route_files: &[DataRouteFile],
+    fault: LoadFault,
+    revalidate: bool,
+) -> Result<LoadedData> {
+    let cache = if revalidate { RequestCache::NoCache } else { RequestCache::Default };
+    let mut data = LoadedData::default();
+    for route_file in route_files {
+        let body = fetch_schedules_body(&manifest_file_url(channel, &route_file.file), cache)
+            .await
+            .with_context(|| format!("Failed to fetch route schedules {:?}", route_file.file))?;
+        let route_data = parse_schedules(&fault.apply(body))?;
+        merge_schedules(&mut data.schedules_map, route_data.schedules_map);
+        data.metadata = data.metadata.or(route_data.metadata);
+    }
+    Ok(data)
+}
+
+// Whether every loaded schedule has ended, as happens when scraping has stopped, in which case no page has anything
+// current to show
+pub fn is_data_expired(schedules_map: &HashMap<TerminalPair, Vec<Schedule>>, today: Date) -> bool {
+    let mut schedules = schedules_map.values().flatten().peekable();
+    schedules.peek().is_some() && schedules.all(|schedule| schedule.date_range.to < today)
+}
+
+// Finds the schedules from the channel's manifest.  Revalidation skips the browser's cached copy, which can be hours
+// old.  Only the main schedules file has to load; partitions that fail are left for the caller to retry.  If there
+// are route files, only the viewed routes' are loaded, leaving the rest for the caller to load once they are shown.
+pub async fn fetch_schedules(
+    fault: LoadFault,
+    revalidate: bool,
+    channel: DataChannel,
+    viewed_terminal_pairs: &[TerminalPair],
+) -> Result<LoadedData> {
+    let cache = if revalidate { RequestCache::NoCache } else { RequestCache::Default };
+    let manifest_response = http::Request::get(&channel.path(MANIFEST_URL)).cache(cache).send().await?;
+    let (schedules_url, compressed_schedules_url, route_files, partitions, feature_flags) = if manifest_response.ok() {
+        let manifest: DataManifest =
+            serde_json::from_str(&manifest_response.text().await?).context("Failed to parse data manifest")?;
+        let is_secure = web_sys::window().map_or(false, |window| window.is_secure_context());
+        (
+            manifest_schedules_url(channel, &manifest)?,
+            manifest_compressed_schedules_url(channel, &manifest, is_secure),
+            split_route_files(&manifest, viewed_terminal_pairs),
+            manifest.partitions,
+            manifest.feature_flags,
+        )
+    } else if channel == DataChannel::Stable {
+        // Published before manifests were written
+        (SCHEDULES_URL.to_string(), None, None, vec![], vec![])
+    } else {
+        bail!("Failed to fetch {} data manifest: HTTP status {}", channel, manifest_response.status());
+    };
+    if let Some((viewed_route_files, pending_route_files)) = route_files {
+        if fault == LoadFault::SlowResponse {
+            gloo_timers::future::TimeoutFuture::new(SLOW_RESPONSE_DELAY_MILLIS).await;
+        }
+        match fetch_route_files(channel, &viewed_route_files, fault, revalidate).await {
+            Ok(mut data) => {
+                data.feature_flags = feature_flags;
+                data.pending_route_files = pending_route_files;
+                return Ok(data);
+            }
+            Err(err) => warn!("Failed to load viewed routes, so loading all schedules: {}", err),
+        }
+    }
+    let compressed_body = match &compressed_schedules_url {
+        Some(url) => match fetch_schedules_body(url, cache).await {
+            Ok(body) if is_decoded_json(&body) => Some(body),
+            Ok(_) => {
+                warn!("Compressed schedules were not decompressed by the browser: {:?}", url);
+                None
+            }
+            Err(err) => {
+                warn!("Failed to fetch compressed schedules {:?}: {}", url, err);
+                None
+            }
+        },
+        None => None,
+    };
+    let body = match compressed_body {
+        Some(body) => body,
+        None => fetch_schedules_body(&schedules_url, cache).await?,
+    };
+    if fault == LoadFault::SlowResponse {
+        gloo_timers::future::TimeoutFuture::new(SLOW_RESPONSE_DELAY_MILLIS).await;
+    }
+    let mut data = parse_schedules(&fault.apply(body))?;
+    data.feature_flags = feature_flags;
+    for partition in partitions {
+        let result = if fault == LoadFault::PartitionFailure {
+            Err(anyhow!("Simulated failure"))
+        } else {
+            fetch_partition(channel, &partition, revalidate).await
+        };
+        match result {
+            Ok(partition_map) => merge_schedules(&mut data.schedules_map, partition_map),
+            Err(err) => {
+                warn!("Failed to load schedules partition {:?}: {}", partition.file, err);
+                data.missing_partitions.push(partition);
+            }
+        }
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+
+    fn schedule() -> Schedule {
+        Schedule {
+            source_url: "https://www.bcferries.com/".to_string(),
+            refreshed_at: date!(2022 - 06 - 01).midnight().assume_utc(),
+            ..Schedule::test_fixture(TerminalPair { from: Terminal::SWB, to: Terminal::PSB })
+        }
+    }
+
+    fn schedules_body() -> Result<String> {
+        let metadata = DataMetadata::new(date!(2022 - 06 - 01).midnight().assume_utc(), "0.1.0");
+        Ok(serde_json::to_string(&PublishedData::WithMetadata { metadata, schedules: vec![schedule()] })?)
+    }
+
+    fn load_event(fault: LoadFault, body: String) -> SchedulesEvent {
+        match parse_schedules(&fault.apply(body)) {
+            Ok(data) => SchedulesEvent::LoadSucceeded(Rc::new(data.schedules_map)),
+            Err(_) => SchedulesEvent::LoadFailed,
+        }
+    }
+
+    #[test]
+    fn test_load_faults() -> Result<()> {
+        for fault in LoadFault::iter() {
+            let loading = SchedulesState::Init.transition(SchedulesEvent::Load).expect("Expect Init to start loading");
+            let state = loading.transition(load_event(fault, schedules_body()?)).expect("Expect load result");
+            match fault {
+                LoadFault::None | LoadFault::SlowResponse | LoadFault::PartitionFailure => {
+                    assert!(matches!(state, SchedulesState::Loaded(_)))
+                }
+                _ => {
+                    assert!(state == SchedulesState::Failed, "Expect {:?} to fail", fault);
+                    // Retrying after the failure recovers once the data is intact
+                    let retrying = state.transition(SchedulesEvent::Load).expect("Expect Failed to retry loading");
+                    let retried = retrying.transition(load_event(LoadFault::None, schedules_body()?));
+                    assert!(matches!(retried, Some(SchedulesState::Loaded(_))));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_manifest_schedules_url() -> Result<()> {
+        let manifest = |channel| DataManifest {
+            channel,
+            schedules_file: "schedules.json".to_string(),
+            schedules_bytes: 0,
+            published_at: OffsetDateTime::UNIX_EPOCH,
+            partitions: vec![],
+            feature_flags: vec![],
+            schedules_encodings: vec![],
+            route_files: vec![],
+        };
+        assert_eq!(manifest_schedules_url(DataChannel::Stable, &manifest(DataChannel::Stable))?, SCHEDULES_URL);
+        assert_eq!(
+            manifest_schedules_url(DataChannel::Preview, &manifest(DataChannel::Preview))?,
+            "/data/preview/schedules.json"
+        );
+        // Never silently shows stable data to someone checking preview data, or the reverse
+        assert!(manifest_schedules_url(DataChannel::Preview, &manifest(DataChannel::Stable)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_manifest_compressed_schedules_url() -> Result<()> {
+        let manifest = |schedules_encodings| DataManifest {
+            channel: DataChannel::Preview,
+            schedules_file: "schedules.json".to_string(),
+            schedules_bytes: 0,
+            published_at: OffsetDateTime::UNIX_EPOCH,
+            partitions: vec![],
+            feature_flags: vec![],
+            schedules_encodings,
+            route_files: vec![],
+        };
+        let both = manifest(vec![ContentEncoding::Gzip, ContentEncoding::Brotli]);
+        assert_eq!(
+            manifest_compressed_schedules_url(DataChannel::Preview, &both, true).as_deref(),
+            Some("/data/preview/schedules.json.br")
+        );
+        assert_eq!(
+            manifest_compressed_schedules_url(DataChannel::Preview, &both, false).as_deref(),
+            Some("/data/preview/schedules.json.gz")
+        );
+        let brotli_only = manifest(vec![ContentEncoding::Brotli]);
+        assert_eq!(manifest_compressed_schedules_url(DataChannel::Preview, &brotli_only, false), None);
+        assert_eq!(manifest_compressed_schedules_url(DataChannel::Preview, &manifest(vec![]), true), None);
+        assert!(is_decoded_json(&schedules_body()?));
+        assert!(!is_decoded_json("\u{1f}\u{fffd}\u{8}"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_route_files() -> Result<()> {
+        let swb_psb = TerminalPair { from: Terminal::SWB, to: Terminal::PSB };
+        let route_file = |terminal_pair: TerminalPair| DataRouteFile {
+            terminal_pair,
+            file: format!("routes/{}.json", terminal_pair),
+            bytes: 0,
+        };
+        let mut manifest = DataManifest {
+            channel: DataChannel::Stable,
+            schedules_file: "schedules.json".to_string(),
+            schedules_bytes: 0,
+            published_at: OffsetDateTime::UNIX_EPOCH,
+            partitions: vec![],
+            feature_flags: vec![],
+            schedules_encodings: vec![],
+            route_files: vec![route_file(swb_psb), route_file(swb_psb.swapped())],
+        };
+        assert_eq!(
+            split_route_files(&manifest, &[swb_psb]),
+            Some((vec![route_file(swb_psb)], vec![route_file(swb_psb.swapped())]))
+        );
+        // Pages that aren't for a route load all the schedules, as do routes without their own file
+        assert_eq!(split_route_files(&manifest, &[]), None);
+        assert_eq!(split_route_files(&manifest, &[TerminalPair { from: Terminal::TSA, to: Terminal::SWB }]), None);
+        manifest.partitions = vec![DataPartition {
+            file: "schedules-2.json".to_string(),
+            bytes: 0,
+            date_range: DateRange { from: date!(2022 - 07 - 01), to: date!(2022 - 07 - 31) },
+        }];
+        assert_eq!(split_route_files(&manifest, &[swb_psb]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_schedules() -> Result<()> {
+        let data = parse_schedules(&schedules_body()?)?;
+        assert_eq!(
+            data.schedules_map.get(&TerminalPair { from: Terminal::SWB, to: Terminal::PSB }).map(Vec::len),
+            Some(1)
+        );
+        assert_eq!(data.metadata.map(|m| m.generator_version), Some("0.1.0".to_string()));
+        let legacy_data = parse_schedules(&serde_json::to_string(&vec![schedule()])?)?;
+        assert_eq!(legacy_data.schedules_map.len(), 1);
+        assert!(legacy_data.metadata.is_none());
+        assert!(parse_schedules("").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_data_expired() -> Result<()> {
+        let schedules_map = into_vec_group_map([schedule()], |s| s.terminal_pair);
+        assert!(!is_data_expired(&schedules_map, date!(2022 - 06 - 30)));
+        assert!(is_data_expired(&schedules_map, date!(2022 - 07 - 01)));
+        // Nothing loaded is not the same as everything ended
+        assert!(!is_data_expired(&HashMap::new(), date!(2022 - 07 - 01)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_schedules() -> Result<()> {
+        let mut schedules_map = parse_schedules(&schedules_body()?)?.schedules_map;
+        let next_schedule =
+            Schedule { date_range: DateRange { from: date!(2022 - 07 - 01), to: date!(2022 - 07 - 31) }, ..schedule() };
+        let other_schedule =
+            Schedule { terminal_pair: TerminalPair { from: Terminal::SWB, to: Terminal::TSA }, ..schedule() };
+        let partition_map = into_vec_group_map([next_schedule, other_schedule], |s| s.terminal_pair);
+        merge_schedules(&mut schedules_map, partition_map.clone());
+        assert_eq!(schedules_map.len(), 2);
+        assert_eq!(schedules_map.get(&TerminalPair { from: Terminal::SWB, to: Terminal::PSB }).map(Vec::len), Some(2));
+        // Merging the same partition again adds nothing
+        merge_schedules(&mut schedules_map, partition_map);
+        assert_eq!(schedules_map.get(&TerminalPair { from: Terminal::SWB, to: Terminal::PSB }).map(Vec::len), Some(2));
+        Ok(())
+    }
+}
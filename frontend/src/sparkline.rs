@@ -0,0 +1,44 @@
+use crate::prelude::*;
+
+// Bar sizes in SVG user units; the SVG is scaled to its CSS size
+const BAR_WIDTH: usize = 3;
+const BAR_GAP: usize = 1;
+const MAX_BAR_HEIGHT: usize = 12;
+
+pub struct SparklineValue {
+    pub label: String,
+    pub value: usize,
+    // Whether a separator is drawn before this value, such as at the start of each week
+    pub starts_group: bool,
+}
+
+// Tiny bar chart with one bar per value, with the labels shown as tooltips
+pub fn sparkline_html(values: &[SparklineValue], class: &'static str) -> Html {
+    let max_value = values.iter().map(|v| v.value).max().unwrap_or(0).max(1);
+    let width = values.len() * (BAR_WIDTH + BAR_GAP);
+    let view_box = format!("0 0 {} {}", width, MAX_BAR_HEIGHT);
+    html! {
+        <svg class={ class } viewBox={ view_box } preserveAspectRatio="none" xmlns="http://www.w3.org/2000/svg">
+            { for values.iter().enumerate().map(|(index, value)| {
+                let x = index * (BAR_WIDTH + BAR_GAP);
+                // Always draw at least a sliver so days without sailings are visible
+                let height = max(1, value.value * MAX_BAR_HEIGHT / max_value);
+                html! { <>
+                    { if value.starts_group && index > 0 { html! {
+                        <rect x={ (x - BAR_GAP).to_string() } y="0" width="0.5" height={ MAX_BAR_HEIGHT.to_string() } class="sparkline-separator"/>
+                    }} else {
+                        html! {}
+                    }}
+                    <rect
+                        x={ x.to_string() }
+                        y={ (MAX_BAR_HEIGHT - height).to_string() }
+                        width={ BAR_WIDTH.to_string() }
+                        height={ height.to_string() }
+                        class={ if value.value == 0 { "sparkline-empty" } else { "" } }>
+                        <title>{ &value.label }</title>
+                    </rect>
+                </> }
+            })}
+        </svg>
+    }
+}
@@ -0,0 +1,220 @@
+use std::cell::RefCell;
+use wasm_bindgen::JsCast;
+
+use crate::prelude::*;
+
+// Stored blobs are wrapped with their version, so that data saved by older versions of the app can be migrated
+#[derive(Deserialize, Serialize)]
+struct VersionedBlob<T> {
+    version: u32,
+    data: T,
+}
+
+#[derive(Debug)]
+pub struct QuotaExceededError;
+
+pub trait Storage {
+    fn get_raw(&self, key: &str) -> Result<Option<String>>;
+    fn set_raw(&self, key: &str, value: &str) -> Result<()>;
+    fn remove(&self, key: &str) -> Result<()>;
+}
+
+pub trait Stored: Serialize + DeserializeOwned {
+    const KEY: &'static str;
+    const VERSION: u32;
+
+    // Convert data stored by an older version to the format of the next version
+    fn migrate(from_version: u32, _data: serde_json::Value) -> Result<serde_json::Value> {
+        bail!("No migration from version {} of {:?}", from_version, Self::KEY)
+    }
+}
+
+pub struct LocalStorage {
+    storage: web_sys::Storage,
+}
+
+// Used in tests, and as a fallback when the browser does not allow local storage (e.g. some private browsing modes)
+pub struct MemoryStorage {
+    map: RefCell<HashMap<String, String>>,
+    quota_bytes: Option<usize>,
+}
+
+impl fmt::Display for QuotaExceededError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Storage quota exceeded")
+    }
+}
+
+impl std::error::Error for QuotaExceededError {}
+
+pub fn is_quota_exceeded(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<QuotaExceededError>().is_some()
+}
+
+fn load_inner<T: Stored>(storage: &dyn Storage) -> Result<Option<T>> {
+    let raw = match storage.get_raw(T::KEY)? {
+        None => return Ok(None),
+        Some(raw) => raw,
+    };
+    let blob: VersionedBlob<serde_json::Value> = serde_json::from_str(&raw)?;
+    ensure!(blob.version <= T::VERSION, "Stored version {} is newer than supported", blob.version);
+    let mut data = blob.data;
+    for version in blob.version..T::VERSION {
+        data = T::migrate(version, data)?;
+    }
+    Ok(Some(serde_json::from_value(data)?))
+}
+
+pub fn load<T: Stored>(storage: &dyn Storage) -> Result<Option<T>> {
+    load_inner(storage).with_context(|| format!("Failed to load stored {:?}", T::KEY))
+}
+
+pub fn save<T: Stored>(storage: &dyn Storage, value: &T) -> Result<()> {
+    let raw = serde_json::to_string(&VersionedBlob { version: T::VERSION, data: value })
+        .expect("Expect stored value to serialize to JSON");
+    storage.set_raw(T::KEY, &raw).with_context(|| format!("Failed to save stored {:?}", T::KEY))
+}
+
+// Space the stored value takes, for showing how much storage a feature uses
+pub fn stored_bytes<T: Stored>(storage: &dyn Storage) -> usize {
+    storage.get_raw(T::KEY).ok().flatten().map(|raw| raw.len()).unwrap_or(0)
+}
+
+// Loading is best effort: unreadable data is discarded rather than breaking the app
+pub fn load_or_default<T: Stored + Default>(storage: &dyn Storage) -> T {
+    load(storage)
+        .unwrap_or_else(|err| {
+            warn!("{:?}", err);
+            None
+        })
+        .unwrap_or_default()
+}
+
+pub fn default_storage() -> Box<dyn Storage> {
+    match LocalStorage::new() {
+        Ok(local_storage) => Box::new(local_storage),
+        Err(err) => {
+            warn!("Using memory storage because local storage is unavailable: {:?}", err);
+            Box::new(MemoryStorage::new(None))
+        }
+    }
+}
+
+impl LocalStorage {
+    pub fn new() -> Result<LocalStorage> {
+        let storage = web_sys::window()
+            .ok_or_else(|| anyhow!("Missing window"))?
+            .local_storage()
+            .map_err(|err| anyhow!("Failed to get local storage: {:?}", err))?
+            .ok_or_else(|| anyhow!("Missing local storage"))?;
+        Ok(LocalStorage { storage })
+    }
+}
+
+impl Storage for LocalStorage {
+    fn get_raw(&self, key: &str) -> Result<Option<String>> {
+        self.storage.get_item(key).map_err(|err| anyhow!("Failed to get item {:?}: {:?}", key, err))
+    }
+
+    fn set_raw(&self, key: &str, value: &str) -> Result<()> {
+        self.storage.set_item(key, value).map_err(|err| {
+            match err.dyn_ref::<web_sys::DomException>().map(|e| e.name()) {
+                Some(name) if name == "QuotaExceededError" => anyhow::Error::new(QuotaExceededError),
+                _ => anyhow!("Failed to set item {:?}: {:?}", key, err),
+            }
+        })
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        self.storage.remove_item(key).map_err(|err| anyhow!("Failed to remove item {:?}: {:?}", key, err))
+    }
+}
+
+impl MemoryStorage {
+    pub fn new(quota_bytes: Option<usize>) -> MemoryStorage {
+        MemoryStorage { map: RefCell::new(HashMap::new()), quota_bytes }
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get_raw(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.map.borrow().get(key).cloned())
+    }
+
+    fn set_raw(&self, key: &str, value: &str) -> Result<()> {
+        let mut map = self.map.borrow_mut();
+        if let Some(quota_bytes) = self.quota_bytes {
+            let other_bytes: usize = map.iter().filter(|(k, _)| *k != key).map(|(k, v)| k.len() + v.len()).sum();
+            if other_bytes + key.len() + value.len() > quota_bytes {
+                return Err(anyhow::Error::new(QuotaExceededError));
+            }
+        }
+        map.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        self.map.borrow_mut().remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+    struct TestPreferences {
+        names: Vec<String>,
+    }
+
+    impl Stored for TestPreferences {
+        const KEY: &'static str = "test-preferences";
+        const VERSION: u32 = 2;
+
+        fn migrate(from_version: u32, data: serde_json::Value) -> Result<serde_json::Value> {
+            match from_version {
+                // Version 1 stored a single name
+                1 => Ok(serde_json::json!({ "names": [data] })),
+                _ => bail!("Unknown version: {}", from_version),
+            }
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() -> Result<()> {
+        let storage = MemoryStorage::new(None);
+        assert_eq!(load::<TestPreferences>(&storage)?, None);
+        let preferences = TestPreferences { names: vec!["a".to_string()] };
+        save(&storage, &preferences)?;
+        assert_eq!(load::<TestPreferences>(&storage)?, Some(preferences));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_migrates_old_version() -> Result<()> {
+        let storage = MemoryStorage::new(None);
+        storage.set_raw(TestPreferences::KEY, r#"{"version":1,"data":"a"}"#)?;
+        assert_eq!(load::<TestPreferences>(&storage)?, Some(TestPreferences { names: vec!["a".to_string()] }));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_rejects_newer_version_and_corrupt_data() -> Result<()> {
+        let storage = MemoryStorage::new(None);
+        storage.set_raw(TestPreferences::KEY, r#"{"version":3,"data":{"names":[]}}"#)?;
+        assert!(load::<TestPreferences>(&storage).is_err());
+        storage.set_raw(TestPreferences::KEY, "{")?;
+        assert!(load::<TestPreferences>(&storage).is_err());
+        assert_eq!(load_or_default::<TestPreferences>(&storage), TestPreferences::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_quota_exceeded() -> Result<()> {
+        let storage = MemoryStorage::new(Some(40));
+        let err = save(&storage, &TestPreferences { names: vec!["x".repeat(40)] }).unwrap_err();
+        assert!(is_quota_exceeded(&err));
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use serde_json::json;
+
+use crate::note_renderer::*;
+use crate::prelude::*;
+use crate::sailings_processor::*;
+use crate::utils::*;
+
+fn boat_terminal_json(terminal: Terminal) -> serde_json::Value {
+    json!({ "@type": "BoatTerminal", "name": terminal.name() })
+}
+
+// Schema.org description of the schedule's sailings on the date, for search engines and other tools
+pub fn schedule_json_ld(date: Date, schedule: &Schedule, sailings: &[SailingWithNotes]) -> serde_json::Value {
+    let trips: Vec<_> = sailings
+        .iter()
+        .map(|sailing| {
+            let (depart_datetime, arrive_datetime) =
+                sailing_datetime_attributes(sailing.depart_date(date), &sailing.sailing);
+            let mut trip = json!({
+                "@type": "BoatTrip",
+                "provider": { "@type": "Organization", "name": schedule.operator.name() },
+                "departureBoatTerminal": boat_terminal_json(schedule.terminal_pair.from),
+                "arrivalBoatTerminal": boat_terminal_json(schedule.terminal_pair.to),
+                "departureTime": depart_datetime,
+                "arrivalTime": arrive_datetime,
+            });
+            if !sailing.notes.is_empty() {
+                trip["description"] = sailing.notes.iter().map(|n| n.renderer().short_text(&n.text)).join("; ").into();
+            }
+            trip
+        })
+        .collect();
+    json!({
+        "@context": "https://schema.org",
+        "@type": "ItemList",
+        "name": format!("{} to {}", schedule.terminal_pair.from.name(), schedule.terminal_pair.to.name()),
+        "itemListElement": trips,
+    })
+}
+
+pub fn schedule_json_ld_html(date: Date, schedule: &Schedule, sailings: &[SailingWithNotes]) -> Html {
+    html! {
+        <script type="application/ld+json">{ schedule_json_ld(date, schedule, sailings).to_string() }</script>
+    }
+}
@@ -0,0 +1,105 @@
+use crate::prelude::*;
+use crate::storage::*;
+
+// Where the user usually starts their trip, for estimating when to leave for the terminal
+#[derive(Copy, Clone, Debug, Deserialize, EnumIter, Eq, PartialEq, Serialize)]
+pub enum HomeCity {
+    Vancouver,
+    Burnaby,
+    Richmond,
+    Surrey,
+    Delta,
+    Victoria,
+    Sidney,
+    Duncan,
+    Nanaimo,
+    SaltSpring,
+}
+
+// Typical drive times in minutes, without traffic.  Terminals are left out when there is no reasonable drive (e.g.
+// one that needs another ferry to reach).
+static DRIVE_MINUTES: &[(HomeCity, Terminal, i64)] = &[
+    (HomeCity::Vancouver, Terminal::TSA, 40),
+    (HomeCity::Burnaby, Terminal::TSA, 40),
+    (HomeCity::Richmond, Terminal::TSA, 25),
+    (HomeCity::Surrey, Terminal::TSA, 35),
+    (HomeCity::Delta, Terminal::TSA, 15),
+    (HomeCity::Victoria, Terminal::SWB, 35),
+    (HomeCity::Victoria, Terminal::BTW, 30),
+    (HomeCity::Victoria, Terminal::MIL, 45),
+    (HomeCity::Victoria, Terminal::CFT, 65),
+    (HomeCity::Victoria, Terminal::CHM, 70),
+    (HomeCity::Sidney, Terminal::SWB, 5),
+    (HomeCity::Sidney, Terminal::BTW, 15),
+    (HomeCity::Sidney, Terminal::MIL, 60),
+    (HomeCity::Sidney, Terminal::CFT, 80),
+    (HomeCity::Sidney, Terminal::CHM, 85),
+    (HomeCity::Duncan, Terminal::SWB, 60),
+    (HomeCity::Duncan, Terminal::MIL, 20),
+    (HomeCity::Duncan, Terminal::CFT, 15),
+    (HomeCity::Duncan, Terminal::CHM, 15),
+    (HomeCity::Nanaimo, Terminal::SWB, 100),
+    (HomeCity::Nanaimo, Terminal::MIL, 60),
+    (HomeCity::Nanaimo, Terminal::CFT, 45),
+    (HomeCity::Nanaimo, Terminal::CHM, 35),
+    (HomeCity::SaltSpring, Terminal::FUL, 20),
+    (HomeCity::SaltSpring, Terminal::PLH, 10),
+    (HomeCity::SaltSpring, Terminal::VES, 10),
+];
+
+// Not set until the user picks a city, since most visitors are only looking up a sailing
+#[derive(Default, Deserialize, Serialize)]
+pub struct HomeCityPreference {
+    pub city: Option<HomeCity>,
+}
+
+impl Stored for HomeCityPreference {
+    const KEY: &'static str = "home-city-preference";
+    const VERSION: u32 = 1;
+}
+
+impl HomeCity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HomeCity::Vancouver => "Vancouver",
+            HomeCity::Burnaby => "Burnaby",
+            HomeCity::Richmond => "Richmond",
+            HomeCity::Surrey => "Surrey",
+            HomeCity::Delta => "Delta",
+            HomeCity::Victoria => "Victoria",
+            HomeCity::Sidney => "Sidney",
+            HomeCity::Duncan => "Duncan",
+            HomeCity::Nanaimo => "Nanaimo",
+            HomeCity::SaltSpring => "Salt Spring Island (Ganges)",
+        }
+    }
+
+    pub fn drive_time(&self, terminal: Terminal) -> Option<Duration> {
+        DRIVE_MINUTES
+            .iter()
+            .find(|(city, t, _)| city == self && *t == terminal)
+            .map(|(_, _, minutes)| Duration::minutes(*minutes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_home_city_drive_time() -> Result<()> {
+        assert_eq!(HomeCity::Victoria.drive_time(Terminal::SWB), Some(Duration::minutes(35)));
+        // There is no road from Vancouver to Swartz Bay
+        assert_eq!(HomeCity::Vancouver.drive_time(Terminal::SWB), None);
+        for (city, terminal, _) in DRIVE_MINUTES {
+            assert_eq!(
+                DRIVE_MINUTES.iter().filter(|(c, t, _)| c == city && t == terminal).count(),
+                1,
+                "Expect one drive time from {:?} to {:?}",
+                city,
+                terminal
+            );
+        }
+        Ok(())
+    }
+}
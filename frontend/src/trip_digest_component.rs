@@ -0,0 +1,138 @@
+use crate::checklist::*;
+use crate::date_format::*;
+use crate::prelude::*;
+use crate::saved_trips::*;
+use crate::storage::*;
+use crate::travel_time::*;
+use crate::types::*;
+use crate::utils::*;
+
+#[derive(PartialEq, Properties)]
+pub struct SaveTripButtonProps {
+    pub trip: SavedTrip,
+}
+
+#[function_component(SaveTripButton)]
+pub fn save_trip_button_component(props: &SaveTripButtonProps) -> Html {
+    let trip = props.trip;
+    let is_saved_state = use_state(|| load_or_default::<SavedTrips>(&*default_storage()).contains(&trip));
+    let onclick = {
+        let is_saved_state = is_saved_state.clone();
+        Callback::from(move |_| {
+            let saved_trips = load_or_default::<SavedTrips>(&*default_storage()).toggled(trip, today_vancouver());
+            save(&*default_storage(), &saved_trips).unwrap_or_else(|err| warn!("{:?}", err));
+            is_saved_state.set(saved_trips.contains(&trip));
+        })
+    };
+    let (icon, title) = if *is_saved_state {
+        ("bi-bookmark-check-fill", "Remove from saved trips")
+    } else {
+        ("bi-bookmark", "Save trip for a reminder the evening before")
+    };
+    html! {
+        <button type="button" class="btn btn-link btn-sm p-0 ms-1 d-print-none link-secondary" {title} {onclick}>
+            <i class={ classes!("bi", icon) }/>
+        </button>
+    }
+}
+
+// Offered in the evening when there are saved trips tomorrow, since the site can't send reminders by itself
+pub fn trip_digest_banner_html() -> Html {
+    let now = now_vancouver();
+    let tomorrow = match now.date().next_day() {
+        Some(tomorrow) => tomorrow,
+        None => return html! {},
+    };
+    let trips_count = load_or_default::<SavedTrips>(&*default_storage()).on_date(tomorrow).len();
+    if now.time() < TRIP_DIGEST_FROM_TIME || trips_count == 0 {
+        return html! {};
+    }
+    html! {
+        <div class="alert alert-info d-print-none" role="status">
+            <i class="bi bi-bookmark-check"/>
+            { format!(" You have {} saved {} tomorrow. ", trips_count, if trips_count == 1 { "trip" } else { "trips" }) }
+            <Link<Route> to={ Route::TripDigest }>{ "Check on them before you go" }</Link<Route>>
+        </div>
+    }
+}
+
+fn trip_digest_entry_html(entry: &TripDigestEntry, home_city: Option<HomeCity>) -> Html {
+    let TripDigestEntry { trip, sailing, alerts } = entry;
+    let area_pair = trip.terminal_pair.area_pair();
+    html! {
+        <div class="card mb-3">
+            <div class="card-body">
+                <h5 class="card-title">
+                    { format_time(trip.depart_time) }
+                    { format!(" {} to {}", trip.terminal_pair.from.name(), trip.terminal_pair.to.name()) }
+                </h5>
+                { match sailing {
+                    Some(sailing) => html! { <>
+                        <div>{ format!("Arrives {}", format_time(sailing.sailing.arrive_time)) }</div>
+                        { for home_city
+                            .and_then(|city| leave_by_suggestion(trip.terminal_pair.from, &sailing.sailing, city))
+                            .map(|suggestion| html! { <div>{ suggestion }</div> }) }
+                        { if sailing.notes.is_empty() { html! {} } else { html! {
+                            <ul class="small text-muted mb-0">
+                                { for sailing.notes.iter().map(|note| html! { <li>{ &note.text }</li> }) }
+                            </ul>
+                        }}}
+                    </> },
+                    None => html! {
+                        <div class="text-danger">
+                            <i class="bi bi-exclamation-triangle"/>
+                            { " This sailing is no longer scheduled. Check the schedule for other sailings." }
+                        </div>
+                    },
+                }}
+                { for alerts.iter().map(|alert| html! {
+                    <div class="alert alert-warning small mt-2 mb-0">{ &alert.message }</div>
+                }) }
+                <a class="card-link d-block mt-2" href={ sailings_page_url(area_pair, trip.date) }>
+                    { "See all sailings that day" }
+                </a>
+            </div>
+        </div>
+    }
+}
+
+#[function_component(TripDigest)]
+pub fn trip_digest_component() -> Html {
+    let schedules_state = use_context::<SchedulesState>().expect("Expect schedules state to be available");
+    let tomorrow = today_vancouver().next_day().expect("Expect today to have a next day");
+    let saved_trips: SavedTrips = load_or_default(&*default_storage());
+    let home_city = load_or_default::<HomeCityPreference>(&*default_storage()).city;
+    html! { <>
+        <h1 class="display-6 mb-3 small">
+            { "Tomorrow's trips" }
+            <small class="text-muted">{ format!(" {}", DATE_FORMATTER.long_date(tomorrow)) }</small>
+        </h1>
+        { match &schedules_state {
+            SchedulesState::Init | SchedulesState::Loading => html! {
+                <div class="alert alert-light border text-center">
+                    <div class="spinner-border" role="status"/>
+                    <div>{ "Loading schedules..." }</div>
+                </div>
+            },
+            SchedulesState::Failed => html! {
+                <div class="alert alert-danger text-center" role="alert">
+                    { "There was a problem loading the ferry schedules; please refresh your browser to try again." }
+                </div>
+            },
+            SchedulesState::Loaded(schedules_map) => {
+                let entries = trip_digest(&saved_trips, tomorrow, schedules_map);
+                if entries.is_empty() {
+                    html! {
+                        <p>
+                            { "You have no saved trips tomorrow. Save a sailing with its " }
+                            <i class="bi bi-bookmark"/>
+                            { " button to see it here the evening before." }
+                        </p>
+                    }
+                } else {
+                    html! { for entries.iter().map(|entry| trip_digest_entry_html(entry, home_city)) }
+                }
+            }
+        }}
+    </> }
+}
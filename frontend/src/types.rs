@@ -1,4 +1,5 @@
-use crate::imports::*;
+use crate::prelude::*;
+use crate::schedules_loader::*;
 
 #[derive(Clone, Eq, Routable, PartialEq)]
 pub enum Route {
@@ -6,6 +7,22 @@ pub enum Route {
     Home,
     #[at("/sailings")]
     Sailings,
+    #[at("/day-pack")]
+    DayPack,
+    #[at("/journey")]
+    JourneyPlanner,
+    #[at("/departures/:terminal")]
+    Departures { terminal: Terminal },
+    #[at("/about-data")]
+    AboutData,
+    #[at("/trips/tomorrow")]
+    TripDigest,
+    #[at("/go")]
+    Go,
+    #[at("/help")]
+    HelpIndex,
+    #[at("/help/:slug")]
+    Help { slug: String },
     #[not_found]
     #[at("/404")]
     NotFound,
@@ -18,6 +35,35 @@ pub struct SailingsQuery {
     pub date: Option<Date>,
 }
 
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DayPackQuery {
+    pub date: Option<Date>,
+    // Last date of a multi-day range
+    pub to: Option<Date>,
+}
+
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+pub struct JourneyQuery {
+    pub from: Option<Terminal>,
+    pub to: Option<Terminal>,
+    pub date: Option<Date>,
+}
+
+// Shortcut link parameters, which are resolved against preferences and schedules rather than parsed as they are (see
+// `resolve_intent`)
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GoQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub when: Option<String>,
+}
+
+// Compact encoding of a whole sailings view, which takes the place of the `SailingsQuery` parameters (see `ViewState`)
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ViewQuery {
+    pub view: Option<String>,
+}
+
 impl SailingsQuery {
     pub fn new() -> SailingsQuery {
         SailingsQuery { from: None, to: None, date: None }
@@ -36,15 +82,248 @@ pub enum SchedulesState {
     Failed,
 }
 
+// Metadata embedded in the loaded schedules data, provided separately so pages that only need schedules are unaffected
+#[derive(Clone, Default, PartialEq)]
+pub struct DataMetadataContext(pub Option<Rc<DataMetadata>>);
+
+// Schedules partitions that failed to load and are being retried in the background, so pages for their dates can say
+// that sailings may be missing
+#[derive(Clone, Default, PartialEq)]
+pub struct MissingPartitionsContext(pub Rc<Vec<DataPartition>>);
+
+pub enum MissingPartitionsEvent {
+    // The partitions that failed to load with the latest schedules
+    Loaded(Vec<DataPartition>),
+    // A partition that loaded when it was retried
+    Retried(DataPartition),
+}
+
+impl Reducible for MissingPartitionsContext {
+    type Action = MissingPartitionsEvent;
+
+    fn reduce(self: Rc<Self>, event: MissingPartitionsEvent) -> Rc<Self> {
+        match event {
+            MissingPartitionsEvent::Loaded(partitions) => Rc::new(MissingPartitionsContext(Rc::new(partitions))),
+            MissingPartitionsEvent::Retried(partition) => {
+                let partitions = self.0.iter().filter(|p| **p != partition).cloned().collect();
+                Rc::new(MissingPartitionsContext(Rc::new(partitions)))
+            }
+        }
+    }
+}
+
+// Fetches the schedules again in the background, for pages showing sailings that can change during the day
+#[derive(Clone, PartialEq)]
+pub struct SchedulesRevalidator(pub Callback<()>);
+
+#[derive(Clone)]
+pub enum SchedulesEvent {
+    Load,
+    LoadSucceeded(Rc<HashMap<TerminalPair, Vec<Schedule>>>),
+    LoadFailed,
+    // Fresher schedules fetched in the background after they had loaded
+    Revalidated(Rc<HashMap<TerminalPair, Vec<Schedule>>>),
+    // Schedules of partitions that failed to load at first, merged onto whichever schedules are loaded by then
+    PartitionsLoaded(Rc<HashMap<TerminalPair, Vec<Schedule>>>),
+    // Every route's schedules, after only the viewed routes' had loaded.  They are only taken if the viewed routes'
+    // schedules are still the loaded ones, since revalidation loads every route anyway.
+    RoutesLoaded {
+        viewed: Rc<HashMap<TerminalPair, Vec<Schedule>>>,
+        schedules: Rc<HashMap<TerminalPair, Vec<Schedule>>>,
+    },
+}
+
+impl SchedulesState {
+    // Returns `None` if the event is not valid in the current state
+    pub fn transition(&self, event: SchedulesEvent) -> Option<SchedulesState> {
+        match (self, event) {
+            (SchedulesState::Init, SchedulesEvent::Load) | (SchedulesState::Failed, SchedulesEvent::Load) => {
+                Some(SchedulesState::Loading)
+            }
+            (SchedulesState::Loading, SchedulesEvent::LoadSucceeded(schedules)) => {
+                Some(SchedulesState::Loaded(schedules))
+            }
+            (SchedulesState::Loading, SchedulesEvent::LoadFailed) => Some(SchedulesState::Failed),
+            (SchedulesState::Loaded(_), SchedulesEvent::Revalidated(schedules)) => {
+                Some(SchedulesState::Loaded(schedules))
+            }
+            (SchedulesState::Loaded(schedules), SchedulesEvent::PartitionsLoaded(partition_schedules)) => {
+                let mut merged = (**schedules).clone();
+                merge_schedules(&mut merged, (*partition_schedules).clone());
+                Some(SchedulesState::Loaded(Rc::new(merged)))
+            }
+            (SchedulesState::Loaded(loaded), SchedulesEvent::RoutesLoaded { viewed, schedules })
+                if Rc::ptr_eq(loaded, &viewed) =>
+            {
+                Some(SchedulesState::Loaded(schedules))
+            }
+            _ => None,
+        }
+    }
+}
+
+// Background loads dispatch their results rather than setting the state they started with, so that each applies to the
+// state as it is by the time it finishes
+impl Reducible for SchedulesState {
+    type Action = SchedulesEvent;
+
+    // Events that aren't valid in the current state are ignored
+    fn reduce(self: Rc<Self>, event: SchedulesEvent) -> Rc<Self> {
+        self.transition(event).map(Rc::new).unwrap_or(self)
+    }
+}
+
 impl PartialEq for SchedulesState {
     fn eq(&self, other: &SchedulesState) -> bool {
-        // For efficiency, we don't compare the contents because schedules are only loaded once
-        matches!(
-            (self, other),
-            (SchedulesState::Init, SchedulesState::Init)
-                | (SchedulesState::Loading, SchedulesState::Loading)
-                | (SchedulesState::Loaded(_), SchedulesState::Loaded(_))
-                | (SchedulesState::Failed, SchedulesState::Failed)
-        )
+        // For efficiency, we don't compare the contents, since schedules are only replaced by loading them again
+        match (self, other) {
+            (SchedulesState::Loaded(a), SchedulesState::Loaded(b)) => Rc::ptr_eq(a, b),
+            _ => matches!(
+                (self, other),
+                (SchedulesState::Init, SchedulesState::Init)
+                    | (SchedulesState::Loading, SchedulesState::Loading)
+                    | (SchedulesState::Failed, SchedulesState::Failed)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_states() -> Vec<SchedulesState> {
+        vec![
+            SchedulesState::Init,
+            SchedulesState::Loading,
+            SchedulesState::Loaded(Rc::new(HashMap::new())),
+            SchedulesState::Failed,
+        ]
+    }
+
+    fn all_events() -> Vec<SchedulesEvent> {
+        vec![
+            SchedulesEvent::Load,
+            SchedulesEvent::LoadSucceeded(Rc::new(HashMap::new())),
+            SchedulesEvent::LoadFailed,
+            SchedulesEvent::Revalidated(Rc::new(HashMap::new())),
+            SchedulesEvent::PartitionsLoaded(Rc::new(HashMap::new())),
+            SchedulesEvent::RoutesLoaded { viewed: Rc::new(HashMap::new()), schedules: Rc::new(HashMap::new()) },
+        ]
+    }
+
+    fn schedule(date_range: DateRange) -> Schedule {
+        Schedule {
+            date_range,
+            source_url: "https://www.bcferries.com/".to_string(),
+            refreshed_at: date!(2022 - 06 - 01).midnight().assume_utc(),
+            ..Schedule::test_fixture(TerminalPair { from: Terminal::SWB, to: Terminal::PSB })
+        }
+    }
+
+    fn loaded_date_ranges(state: &SchedulesState) -> Vec<DateRange> {
+        match state {
+            SchedulesState::Loaded(schedules_map) => schedules_map
+                .values()
+                .flatten()
+                .map(|schedule| schedule.date_range)
+                .sorted_by_key(|date_range| date_range.from)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_schedules_state_transitions() -> Result<()> {
+        for state in all_states() {
+            for event in all_events() {
+                let expected = match (&state, &event) {
+                    (SchedulesState::Init, SchedulesEvent::Load) => Some(SchedulesState::Loading),
+                    (SchedulesState::Failed, SchedulesEvent::Load) => Some(SchedulesState::Loading),
+                    (SchedulesState::Loading, SchedulesEvent::LoadSucceeded(schedules)) => {
+                        Some(SchedulesState::Loaded(schedules.clone()))
+                    }
+                    (SchedulesState::Loaded(_), SchedulesEvent::Revalidated(schedules)) => {
+                        Some(SchedulesState::Loaded(schedules.clone()))
+                    }
+                    (SchedulesState::Loading, SchedulesEvent::LoadFailed) => Some(SchedulesState::Failed),
+                    // Merged into new schedules, so they are only checked for having loaded
+                    (SchedulesState::Loaded(_), SchedulesEvent::PartitionsLoaded(_)) => {
+                        assert!(matches!(state.transition(event), Some(SchedulesState::Loaded(_))));
+                        continue;
+                    }
+                    _ => None,
+                };
+                assert!(state.transition(event) == expected);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedules_state_load_sequence() -> Result<()> {
+        let loading = SchedulesState::Init.transition(SchedulesEvent::Load).expect("Expect Init to start loading");
+        let failed = loading.transition(SchedulesEvent::LoadFailed).expect("Expect Loading to fail");
+        let reloading = failed.transition(SchedulesEvent::Load).expect("Expect Failed to retry loading");
+        let schedules = Rc::new(HashMap::new());
+        let loaded =
+            reloading.transition(SchedulesEvent::LoadSucceeded(schedules.clone())).expect("Expect Loading to succeed");
+        assert!(loaded == SchedulesState::Loaded(schedules.clone()));
+        assert!(loaded.transition(SchedulesEvent::Load).is_none());
+        // Revalidated schedules replace the loaded ones, and count as a change so the page is rendered again
+        let revalidated_schedules = Rc::new(HashMap::new());
+        let revalidated = loaded
+            .transition(SchedulesEvent::Revalidated(revalidated_schedules.clone()))
+            .expect("Expect Loaded to revalidate");
+        assert!(revalidated == SchedulesState::Loaded(revalidated_schedules));
+        assert!(revalidated != loaded);
+        Ok(())
+    }
+    #[test]
+    fn test_schedules_state_partition_retry() -> Result<()> {
+        let (june, july) = (
+            DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 30) },
+            DateRange { from: date!(2022 - 07 - 01), to: date!(2022 - 07 - 31) },
+        );
+        let schedules_map = |date_ranges: &[DateRange]| {
+            Rc::new(into_vec_group_map(date_ranges.iter().copied().map(schedule), |s| s.terminal_pair))
+        };
+        let loading = Rc::new(SchedulesState::Loading);
+        // The July partition fails to load
+        let loaded = loading.reduce(SchedulesEvent::LoadSucceeded(schedules_map(&[june])));
+        let missing = Rc::new(MissingPartitionsContext::default());
+        let july_partition =
+            DataPartition { file: "schedules-2022-07.json".to_string(), bytes: 1000, date_range: july };
+        let missing = missing.reduce(MissingPartitionsEvent::Loaded(vec![july_partition.clone()]));
+        // Schedules are revalidated while the partition is being retried, and it fails to load again
+        let revalidated = loaded.reduce(SchedulesEvent::Revalidated(schedules_map(&[june])));
+        // The retried partition is merged onto the revalidated schedules, not the ones loaded at first
+        let retried = revalidated.clone().reduce(SchedulesEvent::PartitionsLoaded(schedules_map(&[july])));
+        assert_eq!(loaded_date_ranges(&retried), vec![june, july]);
+        assert!(*retried != *revalidated);
+        let missing = missing.reduce(MissingPartitionsEvent::Retried(july_partition));
+        assert!(missing.0.is_empty());
+        // A partition that revalidated schedules already have isn't added again
+        let revalidated = retried.reduce(SchedulesEvent::Revalidated(schedules_map(&[june, july])));
+        let retried = revalidated.reduce(SchedulesEvent::PartitionsLoaded(schedules_map(&[july])));
+        assert_eq!(loaded_date_ranges(&retried), vec![june, july]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedules_state_routes_loaded() -> Result<()> {
+        let june = DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 30) };
+        let viewed = Rc::new(into_vec_group_map([schedule(june)], |s| s.terminal_pair));
+        let other_route =
+            Schedule { terminal_pair: TerminalPair { from: Terminal::SWB, to: Terminal::TSA }, ..schedule(june) };
+        let all_routes = Rc::new(into_vec_group_map([schedule(june), other_route], |s| s.terminal_pair));
+        let loaded = Rc::new(SchedulesState::Loading).reduce(SchedulesEvent::LoadSucceeded(viewed.clone()));
+        let routes_loaded = SchedulesEvent::RoutesLoaded { viewed: viewed.clone(), schedules: all_routes.clone() };
+        assert!(*loaded.clone().reduce(routes_loaded.clone()) == SchedulesState::Loaded(all_routes));
+        // Revalidated schedules already have every route, and are newer
+        let revalidated_schedules = Rc::new(into_vec_group_map([schedule(june)], |s| s.terminal_pair));
+        let revalidated = loaded.reduce(SchedulesEvent::Revalidated(revalidated_schedules.clone()));
+        assert!(*revalidated.reduce(routes_loaded) == SchedulesState::Loaded(revalidated_schedules));
+        Ok(())
     }
 }
@@ -1,4 +1,6 @@
-use crate::imports::*;
+use wasm_bindgen::JsValue;
+
+use crate::prelude::*;
 use crate::types::*;
 
 pub fn format_time(time: Time) -> String {
@@ -6,6 +8,47 @@ pub fn format_time(time: Time) -> String {
         .expect("Expect friendly time to format")
 }
 
+pub fn format_duration(duration: Duration) -> String {
+    match (duration.whole_hours(), duration.whole_minutes() % 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h {}m", hours, minutes),
+    }
+}
+
+// Sailings of different routes between the same areas can depart at the same time, so the route is part of the ID
+pub fn sailing_anchor_id(terminal_pair: TerminalPair, depart_time: Time) -> String {
+    format!(
+        "sailing-{}-{}",
+        terminal_pair,
+        depart_time.format(format_description!("[hour][minute]")).expect("Expect sailing anchor time to format")
+    )
+}
+
+// Sets the fragment of the current location without adding to the history or navigating, so that the page can be
+// scrolled to the element once it has rendered
+pub fn replace_location_hash(id: &str) {
+    let result = web_sys::window()
+        .ok_or_else(|| JsValue::from_str("No window"))
+        .and_then(|w| w.history())
+        .and_then(|h| h.replace_state_with_url(&JsValue::NULL, "", Some(&format!("#{}", id))));
+    result.unwrap_or_else(|err| warn!("Failed to set location hash: {:?}", err));
+}
+
+// The browser only scrolls to the fragment on page load, before the page's content has been rendered
+pub fn scroll_to_location_hash() -> Option<()> {
+    let window = web_sys::window()?;
+    let hash = window.location().hash().ok()?;
+    let id = hash.strip_prefix('#').filter(|id| !id.is_empty())?;
+    window.document()?.get_element_by_id(id)?.scroll_into_view();
+    Some(())
+}
+
+pub fn sailings_page_url(area_pair: AreaPair, date: Date) -> String {
+    let origin = web_sys::window().and_then(|w| w.location().origin().ok()).unwrap_or_default();
+    format!("{}/sailings?from={}&to={}&date={}", origin, area_pair.from, area_pair.to, format_iso8601_date(date))
+}
+
 pub fn human_time(time: OffsetDateTime) -> HumanTime {
     // Using duration because default way of getting system time doesn't work on browser WASM
     HumanTime::from_seconds((time - now_vancouver()).whole_seconds())
@@ -16,3 +59,24 @@ pub fn area_link_html(area: Area, query: SailingsQuery) -> Html {
         <Link<Route, SailingsQuery> to={Route::Sailings} {query}>{ area.long_name() }</Link<Route, SailingsQuery>>
     }
 }
+
+// Local departure and arrival date-times of the sailing, formatted for `<time datetime>` attributes and structured
+// data; sailings that arrive after midnight arrive on the following day
+pub fn sailing_datetime_attributes(date: Date, sailing: &Sailing) -> (String, String) {
+    let format = format_description!("[year]-[month]-[day]T[hour]:[minute]");
+    let arrive_date = if sailing.arrive_time < sailing.depart_time { date.next_day().unwrap_or(date) } else { date };
+    (
+        date.with_time(sailing.depart_time).format(format).expect("Expect depart date-time to format"),
+        arrive_date.with_time(sailing.arrive_time).format(format).expect("Expect arrive date-time to format"),
+    )
+}
+
+// Replaces the page's description, which link previews and search results show, and returns the one it replaced
+pub fn replace_meta_description(content: &str) -> Option<String> {
+    let element = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.query_selector("meta[name=description]").ok().flatten())?;
+    let previous = element.get_attribute("content");
+    element.set_attribute("content", content).unwrap_or_else(|err| warn!("Failed to set description: {:?}", err));
+    previous
+}
@@ -0,0 +1,140 @@
+use crate::prelude::*;
+use crate::types::*;
+
+// A sailings view packed into the single `view=` query parameter, so that views with several modes stay shareable
+// without a long query string.  The value is base64url of:
+//
+// - Version byte, so that old links can still be read if the layout changes
+// - Flags byte: the modes, and whether a date follows
+// - Index of each area in `Area`'s order plus one, or zero if it is not chosen
+// - Date as two big-endian bytes counting days since `DATE_EPOCH`, if the flag is set
+//
+// Areas are stored by position, so new areas must be added at the end of `Area` or the version bumped.  Links with the
+// separate `from`, `to`, and `date` parameters keep working.
+const VIEW_STATE_VERSION: u8 = 1;
+const DATE_EPOCH: Date = date!(2020 - 01 - 01);
+
+const INCLUDE_SUPPLEMENTAL_FLAG: u8 = 1 << 0;
+const BY_SERVICE_DAY_FLAG: u8 = 1 << 1;
+const BOTH_DIRECTIONS_FLAG: u8 = 1 << 2;
+const HAS_DATE_FLAG: u8 = 1 << 3;
+
+// Ways of showing sailings that are otherwise remembered per browser or reset on each visit
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ViewModes {
+    pub include_supplemental: bool,
+    pub by_service_day: bool,
+    pub both_directions: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ViewState {
+    pub from: Option<Area>,
+    pub to: Option<Area>,
+    pub date: Option<Date>,
+    pub modes: ViewModes,
+}
+
+fn encode_area(area: Option<Area>) -> u8 {
+    area.and_then(|area| Area::iter().position(|a| a == area)).map(|index| index as u8 + 1).unwrap_or(0)
+}
+
+fn decode_area(byte: u8) -> Result<Option<Area>> {
+    match byte {
+        0 => Ok(None),
+        _ => Area::iter().nth(byte as usize - 1).map(Some).ok_or_else(|| anyhow!("Unknown area in view: {}", byte)),
+    }
+}
+
+impl ViewState {
+    pub fn encode(&self) -> String {
+        // Dates that do not fit are left out, which only loses them from links far in the future
+        let date_days = self.date.and_then(|date| u16::try_from((date - DATE_EPOCH).whole_days()).ok());
+        let flags = [
+            (self.modes.include_supplemental, INCLUDE_SUPPLEMENTAL_FLAG),
+            (self.modes.by_service_day, BY_SERVICE_DAY_FLAG),
+            (self.modes.both_directions, BOTH_DIRECTIONS_FLAG),
+            (date_days.is_some(), HAS_DATE_FLAG),
+        ]
+        .iter()
+        .filter(|(is_set, _)| *is_set)
+        .fold(0, |flags, (_, flag)| flags | flag);
+        let mut bytes = vec![VIEW_STATE_VERSION, flags, encode_area(self.from), encode_area(self.to)];
+        bytes.extend(date_days.map(u16::to_be_bytes).into_iter().flatten());
+        base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+    }
+
+    pub fn decode(text: &str) -> Result<ViewState> {
+        let inner = || {
+            let bytes = base64::decode_config(text, base64::URL_SAFE_NO_PAD)?;
+            let (version, flags, from, to, rest) = match bytes.as_slice() {
+                [version, flags, from, to, rest @ ..] => (*version, *flags, *from, *to, rest),
+                _ => bail!("View is too short"),
+            };
+            ensure!(version == VIEW_STATE_VERSION, "Unsupported view version: {}", version);
+            let date = match (flags & HAS_DATE_FLAG != 0, rest) {
+                (false, []) => None,
+                (true, &[high, low]) => Some(DATE_EPOCH + Duration::days(u16::from_be_bytes([high, low]).into())),
+                _ => bail!("View date does not match its flag"),
+            };
+            Ok(ViewState {
+                from: decode_area(from)?,
+                to: decode_area(to)?,
+                date,
+                modes: ViewModes {
+                    include_supplemental: flags & INCLUDE_SUPPLEMENTAL_FLAG != 0,
+                    by_service_day: flags & BY_SERVICE_DAY_FLAG != 0,
+                    both_directions: flags & BOTH_DIRECTIONS_FLAG != 0,
+                },
+            }) as Result<_>
+        };
+        inner().with_context(|| format!("Failed to decode view: {:?}", text))
+    }
+
+    pub fn sailings_query(&self) -> SailingsQuery {
+        SailingsQuery { from: self.from, to: self.to, date: self.date }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_state_round_trip() -> Result<()> {
+        let modes = [
+            ViewModes::default(),
+            ViewModes { include_supplemental: true, by_service_day: false, both_directions: true },
+            ViewModes { include_supplemental: false, by_service_day: true, both_directions: false },
+        ];
+        let areas = [None, Some(Area::Brentwood), Some(Area::Victoria)];
+        let dates = [None, Some(DATE_EPOCH), Some(date!(2022 - 06 - 15))];
+        for ((&modes, &from), (&to, &date)) in
+            modes.iter().cartesian_product(&areas).cartesian_product(areas.iter().cartesian_product(&dates))
+        {
+            let view_state = ViewState { from, to, date, modes };
+            assert_eq!(ViewState::decode(&view_state.encode())?, view_state);
+        }
+        for area in Area::iter() {
+            let view_state = ViewState { from: Some(area), to: None, date: None, modes: ViewModes::default() };
+            assert_eq!(ViewState::decode(&view_state.encode())?, view_state);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_view_state_encoding() -> Result<()> {
+        let view_state = ViewState {
+            from: Some(Area::Victoria),
+            to: Some(Area::Vancouver),
+            date: Some(date!(2022 - 06 - 15)),
+            modes: ViewModes { include_supplemental: false, by_service_day: true, both_directions: true },
+        };
+        // Stays short enough to share, and stable since old links must keep working
+        assert_eq!(view_state.encode(), "AQ4NDAOA");
+        assert!(ViewState::decode("AQ4NDA").is_err());
+        assert!(ViewState::decode("Ag4NDAOA").is_err());
+        assert!(ViewState::decode("not base64!").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use js_sys::{Function, Promise, Reflect};
+use std::cell::RefCell;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::VisibilityState;
+
+use crate::prelude::*;
+
+type SentinelRef = Rc<RefCell<Option<JsValue>>>;
+
+// The Screen Wake Lock API is not in web-sys's stable bindings, so it is called through reflection.  Browsers without
+// it just let the screen sleep as usual.
+fn request_screen_wake_lock(sentinel: SentinelRef) {
+    let request = || {
+        let navigator = web_sys::window()?.navigator();
+        let wake_lock = Reflect::get(&navigator, &JsValue::from_str("wakeLock")).ok().filter(|w| w.is_object())?;
+        let request_fn = Reflect::get(&wake_lock, &JsValue::from_str("request")).ok()?.dyn_into::<Function>().ok()?;
+        request_fn.call1(&wake_lock, &JsValue::from_str("screen")).ok()?.dyn_into::<Promise>().ok()
+    };
+    if let Some(promise) = request() {
+        wasm_bindgen_futures::spawn_local(async move {
+            match JsFuture::from(promise).await {
+                Ok(new_sentinel) => *sentinel.borrow_mut() = Some(new_sentinel),
+                Err(err) => warn!("Failed to keep the screen awake: {:?}", err),
+            }
+        });
+    }
+}
+
+fn release_screen_wake_lock(sentinel: &SentinelRef) {
+    if let Some(sentinel) = sentinel.borrow_mut().take() {
+        let release =
+            Reflect::get(&sentinel, &JsValue::from_str("release")).ok().and_then(|r| r.dyn_into::<Function>().ok());
+        if let Some(release) = release {
+            release.call0(&sentinel).map(drop).unwrap_or_else(|err| warn!("Failed to release wake lock: {:?}", err));
+        }
+    }
+}
+
+// Keeps the screen on while `is_active`.  The browser drops the lock whenever the page is hidden, so it is requested
+// again when the page becomes visible.
+pub fn use_screen_wake_lock(is_active: bool) {
+    use_effect_with_deps(
+        move |&is_active| {
+            let sentinel: SentinelRef = Rc::new(RefCell::new(None));
+            let document = web_sys::window().and_then(|w| w.document()).filter(|_| is_active);
+            let listener = {
+                let (sentinel, document) = (sentinel.clone(), document.clone());
+                Closure::<dyn Fn()>::wrap(Box::new(move || {
+                    if document.as_ref().map(|d| d.visibility_state() == VisibilityState::Visible).unwrap_or(false) {
+                        request_screen_wake_lock(sentinel.clone());
+                    }
+                }))
+            };
+            if let Some(document) = &document {
+                request_screen_wake_lock(sentinel.clone());
+                document
+                    .add_event_listener_with_callback("visibilitychange", listener.as_ref().unchecked_ref())
+                    .unwrap_or_else(|err| error!("Failed to listen for visibility changes: {:?}", err));
+            }
+            move || {
+                if let Some(document) = &document {
+                    document
+                        .remove_event_listener_with_callback("visibilitychange", listener.as_ref().unchecked_ref())
+                        .unwrap_or_else(|err| error!("Failed to stop listening for visibility changes: {:?}", err));
+                }
+                release_screen_wake_lock(&sentinel);
+            }
+        },
+        is_active,
+    );
+}
@@ -0,0 +1,97 @@
+// Benchmarks of parsing the recorded schedule pages in `fixtures`, and the Southern Gulf Islands grid pages.  The
+// scraper is only a binary, so the modules the parser needs are included directly.
+#![allow(dead_code, unused_imports, unused_macros)]
+
+#[path = "../src/annotations.rs"]
+mod annotations;
+#[path = "../src/cache.rs"]
+mod cache;
+#[path = "../src/compression.rs"]
+mod compression;
+#[path = "../src/constants.rs"]
+mod constants;
+#[path = "../src/date_list.rs"]
+mod date_list;
+#[path = "../src/depart_time_and_row_annotations.rs"]
+mod depart_time_and_row_annotations;
+#[path = "../src/fixtures.rs"]
+mod fixtures;
+#[path = "../src/macros.rs"]
+mod macros;
+#[path = "../src/parse_report.rs"]
+mod parse_report;
+#[path = "../src/prelude.rs"]
+mod prelude;
+#[path = "../src/progress.rs"]
+mod progress;
+#[path = "../src/route_discovery.rs"]
+mod route_discovery;
+#[path = "../src/scraper.rs"]
+mod scraper;
+#[path = "../src/sgi_grid.rs"]
+mod sgi_grid;
+#[path = "../src/source_urls.rs"]
+mod source_urls;
+#[path = "../src/table_layout.rs"]
+mod table_layout;
+#[path = "../src/types.rs"]
+mod types;
+#[path = "../src/upload.rs"]
+mod upload;
+#[path = "../src/utils.rs"]
+mod utils;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::path::Path;
+
+use crate::fixtures::*;
+use crate::macros::*;
+use crate::prelude::*;
+use crate::scraper::*;
+use crate::sgi_grid::*;
+
+// A scrape spends most of its time downloading, so parsing only needs to stay clear of accidental quadratic behaviour.
+// Each grid fixture takes about 0.1 ms and each schedule page a few ms; anything several times slower is a regression
+// worth looking into.
+const SGI_GRID_FIXTURES: &[(&str, &str)] = &[
+    ("sgi_grid_summer", include_str!("../testdata/sgi_grid_summer.html")),
+    ("sgi_grid_shoulder", include_str!("../testdata/sgi_grid_shoulder.html")),
+    ("sgi_grid_winter", include_str!("../testdata/sgi_grid_winter.html")),
+];
+
+// Parses the whole page, from the HTML through the tables and annotations to the schedule, as a scrape does
+fn bench_parse_schedule_pages(c: &mut Criterion) {
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+    let pages = read_fixture_pages(&fixtures_dir).expect("Expect fixtures to read");
+    assert!(!pages.is_empty(), "Expect fixtures to be recorded");
+    let mut group = c.benchmark_group("parse_schedule_page");
+    for (source, html) in &pages {
+        let date_range = parse_schedule_path_query(&source.schedule_path_query).expect("Expect fixture date range");
+        group.bench_function(&source.name, |b| {
+            b.iter(|| {
+                let document = Html::parse_document(html);
+                parse_schedule_page(&source.source_url, &document, source.terminal_pair, date_range, 0, true)
+                    .expect("Expect fixture to parse")
+                    .expect("Expect fixture to have a schedule")
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_parse_sgi_grids(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_sgi_grid");
+    for (name, html) in SGI_GRID_FIXTURES {
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                let document = Html::parse_document(html);
+                let table_elem = document.select(selector!("table.schedule-grid")).next().expect("Expect grid table");
+                parse_sgi_grid(table_elem).expect("Expect fixture to parse")
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse_schedule_pages, bench_parse_sgi_grids);
+criterion_main!(benches);
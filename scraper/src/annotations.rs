@@ -1,15 +1,24 @@
-use crate::imports::*;
+use crate::date_list::*;
 use crate::macros::*;
+use crate::prelude::*;
+use crate::utils::*;
 
 #[derive(Clone, Debug)]
 pub struct AnnotationDates {
     pub only: HashSet<Date>,
     pub except: HashSet<Date>,
+    // Weekdays from annotations like "Fridays only" or "Except Sundays", which apply to all of the weekday's dates
+    pub only_weekdays: HashSet<Weekday>,
+    pub except_weekdays: HashSet<Weekday>,
 }
 
 #[derive(Clone, Debug)]
 pub struct AnnotationNotes {
     pub map: HashMap<Cow<'static, str>, AnnotationDates>,
+    // Categories of the recognized notes; the notes passed through as-is are left out, to be shown as plain text
+    pub categories: HashMap<Cow<'static, str>, NoteCategory>,
+    // Size limits behind the notes that are `NoteCategory::VehicleRestriction`
+    pub vehicle_restrictions: HashMap<Cow<'static, str>, VehicleRestriction>,
 }
 
 #[derive(Debug)]
@@ -20,32 +29,141 @@ pub struct Annotations {
     pub star_dates_by_time: HashMap<Time, AnnotationDates>,
     pub all_dates: AnnotationDates,
     pub all_notes: AnnotationNotes,
+    // Stops the sailing makes only on some dates, from footnotes like "Sailing operates via Otter Bay on Jul 1, 8"
+    pub all_stops: HashMap<Terminal, AnnotationDates>,
+    // Keep going past unrecognized annotation texts, which are noted on the sailings as-is and collected in
+    // `unrecognized`, rather than failing
+    pub lenient: bool,
+    pub unrecognized: Vec<String>,
 }
 
-fn text_date_restriction<T: Into<Cow<'static, str>>>(notes: &mut AnnotationNotes, text: T) -> &mut AnnotationDates {
-    notes.map.entry(text.into()).or_insert_with(AnnotationDates::new)
+fn text_date_restriction<T: Into<Cow<'static, str>>>(
+    notes: &mut AnnotationNotes,
+    category: NoteCategory,
+    text: T,
+) -> &mut AnnotationDates {
+    let text = text.into();
+    if category != NoteCategory::Other {
+        notes.categories.insert(text.clone(), category);
+    }
+    notes.map.entry(text).or_insert_with(AnnotationDates::new)
+}
+
+fn insert_dates_within(
+    dates: &mut AnnotationDates,
+    qualifier: DateQualifier,
+    date_range: &DateRange,
+    dates_text: &str,
+    annotation_text: &str,
+) -> Result<()> {
+    let inner = || {
+        let date_list = parse_date_list(dates_text)?;
+        ensure!(date_list.qualifier.is_none(), "Expect no \"Only\" or \"Except\" in dates");
+        Ok(date_list) as Result<_>
+    };
+    let date_list =
+        inner().with_context(|| format!("Failed to parse dates {:?} in {:?}", dates_text, annotation_text))?;
+    dates.insert_date_list(&date_list, qualifier, date_range)
+}
+
+// Adds the note for every date, or only for the dates in `dates_text` if there are any
+fn insert_note_on_dates<T: Into<Cow<'static, str>>>(
+    notes: &mut AnnotationNotes,
+    category: NoteCategory,
+    text: T,
+    date_range: &DateRange,
+    dates_text: Option<&str>,
+    annotation_text: &str,
+) -> Result<()> {
+    match dates_text {
+        None => {
+            text_date_restriction(notes, category, text);
+        }
+        Some(dates_text) => {
+            let mut dates = AnnotationDates::new();
+            insert_dates_within(&mut dates, DateQualifier::Only, date_range, dates_text, annotation_text)?;
+            // Without any dates in the schedule, the note would otherwise apply to every date
+            if !dates.is_always() {
+                dates.limit_to_listed_dates();
+                text_date_restriction(notes, category, text).extend(&dates);
+            }
+        }
+    }
+    Ok(())
+}
+
+// A row's notes on one weekday's dates, with the categories and vehicle restrictions of those notes
+pub struct WeekdayNotes {
+    pub notes: HashMap<Cow<'static, str>, DateRestriction>,
+    pub note_categories: HashMap<Cow<'static, str>, NoteCategory>,
+    pub vehicle_restrictions: HashMap<Cow<'static, str>, VehicleRestriction>,
 }
 
 pub fn annotation_notes_date_restictions(
     row_notes: AnnotationNotes,
     weekday: Weekday,
     date_restriction: &DateRestriction,
-) -> HashMap<Cow<'static, str>, DateRestriction> {
-    AnnotationDates::map_to_date_restrictions_by_weekday(row_notes.map.into_iter(), weekday, date_restriction)
+) -> WeekdayNotes {
+    let AnnotationNotes { map, categories, vehicle_restrictions } = row_notes;
+    let notes = AnnotationDates::map_to_date_restrictions_by_weekday(map, weekday, date_restriction);
+    let note_categories = categories.into_iter().filter(|(text, _)| notes.contains_key(text)).collect();
+    let vehicle_restrictions = vehicle_restrictions.into_iter().filter(|(text, _)| notes.contains_key(text)).collect();
+    WeekdayNotes { notes, note_categories, vehicle_restrictions }
 }
 
 impl AnnotationDates {
     pub fn new() -> AnnotationDates {
-        AnnotationDates { only: HashSet::new(), except: HashSet::new() }
+        AnnotationDates {
+            only: HashSet::new(),
+            except: HashSet::new(),
+            only_weekdays: HashSet::new(),
+            except_weekdays: HashSet::new(),
+        }
     }
 
     pub fn is_always(&self) -> bool {
-        self.only.is_empty() && self.except.is_empty()
+        self.only.is_empty()
+            && self.except.is_empty()
+            && self.only_weekdays.is_empty()
+            && self.except_weekdays.is_empty()
     }
 
     pub fn extend(&mut self, other: &AnnotationDates) {
         self.except.extend(&other.except);
         self.only.extend(&other.only);
+        self.except_weekdays.extend(&other.except_weekdays);
+        self.only_weekdays.extend(&other.only_weekdays);
+    }
+
+    // Excepts every weekday that isn't listed as a whole, so that each one only has the dates listed for it, and
+    // weekdays without any are left out rather than unrestricted
+    fn limit_to_listed_dates(&mut self) {
+        let only_weekdays = &self.only_weekdays;
+        self.except_weekdays.extend(
+            iter::successors(Some(Weekday::Monday), |weekday| Some(weekday.next()))
+                .take(7)
+                .filter(|weekday| !only_weekdays.contains(weekday)),
+        );
+    }
+
+    fn insert_date_list(
+        &mut self,
+        date_list: &DateList,
+        qualifier: DateQualifier,
+        date_range: &DateRange,
+    ) -> Result<()> {
+        let dates = date_list.dates_within(date_range)?;
+        match qualifier {
+            DateQualifier::Only => {
+                self.only.extend(dates);
+                self.only_weekdays.extend(date_list.weekdays());
+            }
+            DateQualifier::Except => {
+                self.except.extend(dates);
+                self.except_weekdays.extend(date_list.weekdays());
+            }
+        }
+        Ok(())
     }
 
     fn into_date_restriction(mut self) -> DateRestriction {
@@ -63,17 +181,28 @@ impl AnnotationDates {
         }
     }
 
-    fn into_date_restriction_by<F>(mut self, predicate: F) -> DateRestriction
+    // The restriction on one weekday's dates.  A weekday that is excepted only runs on the dates it is listed for, and
+    // a weekday listed as the only one runs on all its dates but those excepted.
+    fn into_date_restriction_by<F>(mut self, weekday: Weekday, predicate: F) -> DateRestriction
     where
         F: Fn(&Date) -> bool,
     {
-        self.only.retain(&predicate);
-        self.except.retain(&predicate);
-        self.into_date_restriction()
+        self.only.retain(|date| date.weekday() == weekday && predicate(date));
+        self.except.retain(|date| date.weekday() == weekday && predicate(date));
+        if self.except_weekdays.contains(&weekday) {
+            DateRestriction::Only(self.only)
+        } else if self.only_weekdays.contains(&weekday) {
+            self.only.clear();
+            self.into_date_restriction()
+        } else if !self.only_weekdays.is_empty() && self.only.is_empty() {
+            DateRestriction::Only(HashSet::new())
+        } else {
+            self.into_date_restriction()
+        }
     }
 
     pub fn into_date_restriction_by_weekday(self, weekday: Weekday) -> DateRestriction {
-        self.into_date_restriction_by(|date| date.weekday() == weekday)
+        self.into_date_restriction_by(weekday, |_| true)
     }
 
     pub fn into_date_restriction_by_weekday_and_date_restriction(
@@ -81,7 +210,7 @@ impl AnnotationDates {
         weekday: Weekday,
         date_restriction: &DateRestriction,
     ) -> DateRestriction {
-        self.into_date_restriction_by(|date| date.weekday() == weekday && date_restriction.includes_date(*date))
+        self.into_date_restriction_by(weekday, |date| date_restriction.includes_date(*date))
     }
 
     pub fn map_to_date_restrictions_by_weekday<I, K>(
@@ -104,11 +233,13 @@ impl AnnotationDates {
 
 impl AnnotationNotes {
     pub fn new() -> AnnotationNotes {
-        AnnotationNotes { map: HashMap::new() }
+        AnnotationNotes { map: HashMap::new(), categories: HashMap::new(), vehicle_restrictions: HashMap::new() }
     }
 
     pub fn extend(&mut self, other: AnnotationNotes) {
         self.map.extend(other.map.into_iter());
+        self.categories.extend(other.categories.into_iter());
+        self.vehicle_restrictions.extend(other.vehicle_restrictions);
     }
 }
 
@@ -121,28 +252,17 @@ impl Annotations {
             star_dates_by_time: HashMap::new(),
             all_dates: AnnotationDates::new(),
             all_notes: AnnotationNotes::new(),
+            all_stops: HashMap::new(),
+            lenient: false,
+            unrecognized: Vec::new(),
         }
     }
 
     fn parse_single(&mut self, date_range: &DateRange, annotation_text: &str) -> Result<()> {
         let mut inner = || {
-            let annotation_text = regex!(r"\.*$").replace(annotation_text, "");
-            let annotation_text = regex!(r"(?i)\bApril\b").replace_all(annotation_text.as_ref(), "Apr");
-            let annotation_text = regex!(r", \d{4}\b").replace_all(annotation_text.as_ref(), "");
-            let annotation_text = regex!(r"(?i)( & |, and | and )").replace_all(annotation_text.as_ref(), ", ");
-            let annotation_text = regex!(r"(?i)\b([a-z]{3})(\d{1,2})\b").replace_all(annotation_text.as_ref(), "$1 $2");
-            let annotation_text = regex!(r"(?i)\b([a-z]{3} \d{1,2}) ([a-z]{3} \d{1,2})\b")
-                .replace_all(annotation_text.as_ref(), "$1, $2");
-            let annotation_text = regex!(r"(?i)\b([a-z]{3}) (\d{1,2}),? (\d{1,2}),? (\d{1,2})\b")
-                .replace_all(annotation_text.as_ref(), "$1 $2, $1 $3, $1 $4");
-            let annotation_text = regex!(r"(?i)\b([a-z]{3}) (\d{1,2}),? (\d{1,2})\b")
-                .replace_all(annotation_text.as_ref(), "$1 $2, $1 $3");
-            let annotation_text = regex!(r"(?i)^([a-z]{3} \d{1,2})(, [a-z]{3} \d{1,2})* only$")
-                .replace(annotation_text.as_ref(), "Only $1$2");
-            let annotation_text = regex!(r"(?i)^(DG Sailing only .*), no other passengers permitted$")
-                .replace(annotation_text.as_ref(), "$1");
+            let annotation_text = annotation_text.trim_end_matches('.');
             if let Some(captures) =
-                regex!(r"(?i)^\*(\d+:\d+ [AP]M) (Not Available|Only) on: (.*)\*").captures(annotation_text.as_ref())
+                regex!(r"(?i)^\*(\d+:\d+ [AP]M) (Not Available|Only) on: (.*)\*").captures(annotation_text)
             {
                 let time_text = &captures[1];
                 let time = Time::parse(
@@ -153,42 +273,108 @@ impl Annotations {
                 )
                 .with_context(|| format!("Failed to parse time: {:?}", time_text))?;
                 let dates = self.star_dates_by_time.entry(time).or_insert_with(AnnotationDates::new);
-                let dates_hashset = match &captures[2] {
-                    "Not Available" => &mut dates.except,
-                    "Only" => &mut dates.only,
+                let qualifier = match &captures[2] {
+                    "Not Available" => DateQualifier::Except,
+                    "Only" => DateQualifier::Only,
                     other => bail!("Expect \"Not Available\" or \"Only\" in: {:?}", other),
                 };
-                for date_text in captures[3].split(',').map(|s| s.trim()) {
-                    let date_within_range = date_range.parse_date_within(date_text).with_context(|| {
-                        format!("Failed to parse sailing date {:?} in {:?}", date_text, annotation_text)
-                    })?;
-                    if let Some(date) = date_within_range {
-                        dates_hashset.insert(date);
-                    } else {
-                        warn!("Date is outside date range of schedule ({}): {:?}", date_range, date_text);
-                    }
-                }
-            } else if let Some(captures) = regex!(r"(?i)^(Except|Not Available|Only|DG Sailing only)( on)?:? (.*)")
-                .captures(annotation_text.as_ref())
+                insert_dates_within(dates, qualifier, date_range, &captures[3], annotation_text)?;
+            } else if let Some(captures) = regex!(
+                r"(?i)^[!#*]*\s*(?:no )?(?:only )?vehicles? (?:over|under) (\d+)\s*(?:ft|feet|')(?: \([^)]*\))?(?: in)? (height|high|tall|length|long)(?: only)?(?: (?:are )?not permitted)?(?: on:? (.+))?$"
+            )
+            .captures(annotation_text)
             {
-                let dates_hashset = match &captures[1] {
-                    "Except" | "Not Available" => &mut self.all_dates.except,
-                    "Only" => &mut self.all_dates.only,
-                    "DG Sailing only" => &mut self.dg_dates.only,
-                    other => bail!("Expect \"Except\", \"Only\", or \"DG Sailing only\" in: {:?}", other),
+                // Checked before date restrictions, since some of these start with "Only"
+                let dimension = match captures[2].to_lowercase().as_str() {
+                    "height" | "high" | "tall" => VehicleDimension::Height,
+                    _ => VehicleDimension::Length,
                 };
-                for date_text in captures[3].split(&[',', '&']).map(|s| s.trim()) {
-                    let date_within_range = date_range
-                        .parse_date_within(date_text)
-                        .with_context(|| format!("Failed to parse date {:?} in {:?}", date_text, annotation_text))?;
-                    if let Some(date) = date_within_range {
-                        dates_hashset.insert(date);
+                let max_feet = captures[1]
+                    .parse()
+                    .with_context(|| format!("Failed to parse vehicle size {:?}", &captures[1]))?;
+                let restriction = VehicleRestriction { dimension, max_feet };
+                self.all_notes.vehicle_restrictions.insert(restriction.note().into(), restriction);
+                insert_note_on_dates(
+                    &mut self.all_notes,
+                    NoteCategory::VehicleRestriction,
+                    restriction.note(),
+                    date_range,
+                    captures.get(3).map(|m| m.as_str()),
+                    annotation_text,
+                )?;
+            } else if let Some(captures) =
+                regex!(r"(?i)^DG Sailing only(?: on)?:? (.*?)(?:, no other passengers permitted)?$")
+                    .captures(annotation_text)
+            {
+                insert_dates_within(&mut self.dg_dates, DateQualifier::Only, date_range, &captures[1], annotation_text)?;
+            } else if let Some(date_list) = parse_date_list(annotation_text)
+                .map(Some)
+                .or_else(|err| {
+                    // Anything else starting like a date list is one that can't be parsed
+                    if regex!(r"(?i)^(Except|Not Available|Only)\b").is_match(annotation_text) {
+                        Err(err)
                     } else {
-                        warn!("Date is outside date range of schedule ({}): {:?}", date_range, date_text);
+                        Ok(None)
                     }
+                })?
+                .filter(|dl| dl.qualifier.is_some())
+            {
+                let qualifier = date_list.qualifier.expect("Expect date list qualifier");
+                self.all_dates.insert_date_list(&date_list, qualifier, date_range)?;
+            } else if let Some(captures) = regex!(
+                r"(?i)^[!#*]*\s*(?:(?:car ferry )?replaced by (?:a )?passenger[- ]only vessel|passenger[- ]only vessel) on:? (.+)$"
+            )
+            .captures(annotation_text)
+            {
+                insert_note_on_dates(
+                    &mut self.all_notes,
+                    NoteCategory::PassengerOnlyVessel,
+                    PASSENGER_ONLY_VESSEL_NOTE,
+                    date_range,
+                    Some(&captures[1]),
+                    annotation_text,
+                )?;
+            } else if let Some(captures) =
+                regex!(r"(?i)^[!#*]*\s*foot passengers only on:? (.+)$").captures(annotation_text)
+            {
+                insert_note_on_dates(
+                    &mut self.all_notes,
+                    NoteCategory::FootPassengersOnly,
+                    FOOT_PASSENGERS_ONLY_NOTE,
+                    date_range,
+                    Some(&captures[1]),
+                    annotation_text,
+                )?;
+            } else if let Some(captures) = regex!(
+                r"(?i)^[!#*]*\s*(?:(daylight (?:loading|sailing)s? only)|(?:sailing )?subject to tides?(?: conditions)?|tide[- ]restricted(?: sailing)?|tidal restrictions apply)(?: on:? (.+))?$"
+            )
+            .captures(annotation_text)
+            {
+                let condition =
+                    if captures.get(1).is_some() { SailingCondition::DaylightLoading } else { SailingCondition::Tide };
+                insert_note_on_dates(
+                    &mut self.all_notes,
+                    condition.note_category(),
+                    condition.note(),
+                    date_range,
+                    captures.get(2).map(|m| m.as_str()),
+                    annotation_text,
+                )?;
+            } else if let Some(captures) = regex!(
+                r"(?i)^[!#*]*\s*(?:(?:this )?sailing (?:operates|travels|sails|goes|will travel) )?via ([a-z .'()]+?) on:? (.+)$"
+            )
+            .captures(annotation_text)
+            {
+                let terminal = terminal_from_schedule_stop_text(captures[1].trim())?;
+                let mut dates = AnnotationDates::new();
+                insert_dates_within(&mut dates, DateQualifier::Only, date_range, &captures[2], annotation_text)?;
+                // Without any dates in the schedule, the sailing would otherwise make the stop on every date
+                if !dates.is_always() {
+                    dates.limit_to_listed_dates();
+                    self.all_stops.entry(terminal).or_insert_with(AnnotationDates::new).extend(&dates);
                 }
             } else {
-                let replaced_annotation_text = regex!(r"([!#*]*)\s*").replace(annotation_text.as_ref(), "$1 ");
+                let replaced_annotation_text = regex!(r"([!#*]*)\s*").replace(annotation_text, "$1 ");
                 let replaced_annotation_text = regex!(r"[\.,]$").replace(replaced_annotation_text.as_ref(), "");
                 let annotation_text = replaced_annotation_text.trim();
                 if regex!(r"^(Dangerous goods only)|(No passengers permitted - DG Sailing only)|(No passengers permitted - only sails on .*)$").is_match(annotation_text) {
@@ -198,19 +384,41 @@ impl Annotations {
                         "! Saturna-bound vehicles arriving at the booth at least 15 minutes prior to sailing time are offered priority on this sailing" => {
                             text_date_restriction(
                                 &mut self.all_notes,
+                                NoteCategory::VehiclePriority,
                                 "Saturna-bound vehicles arriving at the booth at least 15 minutes prior to sailing time are offered priority on this sailing"
                             );
                         }
                         "Foot passengers only" => {
-                            text_date_restriction(&mut self.all_notes, "Foot passengers only");
+                            text_date_restriction(
+                                &mut self.all_notes,
+                                NoteCategory::FootPassengersOnly,
+                                FOOT_PASSENGERS_ONLY_NOTE,
+                            );
                         }
                         "Note: This sailing departs just after midnight" => {
-                            text_date_restriction(&mut self.all_notes, "This sailing departs just after midnight");
+                            text_date_restriction(
+                                &mut self.all_notes,
+                                NoteCategory::NearMidnight,
+                                "This sailing departs just after midnight",
+                            );
                         }
                         "This sailing departs just before midnight" => {
-                            text_date_restriction(&mut self.all_notes, "This sailing departs just before midnight");
+                            text_date_restriction(
+                                &mut self.all_notes,
+                                NoteCategory::NearMidnight,
+                                "This sailing departs just before midnight",
+                            );
                         }
                         "No sailings available on this route for these dates" => {}
+                        _ if self.lenient => {
+                            warn!("Unrecognized annotation text: {:?}", annotation_text);
+                            self.unrecognized.push(annotation_text.to_string());
+                            text_date_restriction(
+                                &mut self.all_notes,
+                                NoteCategory::Other,
+                                annotation_text.to_string(),
+                            );
+                        }
                         _ => bail!("Unrecognized annotation text: {:?}", annotation_text),
                     }
                 }
@@ -237,3 +445,158 @@ impl Annotations {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_passenger_only_vessel() -> Result<()> {
+        let date_range = DateRange { from: date!(2023 - 01 - 01), to: date!(2023 - 03 - 31) };
+        let mut annotations = Annotations::new();
+        annotations.parse(&date_range, ["! Car ferry replaced by passenger-only vessel on: Jan 9, 16 & Apr 3"])?;
+        let dates = &annotations.all_notes.map[PASSENGER_ONLY_VESSEL_NOTE];
+        assert_eq!(dates.only, HashSet::from([date!(2023 - 01 - 09), date!(2023 - 01 - 16)]));
+        // Only applies to the weekday of its dates
+        let monday_notes =
+            annotation_notes_date_restictions(annotations.all_notes.clone(), Weekday::Monday, &DateRestriction::All);
+        assert_eq!(
+            monday_notes.notes.get(PASSENGER_ONLY_VESSEL_NOTE),
+            Some(&DateRestriction::Only(HashSet::from([date!(2023 - 01 - 09), date!(2023 - 01 - 16)])))
+        );
+        assert_eq!(
+            monday_notes.note_categories.get(PASSENGER_ONLY_VESSEL_NOTE),
+            Some(&NoteCategory::PassengerOnlyVessel)
+        );
+        let tuesday_notes =
+            annotation_notes_date_restictions(annotations.all_notes.clone(), Weekday::Tuesday, &DateRestriction::All);
+        assert!(tuesday_notes.notes.is_empty());
+        assert!(tuesday_notes.note_categories.is_empty());
+        let mut annotations = Annotations::new();
+        annotations.parse(&date_range, ["Passenger-only vessel on Jun 5"])?;
+        assert!(annotations.all_notes.map.is_empty());
+        // Foot-passenger-only sailings still run the car ferry, so they get their own note
+        let mut annotations = Annotations::new();
+        annotations.parse(&date_range, ["* Foot passengers only on: Feb 3"])?;
+        assert_eq!(annotations.all_notes.map[FOOT_PASSENGERS_ONLY_NOTE].only, HashSet::from([date!(2023 - 02 - 03)]));
+        assert_eq!(annotations.all_notes.categories[FOOT_PASSENGERS_ONLY_NOTE], NoteCategory::FootPassengersOnly);
+        assert!(!annotations.all_notes.map.contains_key(PASSENGER_ONLY_VESSEL_NOTE));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_sailing_conditions() -> Result<()> {
+        let date_range = DateRange { from: date!(2023 - 10 - 01), to: date!(2024 - 03 - 31) };
+        let mut annotations = Annotations::new();
+        annotations
+            .parse(&date_range, ["Daylight loading only on: Nov 5, 12 & Jun 3", "* Subject to tide conditions."])?;
+        assert_eq!(
+            annotations.all_notes.map[DAYLIGHT_LOADING_NOTE].only,
+            HashSet::from([date!(2023 - 11 - 05), date!(2023 - 11 - 12)])
+        );
+        assert!(annotations.all_notes.map[TIDE_RESTRICTION_NOTE].is_always());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_vehicle_restrictions() -> Result<()> {
+        let date_range = DateRange { from: date!(2023 - 10 - 01), to: date!(2024 - 03 - 31) };
+        let mut annotations = Annotations::new();
+        annotations.parse(
+            &date_range,
+            ["* Vehicles over 7 ft in height not permitted on: Nov 5", "Only vehicles under 20 feet long."],
+        )?;
+        let height_restriction = VehicleRestriction { dimension: VehicleDimension::Height, max_feet: 7 };
+        let height_note = height_restriction.note();
+        assert_eq!(annotations.all_notes.map[height_note.as_str()].only, HashSet::from([date!(2023 - 11 - 05)]));
+        let length_restriction = VehicleRestriction { dimension: VehicleDimension::Length, max_feet: 20 };
+        let length_note = length_restriction.note();
+        assert!(annotations.all_notes.map[length_note.as_str()].is_always());
+        // Only the restrictions of the notes on the weekday's dates are kept
+        let sunday_notes =
+            annotation_notes_date_restictions(annotations.all_notes.clone(), Weekday::Sunday, &DateRestriction::All);
+        assert_eq!(
+            sunday_notes.vehicle_restrictions,
+            HashMap::from([
+                (Cow::from(height_note), height_restriction),
+                (Cow::from(length_note.clone()), length_restriction)
+            ])
+        );
+        let monday_notes =
+            annotation_notes_date_restictions(annotations.all_notes.clone(), Weekday::Monday, &DateRestriction::All);
+        assert_eq!(monday_notes.vehicle_restrictions, HashMap::from([(Cow::from(length_note), length_restriction)]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_conditional_stops() -> Result<()> {
+        let date_range = DateRange { from: date!(2023 - 06 - 28), to: date!(2023 - 09 - 04) };
+        let mut annotations = Annotations::new();
+        annotations
+            .parse(&date_range, ["* Sailing operates via Otter Bay on Jul 1, 8", "Via Village Bay on: Jul 3"])?;
+        assert_eq!(
+            annotations.all_stops[&Terminal::POB].only,
+            HashSet::from([date!(2023 - 07 - 01), date!(2023 - 07 - 08)])
+        );
+        assert_eq!(annotations.all_stops[&Terminal::PVB].only, HashSet::from([date!(2023 - 07 - 03)]));
+        let saturday_stops = AnnotationDates::map_to_date_restrictions_by_weekday(
+            annotations.all_stops.clone(),
+            Weekday::Saturday,
+            &DateRestriction::All,
+        );
+        assert_eq!(
+            saturday_stops,
+            HashMap::from([(
+                Terminal::POB,
+                DateRestriction::Only(HashSet::from([date!(2023 - 07 - 01), date!(2023 - 07 - 08)]))
+            )])
+        );
+        assert!(Annotations::new().parse(&date_range, ["Sailing operates via Atlantis on Jul 1"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_weekdays() -> Result<()> {
+        let date_range = DateRange { from: date!(2023 - 05 - 01), to: date!(2023 - 05 - 31) };
+        let mut annotations = Annotations::new();
+        annotations.parse(&date_range, ["Fridays and holiday Mondays only"])?;
+        let dates = &annotations.all_dates;
+        assert_eq!(dates.clone().into_date_restriction_by_weekday(Weekday::Friday), DateRestriction::All);
+        assert_eq!(
+            dates.clone().into_date_restriction_by_weekday(Weekday::Monday),
+            DateRestriction::Only(HashSet::from([date!(2023 - 05 - 22)]))
+        );
+        assert!(dates.clone().into_date_restriction_by_weekday(Weekday::Tuesday).is_never());
+        let mut annotations = Annotations::new();
+        annotations.parse(&date_range, ["Not available on Sundays", "Except May 13"])?;
+        let dates = &annotations.all_dates;
+        assert!(dates.clone().into_date_restriction_by_weekday(Weekday::Sunday).is_never());
+        assert_eq!(
+            dates.clone().into_date_restriction_by_weekday(Weekday::Saturday),
+            DateRestriction::Except(HashSet::from([date!(2023 - 05 - 13)]))
+        );
+        let mut annotations = Annotations::new();
+        annotations.parse(&date_range, ["Daylight loading only on: Saturdays"])?;
+        let notes = |weekday| {
+            annotation_notes_date_restictions(annotations.all_notes.clone(), weekday, &DateRestriction::All).notes
+        };
+        assert_eq!(notes(Weekday::Saturday).get(DAYLIGHT_LOADING_NOTE), Some(&DateRestriction::All));
+        assert!(notes(Weekday::Sunday).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_lenient() -> Result<()> {
+        let date_range = DateRange { from: date!(2023 - 10 - 01), to: date!(2024 - 03 - 31) };
+        let texts = ["Foot passengers only", "Bring your own snacks."];
+        assert!(Annotations::new().parse(&date_range, texts).is_err());
+        let mut annotations = Annotations { lenient: true, ..Annotations::new() };
+        annotations.parse(&date_range, texts)?;
+        assert_eq!(annotations.unrecognized, ["Bring your own snacks"]);
+        assert!(annotations.all_notes.map["Bring your own snacks"].is_always());
+        assert!(annotations.all_notes.map.contains_key("Foot passengers only"));
+        // Passed through as-is, so shown as plain text
+        assert!(!annotations.all_notes.categories.contains_key("Bring your own snacks"));
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use parquet::data_type::{ByteArray, ByteArrayType, Int32Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::prelude::*;
+use crate::utils::*;
+
+// Schema of the exported Parquet file, with one row for each day that a sailing operates in each archived snapshot:
+//
+// - `snapshot_date`: date the schedules were scraped; compare snapshots to see how schedules changed over time
+// - `from_terminal`, `to_terminal`: terminal codes, such as "SWB"
+// - `operator`: operator name, such as "BC Ferries"
+// - `sailing_date`: date the sailing operates
+// - `depart_time`, `arrive_time`: local times as "HH:MM"; an arrive time before the depart time is on the next day
+// - `stops`: comma-separated stops as "<type>:<terminal code>", such as "Transfer:PVB"; empty if non-stop
+// - `notes`: notes applying to the sailing on the date, separated by newlines; empty if none
+const PARQUET_SCHEMA: &str = "
+    message sailing_instance {
+        REQUIRED INT32 snapshot_date (DATE);
+        REQUIRED BYTE_ARRAY from_terminal (UTF8);
+        REQUIRED BYTE_ARRAY to_terminal (UTF8);
+        REQUIRED BYTE_ARRAY operator (UTF8);
+        REQUIRED INT32 sailing_date (DATE);
+        REQUIRED BYTE_ARRAY depart_time (UTF8);
+        REQUIRED BYTE_ARRAY arrive_time (UTF8);
+        REQUIRED BYTE_ARRAY stops (UTF8);
+        REQUIRED BYTE_ARRAY notes (UTF8);
+    }
+";
+
+enum ArchiveColumn {
+    Date(fn(&ArchiveRow) -> Date),
+    Text(fn(&ArchiveRow) -> String),
+}
+
+// Must be in the same order as the columns in the schema
+const ARCHIVE_COLUMNS: [ArchiveColumn; 9] = [
+    ArchiveColumn::Date(|r| r.snapshot_date),
+    ArchiveColumn::Text(|r| r.from_terminal.to_string()),
+    ArchiveColumn::Text(|r| r.to_terminal.to_string()),
+    ArchiveColumn::Text(|r| r.operator.to_string()),
+    ArchiveColumn::Date(|r| r.sailing_date),
+    ArchiveColumn::Text(|r| format_archive_time(r.depart_time)),
+    ArchiveColumn::Text(|r| format_archive_time(r.arrive_time)),
+    ArchiveColumn::Text(|r| r.stops.clone()),
+    ArchiveColumn::Text(|r| r.notes.clone()),
+];
+
+const EPOCH_DATE: Date = date!(1970 - 01 - 01);
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ArchiveRow {
+    pub snapshot_date: Date,
+    pub from_terminal: Terminal,
+    pub to_terminal: Terminal,
+    pub operator: &'static str,
+    pub sailing_date: Date,
+    pub depart_time: Time,
+    pub arrive_time: Time,
+    pub stops: String,
+    pub notes: String,
+}
+
+fn snapshot_path(archive_dir: &Path, snapshot_date: Date) -> PathBuf {
+    archive_dir.join(format!("schedules-{}.json", format_iso8601_date(snapshot_date)))
+}
+
+fn parse_snapshot_file_name(file_name: &str) -> Option<Date> {
+    file_name.strip_prefix("schedules-")?.strip_suffix(".json").and_then(|d| parse_iso8601_date(d).ok())
+}
+
+// Saves the schedules as today's snapshot in the archive, replacing any earlier snapshot from today
+pub fn write_archive_snapshot(archive_dir: &Path, schedules: &[Schedule]) -> Result<()> {
+    let path = snapshot_path(archive_dir, today_vancouver());
+    let inner = || {
+        fs::create_dir_all(archive_dir)?;
+        info!("Writing archive snapshot to: {:?}", path);
+        write_file_atomically(&path, |file| {
+            let mut writer = io::BufWriter::new(file);
+            serde_json::to_writer(&mut writer, schedules)?;
+            // Flushed here so that write errors aren't lost when the writer is dropped
+            io::Write::flush(&mut writer)?;
+            Ok(())
+        })
+    };
+    inner().with_context(|| format!("Failed to write archive snapshot: {:?}", path))
+}
+
+pub fn read_archive(archive_dir: &Path) -> Result<Vec<(Date, Vec<Schedule>)>> {
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(archive_dir).with_context(|| format!("Failed to read archive: {:?}", archive_dir))? {
+        let path = entry?.path();
+        let snapshot_date = match path.file_name().and_then(|n| n.to_str()).and_then(parse_snapshot_file_name) {
+            Some(snapshot_date) => snapshot_date,
+            None => continue,
+        };
+        let schedules = serde_json::from_reader(io::BufReader::new(fs::File::open(&path)?))
+            .with_context(|| format!("Failed to parse archive snapshot: {:?}", path))?;
+        snapshots.push((snapshot_date, schedules));
+    }
+    snapshots.sort_unstable_by_key(|(snapshot_date, _)| *snapshot_date);
+    Ok(snapshots)
+}
+
+fn format_archive_time(time: Time) -> String {
+    time.format(format_description!("[hour]:[minute]")).expect("Expect archive time to format")
+}
+
+pub fn archive_rows(snapshot_date: Date, schedules: &[Schedule]) -> Vec<ArchiveRow> {
+    let mut rows = Vec::new();
+    for schedule in schedules {
+        for sailing_date in schedule.date_range.iter_days() {
+            for item in schedule.items.iter().filter(|i| i.is_on_date(sailing_date)) {
+                if !schedule.is_effective_at(sailing_date, item.sailing.depart_time) {
+                    continue;
+                }
+                let stops = item.sailing.stops.iter().map(|s| format!("{:?}:{}", s.type_, s.terminal)).join(",");
+                let notes = item
+                    .notes
+                    .iter()
+                    .filter(|(_, dr)| dr.includes_date(sailing_date))
+                    .map(|(note, _)| note.as_ref())
+                    .sorted()
+                    .join("\n");
+                rows.push(ArchiveRow {
+                    snapshot_date,
+                    from_terminal: schedule.terminal_pair.from,
+                    to_terminal: schedule.terminal_pair.to,
+                    operator: schedule.operator.name(),
+                    sailing_date,
+                    depart_time: item.sailing.depart_time,
+                    arrive_time: item.sailing.arrive_time,
+                    stops,
+                    notes,
+                });
+            }
+        }
+    }
+    rows.sort_unstable_by(|a, b| {
+        (a.from_terminal, a.to_terminal, a.sailing_date, a.depart_time).cmp(&(
+            b.from_terminal,
+            b.to_terminal,
+            b.sailing_date,
+            b.depart_time,
+        ))
+    });
+    rows
+}
+
+fn days_since_epoch(date: Date) -> i32 {
+    (date - EPOCH_DATE).whole_days() as i32
+}
+
+fn write_parquet<W: io::Write + Send>(writer: W, rows: &[ArchiveRow]) -> Result<()> {
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA).expect("Expect Parquet schema to parse"));
+    let mut file_writer = SerializedFileWriter::new(writer, schema, Arc::new(WriterProperties::builder().build()))?;
+    let mut row_group_writer = file_writer.next_row_group()?;
+    for column in ARCHIVE_COLUMNS {
+        let mut column_writer =
+            row_group_writer.next_column()?.expect("Expect a Parquet column for each archive column");
+        match column {
+            ArchiveColumn::Date(f) => {
+                let values: Vec<_> = rows.iter().map(|r| days_since_epoch(f(r))).collect();
+                column_writer.typed::<Int32Type>().write_batch(&values, None, None)?;
+            }
+            ArchiveColumn::Text(f) => {
+                let values: Vec<_> = rows.iter().map(|r| ByteArray::from(f(r).as_str())).collect();
+                column_writer.typed::<ByteArrayType>().write_batch(&values, None, None)?;
+            }
+        }
+        column_writer.close()?;
+    }
+    row_group_writer.close()?;
+    file_writer.close()?;
+    Ok(())
+}
+
+// Writes every sailing in every archived snapshot to a Parquet file, for analysing how schedules change over time
+pub fn export_archive_parquet(archive_dir: &Path, output_path: &Path) -> Result<()> {
+    let inner = || {
+        let rows: Vec<_> = read_archive(archive_dir)?
+            .iter()
+            .flat_map(|(snapshot_date, schedules)| archive_rows(*snapshot_date, schedules))
+            .collect();
+        info!("Writing {} archived sailings to: {:?}", rows.len(), output_path);
+        write_file_atomically(output_path, |file| write_parquet(file, &rows))
+    };
+    inner().with_context(|| format!("Failed to export archive to Parquet: {:?}", output_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    use super::*;
+
+    fn archive_rows_text(rows: &[ArchiveRow]) -> String {
+        rows.iter()
+            .map(|r| {
+                let mut text = ARCHIVE_COLUMNS.iter().map(|column| match column {
+                    ArchiveColumn::Date(f) => format_iso8601_date(f(r)),
+                    ArchiveColumn::Text(f) => format!("{:?}", f(r)),
+                });
+                format!("{}\n", text.join("\t"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_archive_rows_golden() -> Result<()> {
+        let date_range = DateRange { from: date!(2022 - 06 - 03), to: date!(2022 - 06 - 05) };
+        let all_weekdays = || {
+            iter::successors(Some(Weekday::Monday), |w| Some(w.next()))
+                .take(7)
+                .map(|w| (w, DateRestriction::All))
+                .collect()
+        };
+        let schedule = Schedule {
+            date_range,
+            items: vec![
+                ScheduleItem {
+                    sailing: Sailing { depart_time: time!(07:00), arrive_time: time!(07:50), stops: vec![] },
+                    weekdays: all_weekdays(),
+                    notes: HashMap::new(),
+                    note_categories: HashMap::new(),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                },
+                ScheduleItem {
+                    sailing: Sailing {
+                        depart_time: time!(18:00),
+                        arrive_time: time!(19:30),
+                        stops: vec![Stop { type_: StopType::Transfer, terminal: Terminal::POB }],
+                    },
+                    weekdays: HashMap::from([
+                        (Weekday::Friday, DateRestriction::All),
+                        (Weekday::Sunday, DateRestriction::All),
+                    ]),
+                    notes: HashMap::from([(
+                        Cow::from("Foot passengers only"),
+                        DateRestriction::Only(HashSet::from([date!(2022 - 06 - 05)])),
+                    )]),
+                    note_categories: HashMap::new(),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                },
+            ],
+            effective_from_time: Some(time!(12:00)),
+            ..Schedule::test_fixture(TerminalPair { from: Terminal::SWB, to: Terminal::PVB })
+        };
+        let rows = archive_rows(date!(2022 - 06 - 01), &[schedule]);
+        assert_eq!(archive_rows_text(&rows), include_str!("../testdata/archive_rows.golden"));
+        let parquet_file = tempfile::NamedTempFile::new()?;
+        write_parquet(parquet_file.reopen()?, &rows)?;
+        let metadata = SerializedFileReader::new(parquet_file.reopen()?)?.metadata().file_metadata().clone();
+        assert_eq!(metadata.num_rows(), rows.len() as i64);
+        assert_eq!(metadata.schema_descr().num_columns(), ARCHIVE_COLUMNS.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_snapshot_file_name() -> Result<()> {
+        let path = snapshot_path(Path::new("archive"), date!(2022 - 06 - 01));
+        assert_eq!(path, Path::new("archive/schedules-2022-06-01.json"));
+        assert_eq!(parse_snapshot_file_name("schedules-2022-06-01.json"), Some(date!(2022 - 06 - 01)));
+        assert_eq!(parse_snapshot_file_name("schedules.json"), None);
+        Ok(())
+    }
+}
@@ -1,46 +1,235 @@
 use directories::ProjectDirs;
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
 use scraper::Html;
+use std::path::Path;
 use std::sync::atomic::AtomicUsize;
-use std::sync::{atomic, Arc};
-use tokio_retry::{strategy, Retry};
+use std::sync::{atomic, Arc, Mutex};
+use tokio::time::{sleep_until, Instant};
+use tokio_retry::{strategy, RetryIf};
 
-use crate::imports::*;
 use crate::macros::*;
+use crate::prelude::*;
+use crate::types::*;
 use crate::utils::*;
 
-const MAX_RETRIES: usize = 5;
+// Delay before the first retry, doubling for each retry after it up to the maximum, before jitter is applied
+const FIRST_RETRY_DELAY_MILLIS: u64 = 1000;
+const MAX_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+
+// Responses saved in a directory by URL, so that scraping can be re-run against the same pages without the network,
+// such as for parser work and in CI
+#[derive(Clone, Debug)]
+pub enum HttpRecording {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+// What the server identified a cached response's version by, so that once the response is too old to use as is, it
+// can be fetched again only if it has changed
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+struct CacheValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+// A cached response that is too old to use without checking with the server
+#[derive(Debug)]
+struct StaleResponse {
+    contents: String,
+    validators: CacheValidators,
+}
 
 #[derive(Debug)]
 pub struct Cache<'a> {
     max_cache_age: Duration,
     project_dirs: &'a ProjectDirs,
     reqwest_client: reqwest::Client,
+    http_recording: Option<HttpRecording>,
+    max_fetch_attempts: usize,
+    // Least time between starting requests to the same host, so that scraping routes at once doesn't hammer it
+    host_delay: std::time::Duration,
+    next_request_times: Mutex<HashMap<String, Instant>>,
+}
+
+impl HttpRecording {
+    pub fn from_options(options: &Options) -> Option<HttpRecording> {
+        match (&options.record_http, &options.replay_http) {
+            (_, Some(replay_dir)) => Some(HttpRecording::Replay(replay_dir.clone())),
+            (Some(record_dir), None) => Some(HttpRecording::Record(record_dir.clone())),
+            (None, None) => None,
+        }
+    }
+}
+
+// FNV-1a rather than the standard library's hasher like the cache uses, since recordings are kept and shared, and the
+// standard hasher may change between Rust versions
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+fn recorded_response_path(recording_dir: &Path, url: &str) -> PathBuf {
+    let name: String = regex!(r"[^\w\d-]+").replace_all(url, "_").chars().take(100).collect();
+    recording_dir.join(format!("{}_{:016x}.txt", name, stable_hash(url)))
+}
+
+fn record_response(recording_dir: &Path, url: &str, contents: &str) -> Result<()> {
+    let path = recorded_response_path(recording_dir, url);
+    let inner = || {
+        fs::create_dir_all(recording_dir)?;
+        write_file_atomically(&path, |file| Ok(io::Write::write_all(file, contents.as_bytes())?))
+    };
+    inner().with_context(|| format!("Failed to record response for {:?} to: {:?}", url, path))
+}
+
+impl CacheValidators {
+    fn from_headers(headers: &HeaderMap) -> CacheValidators {
+        let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+        CacheValidators {
+            etag: header(ETAG).map(str::to_string),
+            last_modified: header(LAST_MODIFIED).map(str::to_string),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    fn apply(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+}
+
+fn validators_path(cache_path: &Path) -> PathBuf {
+    let mut file_name = cache_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".validators.json");
+    cache_path.with_file_name(file_name)
+}
+
+// A missing or unreadable validators file only means the response is fetched in full
+fn read_validators(cache_path: &Path) -> CacheValidators {
+    fs::read_to_string(validators_path(cache_path))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn write_validators(cache_path: &Path, validators: &CacheValidators) -> Result<()> {
+    let path = validators_path(cache_path);
+    if validators.is_empty() {
+        return match fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        };
+    }
+    write_file_atomically(&path, |file| Ok(serde_json::to_writer(file, validators)?))
+}
+
+// Full jitter, so that routes whose requests failed together don't all retry together
+fn retry_delays(max_fetch_attempts: usize) -> impl Iterator<Item = std::time::Duration> {
+    strategy::ExponentialBackoff::from_millis(2)
+        .factor(FIRST_RETRY_DELAY_MILLIS / 2)
+        .max_delay(MAX_RETRY_DELAY)
+        .map(strategy::jitter)
+        .take(max_fetch_attempts.saturating_sub(1))
+}
+
+// Failures that the same request may not have a moment later.  Errors from transforming the response are retried too,
+// since BC Ferries sometimes serves its error page with a success status.
+fn is_transient_error(err: &Error) -> bool {
+    match err.downcast_ref::<reqwest::Error>() {
+        Some(err) => match err.status() {
+            Some(status) => status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS,
+            None => err.is_timeout() || err.is_connect() || err.is_body() || (err.is_request() && !err.is_builder()),
+        },
+        None => true,
+    }
+}
+
+fn replay_response(recording_dir: &Path, url: &str) -> Result<String> {
+    let path = recorded_response_path(recording_dir, url);
+    fs::read_to_string(&path).with_context(|| format!("No recorded response for {:?} at: {:?}", url, path))
 }
 
 impl<'a> Cache<'a> {
-    pub fn new(max_cache_age: Duration, project_dirs: &'a ProjectDirs) -> Cache<'a> {
+    pub fn new(
+        max_cache_age: Duration,
+        project_dirs: &'a ProjectDirs,
+        http_recording: Option<HttpRecording>,
+        max_fetch_attempts: usize,
+        host_delay: std::time::Duration,
+    ) -> Cache<'a> {
         let reqwest_client = reqwest::Client::new();
-        Cache { max_cache_age, project_dirs, reqwest_client }
+        Cache {
+            max_cache_age,
+            project_dirs,
+            reqwest_client,
+            http_recording,
+            max_fetch_attempts: max(max_fetch_attempts, 1),
+            host_delay,
+            next_request_times: Mutex::new(HashMap::new()),
+        }
     }
 
+    // Takes the host's next request slot and waits for it, so requests to the host are spaced out however many are
+    // waiting at once
+    async fn wait_for_host(&self, url: &str) {
+        let host = reqwest::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string)).unwrap_or_default();
+        let start_at = {
+            let mut next_request_times = self.next_request_times.lock().expect("Expect request times lock");
+            let now = Instant::now();
+            let start_at = next_request_times.get(&host).map_or(now, |&at| max(at, now));
+            next_request_times.insert(host, start_at + self.host_delay);
+            start_at
+        };
+        sleep_until(start_at).await;
+    }
+
+    // Sends the stale response's validators, if any, so that the server can answer that it hasn't changed rather than
+    // sending it again
     async fn fetch_retry_action<T, F>(
         &self,
         url: &str,
         retry_number: Arc<AtomicUsize>,
         transform: &F,
-    ) -> Result<(T, String)>
+        stale: Option<&StaleResponse>,
+    ) -> Result<(T, String, CacheValidators)>
     where
         F: Fn(String) -> Result<(T, String)>,
     {
         let retry_number = retry_number.fetch_add(1, atomic::Ordering::SeqCst) + 1;
         let inner = async {
-            let response = self.reqwest_client.get(url).send().await?.error_for_status()?;
-            transform(response.text().await?)
+            self.wait_for_host(url).await;
+            let mut request = self.reqwest_client.get(url);
+            if let Some(stale) = stale {
+                request = stale.validators.apply(request);
+            }
+            let response = request.send().await?;
+            let (contents, validators) = match stale {
+                Some(stale) if response.status() == StatusCode::NOT_MODIFIED => {
+                    info!("Not modified: {:?}", url);
+                    (stale.contents.clone(), stale.validators.clone())
+                }
+                _ => {
+                    let response = response.error_for_status()?;
+                    let validators = CacheValidators::from_headers(response.headers());
+                    (response.text().await?, validators)
+                }
+            };
+            let (value, contents) = transform(contents)?;
+            Ok((value, contents, validators))
         };
         let result = inner.await;
+        let max_retries = self.max_fetch_attempts - 1;
         match &result {
-            Err(err) if retry_number <= MAX_RETRIES => {
-                warn!("Will retry (#{} of {}) fetching {:?} due to: {}", retry_number, MAX_RETRIES, url, err)
+            Err(err) if retry_number <= max_retries && is_transient_error(err) => {
+                warn!("Will retry (#{} of {}) fetching {:?} due to: {}", retry_number, max_retries, url, err)
             }
             _ => {}
         }
@@ -48,6 +237,24 @@ impl<'a> Cache<'a> {
     }
 
     pub async fn fetch_url<T, F>(&self, url: &str, transform: F) -> Result<T>
+    where
+        F: Fn(String) -> Result<(T, String)>,
+    {
+        if let Some(HttpRecording::Replay(recording_dir)) = &self.http_recording {
+            info!("Replaying: {:?}", url);
+            let (value, _) = transform(replay_response(recording_dir, url)?)
+                .with_context(|| format!("Failed to replay URL: {:?}", url))?;
+            return Ok(value);
+        }
+        let (value, contents) = self.fetch_url_with_cache(url, transform).await?;
+        if let Some(HttpRecording::Record(recording_dir)) = &self.http_recording {
+            record_response(recording_dir, url, &contents)?;
+        }
+        Ok(value)
+    }
+
+    // Returns the contents as well as the value, so that cached responses are recorded too
+    async fn fetch_url_with_cache<T, F>(&self, url: &str, transform: F) -> Result<(T, String)>
     where
         F: Fn(String) -> Result<(T, String)>,
     {
@@ -57,25 +264,34 @@ impl<'a> Cache<'a> {
             let cache_filename = format!("{}_{}", regex!(r"[^\w\d-]+").replace_all(url, "_"), calculate_hash(&url));
             cache_path.push(&cache_filename);
             debug!("Cache path: {:?}", cache_path);
+            let mut stale = None;
             if let Ok(cache_metadata) = fs::metadata(&cache_path) {
                 let cache_modified_time: OffsetDateTime = cache_metadata.modified()?.into();
+                let contents = fs::read_to_string(&cache_path)?;
                 if OffsetDateTime::now_utc() - cache_modified_time < self.max_cache_age {
-                    if let Ok((cached_value, _)) = transform(fs::read_to_string(&cache_path)?) {
+                    if let Ok(cached) = transform(contents.clone()) {
                         info!("Using cache: {:?}", url);
-                        return Ok(cached_value);
+                        return Ok(cached);
                     }
                 }
+                let validators = read_validators(&cache_path);
+                if !validators.is_empty() {
+                    stale = Some(StaleResponse { contents, validators });
+                }
             }
             info!("Fetching: {:?}", url);
             let retry_number = Arc::new(AtomicUsize::new(0));
-            let (value, contents): (_, String) =
-                Retry::spawn(strategy::FibonacciBackoff::from_millis(5).factor(1000).take(MAX_RETRIES), || {
-                    self.fetch_retry_action(url, retry_number.clone(), &transform)
-                })
-                .await?;
+            let (value, contents, validators): (_, String, _) = RetryIf::spawn(
+                retry_delays(self.max_fetch_attempts),
+                || self.fetch_retry_action(url, retry_number.clone(), &transform, stale.as_ref()),
+                is_transient_error,
+            )
+            .await?;
+            // Rewritten even if not modified, so that the cache is fresh again
             fs::create_dir_all(self.project_dirs.cache_dir())?;
-            fs::write(&cache_path, &contents)?;
-            Ok(value) as Result<_>
+            write_file_atomically(&cache_path, |file| Ok(io::Write::write_all(file, contents.as_bytes())?))?;
+            write_validators(&cache_path, &validators)?;
+            Ok((value, contents)) as Result<_>
         };
         inner.await.with_context(|| format!("Failed to fetch URL with cache: {:?}", url))
     }
@@ -93,3 +309,66 @@ impl<'a> Cache<'a> {
         self.fetch_url(url, transform_html).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorded_responses() -> Result<()> {
+        let recording_dir = tempfile::tempdir()?;
+        let url = "https://www.bcferries.com/routes-fares/schedules/seasonal/SWB-TSA";
+        // Recordings are kept, so their names must not change between builds
+        assert_eq!(
+            recorded_response_path(recording_dir.path(), url).strip_prefix(recording_dir.path())?,
+            Path::new(&format!(
+                "https_www_bcferries_com_routes-fares_schedules_seasonal_SWB-TSA_{:016x}.txt",
+                stable_hash(url)
+            ))
+        );
+        assert_eq!(stable_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert!(replay_response(recording_dir.path(), url).is_err());
+        record_response(recording_dir.path(), url, "<html></html>")?;
+        assert_eq!(replay_response(recording_dir.path(), url)?, "<html></html>");
+        assert!(replay_response(recording_dir.path(), &format!("{}?departureDate=2022-06-01", url)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_validators() -> Result<()> {
+        let cache_dir = tempfile::tempdir()?;
+        let cache_path = cache_dir.path().join("https_www_bcferries_com_123");
+        assert_eq!(validators_path(&cache_path), cache_dir.path().join("https_www_bcferries_com_123.validators.json"));
+        assert!(read_validators(&cache_path).is_empty());
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, "\"abc\"".parse()?);
+        let validators = CacheValidators::from_headers(&headers);
+        assert_eq!(validators, CacheValidators { etag: Some("\"abc\"".to_string()), last_modified: None });
+        let request = validators.apply(reqwest::Client::new().get("https://www.bcferries.com/")).build()?;
+        assert_eq!(request.headers().get(IF_NONE_MATCH).map(|v| v.as_bytes()), Some(&b"\"abc\""[..]));
+        assert!(request.headers().get(IF_MODIFIED_SINCE).is_none());
+        write_validators(&cache_path, &validators)?;
+        assert_eq!(read_validators(&cache_path), validators);
+        // Responses without validators don't keep the old ones
+        write_validators(&cache_path, &CacheValidators::default())?;
+        assert!(read_validators(&cache_path).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_retry_delays() -> Result<()> {
+        assert_eq!(retry_delays(1).count(), 0);
+        let delays: Vec<_> = retry_delays(10).collect();
+        assert_eq!(delays.len(), 9);
+        assert!(delays.iter().all(|&delay| delay <= MAX_RETRY_DELAY));
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_transient_error() -> Result<()> {
+        assert!(is_transient_error(&anyhow!("HTML contains error text")));
+        let builder_err = reqwest::Client::new().get("not a URL").build().expect_err("Expect invalid URL to fail");
+        assert!(!is_transient_error(&builder_err.into()));
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use brotli2::write::BrotliEncoder;
+use flate2::write::GzEncoder;
+
+use crate::prelude::*;
+
+// Highest quality, since the output is compressed once and downloaded many times
+const BROTLI_QUALITY: u32 = 11;
+
+pub fn compress(body: &[u8], encoding: ContentEncoding) -> Vec<u8> {
+    let inner = || -> io::Result<_> {
+        match encoding {
+            ContentEncoding::Brotli => {
+                let mut encoder = BrotliEncoder::new(Vec::new(), BROTLI_QUALITY);
+                io::Write::write_all(&mut encoder, body)?;
+                encoder.finish()
+            }
+            ContentEncoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::best());
+                io::Write::write_all(&mut encoder, body)?;
+                encoder.finish()
+            }
+        }
+    };
+    inner().unwrap_or_else(|err| panic!("Expect {} compression in memory to succeed: {}", encoding, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use brotli2::read::BrotliDecoder;
+    use flate2::read::GzDecoder;
+
+    #[test]
+    fn test_compress() -> Result<()> {
+        let body = r#"[{"terminal_pair":"SWB-TSA"}]"#.repeat(100);
+        for encoding in ContentEncoding::iter() {
+            let compressed = compress(body.as_bytes(), encoding);
+            assert!(compressed.len() < body.len() / 10, "Expect {} to compress the repeated body", encoding);
+            let mut decompressed = String::new();
+            match encoding {
+                ContentEncoding::Brotli => {
+                    io::Read::read_to_string(&mut BrotliDecoder::new(&compressed[..]), &mut decompressed)?
+                }
+                ContentEncoding::Gzip => {
+                    io::Read::read_to_string(&mut GzDecoder::new(&compressed[..]), &mut decompressed)?
+                }
+            };
+            assert_eq!(decompressed, body);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use clap::{Arg, ArgMatches, Command, ValueSource};
+use std::path::Path;
+
+use crate::prelude::*;
+
+// Settings for a deployment, so that it doesn't need a long command line.  Each is named after the command-line option
+// it stands for, with underscores or dashes, for example:
+//
+//     terminals = ["SWB-TSA", "TSA-SWB"]
+//     output_s3_bucket = "ferries-data"
+//     concurrency = 8
+//     service_notices = true
+//
+// Options given on the command line override the file's.
+pub const DEFAULT_CONFIG_FILE: &str = "scraper.toml";
+
+// Not a setting itself, since a config file can't name another
+const CONFIG_LONG: &str = "config";
+
+// What the command line gives on its own.  Errors are left for when the arguments are parsed for real, with the
+// settings added.
+fn command_line_matches(command: &Command, cli_args: &[String]) -> ArgMatches {
+    command
+        .clone()
+        .ignore_errors(true)
+        .try_get_matches_from(iter::once(command.get_name().to_string()).chain(cli_args.iter().cloned()))
+        .unwrap_or_default()
+}
+
+// Explicitly passed config files must exist, but the default one is optional
+fn config_path(matches: &ArgMatches) -> Option<(PathBuf, bool)> {
+    match matches.value_of(CONFIG_LONG) {
+        Some(path) => Some((PathBuf::from(path), true)),
+        None => Path::new(DEFAULT_CONFIG_FILE).exists().then(|| (PathBuf::from(DEFAULT_CONFIG_FILE), false)),
+    }
+}
+
+fn is_given(arg: &Arg, matches: &ArgMatches) -> bool {
+    matches.value_source(arg.get_id()) == Some(ValueSource::CommandLine)
+}
+
+fn value_text(key: &str, value: &toml::Value) -> Result<String> {
+    match value {
+        toml::Value::String(text) => Ok(text.clone()),
+        toml::Value::Integer(number) => Ok(number.to_string()),
+        toml::Value::Float(number) => Ok(number.to_string()),
+        _ => bail!("Setting {:?} must be a string or number, or a list of them", key),
+    }
+}
+
+// The settings as command-line arguments, leaving out those that the command line already has
+fn settings_args(command: &Command, settings: &toml::value::Table, matches: &ArgMatches) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    for (key, value) in settings {
+        let long = key.replace('_', "-");
+        let arg = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long.as_str()) && long != CONFIG_LONG)
+            .ok_or_else(|| anyhow!("Unknown setting {:?}", key))?;
+        if is_given(arg, matches) {
+            continue;
+        }
+        let option = format!("--{}", long);
+        match value {
+            toml::Value::Boolean(true) => args.push(option),
+            toml::Value::Boolean(false) => {}
+            toml::Value::Array(values) => {
+                for value in values {
+                    args.extend([option.clone(), value_text(key, value)?]);
+                }
+            }
+            value => args.extend([option, value_text(key, value)?]),
+        }
+    }
+    Ok(args)
+}
+
+// Command-line arguments with the config file's settings inserted after the program name, for parsing as usual
+pub fn args_with_config(command: &Command, args: Vec<String>) -> Result<Vec<String>> {
+    let matches = command_line_matches(command, args.get(1..).unwrap_or_default());
+    let (path, is_explicit) = match config_path(&matches) {
+        Some(config_path) => config_path,
+        None => return Ok(args),
+    };
+    let inner = || {
+        let settings: toml::value::Table = toml::from_str(&fs::read_to_string(&path)?)?;
+        settings_args(command, &settings, &matches)
+    };
+    let settings_args = inner().with_context(|| {
+        format!("Failed to read {}config file: {:?}", if is_explicit { "" } else { "default " }, path)
+    })?;
+    Ok(args.iter().take(1).cloned().chain(settings_args).chain(args.iter().skip(1).cloned()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CliArgs;
+    use clap::CommandFactory;
+
+    fn args(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|text| text.to_string()).collect()
+    }
+
+    #[test]
+    fn test_config_path() -> Result<()> {
+        let command = CliArgs::command();
+        let matches = command_line_matches(&command, &args(&["--config=other.toml", "-t", "PVB-PSB"]));
+        assert_eq!(config_path(&matches), Some((PathBuf::from("other.toml"), true)));
+        let matches = command_line_matches(&command, &args(&["--config", "other.toml"]));
+        assert_eq!(config_path(&matches), Some((PathBuf::from("other.toml"), true)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_settings_args() -> Result<()> {
+        let command = CliArgs::command();
+        let settings: toml::value::Table = toml::from_str(
+            r#"
+                terminals = ["SWB-TSA", "TSA-SWB"]
+                max-cache-age = 24
+                output_file = "schedules.json"
+                service_notices = true
+                check_source_urls = false
+            "#,
+        )?;
+        assert_eq!(
+            settings_args(&command, &settings, &command_line_matches(&command, &[]))?,
+            args(&[
+                "--max-cache-age",
+                "24",
+                "--output-file",
+                "schedules.json",
+                "--service-notices",
+                "--terminals",
+                "SWB-TSA",
+                "--terminals",
+                "TSA-SWB",
+            ])
+        );
+        // The command line overrides settings by long or short option
+        let matches =
+            command_line_matches(&command, &args(&["-t", "PVB-PSB", "--max-cache-age=1", "--output-file", "x"]));
+        assert_eq!(settings_args(&command, &settings, &matches)?, args(&["--service-notices"]));
+        // Values that only come from defaults or the environment are still overridden by settings
+        let matches = command_line_matches(&command, &args(&["--output-file=x"]));
+        assert!(settings_args(&command, &settings, &matches)?.contains(&"--max-cache-age".to_string()));
+        let no_matches = command_line_matches(&command, &[]);
+        let unknown: toml::value::Table = toml::from_str("max_cache_hours = 24")?;
+        assert!(settings_args(&command, &unknown, &no_matches).is_err());
+        let config: toml::value::Table = toml::from_str(r#"config = "other.toml""#)?;
+        assert!(settings_args(&command, &config, &no_matches).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use crate::prelude::*;
+
+// Sailings of the items in schedules for the terminal pair that overlap the schedule, on any of the weekdays
+pub fn leg_sailings<'a>(
+    schedules: &'a [Schedule],
+    schedule: &Schedule,
+    terminal_pair: TerminalPair,
+    weekdays: &HashSet<Weekday>,
+) -> Vec<&'a Sailing> {
+    schedules
+        .iter()
+        .filter(|s| {
+            s.terminal_pair == terminal_pair
+                && s.operator == schedule.operator
+                && s.date_range.from <= schedule.date_range.to
+                && s.date_range.to >= schedule.date_range.from
+        })
+        .flat_map(|s| &s.items)
+        .filter(|item| item.weekdays.keys().any(|weekday| weekdays.contains(weekday)))
+        .map(|item| &item.sailing)
+        .collect()
+}
+
+// Descriptions of thru-fare sailings whose second leg departs before the first leg could arrive, which means the source
+// schedules disagree with each other
+fn impossible_connections(schedules: &[Schedule]) -> Vec<String> {
+    let mut descriptions = Vec::new();
+    for schedule in schedules {
+        for item in &schedule.items {
+            let via = match item.sailing.thrufare_via(schedule.terminal_pair) {
+                Some(via) => via,
+                None => continue,
+            };
+            let weekdays: HashSet<_> = item.weekdays.keys().copied().collect();
+            let first_leg_terminal_pair = TerminalPair { from: schedule.terminal_pair.from, to: via };
+            let second_leg_terminal_pair = TerminalPair { from: via, to: schedule.terminal_pair.to };
+            if is_impossible_connection(
+                &item.sailing,
+                leg_sailings(schedules, schedule, first_leg_terminal_pair, &weekdays),
+                leg_sailings(schedules, schedule, second_leg_terminal_pair, &weekdays),
+            ) {
+                descriptions.push(format!(
+                    "{} {} sailing at {} connecting at {}",
+                    schedule.terminal_pair, schedule.date_range, item.sailing.depart_time, via
+                ));
+            }
+        }
+    }
+    descriptions
+}
+
+// Reports thru-fare connections that cannot be made as data errors, without failing the scrape; the frontend marks
+// these sailings too
+pub fn check_thrufare_connections(schedules: &[Schedule]) {
+    let descriptions = impossible_connections(schedules);
+    for description in &descriptions {
+        warn!("Impossible thru-fare connection: {}", description);
+    }
+    info!("Checked thru-fare connections and found {} impossible", descriptions.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(terminal_pair: TerminalPair, sailings: &[(Time, Time, &[Stop])]) -> Schedule {
+        Schedule {
+            date_range: DateRange { from: date!(2022 - 06 - 30), to: date!(2022 - 09 - 05) },
+            items: sailings
+                .iter()
+                .map(|&(depart_time, arrive_time, stops)| ScheduleItem {
+                    sailing: Sailing { depart_time, arrive_time, stops: stops.to_vec() },
+                    weekdays: HashMap::from([(Weekday::Monday, DateRestriction::All)]),
+                    notes: HashMap::new(),
+                    note_categories: HashMap::new(),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                })
+                .collect(),
+            ..Schedule::test_fixture(terminal_pair)
+        }
+    }
+
+    #[test]
+    fn test_impossible_connections() -> Result<()> {
+        let via_swartz_bay: &[Stop] = &[Stop { type_: StopType::Thrufare, terminal: Terminal::SWB }];
+        let schedules = [
+            schedule(
+                TerminalPair { from: Terminal::TSA, to: Terminal::POB },
+                &[(time!(7:00), time!(10:15), via_swartz_bay), (time!(9:00), time!(12:15), via_swartz_bay)],
+            ),
+            schedule(
+                TerminalPair { from: Terminal::TSA, to: Terminal::SWB },
+                &[(time!(7:00), time!(8:35), &[]), (time!(9:00), time!(10:35), &[])],
+            ),
+            schedule(
+                TerminalPair { from: Terminal::SWB, to: Terminal::POB },
+                &[(time!(9:00), time!(10:15), &[]), (time!(10:30), time!(12:15), &[])],
+            ),
+        ];
+        assert_eq!(
+            impossible_connections(&schedules),
+            ["TSA-POB 2022-06-30 - 2022-09-05 sailing at 9:00:00.0 connecting at SWB"]
+        );
+        Ok(())
+    }
+}
@@ -1,6 +1,55 @@
-use crate::imports::*;
+use crate::prelude::*;
 
 pub static HTML_ERROR_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"An error occurred, try again").expect("Expect HTML error regex to parse"));
 
+// Longer than any sailing on the southern routes, so longer durations indicate a parsing problem
+pub const MAX_PLAUSIBLE_SAILING_DURATION: Duration = Duration::hours(4);
+
+// The northern routes take most of a day to cross the Inside Passage or overnight to cross Hecate Strait
+pub const MAX_PLAUSIBLE_NORTHERN_SAILING_DURATION: Duration = Duration::hours(18);
+
 pub static DISABLED_TERMINAL_PAIRS: Lazy<HashSet<TerminalPair>> = Lazy::new(|| HashSet::from_iter([]));
+
+// Schedules that take effect part way through their first day without their page saying so (pages that do are parsed),
+// keyed by terminal pair and first date
+pub static SCHEDULE_EFFECTIVE_FROM_TIMES: Lazy<HashMap<(TerminalPair, Date), Time>> =
+    Lazy::new(|| HashMap::from_iter([]));
+
+// Old schedule source URL prefixes mapped to where BC Ferries moved them, so that scraping and the "original schedule"
+// links keep working after their site is restructured (`--check-source-urls` warns about redirects to add here)
+pub const SOURCE_URL_REDIRECTS: &[(&str, &str)] = &[];
+
+// Terminals of the routes in each group on the service notices page, by the group's name
+pub static SERVICE_NOTICE_ROUTE_GROUPS: Lazy<HashMap<&'static str, Vec<Terminal>>> = Lazy::new(|| {
+    HashMap::from_iter([
+        ("Metro Vancouver - Vancouver Island", vec![Terminal::TSA, Terminal::SWB]),
+        (
+            "Metro Vancouver - Southern Gulf Islands",
+            vec![Terminal::TSA, Terminal::PLH, Terminal::POB, Terminal::PSB, Terminal::PST, Terminal::PVB],
+        ),
+        (
+            "Vancouver Island - Southern Gulf Islands",
+            vec![Terminal::SWB, Terminal::PLH, Terminal::POB, Terminal::PSB, Terminal::PST, Terminal::PVB],
+        ),
+        ("Vancouver Island - Salt Spring Island", vec![Terminal::FUL, Terminal::SWB, Terminal::VES, Terminal::CFT]),
+        ("Vancouver Island - Mill Bay", vec![Terminal::BTW, Terminal::MIL]),
+        ("Vancouver Island - Thetis and Penelakut Islands", vec![Terminal::CHM, Terminal::THT, Terminal::PEN]),
+    ])
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_terminal_pairs_are_routes() -> Result<()> {
+        let configured = DISABLED_TERMINAL_PAIRS.iter().chain(SCHEDULE_EFFECTIVE_FROM_TIMES.keys().map(|(tp, _)| tp));
+        for terminal_pair in configured {
+            terminal_pair
+                .validate()
+                .with_context(|| format!("Configured terminal pair {} is not a route", terminal_pair))?;
+        }
+        Ok(())
+    }
+}
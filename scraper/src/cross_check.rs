@@ -0,0 +1,70 @@
+use crate::cache::*;
+use crate::macros::*;
+use crate::prelude::*;
+use crate::types::*;
+use crate::utils::*;
+
+// Every time of day mentioned in the response, since we don't depend on the exact shape of the undocumented API
+fn parse_response_times(contents: String) -> Result<(HashSet<Time>, String)> {
+    let mut times = HashSet::new();
+    for captures in regex!(r"(?i)\b(\d{1,2}:\d{2})(:\d{2})?( ?[ap]m)?\b").captures_iter(&contents) {
+        let opt_time = match captures.get(3) {
+            Some(period) => parse_schedule_time(&format!("{}{}", &captures[1], period.as_str())).ok(),
+            None => Time::parse(&captures[1], format_description!("[hour padding:none]:[minute]")).ok(),
+        };
+        times.extend(opt_time);
+    }
+    ensure!(!times.is_empty(), "Expect mobile API response to include some times");
+    Ok((times, contents))
+}
+
+async fn cross_check_date(cache: &Cache<'_>, url_template: &str, schedule: &Schedule, date: Date) -> Result<usize> {
+    let url = url_template
+        .replace("{from}", &schedule.terminal_pair.from.to_string())
+        .replace("{to}", &schedule.terminal_pair.to.to_string())
+        .replace("{date}", &format_iso8601_date(date));
+    let api_times = cache.fetch_url(&url, parse_response_times).await?;
+    let date_items: Vec<_> = schedule.items.iter().filter(|item| item.is_on_date(date)).collect();
+    let mut mismatch_count = 0;
+    for item in &date_items {
+        if !api_times.contains(&item.sailing.depart_time) {
+            warn!(
+                "Cross-check mismatch for {} on {}: scraped sailing at {} is missing from mobile API",
+                schedule.terminal_pair, date, item.sailing.depart_time
+            );
+            mismatch_count += 1;
+        }
+    }
+    let scraped_times: HashSet<_> =
+        date_items.iter().flat_map(|item| [item.sailing.depart_time, item.sailing.arrive_time]).collect();
+    for api_time in api_times.difference(&scraped_times).sorted() {
+        warn!(
+            "Cross-check mismatch for {} on {}: mobile API has a sailing at {} that was not scraped",
+            schedule.terminal_pair, date, api_time
+        );
+        mismatch_count += 1;
+    }
+    Ok(mismatch_count)
+}
+
+// Compares a sample of scraped sailings with the mobile API and reports mismatches, without failing the scrape
+pub async fn cross_check_schedules(options: &Options, cache: &Cache<'_>, url_template: &str, schedules: &[Schedule]) {
+    let today = today_vancouver();
+    let mut mismatch_count = 0;
+    let mut error_count = 0;
+    for schedule in schedules.iter().filter(|s| !s.items.is_empty()) {
+        for date in (0..options.cross_check_days).map(|days| today + Duration::days(days)) {
+            if !schedule.date_range.includes_date_inclusive(date) {
+                continue;
+            }
+            match cross_check_date(cache, url_template, schedule, date).await {
+                Ok(count) => mismatch_count += count,
+                Err(err) => {
+                    warn!("Failed to cross-check {} on {}: {:?}", schedule.terminal_pair, date, err);
+                    error_count += 1;
+                }
+            }
+        }
+    }
+    info!("Cross-check against mobile API found {} mismatches ({} errors)", mismatch_count, error_count);
+}
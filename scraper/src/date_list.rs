@@ -0,0 +1,385 @@
+use crate::prelude::*;
+
+// Whether a date list's dates are the only ones a sailing runs on, or the ones it doesn't
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DateQualifier {
+    Only,
+    Except,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MonthDay {
+    pub month: Month,
+    pub day: u8,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DateListItem {
+    // A date, with the weekday it was written with, if any, to check that it is in the expected year
+    Date(MonthDay, Option<Weekday>),
+    Range(MonthDay, MonthDay),
+    // Every one of the weekday's dates, which is kept as the weekday rather than expanded into dates
+    Weekday(Weekday),
+    // Statutory holidays, or only those on the weekday, as in "holiday Mondays"
+    Holidays(Option<Weekday>),
+}
+
+// Dates as BC Ferries writes them in annotations, such as "Only on: Oct 10–14, 21 & Nov 2" or "Sat, Dec 24 only"
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DateList {
+    pub qualifier: Option<DateQualifier>,
+    pub items: Vec<DateListItem>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Token<'a> {
+    Month(Month),
+    // Plural weekdays, like "Saturdays", are never attached to a date
+    Weekday(Weekday, bool),
+    Number(u16),
+    Holiday,
+    Separator,
+    RangeTo,
+    Word(&'a str),
+}
+
+fn month_for_word(word: &str) -> Option<Month> {
+    let mut month = Month::January;
+    for _ in 0..12 {
+        if month.to_string().to_lowercase().starts_with(word) {
+            return Some(month);
+        }
+        month = month.next();
+    }
+    None
+}
+
+fn weekday_for_word(word: &str) -> Option<(Weekday, bool)> {
+    let (word, plural) = match word.strip_suffix("days") {
+        Some(prefix) => (Cow::Owned(format!("{}day", prefix)), true),
+        None => (Cow::Borrowed(word), false),
+    };
+    let mut weekday = Weekday::Monday;
+    for _ in 0..7 {
+        if weekday.to_string().to_lowercase().starts_with(word.as_ref()) {
+            return Some((weekday, plural));
+        }
+        weekday = weekday.next();
+    }
+    None
+}
+
+fn word_token(word: &str) -> Token<'_> {
+    let lowercase_word = word.to_lowercase();
+    match lowercase_word.as_str() {
+        "and" => Token::Separator,
+        "to" | "through" | "thru" => Token::RangeTo,
+        "holiday" | "holidays" => Token::Holiday,
+        "plus" => Token::Separator,
+        w if w.len() >= 3 => month_for_word(w)
+            .map(Token::Month)
+            .or_else(|| weekday_for_word(w).map(|(weekday, plural)| Token::Weekday(weekday, plural)))
+            .unwrap_or(Token::Word(word)),
+        _ => Token::Word(word),
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_alphabetic() || c.is_ascii_digit() {
+            let mut end = start + c.len_utf8();
+            while let Some(&(i, next)) = chars.peek() {
+                if next.is_alphabetic() != c.is_alphabetic() || !(next.is_alphabetic() || next.is_ascii_digit()) {
+                    break;
+                }
+                end = i + next.len_utf8();
+                chars.next();
+            }
+            let token_text = &text[start..end];
+            // Every holiday in the calendar is statutory, so saying so changes nothing
+            if ["stat", "statutory"].iter().any(|w| token_text.eq_ignore_ascii_case(w)) {
+                continue;
+            }
+            tokens.push(if c.is_alphabetic() {
+                word_token(token_text)
+            } else {
+                Token::Number(token_text.parse().with_context(|| format!("Invalid number: {:?}", token_text))?)
+            });
+        } else {
+            match c {
+                ',' | '&' | ';' => tokens.push(Token::Separator),
+                '-' | '–' | '—' => tokens.push(Token::RangeTo),
+                '.' | ':' => {}
+                c if c.is_whitespace() => {}
+                c => bail!("Unexpected character: {:?}", c),
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_word(token: Option<&Token>, expected: &str) -> bool {
+    matches!(token, Some(Token::Word(word)) if word.eq_ignore_ascii_case(expected))
+}
+
+// Years are left out, since the schedule's date range decides which year each date is in
+fn is_year(token: Option<&Token>) -> bool {
+    matches!(token, Some(Token::Number(number)) if *number >= 1000)
+}
+
+fn is_date_start(token: Option<&Token>) -> bool {
+    matches!(token, Some(Token::Month(_)) | Some(Token::Number(_))) && !is_year(token)
+}
+
+fn parse_items(tokens: &[Token]) -> Result<Vec<DateListItem>> {
+    let mut items = Vec::new();
+    let mut month = None;
+    let mut date_weekday = None;
+    let mut index = 0;
+    while let Some(&token) = tokens.get(index) {
+        index += 1;
+        match token {
+            Token::Separator => {}
+            Token::Number(_) if is_year(Some(&token)) => {}
+            Token::Month(m) => {
+                ensure!(matches!(tokens.get(index), Some(Token::Number(_))), "Expect day after month {}", m);
+                month = Some(m);
+            }
+            Token::Number(number) => {
+                let day = u8::try_from(number).with_context(|| format!("Invalid day: {}", number))?;
+                // A day before the month, such as "10 Oct", unless it follows a month itself, as in "Oct 10 Nov 2"
+                let from = match (tokens.get(index), index.checked_sub(2).and_then(|i| tokens.get(i))) {
+                    (Some(&Token::Month(m)), previous) if !matches!(previous, Some(Token::Month(_))) => {
+                        index += 1;
+                        month = Some(m);
+                        MonthDay { month: m, day }
+                    }
+                    _ => MonthDay { month: month.ok_or_else(|| anyhow!("Expect month before day {}", day))?, day },
+                };
+                if tokens.get(index) == Some(&Token::RangeTo) {
+                    index += 1;
+                    let to_month = match tokens.get(index) {
+                        Some(&Token::Month(m)) => {
+                            index += 1;
+                            month = Some(m);
+                            m
+                        }
+                        _ => from.month,
+                    };
+                    let to_day = match tokens.get(index) {
+                        Some(&Token::Number(to_day)) => u8::try_from(to_day)?,
+                        other => bail!("Expect day at end of range, not {:?}", other),
+                    };
+                    index += 1;
+                    items.push(DateListItem::Range(from, MonthDay { month: to_month, day: to_day }));
+                    date_weekday = None;
+                } else {
+                    items.push(DateListItem::Date(from, date_weekday.take()));
+                }
+            }
+            Token::Weekday(weekday, plural) => {
+                let next = tokens.get(index);
+                let attached = is_date_start(next)
+                    || (!plural && next == Some(&Token::Separator) && is_date_start(tokens.get(index + 1)));
+                if attached {
+                    date_weekday = Some(weekday);
+                } else {
+                    items.push(DateListItem::Weekday(weekday));
+                }
+            }
+            Token::Holiday => match tokens.get(index) {
+                Some(&Token::Weekday(weekday, _)) => {
+                    index += 1;
+                    items.push(DateListItem::Holidays(Some(weekday)));
+                }
+                _ => items.push(DateListItem::Holidays(None)),
+            },
+            Token::RangeTo => bail!("Expect date before range"),
+            Token::Word(word) => bail!("Unexpected word: {:?}", word),
+        }
+    }
+    ensure!(!items.is_empty(), "Expect at least one date");
+    Ok(items)
+}
+
+// Parses a list of dates, with an optional "Only", "Except", or "Not Available" before it, or "only" after it
+pub fn parse_date_list(text: &str) -> Result<DateList> {
+    let inner = || {
+        let tokens = tokenize(text)?;
+        let mut tokens = tokens.as_slice();
+        let mut qualifier = None;
+        if is_word(tokens.first(), "only") {
+            qualifier = Some(DateQualifier::Only);
+            tokens = &tokens[1..];
+        } else if is_word(tokens.first(), "except") {
+            qualifier = Some(DateQualifier::Except);
+            tokens = &tokens[1..];
+        } else if is_word(tokens.first(), "not") && is_word(tokens.get(1), "available") {
+            qualifier = Some(DateQualifier::Except);
+            tokens = &tokens[2..];
+        }
+        if qualifier.is_some() && is_word(tokens.first(), "on") {
+            tokens = &tokens[1..];
+        }
+        if qualifier.is_none() && is_word(tokens.last(), "only") {
+            qualifier = Some(DateQualifier::Only);
+            tokens = &tokens[..tokens.len() - 1];
+        }
+        Ok(DateList { qualifier, items: parse_items(tokens)? }) as Result<_>
+    };
+    inner().with_context(|| format!("Failed to parse date list: {:?}", text))
+}
+
+impl DateList {
+    pub fn weekdays(&self) -> impl Iterator<Item = Weekday> + '_ {
+        self.items.iter().filter_map(|item| match item {
+            DateListItem::Weekday(weekday) => Some(*weekday),
+            _ => None,
+        })
+    }
+
+    // The list's dates in the schedule's date range, except for its weekdays.  Dates outside it are skipped with a
+    // warning, since schedules sometimes list dates from the period before or after.
+    pub fn dates_within(&self, date_range: &DateRange) -> Result<Vec<Date>> {
+        let resolve = |month_day: MonthDay| -> Result<Option<Date>> {
+            let MonthDay { month, day } = month_day;
+            // 2020 is a leap year, so Feb 29 is valid
+            Date::from_calendar_date(2020, month, day).with_context(|| format!("Invalid date: {} {}", month, day))?;
+            let date = date_range.date_within(month, day);
+            if date.is_none() {
+                warn!("Date is outside date range of schedule ({}): {} {}", date_range, month, day);
+            }
+            Ok(date)
+        };
+        let mut dates = Vec::new();
+        for &item in &self.items {
+            match item {
+                DateListItem::Date(month_day, weekday) => {
+                    if let Some(date) = resolve(month_day)? {
+                        if let Some(weekday) = weekday {
+                            ensure!(date.weekday() == weekday, "Expect {} to be a {}", date, weekday);
+                        }
+                        dates.push(date);
+                    }
+                }
+                DateListItem::Range(from, to) => {
+                    if let (Some(from), Some(to)) = (resolve(from)?, resolve(to)?) {
+                        ensure!(from <= to, "Expect range to end after it starts: {} to {}", from, to);
+                        dates.extend(DateRange { from, to }.iter_days());
+                    }
+                }
+                DateListItem::Weekday(_) => {}
+                DateListItem::Holidays(weekday) => {
+                    let holidays =
+                        (date_range.from.year()..=date_range.to.year()).flat_map(bc_holidays).map(|(date, _)| date);
+                    dates.extend(holidays.filter(|date| {
+                        date_range.includes_date_inclusive(*date) && weekday.map_or(true, |w| date.weekday() == w)
+                    }));
+                }
+            }
+        }
+        Ok(dates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn month_day(month: Month, day: u8) -> MonthDay {
+        MonthDay { month, day }
+    }
+
+    #[test]
+    fn test_parse_date_list() -> Result<()> {
+        let date = |month, day| DateListItem::Date(month_day(month, day), None);
+        let cases = [
+            ("Oct 10", None, vec![date(Month::October, 10)]),
+            ("Oct10, Nov2", None, vec![date(Month::October, 10), date(Month::November, 2)]),
+            ("Oct 10 Nov 2", None, vec![date(Month::October, 10), date(Month::November, 2)]),
+            ("10 Oct", None, vec![date(Month::October, 10)]),
+            ("Jan 9, 16 & Apr 3", None, vec![date(Month::January, 9), date(Month::January, 16), date(Month::April, 3)]),
+            (
+                "Dec 24, 25, and 31, 2022",
+                None,
+                vec![date(Month::December, 24), date(Month::December, 25), date(Month::December, 31)],
+            ),
+            ("April 7 and Sept 4", None, vec![date(Month::April, 7), date(Month::September, 4)]),
+            (
+                "Only on: Oct 10–14, 21",
+                Some(DateQualifier::Only),
+                vec![
+                    DateListItem::Range(month_day(Month::October, 10), month_day(Month::October, 14)),
+                    date(Month::October, 21),
+                ],
+            ),
+            (
+                "Except Oct 30 - Nov 2",
+                Some(DateQualifier::Except),
+                vec![DateListItem::Range(month_day(Month::October, 30), month_day(Month::November, 2))],
+            ),
+            (
+                "Not Available on Dec 28 to Jan 3",
+                Some(DateQualifier::Except),
+                vec![DateListItem::Range(month_day(Month::December, 28), month_day(Month::January, 3))],
+            ),
+            (
+                "Sat, Dec 24 only",
+                Some(DateQualifier::Only),
+                vec![DateListItem::Date(month_day(Month::December, 24), Some(Weekday::Saturday))],
+            ),
+            (
+                "Except Saturdays & Dec 26",
+                Some(DateQualifier::Except),
+                vec![DateListItem::Weekday(Weekday::Saturday), date(Month::December, 26)],
+            ),
+            ("Only Sundays.", Some(DateQualifier::Only), vec![DateListItem::Weekday(Weekday::Sunday)]),
+            (
+                "Fridays and holiday Mondays only",
+                Some(DateQualifier::Only),
+                vec![DateListItem::Weekday(Weekday::Friday), DateListItem::Holidays(Some(Weekday::Monday))],
+            ),
+            ("Except statutory holidays", Some(DateQualifier::Except), vec![DateListItem::Holidays(None)]),
+            (
+                "Saturdays plus holiday Mondays only",
+                Some(DateQualifier::Only),
+                vec![DateListItem::Weekday(Weekday::Saturday), DateListItem::Holidays(Some(Weekday::Monday))],
+            ),
+        ];
+        for (text, qualifier, items) in cases {
+            assert_eq!(parse_date_list(text)?, DateList { qualifier, items }, "{:?}", text);
+        }
+        for text in ["", "Only", "Oct", "10", "Oct 10 - ", "Foot passengers only", "Oct 10 (tentative)"] {
+            assert!(parse_date_list(text).is_err(), "{:?}", text);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_dates_within() -> Result<()> {
+        let date_range = DateRange { from: date!(2022 - 12 - 20), to: date!(2023 - 01 - 10) };
+        assert_eq!(
+            parse_date_list("Dec 30 - Jan 2, Jan 9 & Feb 1")?.dates_within(&date_range)?,
+            [
+                date!(2022 - 12 - 30),
+                date!(2022 - 12 - 31),
+                date!(2023 - 01 - 01),
+                date!(2023 - 01 - 02),
+                date!(2023 - 01 - 09)
+            ]
+        );
+        let date_list = parse_date_list("Saturdays & holidays")?;
+        assert_eq!(date_list.weekdays().collect::<Vec<_>>(), [Weekday::Saturday]);
+        assert_eq!(
+            date_list.dates_within(&date_range)?,
+            [date!(2022 - 12 - 25), date!(2022 - 12 - 26), date!(2023 - 01 - 01), date!(2023 - 01 - 02)]
+        );
+        assert_eq!(parse_date_list("Sat, Dec 24")?.dates_within(&date_range)?, [date!(2022 - 12 - 24)]);
+        // The weekday doesn't match, so the schedule is probably for another year
+        assert!(parse_date_list("Sun, Dec 24")?.dates_within(&date_range).is_err());
+        assert!(parse_date_list("Feb 30")?.dates_within(&date_range).is_err());
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use crate::annotations::*;
-use crate::imports::*;
 use crate::macros::*;
+use crate::prelude::*;
 use crate::utils::*;
 
 #[derive(Debug)]
@@ -8,6 +8,7 @@ pub struct DepartTimeAndRowAnnotations {
     pub time: Time,
     pub row_dates: AnnotationDates,
     pub row_notes: AnnotationNotes,
+    pub row_stops: HashMap<Terminal, AnnotationDates>,
 }
 
 impl DepartTimeAndRowAnnotations {
@@ -16,6 +17,7 @@ impl DepartTimeAndRowAnnotations {
         let mut row_dates = AnnotationDates::new();
         let mut row_dates_by_time = HashMap::new();
         let mut row_notes = AnnotationNotes::new();
+        let mut row_stops = HashMap::new();
         let text = if star_suffix_re.is_match(orig_text) {
             row_dates.extend(&annotations.star_dates);
             row_dates_by_time.extend(&annotations.star_dates_by_time);
@@ -23,6 +25,7 @@ impl DepartTimeAndRowAnnotations {
         } else {
             row_dates.extend(&annotations.all_dates);
             row_notes.extend(annotations.all_notes.clone());
+            row_stops.extend(annotations.all_stops.clone());
             Cow::from(orig_text)
         };
         let depart_time = parse_schedule_time(&text)
@@ -30,6 +33,6 @@ impl DepartTimeAndRowAnnotations {
         if let Some(time_date_restriction) = row_dates_by_time.get(&depart_time) {
             row_dates.extend(time_date_restriction);
         }
-        Ok(DepartTimeAndRowAnnotations { time: depart_time, row_dates, row_notes })
+        Ok(DepartTimeAndRowAnnotations { time: depart_time, row_dates, row_notes, row_stops })
     }
 }
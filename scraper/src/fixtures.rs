@@ -0,0 +1,193 @@
+use std::env;
+use std::path::Path;
+
+use crate::prelude::*;
+use crate::scraper::*;
+use crate::utils::*;
+
+// Saved schedule pages, replayed through the schedule parser and compared with snapshots of what they parsed to, so
+// that changes to table and annotation parsing can be checked against real schedules without scraping.  A fixture
+// directory has an index of where each page came from, and for each page its HTML and its snapshot.
+
+const INDEX_FILE_NAME: &str = "index.json";
+
+// Set to rewrite the snapshots that differ, once the differences are checked to be improvements
+const UPDATE_SNAPSHOTS_ENV: &str = "UPDATE_FIXTURE_SNAPSHOTS";
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FixtureSource {
+    pub name: String,
+    pub terminal_pair: TerminalPair,
+    pub source_url: String,
+    pub schedule_path_query: String,
+    // Replaces the parse time in the snapshot, which would otherwise differ every time
+    pub recorded_at: OffsetDateTime,
+}
+
+// Failures are snapshotted too, so a page that the parser can't handle yet can be saved and its fix checked
+#[derive(Debug, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+enum FixtureSnapshot {
+    Parsed { schedule: Box<Schedule>, unrecognized: Vec<String> },
+    Failed { error: String },
+}
+
+impl FixtureSource {
+    pub fn new(
+        terminal_pair: TerminalPair,
+        source_url: &str,
+        schedule_path_query: &str,
+        recorded_at: OffsetDateTime,
+    ) -> FixtureSource {
+        let name = match parse_schedule_path_query(schedule_path_query) {
+            Ok(date_range) => format!("{}_{}", terminal_pair, format_iso8601_date(date_range.from)),
+            Err(_) => format!("{}_{}", terminal_pair, recorded_at.unix_timestamp()),
+        };
+        FixtureSource {
+            name,
+            terminal_pair,
+            source_url: source_url.to_string(),
+            schedule_path_query: schedule_path_query.to_string(),
+            recorded_at,
+        }
+    }
+}
+
+fn read_index(fixtures_dir: &Path) -> Result<Vec<FixtureSource>> {
+    let path = fixtures_dir.join(INDEX_FILE_NAME);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let inner = || -> Result<_> { Ok(serde_json::from_reader(fs::File::open(&path)?)?) };
+    inner().with_context(|| format!("Failed to read fixtures index: {:?}", path))
+}
+
+// Each recorded page's HTML, with where it came from
+pub fn read_fixture_pages(fixtures_dir: &Path) -> Result<Vec<(FixtureSource, String)>> {
+    read_index(fixtures_dir)?
+        .into_iter()
+        .map(|source| {
+            let html_path = fixtures_dir.join(format!("{}.html", source.name));
+            let html = fs::read_to_string(&html_path).with_context(|| format!("Failed to read: {:?}", html_path))?;
+            Ok((source, html))
+        })
+        .collect()
+}
+
+// Always lenient, so that an annotation text that stops being recognized shows up in the snapshot rather than failing
+// the whole page
+fn replay_fixture(source: &FixtureSource, html: &str) -> FixtureSnapshot {
+    let document = Html::parse_document(html);
+    let result = parse_schedule_path_query(&source.schedule_path_query).and_then(|date_range| {
+        parse_schedule_page(&source.source_url, &document, source.terminal_pair, date_range, 0, true)
+    });
+    // Pages are replayed as a route's first schedule, which always has a table
+    match result.and_then(|opt_parsed| opt_parsed.context("Missing schedule")) {
+        Ok((mut schedule, unrecognized)) => {
+            schedule.refreshed_at = source.recorded_at;
+            FixtureSnapshot::Parsed { schedule: Box::new(schedule), unrecognized }
+        }
+        Err(err) => FixtureSnapshot::Failed { error: format!("{:#}", err) },
+    }
+}
+
+// Goes through a JSON value, whose objects have sorted keys, since the schedule's hash maps are in a different order
+// every run
+fn snapshot_json(snapshot: &FixtureSnapshot) -> String {
+    let value = serde_json::to_value(snapshot).expect("Expect fixture snapshot to serialize to JSON");
+    let mut json = serde_json::to_string_pretty(&value).expect("Expect fixture snapshot to serialize to JSON");
+    json.push('\n');
+    json
+}
+
+fn write_text(path: &Path, text: &str) -> Result<()> {
+    write_file_atomically(path, |file| Ok(io::Write::write_all(file, text.as_bytes())?))
+}
+
+// Saves a page as it was scraped, replacing any earlier recording of the same schedule
+pub fn record_fixture(fixtures_dir: &Path, source: &FixtureSource, document: &Html) -> Result<()> {
+    let inner = || {
+        fs::create_dir_all(fixtures_dir)?;
+        let html = document.root_element().html();
+        write_text(&fixtures_dir.join(format!("{}.html", source.name)), &html)?;
+        write_text(
+            &fixtures_dir.join(format!("{}.json", source.name)),
+            &snapshot_json(&replay_fixture(source, &html)),
+        )?;
+        let mut index = read_index(fixtures_dir)?;
+        index.retain(|s| s.name != source.name);
+        index.push(source.clone());
+        index.sort_by(|a, b| a.name.cmp(&b.name));
+        let index_json = serde_json::to_string_pretty(&index).expect("Expect fixtures index to serialize to JSON");
+        write_text(&fixtures_dir.join(INDEX_FILE_NAME), &format!("{}\n", index_json))?;
+        info!("Recorded fixture: {}", source.name);
+        Ok(()) as Result<_>
+    };
+    inner().with_context(|| format!("Failed to record fixture {} in: {:?}", source.name, fixtures_dir))
+}
+
+// Names of the fixtures whose pages no longer parse to their snapshots, with the first line that differs
+fn changed_fixtures(fixtures_dir: &Path, update: bool) -> Result<Vec<(String, String)>> {
+    let mut changed = Vec::new();
+    for (source, html) in read_fixture_pages(fixtures_dir)? {
+        let snapshot_path = fixtures_dir.join(format!("{}.json", source.name));
+        let expected = fs::read_to_string(&snapshot_path).unwrap_or_default();
+        let actual = snapshot_json(&replay_fixture(&source, &html));
+        if actual == expected {
+            continue;
+        }
+        let (actual_lines, expected_lines): (Vec<_>, Vec<_>) = (actual.lines().collect(), expected.lines().collect());
+        let line = (0..max(actual_lines.len(), expected_lines.len()))
+            .find(|&i| actual_lines.get(i) != expected_lines.get(i))
+            .unwrap_or(0);
+        let line_text = |lines: &[&str]| lines.get(line).map(|l| l.trim().to_string()).unwrap_or_default();
+        let difference = format!(
+            "line {}: expected {:?}, but parsed {:?}",
+            line + 1,
+            line_text(&expected_lines),
+            line_text(&actual_lines)
+        );
+        if update {
+            write_text(&snapshot_path, &actual)?;
+        }
+        changed.push((source.name, difference));
+    }
+    Ok(changed)
+}
+
+// Replays every fixture in the directory, as a check before scraping for real
+pub fn check_fixtures(fixtures_dir: &Path) -> Result<()> {
+    let update = env::var(UPDATE_SNAPSHOTS_ENV).is_ok();
+    let total = read_index(fixtures_dir)?.len();
+    let changed = changed_fixtures(fixtures_dir, update)?;
+    for (name, difference) in &changed {
+        if update {
+            warn!("Updated fixture snapshot {}: {}", name, difference);
+        } else {
+            error!("Fixture {} changed: {}", name, difference);
+        }
+    }
+    info!("Replayed {} fixtures", total);
+    ensure!(
+        update || changed.is_empty(),
+        "{} fixtures changed; set {} to update their snapshots",
+        changed.len(),
+        UPDATE_SNAPSHOTS_ENV
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_snapshots() -> Result<()> {
+        let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        assert!(!read_index(&fixtures_dir)?.is_empty(), "Expect fixtures to be recorded");
+        let update = env::var(UPDATE_SNAPSHOTS_ENV).is_ok();
+        let changed = changed_fixtures(&fixtures_dir, update)?;
+        assert!(update || changed.is_empty(), "Expect fixtures to parse to their snapshots: {:?}", changed);
+        Ok(())
+    }
+}
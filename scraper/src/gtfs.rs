@@ -0,0 +1,333 @@
+use std::io::Write;
+use std::path::Path;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+use crate::prelude::*;
+use crate::utils::*;
+
+// GTFS static feed of the schedules, so that transit apps and trip planners such as OpenTripPlanner can use them
+// directly (see https://gtfs.org/schedule/reference/):
+//
+// - Each terminal is a stop, identified by its terminal code
+// - Each terminal pair is a route, so each direction of a BC Ferries route is a separate GTFS route
+// - Each schedule item is a trip with its own service, whose calendar covers the schedule's date range on the item's
+//   weekdays, with `calendar_dates` for the `DateRestriction`s
+//
+// Thru-fare sailings are left out, since each of their legs is already a trip on its own route.  Sailings with stops
+// are trips on each terminal pair they are listed under, as in BC Ferries' schedules.
+
+const GTFS_TIMEZONE: &str = "America/Vancouver";
+
+// Route type for ferries
+const GTFS_ROUTE_TYPE_FERRY: &str = "4";
+
+// `calendar_dates` exception types
+const GTFS_SERVICE_ADDED: &str = "1";
+const GTFS_SERVICE_REMOVED: &str = "2";
+
+const GTFS_WEEKDAYS: [Weekday; 7] = [
+    Weekday::Monday,
+    Weekday::Tuesday,
+    Weekday::Wednesday,
+    Weekday::Thursday,
+    Weekday::Friday,
+    Weekday::Saturday,
+    Weekday::Sunday,
+];
+
+// Contents of one of the feed's CSV files
+struct GtfsTable {
+    file_name: &'static str,
+    header: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+impl GtfsTable {
+    fn new(file_name: &'static str, header: &'static [&'static str]) -> GtfsTable {
+        GtfsTable { file_name, header, rows: Vec::new() }
+    }
+
+    fn push<I: IntoIterator<Item = S>, S: Into<String>>(&mut self, row: I) {
+        let row: Vec<_> = row.into_iter().map(Into::into).collect();
+        assert_eq!(row.len(), self.header.len(), "Expect GTFS row to match header of {}", self.file_name);
+        self.rows.push(row);
+    }
+
+    fn to_csv(&self) -> String {
+        iter::once(self.header.iter().map(|field| csv_field(field)).join(","))
+            .chain(self.rows.iter().map(|row| row.iter().map(|field| csv_field(field)).join(",")))
+            .map(|line| line + "\r\n")
+            .collect()
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn gtfs_date(date: Date) -> String {
+    date.format(format_description!("[year][month][day]")).expect("Expect GTFS date to format")
+}
+
+// Times after midnight on the sailing's service day are past 24:00:00, as GTFS requires
+fn gtfs_time(time: Time, next_day: bool) -> String {
+    let hour = if next_day { time.hour() + 24 } else { time.hour() };
+    format!("{:02}:{:02}:{:02}", hour, time.minute(), time.second())
+}
+
+fn agency_id(operator: Operator) -> String {
+    format!("{:?}", operator)
+}
+
+fn agency_url(operator: Operator, schedules: &[Schedule]) -> Option<String> {
+    match operator {
+        Operator::BcFerries => Some(BCFERRIES_BASE_URL.to_string()),
+        _ => {
+            schedules.iter().find(|s| s.operator == operator && !s.source_url.is_empty()).map(|s| s.source_url.clone())
+        }
+    }
+}
+
+fn route_id(terminal_pair: TerminalPair, operator: Operator) -> String {
+    match operator {
+        Operator::BcFerries => terminal_pair.to_string(),
+        _ => format!("{}-{}", terminal_pair, agency_id(operator)),
+    }
+}
+
+// Calendar for the item's weekdays over the schedule, and the dates its `DateRestriction`s add or remove.  Weekdays
+// that only run on some dates are left out of the calendar, so that those dates are the only ones added.
+fn item_service(schedule: &Schedule, item: &ScheduleItem, service_id: &str) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut calendar = vec![service_id.to_string()];
+    let mut calendar_dates = Vec::new();
+    for weekday in GTFS_WEEKDAYS {
+        let runs_every_week = match item.weekdays.get(&weekday) {
+            None => false,
+            Some(DateRestriction::All) => true,
+            Some(DateRestriction::Except(dates)) => {
+                calendar_dates.extend(dates.iter().map(|&date| (date, GTFS_SERVICE_REMOVED)));
+                true
+            }
+            Some(DateRestriction::Only(dates)) => {
+                calendar_dates.extend(dates.iter().map(|&date| (date, GTFS_SERVICE_ADDED)));
+                false
+            }
+        };
+        calendar.push(if runs_every_week { "1" } else { "0" }.to_string());
+    }
+    calendar.push(gtfs_date(schedule.date_range.from));
+    calendar.push(gtfs_date(schedule.date_range.to));
+    let calendar_dates = calendar_dates
+        .into_iter()
+        .filter(|(date, _)| schedule.date_range.includes_date_inclusive(*date))
+        .sorted()
+        .map(|(date, exception_type)| vec![service_id.to_string(), gtfs_date(date), exception_type.to_string()])
+        .collect();
+    (calendar, calendar_dates)
+}
+
+fn gtfs_tables(schedules: &[Schedule]) -> Vec<GtfsTable> {
+    let mut agency = GtfsTable::new("agency.txt", &["agency_id", "agency_name", "agency_url", "agency_timezone"]);
+    let mut stops = GtfsTable::new("stops.txt", &["stop_id", "stop_name", "stop_lat", "stop_lon"]);
+    let mut routes =
+        GtfsTable::new("routes.txt", &["route_id", "agency_id", "route_short_name", "route_long_name", "route_type"]);
+    let mut trips = GtfsTable::new("trips.txt", &["route_id", "service_id", "trip_id", "trip_headsign"]);
+    let mut stop_times = GtfsTable::new(
+        "stop_times.txt",
+        &["trip_id", "arrival_time", "departure_time", "stop_id", "stop_sequence", "timepoint"],
+    );
+    let mut calendar = GtfsTable::new(
+        "calendar.txt",
+        &[
+            "service_id",
+            "monday",
+            "tuesday",
+            "wednesday",
+            "thursday",
+            "friday",
+            "saturday",
+            "sunday",
+            "start_date",
+            "end_date",
+        ],
+    );
+    let mut calendar_dates = GtfsTable::new("calendar_dates.txt", &["service_id", "date", "exception_type"]);
+
+    // Operators without a web site to link to can't be listed as agencies, so their schedules are left out
+    let operators: Vec<_> = schedules
+        .iter()
+        .map(|s| s.operator)
+        .unique()
+        .filter_map(|operator| agency_url(operator, schedules).map(|url| (operator, url)))
+        .collect();
+    for (operator, url) in &operators {
+        agency.push([agency_id(*operator), operator.name().to_string(), url.clone(), GTFS_TIMEZONE.to_string()]);
+    }
+    let schedules: Vec<_> = schedules
+        .iter()
+        .filter(|s| operators.iter().any(|(operator, _)| *operator == s.operator))
+        .sorted_by_key(|s| (s.terminal_pair, s.date_range.from))
+        .collect();
+
+    let mut used_terminals = HashSet::new();
+    let mut used_routes = HashSet::new();
+    for (schedule_index, schedule) in schedules.iter().enumerate() {
+        let route_id = route_id(schedule.terminal_pair, schedule.operator);
+        for (item_index, item) in schedule.items.iter().enumerate() {
+            if item.sailing.stops.iter().any(|stop| stop.type_ == StopType::Thrufare) {
+                continue;
+            }
+            let trip_id = format!("{}-{}-{}", route_id, schedule_index, item_index);
+            let (item_calendar, item_calendar_dates) = item_service(schedule, item, &trip_id);
+            calendar.push(item_calendar);
+            for row in item_calendar_dates {
+                calendar_dates.push(row);
+            }
+            trips.push([
+                route_id.clone(),
+                trip_id.clone(),
+                trip_id.clone(),
+                schedule.terminal_pair.to.name().to_string(),
+            ]);
+            let arrives_next_day = item.sailing.arrive_time < item.sailing.depart_time;
+            let trip_terminals = iter::once(schedule.terminal_pair.from)
+                .chain(item.sailing.stops.iter().map(|stop| stop.terminal))
+                .chain(iter::once(schedule.terminal_pair.to));
+            let stops_count = item.sailing.stops.len() + 2;
+            for (sequence, terminal) in trip_terminals.enumerate() {
+                used_terminals.insert(terminal);
+                // Only the departure and arrival are timed; the schedules don't give times at stops in between
+                let time = if sequence == 0 {
+                    Some(gtfs_time(item.sailing.depart_time, false))
+                } else if sequence == stops_count - 1 {
+                    Some(gtfs_time(item.sailing.arrive_time, arrives_next_day))
+                } else {
+                    None
+                };
+                stop_times.push([
+                    trip_id.clone(),
+                    time.clone().unwrap_or_default(),
+                    time.clone().unwrap_or_default(),
+                    terminal.to_string(),
+                    (sequence + 1).to_string(),
+                    if time.is_some() { "1" } else { "0" }.to_string(),
+                ]);
+            }
+        }
+        if used_routes.insert(route_id.clone()) {
+            routes.push([
+                route_id,
+                agency_id(schedule.operator),
+                schedule.terminal_pair.to_string(),
+                format!(
+                    "{} ({}) to {} ({})",
+                    schedule.terminal_pair.from.area().long_name(),
+                    schedule.terminal_pair.from.name(),
+                    schedule.terminal_pair.to.area().long_name(),
+                    schedule.terminal_pair.to.name()
+                ),
+                GTFS_ROUTE_TYPE_FERRY.to_string(),
+            ]);
+        }
+    }
+    for terminal in used_terminals.into_iter().sorted() {
+        let coordinates = terminal.coordinates();
+        stops.push([
+            terminal.to_string(),
+            terminal.name().to_string(),
+            format!("{:.4}", coordinates.latitude),
+            format!("{:.4}", coordinates.longitude),
+        ]);
+    }
+    vec![agency, stops, routes, trips, stop_times, calendar, calendar_dates]
+}
+
+pub fn write_gtfs_feed(path: &Path, schedules: &[Schedule]) -> Result<()> {
+    info!("Writing GTFS feed to: {:?}", path);
+    let tables = gtfs_tables(schedules);
+    write_file_atomically(path, |file| {
+        let mut zip = ZipWriter::new(file);
+        for table in &tables {
+            zip.start_file(table.file_name, FileOptions::default())
+                .with_context(|| format!("Failed to start GTFS file: {}", table.file_name))?;
+            zip.write_all(table.to_csv().as_bytes())
+                .with_context(|| format!("Failed to write GTFS file: {}", table.file_name))?;
+        }
+        zip.finish().context("Failed to finish GTFS zip")?;
+        Ok(())
+    })
+    .context("Failed to write GTFS feed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table<'a>(tables: &'a [GtfsTable], file_name: &str) -> &'a GtfsTable {
+        tables.iter().find(|t| t.file_name == file_name).expect("Expect GTFS table")
+    }
+
+    #[test]
+    fn test_gtfs_tables() -> Result<()> {
+        let terminal_pair = TerminalPair { from: Terminal::TSA, to: Terminal::POB };
+        let schedule = Schedule {
+            date_range: DateRange { from: date!(2022 - 06 - 30), to: date!(2022 - 09 - 05) },
+            items: vec![
+                ScheduleItem {
+                    sailing: Sailing {
+                        depart_time: time!(22:30),
+                        arrive_time: time!(0:40),
+                        stops: vec![Stop { type_: StopType::Stop, terminal: Terminal::PVB }],
+                    },
+                    weekdays: HashMap::from([
+                        (Weekday::Monday, DateRestriction::Except(HashSet::from([date!(2022 - 07 - 04)]))),
+                        (Weekday::Friday, DateRestriction::Only(HashSet::from([date!(2022 - 07 - 01)]))),
+                    ]),
+                    notes: HashMap::new(),
+                    note_categories: HashMap::new(),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                },
+                ScheduleItem {
+                    sailing: Sailing {
+                        depart_time: time!(7:00),
+                        arrive_time: time!(10:15),
+                        stops: vec![Stop { type_: StopType::Thrufare, terminal: Terminal::SWB }],
+                    },
+                    weekdays: HashMap::from([(Weekday::Monday, DateRestriction::All)]),
+                    notes: HashMap::new(),
+                    note_categories: HashMap::new(),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                },
+            ],
+            ..Schedule::test_fixture(terminal_pair)
+        };
+        let tables = gtfs_tables(&[schedule]);
+        assert_eq!(
+            table(&tables, "calendar.txt").to_csv(),
+            "service_id,monday,tuesday,wednesday,thursday,friday,saturday,sunday,start_date,end_date\r\n\
+            TSA-POB-0-0,1,0,0,0,0,0,0,20220630,20220905\r\n"
+        );
+        assert_eq!(
+            table(&tables, "calendar_dates.txt").rows,
+            [["TSA-POB-0-0", "20220701", "1"], ["TSA-POB-0-0", "20220704", "2"]]
+        );
+        assert_eq!(
+            table(&tables, "stop_times.txt").rows,
+            [
+                ["TSA-POB-0-0", "22:30:00", "22:30:00", "TSA", "1", "1"],
+                ["TSA-POB-0-0", "", "", "PVB", "2", "0"],
+                ["TSA-POB-0-0", "24:40:00", "24:40:00", "POB", "3", "1"],
+            ]
+        );
+        assert_eq!(table(&tables, "stops.txt").rows.len(), 3);
+        assert_eq!(csv_field("Says \"hi\", twice"), "\"Says \"\"hi\"\", twice\"");
+        Ok(())
+    }
+}
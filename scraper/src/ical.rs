@@ -0,0 +1,236 @@
+use std::path::Path;
+
+use crate::prelude::*;
+use crate::summaries::stops_text;
+use crate::utils::*;
+
+// iCalendar feeds of each route's sailings, so riders can subscribe to a route in their calendar app (see RFC 5545).
+// Each sailing on each date is its own event, since calendar apps handle the schedules' date restrictions poorly as
+// recurrence rules.  Events are marked as free time, because a subscription includes every sailing rather than just
+// the ones the rider is taking.
+
+const ICAL_TIMEZONE: &str = "America/Vancouver";
+
+// Daylight saving time rules for the timezone, which calendar apps need in order to place the local times
+const ICAL_VTIMEZONE: &[&str] = &[
+    "BEGIN:VTIMEZONE",
+    "TZID:America/Vancouver",
+    "BEGIN:DAYLIGHT",
+    "TZOFFSETFROM:-0800",
+    "TZOFFSETTO:-0700",
+    "TZNAME:PDT",
+    "DTSTART:19700308T020000",
+    "RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU",
+    "END:DAYLIGHT",
+    "BEGIN:STANDARD",
+    "TZOFFSETFROM:-0700",
+    "TZOFFSETTO:-0800",
+    "TZNAME:PST",
+    "DTSTART:19701101T020000",
+    "RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU",
+    "END:STANDARD",
+    "END:VTIMEZONE",
+];
+
+// Makes event UIDs globally unique, as the specification requires
+const ICAL_UID_DOMAIN: &str = "ferries.borsboom.io";
+
+// Longer lines must be folded onto continuation lines
+const ICAL_MAX_LINE_OCTETS: usize = 75;
+
+fn ical_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+fn ical_local_datetime(date: Date, time: Time) -> String {
+    format!(
+        "{}T{}",
+        date.format(format_description!("[year][month][day]")).expect("Expect iCalendar date to format"),
+        time.format(format_description!("[hour][minute][second]")).expect("Expect iCalendar time to format")
+    )
+}
+
+fn ical_utc_datetime(at: OffsetDateTime) -> String {
+    at.to_offset(time::UtcOffset::UTC)
+        .format(format_description!("[year][month][day]T[hour][minute][second]Z"))
+        .expect("Expect iCalendar timestamp to format")
+}
+
+// Folds the content line without splitting a UTF-8 character, and ends it with the CRLF that iCalendar requires
+fn push_line(ical: &mut String, line: &str) {
+    let mut line_octets = 0;
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > ICAL_MAX_LINE_OCTETS {
+            // The continuation line's leading space counts towards its length
+            ical.push_str("\r\n ");
+            line_octets = 1;
+        }
+        ical.push(c);
+        line_octets += c.len_utf8();
+    }
+    ical.push_str("\r\n");
+}
+
+fn event_description(schedule: &Schedule, item: &ScheduleItem, date: Date) -> String {
+    let mut lines = vec![stops_text(&item.sailing.stops)];
+    lines.extend(
+        item.notes
+            .iter()
+            .filter(|(_, date_restriction)| date_restriction.includes_date(date))
+            .map(|(text, _)| text.to_string())
+            .sorted(),
+    );
+    if schedule.operator != Operator::BcFerries {
+        lines.push(format!("Operated by {}", schedule.operator.name()));
+    }
+    if schedule.provisional_basis.is_some() {
+        lines.push("Provisional: based on the same period last year, until the schedule is published".to_string());
+    }
+    if !schedule.source_url.is_empty() {
+        lines.push(format!("Source: {}", schedule.source_url));
+    }
+    lines.join("\n")
+}
+
+fn push_event(ical: &mut String, terminal_pair: TerminalPair, schedule: &Schedule, item: &ScheduleItem, date: Date) {
+    let Sailing { depart_time, arrive_time, .. } = item.sailing;
+    let arrive_date = if arrive_time < depart_time { date.next_day().unwrap_or(date) } else { date };
+    let dtstart = ical_local_datetime(date, depart_time);
+    push_line(ical, "BEGIN:VEVENT");
+    push_line(ical, &format!("UID:{}-{}@{}", terminal_pair, dtstart, ICAL_UID_DOMAIN));
+    push_line(ical, &format!("DTSTAMP:{}", ical_utc_datetime(schedule.refreshed_at)));
+    push_line(ical, &format!("DTSTART;TZID={}:{}", ICAL_TIMEZONE, dtstart));
+    push_line(ical, &format!("DTEND;TZID={}:{}", ICAL_TIMEZONE, ical_local_datetime(arrive_date, arrive_time)));
+    push_line(
+        ical,
+        &format!(
+            "SUMMARY:{}",
+            ical_text(&format!("Ferry {} to {}", terminal_pair.from.name(), terminal_pair.to.name()))
+        ),
+    );
+    push_line(ical, &format!("LOCATION:{}", ical_text(&format!("{} ferry terminal", terminal_pair.from.name()))));
+    push_line(ical, &format!("DESCRIPTION:{}", ical_text(&event_description(schedule, item, date))));
+    if !schedule.source_url.is_empty() {
+        push_line(ical, &format!("URL:{}", schedule.source_url));
+    }
+    push_line(ical, "TRANSP:TRANSPARENT");
+    push_line(ical, "END:VEVENT");
+}
+
+fn route_calendar(terminal_pair: TerminalPair, schedules: &[&Schedule]) -> String {
+    let mut ical = String::new();
+    push_line(&mut ical, "BEGIN:VCALENDAR");
+    push_line(&mut ical, "VERSION:2.0");
+    push_line(&mut ical, &format!("PRODID:-//borsboom//{}//EN", env!("CARGO_PKG_NAME")));
+    push_line(&mut ical, "CALSCALE:GREGORIAN");
+    push_line(
+        &mut ical,
+        &format!(
+            "X-WR-CALNAME:{}",
+            ical_text(&format!("Ferries {} to {}", terminal_pair.from.name(), terminal_pair.to.name()))
+        ),
+    );
+    push_line(&mut ical, &format!("X-WR-TIMEZONE:{}", ICAL_TIMEZONE));
+    for line in ICAL_VTIMEZONE {
+        push_line(&mut ical, line);
+    }
+    let events = schedules
+        .iter()
+        .flat_map(|schedule| {
+            schedule.date_range.iter_days().flat_map(move |date| {
+                schedule
+                    .items
+                    .iter()
+                    .filter(move |item| {
+                        item.is_on_date(date) && schedule.is_effective_at(date, item.sailing.depart_time)
+                    })
+                    .map(move |item| (date, *schedule, item))
+            })
+        })
+        .sorted_by_key(|(date, _, item)| (*date, item.sailing.depart_time));
+    for (date, schedule, item) in events {
+        push_event(&mut ical, terminal_pair, schedule, item, date);
+    }
+    push_line(&mut ical, "END:VCALENDAR");
+    ical
+}
+
+// Writes a calendar of each route's sailings to the directory.  Calendars of routes that no longer have any schedules
+// are removed, so the directory always matches the latest run.
+pub fn write_route_calendars(dir: &Path, schedules: &[Schedule]) -> Result<()> {
+    let inner = || {
+        fs::create_dir_all(dir).context("Failed to create directory")?;
+        let route_schedules = into_vec_group_map(schedules.iter(), |s| s.terminal_pair);
+        for (terminal_pair, schedules) in &route_schedules {
+            let ical = route_calendar(*terminal_pair, schedules);
+            write_file_atomically(&dir.join(format!("{}.ics", terminal_pair)), |file| {
+                Ok(io::Write::write_all(file, ical.as_bytes())?)
+            })?;
+        }
+        remove_stale_route_files(dir, "ics", |tp| route_schedules.contains_key(&tp))?;
+        info!("Wrote iCalendar files of {} routes to: {:?}", route_schedules.len(), dir);
+        Ok(()) as Result<_>
+    };
+    inner().context("Failed to write route calendars")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_calendar() -> Result<()> {
+        let terminal_pair = TerminalPair { from: Terminal::TSA, to: Terminal::SWB };
+        let schedule = Schedule {
+            date_range: DateRange { from: date!(2022 - 06 - 30), to: date!(2022 - 07 - 06) },
+            items: vec![
+                ScheduleItem {
+                    sailing: Sailing { depart_time: time!(23:00), arrive_time: time!(0:35), stops: vec![] },
+                    weekdays: HashMap::from([(
+                        Weekday::Friday,
+                        DateRestriction::Except(HashSet::from([date!(2022 - 07 - 01)])),
+                    )]),
+                    notes: HashMap::new(),
+                    note_categories: HashMap::new(),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                },
+                ScheduleItem {
+                    sailing: Sailing { depart_time: time!(9:00), arrive_time: time!(10:35), stops: vec![] },
+                    weekdays: HashMap::from([
+                        (Weekday::Saturday, DateRestriction::All),
+                        (Weekday::Sunday, DateRestriction::All),
+                    ]),
+                    notes: HashMap::from([(
+                        Cow::Borrowed("Foot passengers only, no vehicles"),
+                        DateRestriction::Only(HashSet::from([date!(2022 - 07 - 03)])),
+                    )]),
+                    note_categories: HashMap::new(),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                },
+            ],
+            source_url: "https://www.bcferries.com/routes-fares/schedules/daily/TSA-SWB".to_string(),
+            ..Schedule::test_fixture(terminal_pair)
+        };
+        let ical = route_calendar(terminal_pair, &[&schedule]);
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+        assert!(ical.lines().all(|line| line.len() <= ICAL_MAX_LINE_OCTETS));
+        let starts: Vec<_> = ical.lines().filter_map(|line| line.strip_prefix("DTSTART;")).collect();
+        // The Friday sailing's exception date is left out
+        assert_eq!(starts, ["TZID=America/Vancouver:20220702T090000", "TZID=America/Vancouver:20220703T090000",]);
+        assert!(ical.contains("UID:TSA-SWB-20220702T090000@ferries.borsboom.io\r\n"));
+        assert!(ical.contains("DTSTAMP:19700101T000000Z\r\n"));
+        assert!(ical.contains("DESCRIPTION:Non-stop\\nFoot passengers only\\, no vehicles\\nSource: "));
+        let overnight = route_calendar(
+            terminal_pair,
+            &[&Schedule {
+                date_range: DateRange { from: date!(2022 - 07 - 08), to: date!(2022 - 07 - 08) },
+                ..schedule
+            }],
+        );
+        assert!(overnight.contains("DTEND;TZID=America/Vancouver:20220709T003500\r\n"));
+        Ok(())
+    }
+}
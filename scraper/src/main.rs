@@ -1,24 +1,67 @@
 mod annotations;
+mod archive;
 mod cache;
+mod compression;
+mod config;
+mod connections;
 mod constants;
+mod cross_check;
+mod date_list;
 mod depart_time_and_row_annotations;
-mod imports;
+mod fixtures;
+mod gtfs;
+mod ical;
 mod macros;
 mod output;
+mod parse_report;
+mod prelude;
+mod progress;
+mod provisional;
+mod refits;
+mod reissue;
+mod route_discovery;
+mod schedule_diff;
 mod scraper;
+mod selftest;
+mod service_notices;
+mod sgi_grid;
+mod source_urls;
+mod summaries;
+mod supplemental;
+mod table_layout;
 mod types;
+mod upload;
 mod utils;
+mod validation;
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use directories::ProjectDirs;
 use std::env;
 use std::process;
 
-use crate::cache::Cache;
-use crate::imports::*;
+use crate::archive::{export_archive_parquet, write_archive_snapshot};
+use crate::cache::{Cache, HttpRecording};
+use crate::config::args_with_config;
+use crate::connections::check_thrufare_connections;
+use crate::cross_check::cross_check_schedules;
+use crate::fixtures::check_fixtures;
+use crate::gtfs::write_gtfs_feed;
+use crate::ical::write_route_calendars;
 use crate::output::write_output;
+use crate::prelude::*;
+use crate::progress::init_progress_logger;
+use crate::provisional::add_provisional_schedules;
+use crate::refits::add_refit_alerts;
+use crate::reissue::{mark_reissued_schedules, read_previous_schedules};
+use crate::schedule_diff::print_schedules_diff;
 use crate::scraper::scrape_schedules;
+use crate::selftest::run_selftest;
+use crate::service_notices::add_service_notice_alerts;
+use crate::source_urls::check_source_urls;
+use crate::summaries::write_route_summaries;
+use crate::supplemental::add_supplemental_schedules;
 use crate::types::Options;
+use crate::validation::validate_schedules_file;
 
 #[derive(Parser, Debug)]
 pub struct CliArgs {
@@ -26,6 +69,15 @@ pub struct CliArgs {
     #[clap(short, long, value_name = "LEVEL", default_value = "info")]
     verbosity: log::LevelFilter,
 
+    /// Log each route as it is scraped instead of showing a progress display on a terminal
+    #[clap(long)]
+    no_progress: bool,
+
+    /// Read settings from this TOML file, which are named like these options; without this, `scraper.toml` is read if
+    /// it exists
+    #[clap(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
     #[clap(flatten)]
     options: Options,
 }
@@ -33,22 +85,92 @@ pub struct CliArgs {
 #[tokio::main]
 async fn main() {
     dotenv::dotenv().ok();
-    let cli_args = CliArgs::parse();
-    if env::var(env_logger::DEFAULT_FILTER_ENV).is_ok() {
-        env_logger::init();
+    let mut command = CliArgs::command();
+    let cli_args = match args_with_config(&command, env::args().collect()) {
+        Ok(args) => CliArgs::parse_from(args),
+        Err(error) => command.error(clap::ErrorKind::InvalidValue, format!("{:#}", error)).exit(),
+    };
+    let logger = if env::var(env_logger::DEFAULT_FILTER_ENV).is_ok() {
+        env_logger::Builder::from_default_env().build()
     } else {
         env_logger::builder()
             .filter(Some(env!("CARGO_PKG_NAME")), cli_args.verbosity)
             .format_timestamp(None)
             .format_target(false)
-            .init();
-    }
+            .build()
+    };
+    init_progress_logger(logger, !cli_args.no_progress);
     let inner = async {
         let project_dirs = ProjectDirs::from("io", "borsboom", env!("CARGO_PKG_NAME"))
             .ok_or_else(|| anyhow!("Could not get project directories"))?;
-        let cache = Cache::new(Duration::hours(cli_args.options.max_cache_age), &project_dirs);
-        let schedules = scrape_schedules(&cli_args.options, &cache).await?;
-        write_output(&cli_args.options, &schedules).await?;
+        if cli_args.options.selftest {
+            return run_selftest();
+        }
+        if let Some([old_path, new_path]) = cli_args.options.diff.as_deref() {
+            return print_schedules_diff(old_path, new_path);
+        }
+        if let Some(schedules_path) = &cli_args.options.validate {
+            return validate_schedules_file(schedules_path);
+        }
+        if let Some(fixtures_dir) = &cli_args.options.check_fixtures {
+            return check_fixtures(fixtures_dir);
+        }
+        if let (Some(archive_dir), Some(export_path)) =
+            (&cli_args.options.archive_dir, &cli_args.options.export_parquet)
+        {
+            return export_archive_parquet(archive_dir, export_path);
+        }
+        let cache = Cache::new(
+            Duration::hours(cli_args.options.max_cache_age),
+            &project_dirs,
+            HttpRecording::from_options(&cli_args.options),
+            cli_args.options.max_fetch_attempts,
+            std::time::Duration::from_millis(cli_args.options.host_delay_ms),
+        );
+        let (mut schedules, parse_report) = scrape_schedules(&cli_args.options, &cache).await?;
+        if let Some(parse_report_file) = &cli_args.options.parse_report_file {
+            parse_report.write(parse_report_file)?;
+        }
+        if cli_args.options.check_source_urls {
+            check_source_urls(&mut schedules).await?;
+        }
+        if cli_args.options.service_notices {
+            // Notices are supplementary, so the schedules are still published without them
+            if let Err(err) = add_service_notice_alerts(&cache, &mut schedules).await {
+                warn!("{:?}", err);
+            }
+        }
+        add_refit_alerts(&mut schedules)?;
+        check_thrufare_connections(&schedules);
+        for review in overdue_content_reviews(today_vancouver()) {
+            warn!("{}", review.warning());
+        }
+        if let Some(previous_file) = &cli_args.options.previous_file {
+            mark_reissued_schedules(&mut schedules, &read_previous_schedules(previous_file)?);
+        }
+        if let (true, Some(archive_dir)) = (cli_args.options.provisional_schedules, &cli_args.options.archive_dir) {
+            add_provisional_schedules(archive_dir, &mut schedules)?;
+        }
+        if let Some(url_template) = &cli_args.options.cross_check_url {
+            cross_check_schedules(&cli_args.options, &cache, url_template, &schedules).await;
+        }
+        add_supplemental_schedules(&mut schedules, OffsetDateTime::now_utc())?;
+        if let Some(gtfs_file) = &cli_args.options.gtfs_file {
+            write_gtfs_feed(gtfs_file, &schedules)?;
+        }
+        if let Some(markdown_dir) = &cli_args.options.markdown_dir {
+            write_route_summaries(markdown_dir, &schedules)?;
+        }
+        if let Some(ical_dir) = &cli_args.options.ical_dir {
+            write_route_calendars(ical_dir, &schedules)?;
+        }
+        let metadata = DataMetadata::new(OffsetDateTime::now_utc(), env!("CARGO_PKG_VERSION"));
+        let data = PublishedData::WithMetadata { metadata, schedules };
+        write_output(&cli_args.options, &data).await?;
+        if let Some(archive_dir) = &cli_args.options.archive_dir {
+            write_archive_snapshot(archive_dir, &data.into_schedules())?;
+        }
+        parse_report.check()?;
         Ok(()) as Result<()>
     };
     if let Err(error) = inner.await {
@@ -1,88 +1,477 @@
-use crate::imports::*;
+use std::path::{Path, PathBuf};
+use time::{PrimitiveDateTime, UtcOffset};
+
+use crate::compression::compress;
+use crate::prelude::*;
 use crate::types::*;
+use crate::upload::*;
+use crate::utils::*;
 
-use tempfile::NamedTempFile;
+const MANIFEST_FILE_NAME: &str = "manifest.json";
 
-static S3_CACHE_MAX_AGE: Lazy<Duration> = Lazy::new(|| Duration::hours(12));
+const ICAL_KEY_DIR: &str = "ical";
 
-async fn upload_to_s3(
-    aws_config: &aws_types::SdkConfig,
-    bucket: &str,
-    key: &str,
-    schedules: &[Schedule],
-) -> Result<()> {
-    info!("Uploading schedules JSON to: s3://{}/{}", bucket, key);
-    let s3_client = aws_sdk_s3::Client::new(aws_config);
-    s3_client
-        .put_object()
-        .bucket(bucket)
-        .key(key)
-        .content_type("application/json")
-        .acl(aws_sdk_s3::model::ObjectCannedAcl::PublicRead)
-        .cache_control(format!("max-age={},public", S3_CACHE_MAX_AGE.whole_seconds()))
-        .body(aws_sdk_s3::types::ByteStream::from(
-            serde_json::to_vec(schedules).expect("Expect schedules to serialize to JSON"),
-        ))
-        .send()
-        .await
-        .with_context(|| format!("Failed to upload schedules JSON to S3: s3://{}/{}", bucket, key))?;
-    Ok(())
+const ROUTE_FILES_DIR: &str = "routes";
+
+const PARTITION_FILES_DIR: &str = "partitions";
+
+const PARTITION_MONTH_FORMAT: &TimeFormat = format_description!("[year]-[month]");
+
+const JSON_TYPE: &str = "application/json";
+
+// In UTC, to the minute, so that snapshots sort by name and don't depend on the machine's time zone
+const SNAPSHOT_TIME_FORMAT: &TimeFormat = format_description!("[year]-[month]-[day]T[hour]:[minute]Z");
+
+// The manifest sits next to the schedules object, so it names the schedules object relative to itself
+fn sibling_key<'a>(key: &'a str, sibling_file_name: &str) -> (&'a str, String) {
+    match key.rsplit_once('/') {
+        Some((dir, file_name)) => (file_name, format!("{}/{}", dir, sibling_file_name)),
+        None => (key, sibling_file_name.to_string()),
+    }
+}
+
+// Local output is published to channels like uploads are, with the output file moved to the channel's subdirectory
+fn channel_output_file_path(channel: DataChannel, output_file_path: &Path) -> PathBuf {
+    match (channel.subdirectory(), output_file_path.file_name()) {
+        (Some(subdirectory), Some(file_name)) => output_file_path.with_file_name(subdirectory).join(file_name),
+        _ => output_file_path.to_path_buf(),
+    }
+}
+
+// Checked before anything is written, so that a mistake in the flags file doesn't publish a half-updated manifest
+fn read_feature_flags(options: &Options) -> Result<Vec<FeatureFlag>> {
+    let path = match &options.feature_flags_file {
+        Some(path) => path,
+        None => return Ok(vec![]),
+    };
+    let inner = || -> Result<_> {
+        let feature_flags: Vec<FeatureFlag> = serde_json::from_reader(fs::File::open(path)?)?;
+        for flag in &feature_flags {
+            ensure!(!flag.name.is_empty(), "Feature flag has no name");
+            ensure!(flag.rollout_percent <= 100, "Feature flag {:?} rolls out to more than 100%", flag.name);
+        }
+        if let Some(name) = feature_flags.iter().map(|f| &f.name).duplicates().next() {
+            bail!("Feature flag {:?} is listed more than once", name);
+        }
+        Ok(feature_flags)
+    };
+    inner().with_context(|| format!("Failed to read feature flags from: {:?}", path))
 }
 
-async fn invalidate_cloudfront_distribution(
-    aws_config: &aws_types::SdkConfig,
-    distribution_id: &str,
-    s3_key: &str,
+fn manifest_json(
+    channel: DataChannel,
+    schedules_file: &str,
+    schedules_json: &[u8],
+    feature_flags: &[FeatureFlag],
+    schedules_encodings: &[ContentEncoding],
+    route_files: &[DataRouteFile],
+    partitions: &[DataPartition],
+) -> Vec<u8> {
+    let manifest = DataManifest {
+        channel,
+        schedules_file: schedules_file.to_string(),
+        schedules_bytes: schedules_json.len(),
+        published_at: OffsetDateTime::now_utc(),
+        partitions: partitions.to_vec(),
+        feature_flags: feature_flags.to_vec(),
+        schedules_encodings: schedules_encodings.to_vec(),
+        route_files: route_files.to_vec(),
+    };
+    serde_json::to_vec(&manifest).expect("Expect manifest to serialize to JSON")
+}
+
+// The output file's stem and extension, between which snapshots' times go (e.g. `schedules-2024-05-01T03:00Z.json`)
+fn snapshot_name_parts(output_file_path: &Path) -> (String, String) {
+    let stem = output_file_path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let extension = output_file_path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (stem, extension)
+}
+
+fn parse_snapshot_time(output_file_path: &Path, file_name: &str) -> Option<PrimitiveDateTime> {
+    let (stem, extension) = snapshot_name_parts(output_file_path);
+    let time_text = file_name.strip_prefix(&stem)?.strip_prefix('-')?.strip_suffix(&extension)?;
+    PrimitiveDateTime::parse(time_text, SNAPSHOT_TIME_FORMAT).ok()
+}
+
+// Keeps a copy of the output named for when it was written, so that earlier outputs can be gone back to after a bad
+// scrape.  Only the `keep` most recent snapshots are kept.
+fn write_output_snapshot(
+    output_file_path: &Path,
+    schedules_json: &[u8],
+    keep: usize,
+    now: OffsetDateTime,
 ) -> Result<()> {
-    let cloudfront_client = aws_sdk_cloudfront::Client::new(aws_config);
-    let path = format!("/{}", s3_key);
-    info!("Invalidating CloudFront distribution {:?} for path: {:?}", distribution_id, path);
-    cloudfront_client
-        .create_invalidation()
-        .distribution_id(distribution_id)
-        .invalidation_batch(
-            aws_sdk_cloudfront::model::InvalidationBatch::builder()
-                .caller_reference(OffsetDateTime::now_utc().unix_timestamp_nanos().to_string())
-                .paths(aws_sdk_cloudfront::model::Paths::builder().quantity(1).items(path).build())
-                .build(),
-        )
-        .send()
-        .await
-        .with_context(|| {
-            format!("Failed to create CloudFront invalidation for distribution ID: {:?}", distribution_id)
-        })?;
+    let (stem, extension) = snapshot_name_parts(output_file_path);
+    let time_text = now.to_offset(UtcOffset::UTC).format(SNAPSHOT_TIME_FORMAT).expect("Expect snapshot time to format");
+    let snapshot_path = output_file_path.with_file_name(format!("{}-{}{}", stem, time_text, extension));
+    info!("Writing output snapshot to: {:?}", snapshot_path);
+    write_file_atomically(&snapshot_path, |file| Ok(io::Write::write_all(file, schedules_json)?))?;
+    let dir = match output_file_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to list output snapshots in: {:?}", dir))? {
+        let path = entry?.path();
+        let snapshot_time =
+            path.file_name().and_then(|n| n.to_str()).and_then(|n| parse_snapshot_time(output_file_path, n));
+        if let Some(snapshot_time) = snapshot_time {
+            snapshots.push((snapshot_time, path));
+        }
+    }
+    snapshots.sort_unstable_by(|a, b| b.cmp(a));
+    for (_, path) in snapshots.into_iter().skip(keep) {
+        info!("Removing old output snapshot: {:?}", path);
+        fs::remove_file(&path).with_context(|| format!("Failed to remove old output snapshot: {:?}", path))?;
+    }
     Ok(())
 }
 
-pub async fn write_output(options: &Options, schedules: &[Schedule]) -> Result<()> {
+// Each route's schedules, published like the whole schedules are, so that loading a route's file is the same as loading
+// the schedules file
+fn route_files_json(data: &PublishedData) -> Vec<(DataRouteFile, Vec<u8>)> {
+    let route_schedules = into_vec_group_map(data.schedules().iter().cloned(), |s| s.terminal_pair);
+    route_schedules
+        .into_iter()
+        .sorted_by_key(|(terminal_pair, _)| *terminal_pair)
+        .map(|(terminal_pair, schedules)| {
+            let route_json = serde_json::to_vec(&data.with_schedules(schedules))
+                .expect("Expect route schedules to serialize to JSON");
+            let file = format!("{}/{}.json", ROUTE_FILES_DIR, terminal_pair);
+            (DataRouteFile { terminal_pair, file, bytes: route_json.len() }, route_json)
+        })
+        .collect()
+}
+
+fn write_route_files(output_file_path: &Path, route_files: &[(DataRouteFile, Vec<u8>)]) -> Result<()> {
+    let dir = output_file_path.with_file_name(ROUTE_FILES_DIR);
+    let inner = || {
+        fs::create_dir_all(&dir).context("Failed to create directory")?;
+        for (route_file, route_json) in route_files {
+            write_file_atomically(&output_file_path.with_file_name(&route_file.file), |file| {
+                Ok(io::Write::write_all(file, route_json)?)
+            })?;
+        }
+        remove_stale_route_files(&dir, "json", |tp| route_files.iter().any(|(f, _)| f.terminal_pair == tp))?;
+        info!("Wrote schedules JSON of {} routes to: {:?}", route_files.len(), dir);
+        Ok(()) as Result<_>
+    };
+    inner().with_context(|| format!("Failed to write route schedules to: {:?}", dir))
+}
+
+// Schedules that start after the current month go in a file for each month they start in, leaving the rest for the
+// schedules file
+fn partition_files_json(data: &PublishedData, today: Date) -> (PublishedData, Vec<(DataPartition, Vec<u8>)>) {
+    let month_start = |date: Date| {
+        Date::from_calendar_date(date.year(), date.month(), 1).expect("Expect every month to have a first day")
+    };
+    let (later_schedules, current_schedules): (Vec<_>, Vec<_>) =
+        data.schedules().iter().cloned().partition(|s| month_start(s.date_range.from) > month_start(today));
+    let partition_files = into_vec_group_map(later_schedules, |s| month_start(s.date_range.from))
+        .into_iter()
+        .sorted_by_key(|(month, _)| *month)
+        .map(|(month, schedules)| {
+            let date_range = DateRange {
+                from: schedules.iter().map(|s| s.date_range.from).min().unwrap_or(month),
+                to: schedules.iter().map(|s| s.date_range.to).max().unwrap_or(month),
+            };
+            let partition_json =
+                serde_json::to_vec(&data.with_schedules(schedules)).expect("Expect partition to serialize to JSON");
+            let month_text = month.format(PARTITION_MONTH_FORMAT).expect("Expect partition month to format");
+            let file = format!("{}/{}.json", PARTITION_FILES_DIR, month_text);
+            (DataPartition { file, bytes: partition_json.len(), date_range }, partition_json)
+        })
+        .collect();
+    (data.with_schedules(current_schedules), partition_files)
+}
+
+fn write_partition_files(output_file_path: &Path, partition_files: &[(DataPartition, Vec<u8>)]) -> Result<()> {
+    let dir = output_file_path.with_file_name(PARTITION_FILES_DIR);
+    let inner = || {
+        fs::create_dir_all(&dir).context("Failed to create directory")?;
+        for (partition, partition_json) in partition_files {
+            write_file_atomically(&output_file_path.with_file_name(&partition.file), |file| {
+                Ok(io::Write::write_all(file, partition_json)?)
+            })?;
+        }
+        // Months that have started are in the schedules file now
+        for entry in fs::read_dir(&dir).context("Failed to list directory")? {
+            let path = entry.context("Failed to read directory entry")?.path();
+            let is_current = partition_files.iter().any(|(p, _)| output_file_path.with_file_name(&p.file) == path);
+            if !is_current && path.extension() == Some("json".as_ref()) {
+                fs::remove_file(&path).with_context(|| format!("Failed to remove stale file: {:?}", path))?;
+            }
+        }
+        info!("Wrote schedules JSON of {} partitions to: {:?}", partition_files.len(), dir);
+        Ok(()) as Result<_>
+    };
+    inner().with_context(|| format!("Failed to write schedules partitions to: {:?}", dir))
+}
+
+// The other formats that were written locally, uploaded beside the schedules JSON so that they are published with it
+fn other_format_objects(options: &Options, schedules_key: &str) -> Result<Vec<UploadObject>> {
+    let mut objects = Vec::new();
+    if let Some(gtfs_file) = &options.gtfs_file {
+        let (_, key) = sibling_key(schedules_key, &gtfs_file.file_name().unwrap_or_default().to_string_lossy());
+        objects.push(UploadObject::from_file(key, gtfs_file, "application/zip")?);
+    }
+    if let Some(ical_dir) = &options.ical_dir {
+        let paths = fs::read_dir(ical_dir)
+            .with_context(|| format!("Failed to list iCalendar files in: {:?}", ical_dir))?
+            .map(|entry| Ok(entry?.path()))
+            .collect::<Result<Vec<_>>>()?;
+        for path in paths.into_iter().filter(|path| path.extension() == Some("ics".as_ref())).sorted() {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let (_, key) = sibling_key(schedules_key, &format!("{}/{}", ICAL_KEY_DIR, file_name));
+            objects.push(UploadObject::from_file(key, &path, "text/calendar")?);
+        }
+    }
+    Ok(objects)
+}
+
+// Writes the schedules before the manifest, so that anything that reads the manifest first never finds it referring to
+// schedules that are not in place yet
+pub async fn write_output(options: &Options, data: &PublishedData) -> Result<()> {
     let inner = async {
         if let (None, None) = (options.output_file.as_ref(), options.output_s3_bucket.as_ref()) {
-            serde_json::to_writer_pretty(io::stdout(), &schedules)
+            serde_json::to_writer_pretty(io::stdout(), data)
                 .context("Failed to write schedules JSON to standard output")?;
         } else {
+            let feature_flags = read_feature_flags(options)?;
+            let (schedules_data, partition_files_json) = if options.partition_output {
+                partition_files_json(data, today_vancouver())
+            } else {
+                (data.with_schedules(data.schedules().to_vec()), vec![])
+            };
+            let partitions: Vec<_> = partition_files_json.iter().map(|(partition, _)| partition.clone()).collect();
+            let schedules_json = serde_json::to_vec(&schedules_data).expect("Expect schedules to serialize to JSON");
+            let compressed_schedules: Vec<_> = if options.precompress_output {
+                ContentEncoding::iter().map(|encoding| (encoding, compress(&schedules_json, encoding))).collect()
+            } else {
+                vec![]
+            };
+            let schedules_encodings: Vec<_> = compressed_schedules.iter().map(|(encoding, _)| *encoding).collect();
+            let route_files_json = if options.route_files { route_files_json(data) } else { vec![] };
+            let route_files: Vec<_> = route_files_json.iter().map(|(route_file, _)| route_file.clone()).collect();
             if let Some(output_file_path) = &options.output_file {
+                let output_file_path = &channel_output_file_path(options.channel, output_file_path);
+                if let Some(dir) = output_file_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                    fs::create_dir_all(dir).with_context(|| format!("Failed to create output directory: {:?}", dir))?;
+                }
                 info!("Writing schedules JSON to: {:?}", output_file_path);
-                let mut temp_file = NamedTempFile::new_in(
-                    output_file_path
-                        .parent()
-                        .unwrap_or(&std::env::current_dir().context("Failed to get current directory")?),
-                )
-                .with_context(|| format!("Failed to create schedules JSON output file: {:?}", output_file_path))?;
-                serde_json::to_writer(&mut temp_file, &schedules)
-                    .with_context(|| format!("Failed to write schedules JSON to file: {:?}", output_file_path))?;
-                temp_file
-                    .persist(output_file_path)
-                    .context(format!("Failed to persist temporary file to: {:?}", output_file_path))?;
+                write_file_atomically(output_file_path, |file| Ok(io::Write::write_all(file, &schedules_json)?))?;
+                if options.keep_output_snapshots > 0 {
+                    // Snapshots hold all the schedules, partitioned or not, so that any of them can be gone back to
+                    write_output_snapshot(
+                        output_file_path,
+                        &serde_json::to_vec(data).expect("Expect schedules to serialize to JSON"),
+                        options.keep_output_snapshots,
+                        OffsetDateTime::now_utc(),
+                    )?;
+                }
+                let schedules_file = output_file_path.file_name().unwrap_or_default().to_string_lossy();
+                for (encoding, compressed_json) in &compressed_schedules {
+                    let compressed_path = output_file_path.with_file_name(encoding.file_name(&schedules_file));
+                    info!("Writing compressed schedules JSON to: {:?}", compressed_path);
+                    write_file_atomically(&compressed_path, |file| Ok(io::Write::write_all(file, compressed_json)?))?;
+                }
+                if options.route_files {
+                    write_route_files(output_file_path, &route_files_json)?;
+                }
+                if options.partition_output {
+                    write_partition_files(output_file_path, &partition_files_json)?;
+                }
+                let manifest_json = manifest_json(
+                    options.channel,
+                    &schedules_file,
+                    &schedules_json,
+                    &feature_flags,
+                    &schedules_encodings,
+                    &route_files,
+                    &partitions,
+                );
+                write_file_atomically(&output_file_path.with_file_name(MANIFEST_FILE_NAME), |file| {
+                    Ok(io::Write::write_all(file, &manifest_json)?)
+                })?;
             }
-            if let Some(bucket) = &options.output_s3_bucket {
-                let aws_config = aws_config::from_env().load().await;
-                upload_to_s3(&aws_config, bucket, &options.output_s3_key, schedules).await?;
-                if let Some(distribution_id) = &options.invalidate_cloudfront_distribution_id {
-                    invalidate_cloudfront_distribution(&aws_config, distribution_id, &options.output_s3_key).await?;
+            if options.output_s3_bucket.is_some() {
+                let schedules_key = options.channel.path(&options.output_s3_key);
+                let (schedules_file, manifest_key) = sibling_key(&schedules_key, MANIFEST_FILE_NAME);
+                let manifest_json = manifest_json(
+                    options.channel,
+                    schedules_file,
+                    &schedules_json,
+                    &feature_flags,
+                    &schedules_encodings,
+                    &route_files,
+                    &partitions,
+                );
+                let mut objects = other_format_objects(options, &schedules_key)?;
+                for (encoding, compressed_json) in compressed_schedules {
+                    objects.push(UploadObject {
+                        key: encoding.file_name(&schedules_key),
+                        body: compressed_json,
+                        content_type: JSON_TYPE,
+                        content_encoding: Some(encoding),
+                    });
+                }
+                let json_object =
+                    |key, body| UploadObject { key, body, content_type: JSON_TYPE, content_encoding: None };
+                for (route_file, route_json) in route_files_json {
+                    objects.push(json_object(sibling_key(&schedules_key, &route_file.file).1, route_json));
                 }
+                for (partition, partition_json) in partition_files_json {
+                    objects.push(json_object(sibling_key(&schedules_key, &partition.file).1, partition_json));
+                }
+                objects.push(json_object(schedules_key, schedules_json));
+                objects.push(json_object(manifest_key, manifest_json));
+                upload_objects(options, objects).await?;
             }
         }
         Ok(()) as Result<_>
     };
     inner.await.context("Failed to write output")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sibling_key() -> Result<()> {
+        assert_eq!(sibling_key("schedules.json", "manifest.json"), ("schedules.json", "manifest.json".to_string()));
+        assert_eq!(
+            sibling_key("data/v2/schedules.json", "manifest.json"),
+            ("schedules.json", "data/v2/manifest.json".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_channel_output_file_path() -> Result<()> {
+        let output_file_path = Path::new("data/schedules.json");
+        assert_eq!(channel_output_file_path(DataChannel::Stable, output_file_path), output_file_path);
+        assert_eq!(
+            channel_output_file_path(DataChannel::Preview, output_file_path),
+            Path::new("data/preview/schedules.json")
+        );
+        assert_eq!(
+            channel_output_file_path(DataChannel::Preview, Path::new("schedules.json")),
+            Path::new("preview/schedules.json")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_output_channel() -> Result<()> {
+        #[derive(clap::Parser)]
+        struct TestArgs {
+            #[clap(flatten)]
+            options: Options,
+        }
+        let dir = tempfile::tempdir()?;
+        let output_file_path = dir.path().join("schedules.json");
+        let TestArgs { options } = clap::Parser::try_parse_from([
+            "scraper".as_ref(),
+            "--channel".as_ref(),
+            "preview".as_ref(),
+            "--output-file".as_ref(),
+            output_file_path.as_os_str(),
+        ])?;
+        write_output(&options, &PublishedData::SchedulesOnly(vec![])).await?;
+        // The stable channel's files are left alone
+        assert!(!output_file_path.exists());
+        let manifest: DataManifest = serde_json::from_slice(&fs::read(dir.path().join("preview/manifest.json"))?)?;
+        assert_eq!(manifest.channel, DataChannel::Preview);
+        assert_eq!(manifest.schedules_file, "schedules.json");
+        assert!(dir.path().join("preview/schedules.json").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_route_files() -> Result<()> {
+        let schedule = |terminal_pair| Schedule {
+            date_range: DateRange { from: date!(2024 - 05 - 01), to: date!(2024 - 05 - 31) },
+            ..Schedule::test_fixture(terminal_pair)
+        };
+        let swb_tsa = TerminalPair { from: Terminal::SWB, to: Terminal::TSA };
+        let metadata = DataMetadata::new(OffsetDateTime::UNIX_EPOCH, "1.2.3");
+        let data = PublishedData::WithMetadata {
+            metadata: metadata.clone(),
+            schedules: vec![schedule(swb_tsa), schedule(swb_tsa.swapped()), schedule(swb_tsa)],
+        };
+        let dir = tempfile::tempdir()?;
+        let output_file_path = dir.path().join("schedules.json");
+        fs::create_dir(dir.path().join(ROUTE_FILES_DIR))?;
+        fs::write(dir.path().join(ROUTE_FILES_DIR).join("PSB-POB.json"), "[]")?;
+        let route_files_json = route_files_json(&data);
+        write_route_files(&output_file_path, &route_files_json)?;
+        let route_files: Vec<_> = route_files_json.into_iter().map(|(route_file, _)| route_file).collect();
+        assert_eq!(
+            route_files.iter().map(|f| (f.terminal_pair, f.file.as_str())).collect::<Vec<_>>(),
+            [(swb_tsa, "routes/SWB-TSA.json"), (swb_tsa.swapped(), "routes/TSA-SWB.json")]
+        );
+        let route_data: PublishedData = serde_json::from_slice(&fs::read(dir.path().join(&route_files[0].file))?)?;
+        assert_eq!(route_data.metadata(), Some(&metadata));
+        assert_eq!(route_data.into_schedules().len(), 2);
+        // Routes that are no longer published are removed
+        assert!(!dir.path().join(ROUTE_FILES_DIR).join("PSB-POB.json").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_partition_files() -> Result<()> {
+        let schedule = |from, to| Schedule {
+            date_range: DateRange { from, to },
+            ..Schedule::test_fixture(TerminalPair { from: Terminal::SWB, to: Terminal::TSA })
+        };
+        let date_ranges = |schedules: &[Schedule]| schedules.iter().map(|s| s.date_range).collect::<Vec<_>>();
+        let data = PublishedData::SchedulesOnly(vec![
+            schedule(date!(2024 - 04 - 01), date!(2024 - 05 - 14)),
+            schedule(date!(2024 - 05 - 15), date!(2024 - 06 - 30)),
+            schedule(date!(2024 - 07 - 01), date!(2024 - 07 - 31)),
+            schedule(date!(2024 - 07 - 15), date!(2024 - 09 - 02)),
+        ]);
+        let (schedules_data, partition_files_json) = partition_files_json(&data, date!(2024 - 05 - 10));
+        // Schedules that start this month or earlier stay in the schedules file
+        assert_eq!(date_ranges(schedules_data.schedules()), date_ranges(&data.schedules()[..2]));
+        let partitions: Vec<_> = partition_files_json.iter().map(|(partition, _)| partition.clone()).collect();
+        assert_eq!(
+            partitions.iter().map(|p| (p.file.as_str(), p.date_range)).collect::<Vec<_>>(),
+            [("partitions/2024-07.json", DateRange { from: date!(2024 - 07 - 01), to: date!(2024 - 09 - 02) })]
+        );
+        let dir = tempfile::tempdir()?;
+        let output_file_path = dir.path().join("schedules.json");
+        fs::create_dir(dir.path().join(PARTITION_FILES_DIR))?;
+        fs::write(dir.path().join(PARTITION_FILES_DIR).join("2024-05.json"), "[]")?;
+        write_partition_files(&output_file_path, &partition_files_json)?;
+        let partition_data: PublishedData = serde_json::from_slice(&fs::read(dir.path().join(&partitions[0].file))?)?;
+        assert_eq!(date_ranges(partition_data.schedules()), date_ranges(&data.schedules()[2..]));
+        // Months that have started are removed
+        assert!(!dir.path().join(PARTITION_FILES_DIR).join("2024-05.json").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_output_snapshot() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output_file_path = dir.path().join("schedules.json");
+        fs::write(dir.path().join("schedules-notes.json"), "")?;
+        for hour in [3, 9, 15] {
+            let now = date!(2024 - 05 - 01).with_hms(hour, 0, 0)?.assume_offset(UtcOffset::from_hms(-7, 0, 0)?);
+            write_output_snapshot(&output_file_path, b"[]", 2, now)?;
+        }
+        let file_names: Vec<_> = fs::read_dir(dir.path())?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .sorted()
+            .collect();
+        // The oldest snapshot is removed, but files that aren't snapshots are left alone
+        assert_eq!(
+            file_names,
+            vec!["schedules-2024-05-01T16:00Z.json", "schedules-2024-05-01T22:00Z.json", "schedules-notes.json"]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use std::path::Path;
+
+use crate::prelude::*;
+use crate::utils::*;
+
+// Annotation text that `--lenient-annotations` let through, which needs a parser change before the schedule can be
+// trusted
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct UnrecognizedAnnotation {
+    pub terminal_pair: TerminalPair,
+    pub date_range: DateRange,
+    pub text: String,
+}
+
+// What a lenient scrape could not parse, so the output can still be published while someone fixes the parser
+#[derive(Debug, Default, Serialize)]
+pub struct ParseReport {
+    pub unrecognized_annotations: Vec<UnrecognizedAnnotation>,
+}
+
+impl ParseReport {
+    pub fn add_unrecognized_annotations(
+        &mut self,
+        terminal_pair: TerminalPair,
+        date_range: DateRange,
+        texts: Vec<String>,
+    ) {
+        for text in texts {
+            let annotation = UnrecognizedAnnotation { terminal_pair, date_range, text };
+            if !self.unrecognized_annotations.contains(&annotation) {
+                self.unrecognized_annotations.push(annotation);
+            }
+        }
+    }
+
+    pub fn extend(&mut self, other: ParseReport) {
+        for annotation in other.unrecognized_annotations {
+            if !self.unrecognized_annotations.contains(&annotation) {
+                self.unrecognized_annotations.push(annotation);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.unrecognized_annotations.is_empty()
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write_file_atomically(path, |file| Ok(serde_json::to_writer_pretty(io::BufWriter::new(file), self)?))
+            .with_context(|| format!("Failed to write parse report to: {:?}", path))
+    }
+
+    // Fails the run only after the output is written, so a new annotation doesn't hold back every other schedule
+    pub fn check(&self) -> Result<()> {
+        ensure!(
+            self.is_empty(),
+            "{} unrecognized annotation texts were noted on sailings as-is: {}",
+            self.unrecognized_annotations.len(),
+            self.unrecognized_annotations
+                .iter()
+                .map(|a| format!("{} {}: {:?}", a.terminal_pair, a.date_range, a.text))
+                .join("; ")
+        );
+        Ok(())
+    }
+}
@@ -5,7 +5,4 @@ pub use std::fs;
 pub use std::io;
 pub use std::path::PathBuf;
 
-pub use ferrysched_shared::constants::*;
-pub use ferrysched_shared::imports::*;
-pub use ferrysched_shared::types::*;
-pub use ferrysched_shared::utils::*;
+pub use ferrysched_shared::prelude::*;
@@ -0,0 +1,232 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::prelude::*;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RouteStage {
+    Fetching,
+    Parsing,
+    Validating,
+}
+
+impl RouteStage {
+    fn description(self) -> &'static str {
+        match self {
+            RouteStage::Fetching => "fetching",
+            RouteStage::Parsing => "parsing",
+            RouteStage::Validating => "validating",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ProgressState {
+    total_routes: usize,
+    done_routes: usize,
+    // Routes being scraped, in the order they started
+    current: Vec<(TerminalPair, RouteStage)>,
+    warnings: usize,
+    started_at: Instant,
+    // Lines of the status display currently on the terminal, which are cleared before anything else is written
+    drawn_lines: usize,
+}
+
+// Status of a scraping run: which routes are being scraped and at what stage, how many warnings there have been, and
+// how long is left.  On a terminal it is a status display kept below the log output; otherwise, each finished route is
+// logged.
+pub struct Progress {
+    interactive: AtomicBool,
+    state: Mutex<ProgressState>,
+}
+
+pub static PROGRESS: Lazy<Progress> = Lazy::new(|| Progress {
+    interactive: AtomicBool::new(false),
+    state: Mutex::new(ProgressState {
+        total_routes: 0,
+        done_routes: 0,
+        current: Vec::new(),
+        warnings: 0,
+        started_at: Instant::now(),
+        drawn_lines: 0,
+    }),
+});
+
+fn format_seconds(seconds: u64) -> String {
+    match (seconds / 60, seconds % 60) {
+        (0, seconds) => format!("{}s", seconds),
+        (minutes, seconds) => format!("{}m {}s", minutes, seconds),
+    }
+}
+
+impl ProgressState {
+    fn eta_text(&self, now: Instant) -> String {
+        if self.done_routes == 0 {
+            return "ETA unknown".to_string();
+        }
+        let per_route = now.duration_since(self.started_at).as_secs_f64() / self.done_routes as f64;
+        let remaining = per_route * self.total_routes.saturating_sub(self.done_routes) as f64;
+        format!("ETA {}", format_seconds(remaining.round() as u64))
+    }
+
+    fn status_lines(&self, now: Instant) -> Vec<String> {
+        let counts_text = format!("[{}/{}]", self.done_routes, self.total_routes);
+        let route_line = match self
+            .current
+            .iter()
+            .map(|(terminal_pair, stage)| format!("{}: {}", terminal_pair, stage.description()))
+            .join(", ")
+        {
+            current_text if current_text.is_empty() => counts_text,
+            current_text => format!("{} {}", counts_text, current_text),
+        };
+        let warnings_text = match self.warnings {
+            1 => "1 warning".to_string(),
+            warnings => format!("{} warnings", warnings),
+        };
+        vec![route_line, format!("{}, {}", warnings_text, self.eta_text(now))]
+    }
+
+    fn clear(&mut self, out: &mut impl Write) {
+        if self.drawn_lines > 0 {
+            // Moves up to the first line of the display and clears from there to the end of the screen
+            write!(out, "\x1b[{}A\x1b[J", self.drawn_lines).ok();
+            self.drawn_lines = 0;
+        }
+    }
+
+    fn draw(&mut self, out: &mut impl Write) {
+        let lines = self.status_lines(Instant::now());
+        for line in &lines {
+            writeln!(out, "{}", line).ok();
+        }
+        self.drawn_lines = lines.len();
+        out.flush().ok();
+    }
+}
+
+impl Progress {
+    fn update<F: FnOnce(&mut ProgressState)>(&self, f: F) {
+        let mut state = self.state.lock().expect("Expect progress state lock");
+        let interactive = self.interactive.load(Ordering::Relaxed);
+        let mut stderr = io::stderr();
+        if interactive {
+            state.clear(&mut stderr);
+        }
+        f(&mut state);
+        if interactive && state.total_routes > 0 {
+            state.draw(&mut stderr);
+        }
+    }
+
+    pub fn start(&self, total_routes: usize) {
+        self.update(|state| {
+            *state = ProgressState {
+                total_routes,
+                done_routes: 0,
+                current: Vec::new(),
+                warnings: state.warnings,
+                started_at: Instant::now(),
+                drawn_lines: 0,
+            };
+        });
+    }
+
+    pub fn stage(&self, terminal_pair: TerminalPair, stage: RouteStage) {
+        debug!("{}: {}", terminal_pair, stage.description());
+        self.update(|state| match state.current.iter_mut().find(|(tp, _)| *tp == terminal_pair) {
+            Some(current) => current.1 = stage,
+            None => state.current.push((terminal_pair, stage)),
+        });
+    }
+
+    pub fn finish_route(&self, terminal_pair: TerminalPair, schedules: usize) {
+        let eta_text = {
+            let mut state = self.state.lock().expect("Expect progress state lock");
+            state.done_routes += 1;
+            state.current.retain(|(tp, _)| *tp != terminal_pair);
+            format!("[{}/{}] {}", state.done_routes, state.total_routes, state.eta_text(Instant::now()))
+        };
+        info!("Scraped {} schedules for {} {}", schedules, terminal_pair, eta_text);
+    }
+
+    // Removes the status display, leaving the log output
+    pub fn finish(&self) {
+        let mut summary = String::new();
+        self.update(|state| {
+            summary = format!(
+                "Scraped {}/{} routes in {} with {} warnings",
+                state.done_routes,
+                state.total_routes,
+                format_seconds(state.started_at.elapsed().as_secs()),
+                state.warnings
+            );
+            state.total_routes = 0;
+            state.current.clear();
+        });
+        info!("{}", summary);
+    }
+}
+
+// Logs through `env_logger`, counting warnings and keeping the status display below what is logged
+pub struct ProgressLogger {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for ProgressLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        PROGRESS.update(|state| {
+            if record.level() == log::Level::Warn {
+                state.warnings += 1;
+            }
+            self.inner.log(record);
+        });
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+// The status display is only shown on a terminal, since it rewrites the lines below the log output
+pub fn init_progress_logger(logger: env_logger::Logger, show_progress: bool) {
+    PROGRESS.interactive.store(show_progress && atty::is(atty::Stream::Stderr), Ordering::Relaxed);
+    let max_level = logger.filter();
+    log::set_boxed_logger(Box::new(ProgressLogger { inner: logger })).expect("Expect logger to be set only once");
+    log::set_max_level(max_level);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_lines() -> Result<()> {
+        let started_at = Instant::now();
+        let mut state = ProgressState {
+            total_routes: 40,
+            done_routes: 10,
+            current: vec![(TerminalPair { from: Terminal::SWB, to: Terminal::TSA }, RouteStage::Parsing)],
+            warnings: 1,
+            started_at,
+            drawn_lines: 0,
+        };
+        let now = started_at + std::time::Duration::from_secs(25);
+        assert_eq!(state.status_lines(now), ["[10/40] SWB-TSA: parsing", "1 warning, ETA 1m 15s"]);
+        state.current.push((TerminalPair { from: Terminal::TSA, to: Terminal::SWB }, RouteStage::Fetching));
+        assert_eq!(state.status_lines(now)[0], "[10/40] SWB-TSA: parsing, TSA-SWB: fetching");
+        state.done_routes = 0;
+        state.current.clear();
+        assert_eq!(state.status_lines(now), ["[0/40]", "1 warning, ETA unknown"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use crate::archive::read_archive;
+use crate::prelude::*;
+
+// Schedule periods usually start on the same weekday each year, so last year's period may have started a little later
+// in the calendar
+const PERIOD_START_TOLERANCE_DAYS: i64 = 14;
+
+// The same calendar date in another year, with Feb 29 becoming Feb 28 in years without it
+fn shift_years(date: Date, years: i32) -> Date {
+    let year = date.year() + years;
+    Date::from_calendar_date(year, date.month(), date.day())
+        .or_else(|_| Date::from_calendar_date(year, date.month(), date.day() - 1))
+        .expect("Expect date to exist in shifted year")
+}
+
+// Only what runs every week carries over to another year, since dates of holidays and other exceptions move
+fn provisional_item(item: &ScheduleItem) -> Option<ScheduleItem> {
+    let weekdays: HashMap<_, _> = item
+        .weekdays
+        .iter()
+        .filter(|(_, date_restriction)| !matches!(date_restriction, DateRestriction::Only(_)))
+        .map(|(&weekday, _)| (weekday, DateRestriction::All))
+        .collect();
+    (!weekdays.is_empty()).then(|| ScheduleItem {
+        sailing: item.sailing.clone(),
+        weekdays,
+        notes: item
+            .notes
+            .iter()
+            .filter(|(_, date_restriction)| **date_restriction == DateRestriction::All)
+            .map(|(note, date_restriction)| (note.clone(), date_restriction.clone()))
+            .collect(),
+        note_categories: item.note_categories.clone(),
+        vehicle_restrictions: item.vehicle_restrictions.clone(),
+        conditional_stops: vec![],
+    })
+}
+
+fn provisional_schedule(basis: &Schedule, from: Date) -> Schedule {
+    Schedule {
+        terminal_pair: basis.terminal_pair,
+        date_range: DateRange { from, to: shift_years(basis.date_range.to, 1) },
+        items: basis.items.iter().filter_map(provisional_item).collect(),
+        source_url: basis.source_url.clone(),
+        refreshed_at: basis.refreshed_at,
+        alerts: vec![],
+        effective_from_time: None,
+        operator: basis.operator,
+        header_notes: vec![],
+        previous_items: vec![],
+        suspensions: vec![],
+        provisional_basis: Some(basis.date_range),
+    }
+}
+
+// Provisional schedules for the period after each BC Ferries route's last published schedule, extrapolated from the
+// archived schedule that covered the same dates last year
+fn provisional_schedules(archived: &[&Schedule], schedules: &[Schedule]) -> Vec<Schedule> {
+    let last_dates = schedules
+        .iter()
+        .filter(|s| s.operator == Operator::BcFerries)
+        .map(|s| (s.terminal_pair, s.date_range.to))
+        .into_grouping_map()
+        .max();
+    last_dates
+        .into_iter()
+        .sorted()
+        .filter_map(|(terminal_pair, last_date)| {
+            let from = last_date.next_day()?;
+            let last_year_from = shift_years(from, -1);
+            let basis = archived
+                .iter()
+                .filter(|s| s.terminal_pair == terminal_pair && s.operator == Operator::BcFerries)
+                .filter(|s| {
+                    s.date_range.to >= last_year_from
+                        && s.date_range.from <= last_year_from + Duration::days(PERIOD_START_TOLERANCE_DAYS)
+                })
+                .min_by_key(|s| s.date_range.from)?;
+            let provisional = provisional_schedule(basis, from);
+            (provisional.date_range.to >= from && !provisional.items.is_empty()).then_some(provisional)
+        })
+        .collect()
+}
+
+// Helps long-range planners, who would otherwise see no sailings at all past the published schedules
+pub fn add_provisional_schedules(archive_dir: &Path, schedules: &mut Vec<Schedule>) -> Result<()> {
+    let snapshots = read_archive(archive_dir)?;
+    // Later snapshots have the latest version of each schedule, and provisional schedules are never a basis for more
+    let archived: Vec<_> = snapshots
+        .iter()
+        .rev()
+        .flat_map(|(_, schedules)| schedules)
+        .filter(|s| s.provisional_basis.is_none())
+        .unique_by(|s| (s.terminal_pair, s.operator, s.date_range))
+        .collect();
+    for provisional in provisional_schedules(&archived, schedules) {
+        info!("Adding provisional schedule for {}, {}", provisional.terminal_pair, provisional.date_range);
+        schedules.push(provisional);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TERMINAL_PAIR: TerminalPair = TerminalPair { from: Terminal::SWB, to: Terminal::PSB };
+
+    fn schedule(date_range: DateRange, items: Vec<ScheduleItem>) -> Schedule {
+        Schedule { date_range, items, ..Schedule::test_fixture(TERMINAL_PAIR) }
+    }
+
+    fn item(depart_time: Time, weekdays: Vec<(Weekday, DateRestriction)>) -> ScheduleItem {
+        ScheduleItem {
+            sailing: Sailing { depart_time, arrive_time: depart_time + Duration::hours(1), stops: vec![] },
+            weekdays: weekdays.into_iter().collect(),
+            notes: HashMap::new(),
+            note_categories: HashMap::new(),
+            vehicle_restrictions: HashMap::new(),
+            conditional_stops: vec![],
+        }
+    }
+
+    #[test]
+    fn test_provisional_schedules() -> Result<()> {
+        let last_year = schedule(
+            DateRange { from: date!(2021 - 10 - 12), to: date!(2022 - 01 - 03) },
+            vec![
+                item(
+                    time!(09:00),
+                    vec![
+                        (Weekday::Monday, DateRestriction::Except(HashSet::from([date!(2021 - 12 - 27)]))),
+                        (Weekday::Tuesday, DateRestriction::All),
+                    ],
+                ),
+                // Holiday-only sailings don't carry over
+                item(
+                    time!(12:00),
+                    vec![(Weekday::Monday, DateRestriction::Only(HashSet::from([date!(2021 - 12 - 27)])))],
+                ),
+            ],
+        );
+        let current = schedule(
+            DateRange { from: date!(2022 - 06 - 30), to: date!(2022 - 10 - 10) },
+            vec![item(time!(10:00), vec![(Weekday::Monday, DateRestriction::All)])],
+        );
+        let provisional = provisional_schedules(&[&last_year], &[current]);
+        assert_eq!(provisional.len(), 1);
+        assert_eq!(provisional[0].date_range, DateRange { from: date!(2022 - 10 - 11), to: date!(2023 - 01 - 03) });
+        assert_eq!(provisional[0].provisional_basis, Some(last_year.date_range));
+        assert_eq!(provisional[0].items.len(), 1);
+        assert_eq!(provisional[0].items[0].weekdays[&Weekday::Monday], DateRestriction::All);
+        assert_eq!(shift_years(date!(2024 - 02 - 29), 1), date!(2025 - 02 - 28));
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use crate::prelude::*;
+
+// Planned vessel refits, maintained by hand from BC Ferries' announcements.  Each entry is like:
+//
+//     {
+//         "vessel": "Queen of Cumberland",
+//         "terminals": ["SWB", "PLH", "POB", "PSB", "PST", "PVB"],
+//         "dates": { "from": "2024-01-08", "to": "2024-02-29" },
+//         "announcement_url": "https://www.bcferries.com/..."
+//     }
+//
+// where `terminals` are those of the routes the vessel usually serves.  Entries can be removed once they are over.
+const REFITS_JSON: &str = include_str!("../data/refits.json");
+
+#[derive(Debug, Deserialize)]
+struct Refit {
+    vessel: String,
+    terminals: Vec<Terminal>,
+    dates: DateRange,
+    announcement_url: String,
+}
+
+fn parse_refits(json: &str) -> Result<Vec<Refit>> {
+    serde_json::from_str(json).context("Failed to parse refits JSON")
+}
+
+fn refit_alert(refit: &Refit, schedule: &Schedule) -> Option<Alert> {
+    let terminal_pair = schedule.terminal_pair;
+    if !refit.terminals.contains(&terminal_pair.from) || !refit.terminals.contains(&terminal_pair.to) {
+        return None;
+    }
+    let relevant_dates = DateRange {
+        from: max(refit.dates.from, schedule.date_range.from),
+        to: min(refit.dates.to, schedule.date_range.to),
+    };
+    if relevant_dates.from > relevant_dates.to {
+        return None;
+    }
+    let format_date = |date: Date| {
+        date.format(format_description!("[month repr:short] [day padding:none]")).expect("Expect refit date to format")
+    };
+    Some(Alert {
+        message: format!(
+            "The {} is out of service for a planned refit from {} to {}, so service may be reduced even if this \
+            schedule has not changed. See {}",
+            refit.vessel,
+            format_date(refit.dates.from),
+            format_date(refit.dates.to),
+            refit.announcement_url
+        ),
+        level: AlertLevel::Warning,
+        severity: Some(AlertSeverity::Advisory),
+        relevant_date_ranges: vec![relevant_dates],
+    })
+}
+
+// Warns on the routes of vessels with planned refits, for the dates the refits overlap each schedule
+pub fn add_refit_alerts(schedules: &mut [Schedule]) -> Result<()> {
+    let refits = parse_refits(REFITS_JSON)?;
+    for schedule in schedules {
+        let alerts: Vec<_> = refits.iter().filter_map(|refit| refit_alert(refit, schedule)).collect();
+        schedule.alerts.extend(alerts);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_refits_file() -> Result<()> {
+        parse_refits(REFITS_JSON)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_refit_alert() -> Result<()> {
+        let refits = parse_refits(
+            r#"[{
+                "vessel": "Queen of Cumberland",
+                "terminals": ["SWB", "PSB", "PVB"],
+                "dates": { "from": "2024-01-08", "to": "2024-04-15" },
+                "announcement_url": "https://example.com/refit"
+            }]"#,
+        )?;
+        let schedule = |terminal_pair| Schedule {
+            date_range: DateRange { from: date!(2024 - 01 - 01), to: date!(2024 - 03 - 31) },
+            ..Schedule::test_fixture(terminal_pair)
+        };
+        let alert = refit_alert(&refits[0], &schedule(TerminalPair { from: Terminal::PSB, to: Terminal::SWB }))
+            .expect("Expect alert for the vessel's route");
+        assert_eq!(alert.relevant_date_ranges, [DateRange { from: date!(2024 - 01 - 08), to: date!(2024 - 03 - 31) }]);
+        assert!(alert
+            .message
+            .starts_with("The Queen of Cumberland is out of service for a planned refit from Jan 8 to Apr 15"));
+        assert!(refit_alert(&refits[0], &schedule(TerminalPair { from: Terminal::TSA, to: Terminal::SWB })).is_none());
+        Ok(())
+    }
+}
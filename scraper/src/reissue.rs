@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use crate::prelude::*;
+
+fn same_items(a: &[ScheduleItem], b: &[ScheduleItem]) -> bool {
+    // Item order depends on how they were merged, so compare regardless of order
+    a.len() == b.len() && a.iter().all(|item| b.contains(item))
+}
+
+pub fn read_schedules_file(path: &Path) -> Result<Vec<Schedule>> {
+    let data: PublishedData = serde_json::from_reader(io::BufReader::new(fs::File::open(path)?))?;
+    Ok(data.into_schedules())
+}
+
+pub fn read_previous_schedules(path: &Path) -> Result<Vec<Schedule>> {
+    if !path.exists() {
+        warn!("Previously published schedules file does not exist: {:?}", path);
+        return Ok(vec![]);
+    }
+    read_schedules_file(path).with_context(|| format!("Failed to read previously published schedules: {:?}", path))
+}
+
+// Keeps the items of the previously published schedule for periods that were re-issued with changes, so the frontend
+// can mark the changed sailings
+pub fn mark_reissued_schedules(schedules: &mut [Schedule], previous_schedules: &[Schedule]) {
+    for schedule in schedules {
+        let previous_schedule = previous_schedules.iter().find(|p| {
+            p.terminal_pair == schedule.terminal_pair
+                && p.date_range == schedule.date_range
+                && p.operator == schedule.operator
+        });
+        if let Some(previous_schedule) = previous_schedule {
+            if same_items(&schedule.items, &previous_schedule.items) {
+                // Unchanged since the last run, so keep showing any earlier re-issue
+                schedule.previous_items = previous_schedule.previous_items.clone();
+            } else {
+                info!("Schedule for {}, {} was re-issued with changes", schedule.terminal_pair, schedule.date_range);
+                schedule.previous_items = previous_schedule.items.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(depart_times: &[Time]) -> Schedule {
+        Schedule {
+            items: depart_times
+                .iter()
+                .map(|&depart_time| ScheduleItem {
+                    sailing: Sailing { depart_time, arrive_time: depart_time + Duration::hours(1), stops: vec![] },
+                    weekdays: HashMap::from([(Weekday::Monday, DateRestriction::All)]),
+                    notes: HashMap::new(),
+                    note_categories: HashMap::new(),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                })
+                .collect(),
+            ..Schedule::test_fixture(TerminalPair { from: Terminal::SWB, to: Terminal::PVB })
+        }
+    }
+
+    #[test]
+    fn test_mark_reissued_schedules() -> Result<()> {
+        let published = schedule(&[time!(07:00), time!(09:00)]);
+        let mut unchanged = vec![schedule(&[time!(09:00), time!(07:00)])];
+        mark_reissued_schedules(&mut unchanged, std::slice::from_ref(&published));
+        assert!(unchanged[0].previous_items.is_empty());
+        let mut reissued = vec![schedule(&[time!(07:00), time!(09:30)])];
+        mark_reissued_schedules(&mut reissued, std::slice::from_ref(&published));
+        assert_eq!(reissued[0].previous_items, published.items);
+        // The next run keeps the marker while the re-issued schedule is unchanged
+        let mut next_run = vec![schedule(&[time!(07:00), time!(09:30)])];
+        mark_reissued_schedules(&mut next_run, &reissued);
+        assert_eq!(next_run[0].previous_items, published.items);
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use crate::cache::*;
+use crate::constants::*;
+use crate::macros::*;
+use crate::prelude::*;
+use crate::source_urls::*;
+use crate::types::*;
+
+// Route that has a schedules page, and where its schedules are listed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RouteSource {
+    pub terminal_pair: TerminalPair,
+    pub base_url: String,
+}
+
+impl RouteSource {
+    fn configured(terminal_pair: TerminalPair) -> RouteSource {
+        RouteSource {
+            terminal_pair,
+            base_url: redirect_source_url(&format!(
+                "{}/{}",
+                OTHER_ROUTE_SCHEDULES_BASE_URL,
+                terminal_pair.to_schedule_code_pair()
+            )),
+        }
+    }
+}
+
+// Routes linked from the schedules index.  Links to routes with a terminal that isn't in `Terminal` are skipped with a
+// warning, since their schedules can't be represented until it is added.
+fn parse_schedules_index(document: &Html) -> Vec<RouteSource> {
+    let route_href_regex = regex!(
+        r"^(?:https://www\.bcferries\.com)?(/routes-fares/schedules/[a-z-]+)/([A-Za-z]{3}-[A-Za-z]{3})/?(?:[?#].*)?$"
+    );
+    let mut routes: Vec<RouteSource> = Vec::new();
+    for href in document.select(selector!("a[href]")).filter_map(|e| e.value().attr("href")) {
+        let captures = match route_href_regex.captures(href) {
+            Some(captures) => captures,
+            None => continue,
+        };
+        let code_pair = captures[2].to_uppercase();
+        let terminal_pair: TerminalPair = match code_pair.parse() {
+            Ok(terminal_pair) => terminal_pair,
+            Err(err) => {
+                warn!("Schedules index links to a route with an unknown terminal: {:?}: {:?}", href, err);
+                continue;
+            }
+        };
+        if routes.iter().any(|r| r.terminal_pair == terminal_pair) {
+            continue;
+        }
+        // Schedule pages are linked with varying case, but the source URLs are always upper case
+        let base_url = redirect_source_url(&format!("{}{}/{}", BCFERRIES_BASE_URL, &captures[1], terminal_pair));
+        routes.push(RouteSource { terminal_pair, base_url });
+    }
+    routes
+}
+
+async fn discover_routes(cache: &Cache<'_>) -> Result<Vec<RouteSource>> {
+    let inner = async {
+        let document = cache
+            .get_html(ALL_SCHEDULES_URL, &HTML_ERROR_REGEX)
+            .await
+            .with_context(|| format!("Failed to download schedules index HTML from: {:?}", ALL_SCHEDULES_URL))?;
+        let routes = parse_schedules_index(&document);
+        ensure!(!routes.is_empty(), "Failed to find any route links");
+        Ok(routes) as Result<_>
+    };
+    inner.await.context("Failed to discover routes from schedules index")
+}
+
+// Configured routes missing from the index are still scraped, so a change to the index page's layout only loses the
+// routes nobody has configured yet, rather than all of them
+fn merge_routes(discovered: Vec<RouteSource>) -> Vec<RouteSource> {
+    for terminal_pair in ALL_TERMINAL_PAIRS.iter().sorted() {
+        if !discovered.iter().any(|r| r.terminal_pair == *terminal_pair) {
+            warn!("Configured route no longer appears in schedules index: {}", terminal_pair);
+        }
+    }
+    for route in discovered.iter().filter(|r| !ALL_TERMINAL_PAIRS.contains(&r.terminal_pair)) {
+        warn!(
+            "Scraping route that is not configured (add it to ALL_TERMINAL_PAIRS to show it in the front-end): {}",
+            route.terminal_pair
+        );
+    }
+    let missing_configured: Vec<_> = ALL_TERMINAL_PAIRS
+        .iter()
+        .filter(|tp| !discovered.iter().any(|r| r.terminal_pair == **tp))
+        .map(|&tp| RouteSource::configured(tp))
+        .collect();
+    discovered.into_iter().chain(missing_configured).sorted_by_key(|r| r.terminal_pair).collect()
+}
+
+// Routes to scrape: those linked from BC Ferries' schedules index, so new seasonal routes are picked up without a code
+// change, narrowed by the command-line route filters.  Falls back to the configured routes if the index can't be read.
+pub async fn routes_to_scrape(options: &Options, cache: &Cache<'_>) -> Vec<RouteSource> {
+    let routes = match discover_routes(cache).await {
+        Ok(discovered) => merge_routes(discovered),
+        Err(err) => {
+            warn!("{:?}", err);
+            ALL_TERMINAL_PAIRS.iter().sorted().map(|&tp| RouteSource::configured(tp)).collect()
+        }
+    };
+    for terminal_pair in options.terminals.iter().chain(&options.skip_terminals) {
+        if !routes.iter().any(|r| r.terminal_pair == *terminal_pair) {
+            warn!("Route filter does not match any route: {}", terminal_pair);
+        }
+    }
+    routes.into_iter().filter(|r| options.includes_route(r.terminal_pair)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_schedules_index() -> Result<()> {
+        let document = Html::parse_document(
+            r#"<ul>
+                <li><a href="/routes-fares/schedules/seasonal/TSA-SWB">Vancouver - Victoria</a></li>
+                <li><a href="https://www.bcferries.com/routes-fares/schedules/seasonal/swb-tsa?departureDate=2022-06-15">Victoria - Vancouver</a></li>
+                <li><a href="/routes-fares/schedules/seasonal/TSA-SWB/">Vancouver - Victoria</a></li>
+                <li><a href="/routes-fares/schedules/daily/XYZ-SWB">Somewhere new - Victoria</a></li>
+                <li><a href="/routes-fares/schedules/southern-gulf-islands">Southern Gulf Islands</a></li>
+            </ul>"#,
+        );
+        assert_eq!(
+            parse_schedules_index(&document),
+            [
+                RouteSource {
+                    terminal_pair: TerminalPair { from: Terminal::TSA, to: Terminal::SWB },
+                    base_url: "https://www.bcferries.com/routes-fares/schedules/seasonal/TSA-SWB".to_string(),
+                },
+                RouteSource {
+                    terminal_pair: TerminalPair { from: Terminal::SWB, to: Terminal::TSA },
+                    base_url: "https://www.bcferries.com/routes-fares/schedules/seasonal/SWB-TSA".to_string(),
+                },
+            ]
+        );
+        Ok(())
+    }
+}
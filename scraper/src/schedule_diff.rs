@@ -0,0 +1,177 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::prelude::*;
+use crate::reissue::read_schedules_file;
+use crate::summaries::*;
+
+// A human-readable changelog between two schedules outputs, for reviewing a scrape before publishing it.  Schedules
+// are matched by route, date range and operator, so a schedule whose date range changed shows as removed and added.
+
+type ScheduleKey = (TerminalPair, DateRange, Operator);
+
+fn schedule_key(schedule: &Schedule) -> ScheduleKey {
+    (schedule.terminal_pair, schedule.date_range, schedule.operator)
+}
+
+fn schedule_title(schedule: &Schedule) -> String {
+    let mut title =
+        format!("{} to {}", format_summary_date(schedule.date_range.from), format_summary_date(schedule.date_range.to));
+    if schedule.operator != Operator::BcFerries {
+        write!(title, " ({})", schedule.operator.name()).expect("Expect write to string");
+    }
+    title
+}
+
+fn sailing_text(sailing: &Sailing) -> String {
+    format!("{} to {}", format_summary_time(sailing.depart_time), format_summary_time(sailing.arrive_time))
+}
+
+// Everything about an item besides its sailing, which is what identifies it between the two outputs
+fn item_text(item: &ScheduleItem) -> String {
+    let weekdays: HashSet<_> = item.weekdays.keys().copied().collect();
+    [weekdays_pattern(&weekdays), stops_text(&item.sailing.stops)].into_iter().chain(item_exceptions(item)).join("; ")
+}
+
+fn items_diff(old_items: &[ScheduleItem], new_items: &[ScheduleItem]) -> Vec<String> {
+    let old_items: HashMap<_, _> = old_items.iter().map(|item| (&item.sailing, item)).collect();
+    let new_items: HashMap<_, _> = new_items.iter().map(|item| (&item.sailing, item)).collect();
+    let sailings = old_items.keys().chain(new_items.keys()).copied().unique().sorted();
+    sailings
+        .filter_map(|sailing| match (old_items.get(sailing), new_items.get(sailing)) {
+            (Some(old_item), Some(new_item)) if old_item == new_item => None,
+            (Some(old_item), Some(new_item)) => {
+                Some(format!("~ {}: {} -> {}", sailing_text(sailing), item_text(old_item), item_text(new_item)))
+            }
+            (Some(old_item), None) => Some(format!("- {}: {}", sailing_text(sailing), item_text(old_item))),
+            (None, Some(new_item)) => Some(format!("+ {}: {}", sailing_text(sailing), item_text(new_item))),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+fn header_notes_diff(old_notes: &[String], new_notes: &[String]) -> Vec<String> {
+    let removed = old_notes.iter().filter(|note| !new_notes.contains(note)).map(|note| format!("- Note: {}", note));
+    let added = new_notes.iter().filter(|note| !old_notes.contains(note)).map(|note| format!("+ Note: {}", note));
+    removed.chain(added).collect()
+}
+
+pub fn schedules_diff(old_schedules: &[Schedule], new_schedules: &[Schedule]) -> String {
+    let old_schedules: HashMap<_, _> = old_schedules.iter().map(|s| (schedule_key(s), s)).collect();
+    let new_schedules: HashMap<_, _> = new_schedules.iter().map(|s| (schedule_key(s), s)).collect();
+    let keys = old_schedules.keys().chain(new_schedules.keys()).copied().unique().sorted_by_key(
+        |(terminal_pair, date_range, operator)| {
+            (
+                terminal_pair.from.area(),
+                terminal_pair.to.area(),
+                *terminal_pair,
+                date_range.from,
+                operator.is_supplemental(),
+            )
+        },
+    );
+    let mut diff = String::new();
+    let mut diff_terminal_pair = None;
+    for key in keys {
+        let (old_schedule, new_schedule) = (old_schedules.get(&key), new_schedules.get(&key));
+        let (heading, lines) = match (old_schedule, new_schedule) {
+            (Some(old_schedule), Some(new_schedule)) => {
+                let lines: Vec<_> = header_notes_diff(&old_schedule.header_notes, &new_schedule.header_notes)
+                    .into_iter()
+                    .chain(items_diff(&old_schedule.items, &new_schedule.items))
+                    .collect();
+                if lines.is_empty() {
+                    continue;
+                }
+                (format!("Changed {}", schedule_title(new_schedule)), lines)
+            }
+            (None, Some(new_schedule)) => {
+                (format!("Added {}: {} sailings", schedule_title(new_schedule), new_schedule.items.len()), vec![])
+            }
+            (Some(old_schedule), None) => {
+                (format!("Removed {}: {} sailings", schedule_title(old_schedule), old_schedule.items.len()), vec![])
+            }
+            (None, None) => continue,
+        };
+        let (terminal_pair, _, _) = key;
+        if diff_terminal_pair != Some(terminal_pair) {
+            writeln!(diff, "{}{}", if diff.is_empty() { "" } else { "\n" }, terminal_pair)
+                .expect("Expect write to string");
+            diff_terminal_pair = Some(terminal_pair);
+        }
+        writeln!(diff, "  {}", heading).expect("Expect write to string");
+        for line in lines {
+            writeln!(diff, "    {}", line).expect("Expect write to string");
+        }
+    }
+    if diff.is_empty() {
+        diff.push_str("No changes\n");
+    }
+    diff
+}
+
+// Prints the changes from the old schedules JSON file to the new one
+pub fn print_schedules_diff(old_path: &Path, new_path: &Path) -> Result<()> {
+    let read = |path: &Path| {
+        read_schedules_file(path).with_context(|| format!("Failed to read schedules to compare: {:?}", path))
+    };
+    print!("{}", schedules_diff(&read(old_path)?, &read(new_path)?));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(depart_time: Time, weekdays: &[Weekday]) -> ScheduleItem {
+        ScheduleItem {
+            sailing: Sailing { depart_time, arrive_time: depart_time + Duration::minutes(95), stops: vec![] },
+            weekdays: weekdays.iter().map(|&weekday| (weekday, DateRestriction::All)).collect(),
+            notes: HashMap::new(),
+            note_categories: HashMap::new(),
+            vehicle_restrictions: HashMap::new(),
+            conditional_stops: vec![],
+        }
+    }
+
+    fn schedule(terminal_pair: TerminalPair, date_range: DateRange, items: Vec<ScheduleItem>) -> Schedule {
+        Schedule { date_range, items, ..Schedule::test_fixture(terminal_pair) }
+    }
+
+    #[test]
+    fn test_schedules_diff() -> Result<()> {
+        let swb_tsa = TerminalPair { from: Terminal::SWB, to: Terminal::TSA };
+        let june = DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 30) };
+        let july = DateRange { from: date!(2022 - 07 - 01), to: date!(2022 - 07 - 31) };
+        let old = vec![
+            schedule(
+                swb_tsa,
+                june,
+                vec![item(time!(07:00), &[Weekday::Monday]), item(time!(09:00), &[Weekday::Monday])],
+            ),
+            schedule(swb_tsa.swapped(), june, vec![item(time!(07:00), &[Weekday::Monday])]),
+        ];
+        let mut changed_item = item(time!(09:00), &[Weekday::Monday, Weekday::Tuesday]);
+        changed_item.notes.insert("Foot passengers only".into(), DateRestriction::All);
+        let new = vec![
+            Schedule {
+                header_notes: vec!["Reservations recommended".to_string()],
+                ..schedule(swb_tsa, june, vec![changed_item, item(time!(11:00), &[Weekday::Monday])])
+            },
+            schedule(swb_tsa, july, vec![item(time!(07:00), &[Weekday::Monday])]),
+            schedule(swb_tsa.swapped(), june, vec![item(time!(07:00), &[Weekday::Monday])]),
+        ];
+        assert_eq!(
+            schedules_diff(&old, &new),
+            "SWB-TSA\n\
+             \x20 Changed Jun 1, 2022 to Jun 30, 2022\n\
+             \x20   + Note: Reservations recommended\n\
+             \x20   - 7:00 am to 8:35 am: Mon; Non-stop\n\
+             \x20   ~ 9:00 am to 10:35 am: Mon; Non-stop -> Mon, Tue; Non-stop; Foot passengers only\n\
+             \x20   + 11:00 am to 12:35 pm: Mon; Non-stop\n\
+             \x20 Added Jul 1, 2022 to Jul 31, 2022: 1 sailings\n"
+        );
+        assert_eq!(schedules_diff(&old, &old), "No changes\n");
+        Ok(())
+    }
+}
@@ -1,13 +1,22 @@
+use futures::stream::{self, StreamExt, TryStreamExt};
+
 use crate::annotations::*;
 use crate::cache::*;
 use crate::constants::*;
 use crate::depart_time_and_row_annotations::*;
-use crate::imports::*;
+use crate::fixtures::*;
 use crate::macros::*;
+use crate::parse_report::*;
+use crate::prelude::*;
+use crate::progress::*;
+use crate::route_discovery::*;
+use crate::sgi_grid::*;
+use crate::source_urls::*;
+use crate::table_layout::*;
 use crate::types::*;
 use crate::utils::*;
 
-fn parse_schedule_path_query(schedule_path_query: &str) -> Result<DateRange> {
+pub fn parse_schedule_path_query(schedule_path_query: &str) -> Result<DateRange> {
     let captures = &regex!("departureDate=([0-9-]*)|departureDateCode=R[0-9]+_([0-9_]*)")
         .captures(schedule_path_query)
         .ok_or_else(|| {
@@ -28,10 +37,11 @@ fn parse_schedule_path_query(schedule_path_query: &str) -> Result<DateRange> {
 fn parse_annotations(
     depart_times_annotations_texts: Vec<String>,
     date_range: &DateRange,
+    lenient: bool,
 ) -> Result<Option<(Annotations, Vec<String>)>> {
     let inner = || {
         let mut depart_times_texts = Vec::new();
-        let mut annotations = Annotations::new();
+        let mut annotations = Annotations { lenient, ..Annotations::new() };
         for depart_times_annotation_text in depart_times_annotations_texts {
             let trimed_commas = regex!(r"^(,\s*)?(.*)(\s*,)?$").replace(&depart_times_annotation_text, "$2");
             if regex!(r"^\d").is_match(trimed_commas.as_ref()) {
@@ -78,98 +88,275 @@ fn parse_stops(stops_texts: Vec<String>) -> Result<Vec<Stop>> {
     inner().with_context(|| format!("Failed to parse stops: {:?}", stops_texts))
 }
 
-fn parse_table(table_elem: ElementRef, date_range: &DateRange) -> Result<Vec<ScheduleItem>> {
+pub fn max_plausible_sailing_duration(terminal_pair: TerminalPair) -> Duration {
+    if terminal_pair.from.area().region() == Region::NorthCoast {
+        MAX_PLAUSIBLE_NORTHERN_SAILING_DURATION
+    } else {
+        MAX_PLAUSIBLE_SAILING_DURATION
+    }
+}
+
+// If most sailings only have plausible durations when depart and arrive times are swapped, the table's columns were
+// probably not in the order we expect.  Only items whose arrival was an actual time (not a duration) are swappable.
+fn swap_implausible_depart_arrive_times(
+    items: &mut [ScheduleItem],
+    swappable: &[bool],
+    max_plausible_duration: Duration,
+) -> bool {
+    let count_implausible = |swap: bool| {
+        items
+            .iter()
+            .zip(swappable)
+            .filter(|(item, &is_swappable)| {
+                let (depart_time, arrive_time) = if swap && is_swappable {
+                    (item.sailing.arrive_time, item.sailing.depart_time)
+                } else {
+                    (item.sailing.depart_time, item.sailing.arrive_time)
+                };
+                sailing_duration(depart_time, arrive_time) > max_plausible_duration
+            })
+            .count()
+    };
+    let implausible_count = count_implausible(false);
+    if implausible_count * 2 <= items.len() || count_implausible(true) >= implausible_count {
+        return false;
+    }
+    for (item, _) in items.iter_mut().zip(swappable).filter(|(_, &is_swappable)| is_swappable) {
+        std::mem::swap(&mut item.sailing.depart_time, &mut item.sailing.arrive_time);
+    }
+    true
+}
+
+// Columns of route schedule tables whose headers are not recognized: an icon, depart time and annotations, arrive time
+// or duration, vessel, stops, and a details toggle
+const ROUTE_TABLE_COLUMNS: &[ColumnKind] = &[
+    ColumnKind::Other,
+    ColumnKind::Depart,
+    ColumnKind::Arrive,
+    ColumnKind::Other,
+    ColumnKind::Stops,
+    ColumnKind::Other,
+];
+
+// Also returns the annotation texts that lenient parsing let through
+fn parse_table(
+    table_elem: ElementRef,
+    terminal_pair: TerminalPair,
+    date_range: &DateRange,
+    lenient: bool,
+) -> Result<(Vec<ScheduleItem>, Vec<String>)> {
     let inner = || {
+        let layout = TableLayout::parse(table_elem, selector!("tr.schedule-table-row"))?;
+        let columns = layout.typed_columns(ROUTE_TABLE_COLUMNS);
+        let column_index = |kind| {
+            columns
+                .iter()
+                .position(|&k| k == kind)
+                .ok_or_else(|| anyhow!("Missing {:?} column in schedule table: {:?}", kind, layout.headers))
+        };
+        let (depart_index, arrive_index, stops_index) =
+            (column_index(ColumnKind::Depart)?, column_index(ColumnKind::Arrive)?, column_index(ColumnKind::Stops)?);
         let mut items = Vec::new();
-        for day_row_elem in table_elem.select(selector!("thead tr")) {
-            let weekday_text = day_row_elem
-                .value()
-                .attr("data-schedule-day")
-                .ok_or_else(|| anyhow!("Expect day row element to have 'data-schedule-day' attribute"))?;
-            let weekday_sailings_tbody_elem = day_row_elem
-                .parent_element()
-                .expect("Expect weekday row element to have parent")
-                .next_sibling_element()
-                .ok_or_else(|| anyhow!("Expect schedule row thead element after weekday row element"))?;
-            for sailing_row_elem in weekday_sailings_tbody_elem.select(selector!("tr.schedule-table-row")) {
-                let cell_elems: Vec<_> = sailing_row_elem.select(selector!("td")).collect();
+        let mut arrive_is_times = Vec::new();
+        let mut unrecognized = Vec::new();
+        for section in &layout.sections {
+            let weekday_text =
+                section.day.as_deref().ok_or_else(|| anyhow!("Expect schedule rows to follow a weekday row"))?;
+            let weekday = parse_weekday(weekday_text)?;
+            for cells in &section.rows {
                 ensure!(
-                    cell_elems.len() == 6,
-                    "Row should have six cells: {:?}",
-                    cell_elems.iter().map(element_text).collect::<Vec<_>>()
+                    cells.len() == columns.len(),
+                    "Row should have {} cells: {:?}",
+                    columns.len(),
+                    cells.iter().map(LayoutCell::text).collect::<Vec<_>>()
                 );
-                let (annotations, depart_times_texts) =
-                    match parse_annotations(element_texts(&cell_elems[1]), date_range)? {
+                let (mut annotations, depart_times_texts) =
+                    match parse_annotations(cells[depart_index].texts.clone(), date_range, lenient)? {
                         None => continue,
                         Some(result) => result,
                     };
+                unrecognized.append(&mut annotations.unrecognized);
                 let depart_times = parse_depart_times_and_annotations(depart_times_texts, &annotations)?;
                 ensure!(depart_times.len() == 1, "Expect exactly one depart time in row");
                 let depart_time = depart_times.into_iter().next().expect("Expect at least one depart time in row");
-                let weekday = parse_weekday(weekday_text)?;
-                let arrive_time = parse_arrive_time_or_duration(depart_time.time, &element_text(&cell_elems[2]))?;
+                let arrive_text = cells[arrive_index].text();
+                let arrive_time = parse_arrive_time_or_duration(depart_time.time, &arrive_text)?;
                 if arrive_time != depart_time.time {
-                    let stops = parse_stops(element_texts(&cell_elems[4]))?;
+                    let stops = parse_stops(cells[stops_index].texts.clone())?;
                     let date_restriction = depart_time.row_dates.into_date_restriction_by_weekday(weekday);
-                    let notes = annotation_notes_date_restictions(depart_time.row_notes, weekday, &date_restriction);
+                    let WeekdayNotes { notes, note_categories, vehicle_restrictions } =
+                        annotation_notes_date_restictions(depart_time.row_notes, weekday, &date_restriction);
+                    let conditional_stops = AnnotationDates::map_to_date_restrictions_by_weekday(
+                        depart_time.row_stops,
+                        weekday,
+                        &date_restriction,
+                    )
+                    .into_iter()
+                    .filter(|(terminal, _)| !stops.iter().any(|stop| stop.terminal == *terminal))
+                    .map(|(terminal, dates)| ConditionalStop { stop: Stop { type_: StopType::Stop, terminal }, dates })
+                    .sorted_by_key(|cs| cs.stop)
+                    .collect();
                     items.push(ScheduleItem {
-                        sailing: Sailing { depart_time: depart_time.time, arrive_time, stops: stops.clone() },
+                        sailing: Sailing { depart_time: depart_time.time, arrive_time, stops },
                         weekdays: HashMap::from_iter([(weekday, date_restriction)]),
                         notes,
+                        note_categories,
+                        vehicle_restrictions,
+                        conditional_stops,
                     });
+                    arrive_is_times.push(parse_schedule_time(strip_next_day_marker(&arrive_text)).is_ok());
                 }
             }
         }
-        ScheduleItem::merge_items(items)
+        if swap_implausible_depart_arrive_times(
+            &mut items,
+            &arrive_is_times,
+            max_plausible_sailing_duration(terminal_pair),
+        ) {
+            warn!(
+                "Most sailings in {} schedule have implausible durations; interpreted depart and arrive columns as swapped",
+                date_range
+            );
+        }
+        Ok((ScheduleItem::merge_items(items)?, unrecognized))
     };
     inner().context("Failed to parse route schedule table")
 }
 
-async fn scrape_schedule(
-    options: &Options,
+// Informational paragraphs in the schedule page header, which are not part of the schedule table
+fn parse_header_notes(document: &Html) -> Vec<String> {
+    document
+        .select(selector!("div.seasonal-schedule-wrapper > p"))
+        .map(|elem| element_text(&elem))
+        .filter(|text| !text.is_empty())
+        .collect()
+}
+
+// Statements like "No service Jan 6 – Feb 28" in the header notes, which mean the route has no sailings at all on
+// those dates even though the schedule table still lists them
+fn parse_suspensions(header_notes: &[String], date_range: &DateRange) -> Result<Vec<DateRange>> {
+    let mut suspensions = Vec::new();
+    for note in header_notes {
+        for captures in regex!(
+            r"(?i)\bno (?:service|sailings)\b[^.]*?\b(jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.? (\d{1,2})\s*(?:–|—|-|to|through)\s*(?:(jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.? )?(\d{1,2})\b"
+        )
+        .captures_iter(note)
+        {
+            let from_month = &captures[1];
+            let to_month = captures.get(3).map(|m| m.as_str()).unwrap_or(from_month);
+            let from_text = format!("{} {}", from_month, &captures[2]);
+            let to_text = format!("{} {}", to_month, &captures[4]);
+            match (date_range.parse_date_within(&from_text)?, date_range.parse_date_within(&to_text)?) {
+                (Some(from), Some(to)) if from <= to => suspensions.push(DateRange { from, to }),
+                _ => warn!("Ignoring service suspension outside of {} schedule: {:?}", date_range, note),
+            }
+        }
+    }
+    Ok(suspensions)
+}
+
+// A statement like "This schedule takes effect at 12:00 pm on Jun 27" in the header notes, for a schedule that replaces
+// the previous one part way through its first day
+fn parse_effective_from_time(header_notes: &[String]) -> Result<Option<Time>> {
+    header_notes
+        .iter()
+        .find_map(|note| {
+            regex!(r"(?i)\b(?:effective|takes? effect)\b[^.]*?\b(\d{1,2}[:.]\d{2} ?[ap]m)\b").captures(note)
+        })
+        .map(|captures| parse_schedule_time(&captures[1]))
+        .transpose()
+}
+
+// Parses a schedule page that has already been fetched, which is also how saved fixture pages are replayed
+pub fn parse_schedule_page(
     source_url: &str,
     document: &Html,
     terminal_pair: TerminalPair,
+    date_range: DateRange,
     index: usize,
-    schedule_path_query_text: &str,
-    today: Date,
-) -> Result<Option<Schedule>> {
-    let inner = async {
-        let date_range = parse_schedule_path_query(schedule_path_query_text)
-            .with_context(|| format!("Failed to schedule path/query: {:?}", schedule_path_query_text))?;
-        if !should_scrape_schedule_date(date_range, today, options.date) {
-            return Ok(None);
-        }
-        if DISABLED_TERMINAL_PAIRS.contains(&terminal_pair) {
-            info!("Skipping parsing disabled schedule for {}, {}", terminal_pair, date_range);
-            return Ok(Some(Schedule {
+    lenient_annotations: bool,
+) -> Result<Option<(Schedule, Vec<String>)>> {
+    let configured_effective_from_time = SCHEDULE_EFFECTIVE_FROM_TIMES.get(&(terminal_pair, date_range.from)).copied();
+    if DISABLED_TERMINAL_PAIRS.contains(&terminal_pair) {
+        info!("Skipping parsing disabled schedule for {}, {}", terminal_pair, date_range);
+        return Ok(Some((
+            Schedule {
                 terminal_pair,
                 date_range,
                 items: vec![],
                 source_url: source_url.to_string(),
                 refreshed_at: now_vancouver(),
-                alerts: vec![Alert {message: "THIS SCHEDULE IS CURRENTLY UNAVAILABLE!  BC Ferries has re-worked the schedule page on their website and the scraper needs to be updated to understand it.  I'm working on it!".to_string(), level: AlertLevel::Danger}],
-            }));
-        }
-        info!("Parsing schedule for {}, {}", terminal_pair, date_range);
-        let opt_table_elem = document.select(selector!("div.seasonal-schedule-wrapper table")).next();
-        if let Some(table_elem) = opt_table_elem {
-            let items = parse_table(table_elem, &date_range)?;
-            Ok(Some(Schedule {
+                alerts: vec![Alert {message: "THIS SCHEDULE IS CURRENTLY UNAVAILABLE!  BC Ferries has re-worked the schedule page on their website and the scraper needs to be updated to understand it.  I'm working on it!".to_string(), level: AlertLevel::Danger, severity: None, relevant_date_ranges: vec![]}],
+                effective_from_time: configured_effective_from_time,
+                operator: Operator::BcFerries,
+                header_notes: vec![],
+                previous_items: vec![],
+                suspensions: vec![],
+                provisional_basis: None,
+            },
+            vec![],
+        )));
+    }
+    info!("Parsing schedule for {}, {}", terminal_pair, date_range);
+    let opt_grid_elem = document.select(selector!("div.seasonal-schedule-wrapper table.schedule-grid")).next();
+    let opt_table_elem = document.select(selector!("div.seasonal-schedule-wrapper table")).next();
+    if let Some(table_elem) = opt_table_elem {
+        let (items, unrecognized) = match opt_grid_elem {
+            Some(grid_elem) => (parse_sgi_grid(grid_elem)?.remove(&terminal_pair).unwrap_or_default(), vec![]),
+            None => parse_table(table_elem, terminal_pair, &date_range, lenient_annotations)?,
+        };
+        let header_notes = parse_header_notes(document);
+        let suspensions = parse_suspensions(&header_notes, &date_range)?;
+        let effective_from_time = match configured_effective_from_time {
+            Some(time) => Some(time),
+            None => parse_effective_from_time(&header_notes)?,
+        };
+        Ok(Some((
+            Schedule {
                 terminal_pair,
                 date_range,
                 items,
                 source_url: source_url.to_string(),
                 refreshed_at: now_vancouver(),
                 alerts: vec![],
-            })) as Result<_>
-        } else if index == 0 {
-            // If the table element is missing in the initial schedule page for the route, we have a problem
-            bail!("Missing table element in schedule");
-        } else {
-            // However, sometimes the initial page has links to non-existent schedules in which case we can ignore them
-            Ok(None)
+                effective_from_time,
+                operator: Operator::BcFerries,
+                header_notes,
+                previous_items: vec![],
+                suspensions,
+                provisional_basis: None,
+            },
+            unrecognized,
+        )))
+    } else if index == 0 {
+        // If the table element is missing in the initial schedule page for the route, we have a problem
+        bail!("Missing table element in schedule");
+    } else {
+        // However, sometimes the initial page has links to non-existent schedules in which case we can ignore them
+        Ok(None)
+    }
+}
+
+async fn scrape_schedule(
+    options: &Options,
+    source_url: &str,
+    document: &Html,
+    terminal_pair: TerminalPair,
+    index: usize,
+    schedule_path_query_text: &str,
+    today: Date,
+) -> Result<Option<(Schedule, Vec<String>)>> {
+    let inner = async {
+        let date_range = parse_schedule_path_query(schedule_path_query_text)
+            .with_context(|| format!("Failed to schedule path/query: {:?}", schedule_path_query_text))?;
+        if !should_scrape_schedule_date(date_range, today, options.date) {
+            return Ok(None);
         }
+        if let Some(fixtures_dir) = &options.record_fixtures {
+            let source = FixtureSource::new(terminal_pair, source_url, schedule_path_query_text, now_vancouver());
+            record_fixture(fixtures_dir, &source, document)?;
+        }
+        parse_schedule_page(source_url, document, terminal_pair, date_range, index, options.lenient_annotations)
     };
     inner.await.with_context(|| format!("Failed to scrape route schedule for {} from: {:?}", terminal_pair, source_url))
 }
@@ -177,16 +364,16 @@ async fn scrape_schedule(
 pub async fn scrape_route_schedules(
     options: &Options,
     cache: &Cache<'_>,
-    terminal_pair: TerminalPair,
+    route: &RouteSource,
     today: Date,
+    report: &mut ParseReport,
 ) -> Result<Vec<Schedule>> {
-    if options.terminals.is_some() && options.terminals != Some(terminal_pair) {
-        return Ok(vec![]);
-    }
-    let base_url = format!("{}/{}", OTHER_ROUTE_SCHEDULES_BASE_URL, terminal_pair.to_schedule_code_pair());
+    let RouteSource { terminal_pair, base_url } = route;
+    let terminal_pair = *terminal_pair;
     let inner = async {
+        PROGRESS.stage(terminal_pair, RouteStage::Fetching);
         let base_document = cache
-            .get_html(&base_url, &HTML_ERROR_REGEX)
+            .get_html(base_url, &HTML_ERROR_REGEX)
             .await
             .with_context(|| format!("Failed to download base schedule HTML from: {:?}", base_url))?;
         let date_range_modal_elem =
@@ -198,9 +385,10 @@ pub async fn scrape_route_schedules(
                 anyhow!("Missing schedule path/query in date range link element: {}", schedule_path_query_elem.html())
             })?;
             let opt_schedule = if index == 0 {
+                PROGRESS.stage(terminal_pair, RouteStage::Parsing);
                 scrape_schedule(
                     options,
-                    &base_url,
+                    base_url,
                     &base_document,
                     terminal_pair,
                     index,
@@ -209,31 +397,138 @@ pub async fn scrape_route_schedules(
                 )
                 .await?
             } else {
-                let source_url = format!("{}{}", BCFERRIES_BASE_URL, schedule_path_query_text);
+                let source_url = redirect_source_url(&format!("{}{}", BCFERRIES_BASE_URL, schedule_path_query_text));
+                PROGRESS.stage(terminal_pair, RouteStage::Fetching);
                 let document = cache
                     .get_html(&source_url, &HTML_ERROR_REGEX)
                     .await
                     .with_context(|| format!("Failed to download schedule HTML from: {:?}", source_url))?;
+                PROGRESS.stage(terminal_pair, RouteStage::Parsing);
                 scrape_schedule(options, &source_url, &document, terminal_pair, index, schedule_path_query_text, today)
                     .await?
             };
-            opt_schedule.iter().for_each(|s| debug!("Parsed schedule: {:#?}", s));
-            schedules.extend(opt_schedule);
+            if let Some((schedule, unrecognized)) = opt_schedule {
+                debug!("Parsed schedule: {:#?}", schedule);
+                report.add_unrecognized_annotations(terminal_pair, schedule.date_range, unrecognized);
+                schedules.push(schedule);
+            }
         }
         ensure!(!schedules.is_empty(), "Failed to find any schedule elements");
+        PROGRESS.stage(terminal_pair, RouteStage::Validating);
+        for (a, b) in schedules.iter().tuple_combinations() {
+            let (earlier, later) = if a.date_range.from <= b.date_range.from { (a, b) } else { (b, a) };
+            // A schedule that takes effect part way through a day shares that day with the one it replaces
+            let is_switchover = later.effective_from_time.is_some() && earlier.date_range.to == later.date_range.from;
+            if earlier.date_range.includes_date_inclusive(later.date_range.from) && !is_switchover {
+                warn!("Schedules for {} overlap: {} and {}", terminal_pair, earlier.date_range, later.date_range);
+            }
+        }
+        PROGRESS.finish_route(terminal_pair, schedules.len());
         Ok(schedules) as Result<_>
     };
     inner.await.with_context(|| format!("Failed to scrape route schedule for {} from: {:?}", terminal_pair, base_url))
 }
 
-pub async fn scrape_schedules(options: &Options, cache: &Cache<'_>) -> Result<Vec<Schedule>> {
+pub async fn scrape_schedules(options: &Options, cache: &Cache<'_>) -> Result<(Vec<Schedule>, ParseReport)> {
     let inner = async {
         let today = today_vancouver();
+        let routes = routes_to_scrape(options, cache).await;
+        PROGRESS.start(routes.len());
+        // Results are kept in route order, so the output doesn't depend on which route finishes first
+        let route_results: Vec<_> = stream::iter(&routes)
+            .map(|route| async move {
+                let mut route_report = ParseReport::default();
+                let schedules = scrape_route_schedules(options, cache, route, today, &mut route_report).await?;
+                Ok((schedules, route_report)) as Result<_>
+            })
+            .buffered(max(options.concurrency, 1))
+            .try_collect()
+            .await?;
         let mut result = Vec::new();
-        for &terminal_pair in ALL_TERMINAL_PAIRS.iter() {
-            result.extend(scrape_route_schedules(options, cache, terminal_pair, today).await?);
+        let mut report = ParseReport::default();
+        for (schedules, route_report) in route_results {
+            result.extend(schedules);
+            report.extend(route_report);
         }
-        Ok(result) as Result<_>
+        Ok((result, report)) as Result<_>
     };
-    inner.await.context("Failed to scrape schedules")
+    let result = inner.await;
+    PROGRESS.finish();
+    result.context("Failed to scrape schedules")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_suspensions() -> Result<()> {
+        let date_range = DateRange { from: date!(2022 - 10 - 11), to: date!(2023 - 03 - 31) };
+        let notes = [
+            "Seasonal service. No service Jan 6 – Feb 28.".to_string(),
+            "There will be no sailings December 24-26 due to holidays.".to_string(),
+            "No service from 6:00 am to 8:00 am.".to_string(),
+        ];
+        assert_eq!(
+            parse_suspensions(&notes, &date_range)?,
+            vec![
+                DateRange { from: date!(2023 - 01 - 06), to: date!(2023 - 02 - 28) },
+                DateRange { from: date!(2022 - 12 - 24), to: date!(2022 - 12 - 26) },
+            ]
+        );
+        // Dates outside of the schedule are ignored rather than guessed at
+        assert_eq!(parse_suspensions(&["No service Apr 1 – Apr 30".to_string()], &date_range)?, vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_effective_from_time() -> Result<()> {
+        let page = include_str!("../testdata/sgi_grid_summer.html");
+        let terminal_pair = TerminalPair { from: Terminal::TSA, to: Terminal::PSB };
+        let date_range = DateRange { from: date!(2022 - 06 - 27), to: date!(2022 - 09 - 05) };
+        let parse = |html: &str| -> Result<_> {
+            let (schedule, _) =
+                parse_schedule_page("", &Html::parse_document(html), terminal_pair, date_range, 0, false)?
+                    .expect("Expect schedule");
+            Ok(schedule.effective_from_time)
+        };
+        assert_eq!(parse(page)?, None);
+        let noted_page = page.replacen(
+            r#"<div class="seasonal-schedule-wrapper">"#,
+            r#"<div class="seasonal-schedule-wrapper"><p>This schedule takes effect at 12:00 pm on Jun 27.</p>"#,
+            1,
+        );
+        assert_eq!(parse(&noted_page)?, Some(time!(12:00)));
+        assert_eq!(parse_effective_from_time(&["Effective Jun 27.".to_string()])?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_northern_sailing_times() -> Result<()> {
+        assert_eq!(parse_arrive_time_or_duration(time!(21:30), "6:00 am (next day)")?, time!(6:00));
+        assert_eq!(parse_arrive_time_or_duration(time!(21:30), "6:00 AM +1")?, time!(6:00));
+        assert_eq!(parse_arrive_time_or_duration(time!(7:30), "16h 0m")?, time!(23:30));
+        let item = |depart_time, arrive_time| ScheduleItem {
+            sailing: Sailing { depart_time, arrive_time, stops: vec![] },
+            weekdays: HashMap::from([(Weekday::Monday, DateRestriction::All)]),
+            notes: HashMap::new(),
+            note_categories: HashMap::new(),
+            vehicle_restrictions: HashMap::new(),
+            conditional_stops: vec![],
+        };
+        // A day-long Inside Passage sailing is plausible, while on a southern route a sailing that seems to take most of a
+        // day has its columns swapped
+        let inside_passage = TerminalPair { from: Terminal::PPH, to: Terminal::PPR };
+        let mut items = vec![item(time!(7:30), time!(23:30))];
+        assert!(!swap_implausible_depart_arrive_times(
+            &mut items,
+            &[true],
+            max_plausible_sailing_duration(inside_passage)
+        ));
+        assert_eq!(items[0].sailing.depart_time, time!(7:30));
+        let route1 = TerminalPair { from: Terminal::TSA, to: Terminal::SWB };
+        let mut items = vec![item(time!(9:05), time!(7:30))];
+        assert!(swap_implausible_depart_arrive_times(&mut items, &[true], max_plausible_sailing_duration(route1)));
+        Ok(())
+    }
 }
@@ -0,0 +1,123 @@
+use crate::annotations::*;
+use crate::prelude::*;
+
+// Every annotation text seen in BC Ferries' schedules, maintained by hand as new ones turn up in scraping errors, so
+// changes to the annotation grammar can be checked without scraping.  Add new texts rather than editing old ones,
+// since old schedules may still be re-issued with them.
+const ANNOTATION_CORPUS_JSON: &str = include_str!("../data/annotation_corpus.json");
+
+// Every date in a leap year parses within this range, so no texts are rejected for their dates alone
+const CORPUS_DATE_RANGE: DateRange = DateRange { from: date!(2020 - 01 - 01), to: date!(2020 - 12 - 31) };
+
+// What a parsed annotation text contributes to its sailings, used to report how much of the grammar the corpus covers
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum AnnotationForm {
+    TimeDates,
+    Dates,
+    DangerousGoodsDates,
+    DangerousGoodsOnly,
+    Notes,
+    ConditionalStops,
+    Ignored,
+}
+
+const ANNOTATION_FORMS: [AnnotationForm; 7] = [
+    AnnotationForm::TimeDates,
+    AnnotationForm::Dates,
+    AnnotationForm::DangerousGoodsDates,
+    AnnotationForm::DangerousGoodsOnly,
+    AnnotationForm::Notes,
+    AnnotationForm::ConditionalStops,
+    AnnotationForm::Ignored,
+];
+
+impl AnnotationForm {
+    fn description(self) -> &'static str {
+        match self {
+            AnnotationForm::TimeDates => "sailing time dates",
+            AnnotationForm::Dates => "sailing dates",
+            AnnotationForm::DangerousGoodsDates => "dangerous goods dates",
+            AnnotationForm::DangerousGoodsOnly => "dangerous goods only",
+            AnnotationForm::Notes => "notes",
+            AnnotationForm::ConditionalStops => "conditional stops",
+            AnnotationForm::Ignored => "ignored",
+        }
+    }
+}
+
+struct SelftestReport {
+    total: usize,
+    coverage: HashMap<AnnotationForm, usize>,
+    failures: Vec<(String, String)>,
+}
+
+fn parse_corpus(json: &str) -> Result<Vec<String>> {
+    serde_json::from_str(json).context("Failed to parse annotation corpus JSON")
+}
+
+fn annotation_forms(annotations: &Annotations) -> Vec<AnnotationForm> {
+    let forms = [
+        (AnnotationForm::TimeDates, !annotations.star_dates_by_time.is_empty()),
+        (AnnotationForm::Dates, !annotations.all_dates.is_always()),
+        (AnnotationForm::DangerousGoodsDates, !annotations.dg_dates.is_always()),
+        (AnnotationForm::DangerousGoodsOnly, annotations.is_dg_only),
+        (AnnotationForm::Notes, !annotations.all_notes.map.is_empty()),
+        (AnnotationForm::ConditionalStops, !annotations.all_stops.is_empty()),
+    ];
+    let forms: Vec<_> = forms.into_iter().filter_map(|(form, is_present)| is_present.then_some(form)).collect();
+    if forms.is_empty() {
+        vec![AnnotationForm::Ignored]
+    } else {
+        forms
+    }
+}
+
+fn check_corpus(texts: &[String]) -> SelftestReport {
+    let mut report = SelftestReport { total: texts.len(), coverage: HashMap::new(), failures: Vec::new() };
+    for text in texts {
+        let mut annotations = Annotations::new();
+        match annotations.parse(&CORPUS_DATE_RANGE, [text]) {
+            Ok(()) => {
+                for form in annotation_forms(&annotations) {
+                    *report.coverage.entry(form).or_insert(0) += 1;
+                }
+            }
+            Err(err) => report.failures.push((text.clone(), format!("{:?}", err))),
+        }
+    }
+    report
+}
+
+// Parses every annotation text in the bundled corpus, as a quick check before scraping for real
+pub fn run_selftest() -> Result<()> {
+    let report = check_corpus(&parse_corpus(ANNOTATION_CORPUS_JSON)?);
+    for form in ANNOTATION_FORMS {
+        match report.coverage.get(&form) {
+            Some(count) => info!("{}: {} annotation texts", form.description(), count),
+            None => warn!("{}: not covered by any annotation text", form.description()),
+        }
+    }
+    for (text, err) in &report.failures {
+        error!("Newly failing annotation text {:?}: {}", text, err);
+    }
+    info!("Parsed {} of {} annotation texts", report.total - report.failures.len(), report.total);
+    ensure!(report.failures.is_empty(), "{} annotation texts failed to parse", report.failures.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotation_corpus() -> Result<()> {
+        let texts = parse_corpus(ANNOTATION_CORPUS_JSON)?;
+        assert_eq!(texts.iter().unique().count(), texts.len(), "Expect corpus texts to be unique");
+        let report = check_corpus(&texts);
+        assert!(report.failures.is_empty(), "Expect corpus to parse: {:?}", report.failures);
+        for form in ANNOTATION_FORMS {
+            assert!(report.coverage.contains_key(&form), "Expect corpus to cover {:?}", form);
+        }
+        Ok(())
+    }
+}
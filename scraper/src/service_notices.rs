@@ -0,0 +1,271 @@
+use crate::cache::Cache;
+use crate::constants::*;
+use crate::macros::*;
+use crate::prelude::*;
+use crate::utils::*;
+
+// The service notices page lists notices grouped by route:
+//
+// - `div.service-notices-route`: one group per route, with its name (like the page's anchors) in an `h3`
+// - `div.service-notice` within a group: one notice, with `.service-notice-title` and `.service-notice-body`
+//
+// Notice bodies are free text, so the kind of disruption and the dates it affects are found by looking for keywords
+// and month/day mentions.  Two mentions joined by a word like "to" are a range of dates, and the rest are single dates.
+
+#[derive(Debug, Eq, PartialEq)]
+struct ServiceNotice {
+    route_group: String,
+    title: String,
+    body: String,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum NoticeRelevance {
+    WholeSchedule,
+    Dates(Vec<DateRange>),
+    // Only mentions dates outside the schedule
+    None,
+}
+
+fn parse_service_notices(document: &Html) -> Result<Vec<ServiceNotice>> {
+    let mut notices = Vec::new();
+    for group_elem in document.select(selector!("div.service-notices-route")) {
+        let route_group = group_elem
+            .select(selector!("h3"))
+            .next()
+            .map(|elem| element_text(&elem))
+            .ok_or_else(|| anyhow!("Missing route name in service notices group"))?;
+        for notice_elem in group_elem.select(selector!("div.service-notice")) {
+            let text_of = |selector| notice_elem.select(selector).next().map(|elem| element_text(&elem));
+            notices.push(ServiceNotice {
+                route_group: route_group.clone(),
+                title: text_of(selector!(".service-notice-title")).unwrap_or_default(),
+                body: text_of(selector!(".service-notice-body")).unwrap_or_default(),
+            });
+        }
+    }
+    Ok(notices)
+}
+
+// Keywords are matched as whole words, so that e.g. "latest" isn't taken to mean "late"
+fn classify_notice(text: &str) -> AlertSeverity {
+    if regex!(r"(?i)\bcancel(?:s|led|ling|lations?)?\b").is_match(text) {
+        AlertSeverity::Cancellation
+    } else if regex!(r"(?i)\b(?:delay(?:s|ed)?|late|behind schedule)\b").is_match(text) {
+        AlertSeverity::Delay
+    } else {
+        AlertSeverity::Advisory
+    }
+}
+
+fn notice_relevance(text: &str, date_range: &DateRange) -> Result<NoticeRelevance> {
+    let month_day_regex = regex!(
+        r"(?i)\b(jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|aug(?:ust)?|sep(?:t(?:ember)?)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?)\.?\s+(\d{1,2})(?:st|nd|rd|th)?\b"
+    );
+    let range_separator_regex = regex!(r"(?i)^\s*(?:to|until|through|-|–)\s*$");
+    let mut mentions = Vec::new();
+    for captures in month_day_regex.captures_iter(text) {
+        let whole = captures.get(0).expect("Expect regex match to have a whole capture");
+        let date = date_range.parse_date_within(&format!("{} {}", &captures[1][..3], &captures[2]))?;
+        mentions.push((whole.start(), whole.end(), date));
+    }
+    if mentions.is_empty() {
+        return Ok(NoticeRelevance::WholeSchedule);
+    }
+    let mut dates = Vec::new();
+    let mut mentions = mentions.into_iter().peekable();
+    while let Some((_, end, from)) = mentions.next() {
+        let range_to = mentions.next_if(|(start, _, _)| range_separator_regex.is_match(&text[end..*start]));
+        let dates_mentioned = match range_to {
+            None => from.map(|from| DateRange { from, to: from }),
+            Some((_, _, None)) if from.is_none() => None,
+            // Ranges that start or end outside the schedule are cut off at its ends
+            Some((_, _, to)) => {
+                Some(DateRange { from: from.unwrap_or(date_range.from), to: to.unwrap_or(date_range.to) })
+            }
+        };
+        dates.extend(dates_mentioned);
+    }
+    Ok(if dates.is_empty() {
+        NoticeRelevance::None
+    } else {
+        NoticeRelevance::Dates(dates.into_iter().sorted_by_key(|dates| dates.from).dedup().collect())
+    })
+}
+
+fn notice_alerts(notices: &[ServiceNotice], schedule: &Schedule) -> Result<Vec<Alert>> {
+    let mut alerts = Vec::new();
+    for notice in notices {
+        let terminals = match SERVICE_NOTICE_ROUTE_GROUPS.get(notice.route_group.as_str()) {
+            Some(terminals) => terminals,
+            None => continue,
+        };
+        if !terminals.contains(&schedule.terminal_pair.from) || !terminals.contains(&schedule.terminal_pair.to) {
+            continue;
+        }
+        let text = format!("{} {}", notice.title, notice.body);
+        let relevant_date_ranges = match notice_relevance(&text, &schedule.date_range)? {
+            NoticeRelevance::WholeSchedule => vec![],
+            NoticeRelevance::Dates(dates) => dates,
+            NoticeRelevance::None => continue,
+        };
+        let severity = classify_notice(&text);
+        alerts.push(Alert {
+            message: [notice.title.as_str(), notice.body.as_str()].iter().filter(|s| !s.is_empty()).join(": "),
+            level: severity.level(),
+            severity: Some(severity),
+            relevant_date_ranges,
+        });
+    }
+    alerts.sort_by_key(|alert| alert.severity);
+    Ok(alerts)
+}
+
+// Adds alerts for the service notices of each schedule's route
+pub async fn add_service_notice_alerts(cache: &Cache<'_>, schedules: &mut [Schedule]) -> Result<()> {
+    let inner = async {
+        let document = cache.get_html(ALL_SERVICE_NOTICES_URL, &HTML_ERROR_REGEX).await?;
+        let notices = parse_service_notices(&document)?;
+        for route_group in notices.iter().map(|n| &n.route_group).unique() {
+            if !SERVICE_NOTICE_ROUTE_GROUPS.contains_key(route_group.as_str()) {
+                warn!(
+                    "Service notices for unknown route group (add to SERVICE_NOTICE_ROUTE_GROUPS): {:?}",
+                    route_group
+                );
+            }
+        }
+        for schedule in schedules {
+            let alerts = notice_alerts(&notices, schedule)?;
+            schedule.alerts.extend(alerts);
+        }
+        Ok(()) as Result<_>
+    };
+    inner.await.with_context(|| format!("Failed to add service notice alerts from: {:?}", ALL_SERVICE_NOTICES_URL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTICES_HTML: &str = r#"
+        <div class="service-notices-route">
+            <h3>Vancouver Island - Southern Gulf Islands</h3>
+            <div class="service-notice">
+                <div class="service-notice-title">Sailing cancellation</div>
+                <div class="service-notice-body">The 7:00 am sailing on March 3 is cancelled for maintenance.</div>
+            </div>
+            <div class="service-notice">
+                <div class="service-notice-title">Terminal parking</div>
+                <div class="service-notice-body">Parking at Swartz Bay is limited.</div>
+            </div>
+        </div>
+        <div class="service-notices-route">
+            <h3>Metro Vancouver - Southern Gulf Islands</h3>
+            <div class="service-notice">
+                <div class="service-notice-title">Delays</div>
+                <div class="service-notice-body">Sailings are running late from Dec. 24th to Dec 26 due to traffic.</div>
+            </div>
+        </div>
+    "#;
+
+    fn schedule(terminal_pair: TerminalPair, date_range: DateRange) -> Schedule {
+        Schedule { date_range, ..Schedule::test_fixture(terminal_pair) }
+    }
+
+    #[test]
+    fn test_classify_notice() -> Result<()> {
+        assert_eq!(classify_notice("Sailing CANCELLATIONS today"), AlertSeverity::Cancellation);
+        assert_eq!(classify_notice("Sailings running behind schedule"), AlertSeverity::Delay);
+        assert_eq!(classify_notice("Construction at the terminal"), AlertSeverity::Advisory);
+        // Only whole words count
+        assert_eq!(classify_notice("See the latest terminal information"), AlertSeverity::Advisory);
+        assert_eq!(classify_notice("Cancellation policy for cancelled reservations"), AlertSeverity::Cancellation);
+        Ok(())
+    }
+
+    #[test]
+    fn test_notice_relevance() -> Result<()> {
+        let date_range = DateRange { from: date!(2023 - 10 - 01), to: date!(2024 - 03 - 31) };
+        assert_eq!(notice_relevance("Limited parking", &date_range)?, NoticeRelevance::WholeSchedule);
+        assert_eq!(
+            notice_relevance("Closed from December 24th until Jan 2", &date_range)?,
+            NoticeRelevance::Dates(vec![DateRange { from: date!(2023 - 12 - 24), to: date!(2024 - 01 - 02) }])
+        );
+        // Separately mentioned dates aren't merged into the range between them
+        assert_eq!(
+            notice_relevance("Reduced service on Mar 1 and Oct 15", &date_range)?,
+            NoticeRelevance::Dates(vec![
+                DateRange { from: date!(2023 - 10 - 15), to: date!(2023 - 10 - 15) },
+                DateRange { from: date!(2024 - 03 - 01), to: date!(2024 - 03 - 01) },
+            ])
+        );
+        assert_eq!(
+            notice_relevance("Closed Sep 20 - Oct 3", &date_range)?,
+            NoticeRelevance::Dates(vec![DateRange { from: date!(2023 - 10 - 01), to: date!(2023 - 10 - 03) }])
+        );
+        assert_eq!(notice_relevance("Extra sailings on July 1", &date_range)?, NoticeRelevance::None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_notice_alerts() -> Result<()> {
+        let notices = parse_service_notices(&Html::parse_document(NOTICES_HTML))?;
+        assert_eq!(notices.len(), 3);
+        let spring = DateRange { from: date!(2024 - 01 - 01), to: date!(2024 - 03 - 31) };
+        let alerts =
+            notice_alerts(&notices, &schedule(TerminalPair { from: Terminal::SWB, to: Terminal::PSB }, spring))?;
+        assert_eq!(
+            alerts.iter().map(|a| a.severity).collect::<Vec<_>>(),
+            [Some(AlertSeverity::Cancellation), Some(AlertSeverity::Advisory)]
+        );
+        assert_eq!(
+            alerts[0].relevant_date_ranges,
+            [DateRange { from: date!(2024 - 03 - 03), to: date!(2024 - 03 - 03) }]
+        );
+        assert_eq!(alerts[1].relevant_date_ranges, []);
+        assert!(alerts[0].message.starts_with("Sailing cancellation: The 7:00 am"));
+        // The delay notice is for another route, and the cancellation is outside the winter schedule
+        let winter = DateRange { from: date!(2023 - 10 - 01), to: date!(2023 - 12 - 31) };
+        let alerts =
+            notice_alerts(&notices, &schedule(TerminalPair { from: Terminal::TSA, to: Terminal::PSB }, winter))?;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].severity, Some(AlertSeverity::Delay));
+        assert_eq!(
+            alerts[0].relevant_date_ranges,
+            [DateRange { from: date!(2023 - 12 - 24), to: date!(2023 - 12 - 26) }]
+        );
+        Ok(())
+    }
+
+    // Each schedule's alerts from a saved copy of the service notices page, one per line
+    fn notices_snapshot(html: &str, schedules: &[Schedule]) -> Result<String> {
+        let notices = parse_service_notices(&Html::parse_document(html))?;
+        let mut lines = Vec::new();
+        for schedule in schedules {
+            for alert in notice_alerts(&notices, schedule)? {
+                let dates = alert.relevant_date_ranges.iter().map(|dates| dates.to_string()).join(", ");
+                lines.push(format!(
+                    "{} {:?} [{}] {}\n",
+                    schedule.terminal_pair,
+                    alert.severity.expect("Expect service notice alert to have a severity"),
+                    dates,
+                    alert.message
+                ));
+            }
+        }
+        Ok(lines.concat())
+    }
+
+    #[test]
+    fn test_service_notices_page() -> Result<()> {
+        let winter = DateRange { from: date!(2023 - 10 - 11), to: date!(2024 - 03 - 31) };
+        let schedules = [
+            schedule(TerminalPair { from: Terminal::TSA, to: Terminal::SWB }, winter),
+            schedule(TerminalPair { from: Terminal::SWB, to: Terminal::PSB }, winter),
+            schedule(TerminalPair { from: Terminal::TSA, to: Terminal::PLH }, winter),
+        ];
+        let snapshot = notices_snapshot(include_str!("../testdata/service_notices.html"), &schedules)?;
+        assert_eq!(snapshot, include_str!("../testdata/service_notices.golden"));
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use crate::macros::*;
+use crate::prelude::*;
+use crate::table_layout::*;
+use crate::utils::*;
+
+// The Southern Gulf Islands (route 9) schedule is published as a grid instead of one table per terminal pair:
+//
+// - `thead tr.schedule-grid-terminals`: column headers naming each terminal, like the stops column of other schedules
+// - `thead tr[data-schedule-day]`: a weekday, followed by a `tbody` of `tr.schedule-grid-row` rows for that day
+// - Each row is one vessel's trip, with the time it calls at each terminal column.  Blank or dash cells are terminals
+//   it does not call at, and an asterisk after a time means passengers connect to another vessel at that terminal.
+//
+// A trip can be travelled between any two of its terminals, so each row becomes a sailing for every pair of terminals
+// it calls at, with the terminals in between as stops and the time listed for the destination as the arrival time.
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct GridCall {
+    terminal: Terminal,
+    time: Time,
+    is_connection: bool,
+}
+
+fn parse_grid_terminals(layout: &TableLayout) -> Result<Vec<Terminal>> {
+    ensure!(!layout.headers.is_empty(), "Missing terminals header row in schedule grid");
+    layout.headers.iter().map(|text| terminal_from_schedule_stop_text(text)).collect()
+}
+
+fn parse_grid_call(terminal: Terminal, text: &str) -> Result<Option<GridCall>> {
+    let text = text.trim();
+    if text.is_empty() || text == "-" || text == "—" {
+        return Ok(None);
+    }
+    let (time_text, is_connection) = match text.strip_suffix('*') {
+        Some(time_text) => (time_text.trim(), true),
+        None => (text, false),
+    };
+    let time = parse_schedule_time(time_text).with_context(|| format!("Failed to parse {} grid time", terminal))?;
+    Ok(Some(GridCall { terminal, time, is_connection }))
+}
+
+fn grid_row_sailings(calls: &[GridCall]) -> Vec<(TerminalPair, Sailing)> {
+    let mut sailings = Vec::new();
+    for (from_index, from_call) in calls.iter().enumerate() {
+        for (to_index, to_call) in calls.iter().enumerate().skip(from_index + 1) {
+            let stops = calls[from_index + 1..to_index]
+                .iter()
+                .map(|call| Stop {
+                    type_: if call.is_connection { StopType::Transfer } else { StopType::Stop },
+                    terminal: call.terminal,
+                })
+                .collect();
+            sailings.push((
+                TerminalPair { from: from_call.terminal, to: to_call.terminal },
+                Sailing { depart_time: from_call.time, arrive_time: to_call.time, stops },
+            ));
+        }
+    }
+    sailings
+}
+
+// Schedule items for every terminal pair in the grid
+pub fn parse_sgi_grid(table_elem: ElementRef) -> Result<HashMap<TerminalPair, Vec<ScheduleItem>>> {
+    let inner = || {
+        let layout = TableLayout::parse(table_elem, selector!("tr.schedule-grid-row"))?;
+        let terminals = parse_grid_terminals(&layout)?;
+        let mut pair_items: HashMap<TerminalPair, Vec<ScheduleItem>> = HashMap::new();
+        for section in &layout.sections {
+            let weekday = parse_weekday(
+                section.day.as_deref().ok_or_else(|| anyhow!("Expect schedule grid rows to follow a weekday row"))?,
+            )?;
+            for cells in &section.rows {
+                let cell_texts: Vec<_> = cells.iter().map(LayoutCell::text).collect();
+                ensure!(
+                    cell_texts.len() == terminals.len(),
+                    "Grid row should have a cell for each of the {} terminals: {:?}",
+                    terminals.len(),
+                    cell_texts
+                );
+                let mut calls = Vec::new();
+                for (&terminal, text) in terminals.iter().zip(&cell_texts) {
+                    calls.extend(parse_grid_call(terminal, text)?);
+                }
+                // Columns are in the same order for trips in both directions, so the times give the order of calls
+                calls.sort_by_key(|call| call.time);
+                for (terminal_pair, sailing) in grid_row_sailings(&calls) {
+                    pair_items.entry(terminal_pair).or_default().push(ScheduleItem {
+                        sailing,
+                        weekdays: HashMap::from([(weekday, DateRestriction::All)]),
+                        notes: HashMap::new(),
+                        note_categories: HashMap::new(),
+                        vehicle_restrictions: HashMap::new(),
+                        conditional_stops: vec![],
+                    });
+                }
+            }
+        }
+        pair_items
+            .into_iter()
+            .map(|(terminal_pair, items)| Ok((terminal_pair, ScheduleItem::merge_items(items)?)))
+            .collect::<Result<HashMap<_, _>>>()
+    };
+    inner().context("Failed to parse Southern Gulf Islands schedule grid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_snapshot(html: &str) -> Result<String> {
+        let document = Html::parse_document(html);
+        let table_elem = document.select(selector!("table.schedule-grid")).next().expect("Expect grid table");
+        let pair_items = parse_sgi_grid(table_elem)?;
+        let mut lines = Vec::new();
+        for (terminal_pair, items) in pair_items.iter().sorted_by_key(|(tp, _)| tp.to_schedule_code_pair()) {
+            for item in items {
+                let weekdays = item.weekdays.keys().sorted_by_key(|w| w.number_days_from_monday()).join(",");
+                let stops = item.sailing.stops.iter().map(|s| format!("{:?} {}", s.type_, s.terminal)).join(", ");
+                lines.push(format!(
+                    "{} {} {}-{} [{}]\n",
+                    terminal_pair, weekdays, item.sailing.depart_time, item.sailing.arrive_time, stops
+                ));
+            }
+        }
+        Ok(lines.concat())
+    }
+
+    #[test]
+    fn test_parse_sgi_grid_summer() -> Result<()> {
+        let snapshot = grid_snapshot(include_str!("../testdata/sgi_grid_summer.html"))?;
+        assert_eq!(snapshot, include_str!("../testdata/sgi_grid_summer.golden"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_sgi_grid_shoulder() -> Result<()> {
+        let snapshot = grid_snapshot(include_str!("../testdata/sgi_grid_shoulder.html"))?;
+        assert_eq!(snapshot, include_str!("../testdata/sgi_grid_shoulder.golden"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_sgi_grid_winter() -> Result<()> {
+        let snapshot = grid_snapshot(include_str!("../testdata/sgi_grid_winter.html"))?;
+        assert_eq!(snapshot, include_str!("../testdata/sgi_grid_winter.golden"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_grid_call() -> Result<()> {
+        assert_eq!(parse_grid_call(Terminal::PVB, " - ")?, None);
+        assert_eq!(
+            parse_grid_call(Terminal::PVB, "8:45 am*")?,
+            Some(GridCall { terminal: Terminal::PVB, time: time!(08:45), is_connection: true })
+        );
+        Ok(())
+    }
+}
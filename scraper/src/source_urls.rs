@@ -0,0 +1,78 @@
+use reqwest::{header, redirect};
+
+use crate::constants::*;
+use crate::prelude::*;
+
+const MAX_REDIRECTS: usize = 10;
+
+fn redirect_url_with(url: &str, redirects: &[(&str, &str)]) -> String {
+    redirects
+        .iter()
+        .find_map(|(old, new)| url.strip_prefix(old).map(|rest| format!("{}{}", new, rest)))
+        .unwrap_or_else(|| url.to_string())
+}
+
+// The current location of a schedule source URL, according to `SOURCE_URL_REDIRECTS`
+pub fn redirect_source_url(url: &str) -> String {
+    redirect_url_with(url, SOURCE_URL_REDIRECTS)
+}
+
+async fn resolve_redirects(client: &reqwest::Client, url: &str) -> Result<String> {
+    let mut url = url.to_string();
+    for _ in 0..MAX_REDIRECTS {
+        let response = client.get(&url).send().await?;
+        if !response.status().is_redirection() {
+            response.error_for_status()?;
+            return Ok(url);
+        }
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .ok_or_else(|| anyhow!("Redirect response has no location: {:?}", url))?
+            .to_str()?;
+        let next_url = response.url().join(location)?.to_string();
+        warn!("Schedule source URL redirects (add to SOURCE_URL_REDIRECTS): {:?} -> {:?}", url, next_url);
+        url = next_url;
+    }
+    bail!("Too many redirects")
+}
+
+// Link-checker pass over the schedules' source URLs: follows redirects one at a time so each is logged, updates the
+// schedules to link to the final location, and warns about links that are broken
+pub async fn check_source_urls(schedules: &mut [Schedule]) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .redirect(redirect::Policy::none())
+        .build()
+        .context("Failed to create HTTP client for checking source URLs")?;
+    let mut resolved_urls: HashMap<String, String> = HashMap::new();
+    for schedule in schedules {
+        if !resolved_urls.contains_key(&schedule.source_url) {
+            let resolved_url = match resolve_redirects(&client, &schedule.source_url).await {
+                Ok(resolved_url) => resolved_url,
+                Err(err) => {
+                    warn!(
+                        "Broken schedule source URL for {}: {:?}: {}",
+                        schedule.terminal_pair, schedule.source_url, err
+                    );
+                    schedule.source_url.clone()
+                }
+            };
+            resolved_urls.insert(schedule.source_url.clone(), resolved_url);
+        }
+        schedule.source_url = resolved_urls[&schedule.source_url].clone();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redirect_url_with() -> Result<()> {
+        let redirects = [("https://example.com/old/", "https://example.com/new/")];
+        assert_eq!(redirect_url_with("https://example.com/old/a?b=c", &redirects), "https://example.com/new/a?b=c");
+        assert_eq!(redirect_url_with("https://example.com/other", &redirects), "https://example.com/other");
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::prelude::*;
+use crate::utils::*;
+
+// Markdown summaries of each route's schedules, for publishing somewhere that people read rather than query, such as a
+// docs site or wiki.  They are regenerated from the parsed schedules on each run, so they never drift from the JSON.
+
+const INDEX_FILE_NAME: &str = "README.md";
+
+pub fn format_summary_time(time: Time) -> String {
+    time.format(format_description!("[hour repr:12 padding:none]:[minute] [period case:lower]"))
+        .expect("Expect summary time to format")
+}
+
+pub fn format_summary_date(date: Date) -> String {
+    date.format(format_description!("[month repr:short] [day padding:none], [year]"))
+        .expect("Expect summary date to format")
+}
+
+fn format_summary_dates(dates: impl IntoIterator<Item = Date>) -> String {
+    dates
+        .into_iter()
+        .sorted()
+        .map(|date| date.format(format_description!("[month repr:short] [day padding:none]")))
+        .map(|text| text.expect("Expect summary date to format"))
+        .join(", ")
+}
+
+fn weekday_short_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Monday => "Mon",
+        Weekday::Tuesday => "Tue",
+        Weekday::Wednesday => "Wed",
+        Weekday::Thursday => "Thu",
+        Weekday::Friday => "Fri",
+        Weekday::Saturday => "Sat",
+        Weekday::Sunday => "Sun",
+    }
+}
+
+// Weekdays a sailing runs on, such as "Daily" or "Mon-Fri"
+pub fn weekdays_pattern(weekdays: &HashSet<Weekday>) -> String {
+    let week = iter::successors(Some(Weekday::Monday), |weekday| Some(weekday.next())).take(7);
+    let mut workweek = week.clone().take(5);
+    if weekdays.len() == 7 {
+        "Daily".to_string()
+    } else if weekdays.len() == 5 && workweek.all(|weekday| weekdays.contains(&weekday)) {
+        "Mon-Fri".to_string()
+    } else {
+        week.filter(|weekday| weekdays.contains(weekday)).map(weekday_short_name).join(", ")
+    }
+}
+
+// Dates that the item's weekdays do not follow the usual pattern, and notes for the sailing
+pub fn item_exceptions(item: &ScheduleItem) -> Vec<String> {
+    let mut except_dates = HashSet::new();
+    let mut only_dates = HashSet::new();
+    for date_restriction in item.weekdays.values() {
+        match date_restriction {
+            DateRestriction::All => {}
+            DateRestriction::Only(dates) => only_dates.extend(dates.iter().copied()),
+            DateRestriction::Except(dates) => except_dates.extend(dates.iter().copied()),
+        }
+    }
+    let mut exceptions = Vec::new();
+    if !only_dates.is_empty() {
+        exceptions.push(format!("Only on {}", format_summary_dates(only_dates)));
+    }
+    if !except_dates.is_empty() {
+        exceptions.push(format!("Not on {}", format_summary_dates(except_dates)));
+    }
+    exceptions.extend(item.notes.iter().sorted_by_key(|(text, _)| *text).map(|(text, date_restriction)| {
+        match date_restriction {
+            DateRestriction::All => text.to_string(),
+            DateRestriction::Only(dates) => format!("{} ({})", text, format_summary_dates(dates.iter().copied())),
+            DateRestriction::Except(dates) => {
+                format!("{} (except {})", text, format_summary_dates(dates.iter().copied()))
+            }
+        }
+    }));
+    exceptions
+}
+
+pub fn stops_text(stops: &[Stop]) -> String {
+    if stops.is_empty() {
+        return "Non-stop".to_string();
+    }
+    stops
+        .iter()
+        .map(|stop| match stop.type_ {
+            StopType::Stop => format!("Stops at {}", stop.terminal.name()),
+            StopType::Transfer => format!("Transfer at {}", stop.terminal.name()),
+            StopType::Thrufare => format!("Thru-fare via {}", stop.terminal.name()),
+        })
+        .join("; ")
+}
+
+// Pipes would end the table cell early
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn route_title(terminal_pair: TerminalPair) -> String {
+    format!(
+        "{} ({}) to {} ({})",
+        terminal_pair.from.area().long_name(),
+        terminal_pair.from.name(),
+        terminal_pair.to.area().long_name(),
+        terminal_pair.to.name()
+    )
+}
+
+fn route_file_name(terminal_pair: TerminalPair) -> String {
+    format!("{}.md", terminal_pair)
+}
+
+fn route_summary_markdown(terminal_pair: TerminalPair, schedules: &[&Schedule]) -> String {
+    let mut markdown = format!("# {}\n", route_title(terminal_pair));
+    for schedule in schedules.iter().sorted_by_key(|s| (s.date_range.from, s.operator.is_supplemental())) {
+        write!(
+            markdown,
+            "\n## {} to {}",
+            format_summary_date(schedule.date_range.from),
+            format_summary_date(schedule.date_range.to)
+        )
+        .expect("Expect write to string");
+        if schedule.operator != Operator::BcFerries {
+            write!(markdown, " ({})", schedule.operator.name()).expect("Expect write to string");
+        }
+        markdown.push('\n');
+        if schedule.provisional_basis.is_some() {
+            markdown
+                .push_str("\n_Provisional: based on the same period last year, until the schedule is published._\n");
+        }
+        if !schedule.source_url.is_empty() {
+            write!(markdown, "\nSource: <{}>\n", schedule.source_url).expect("Expect write to string");
+        }
+        let bullets: Vec<_> = schedule
+            .header_notes
+            .iter()
+            .cloned()
+            .chain(schedule.alerts.iter().map(|alert| format!("**Alert:** {}", alert.message)))
+            .collect();
+        if !bullets.is_empty() {
+            markdown.push('\n');
+            for bullet in bullets {
+                writeln!(markdown, "- {}", bullet).expect("Expect write to string");
+            }
+        }
+        markdown.push_str("\n| Depart | Arrive | Days | Stops | Exceptions |\n|---|---|---|---|---|\n");
+        for item in schedule.items.iter().sorted_by_key(|item| &item.sailing) {
+            let weekdays: HashSet<_> = item.weekdays.keys().copied().collect();
+            writeln!(
+                markdown,
+                "| {} | {} | {} | {} | {} |",
+                format_summary_time(item.sailing.depart_time),
+                format_summary_time(item.sailing.arrive_time),
+                weekdays_pattern(&weekdays),
+                table_cell(&stops_text(&item.sailing.stops)),
+                table_cell(&item_exceptions(item).join("; ")),
+            )
+            .expect("Expect write to string");
+        }
+    }
+    markdown
+}
+
+fn index_markdown(terminal_pairs: &[TerminalPair]) -> String {
+    let mut markdown = "# Route schedules\n\n".to_string();
+    for &terminal_pair in terminal_pairs {
+        writeln!(markdown, "- [{}]({})", route_title(terminal_pair), route_file_name(terminal_pair))
+            .expect("Expect write to string");
+    }
+    markdown
+}
+
+// Writes a summary for each route and an index of them to the directory.  Summaries of routes that no longer have any
+// schedules are removed, so the directory always matches the latest run.
+pub fn write_route_summaries(dir: &Path, schedules: &[Schedule]) -> Result<()> {
+    let inner = || {
+        fs::create_dir_all(dir).context("Failed to create directory")?;
+        let route_schedules = into_vec_group_map(schedules.iter(), |s| s.terminal_pair);
+        let terminal_pairs: Vec<_> =
+            route_schedules.keys().copied().sorted_by_key(|tp| (tp.from.area(), tp.to.area(), *tp)).collect();
+        for terminal_pair in &terminal_pairs {
+            let markdown = route_summary_markdown(*terminal_pair, &route_schedules[terminal_pair]);
+            write_file_atomically(&dir.join(route_file_name(*terminal_pair)), |file| {
+                Ok(io::Write::write_all(file, markdown.as_bytes())?)
+            })?;
+        }
+        write_file_atomically(&dir.join(INDEX_FILE_NAME), |file| {
+            Ok(io::Write::write_all(file, index_markdown(&terminal_pairs).as_bytes())?)
+        })?;
+        remove_stale_route_files(dir, "md", |tp| route_schedules.contains_key(&tp))?;
+        info!("Wrote Markdown summaries of {} routes to: {:?}", terminal_pairs.len(), dir);
+        Ok(()) as Result<_>
+    };
+    inner().context("Failed to write route summaries")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_summary_markdown() -> Result<()> {
+        let terminal_pair = TerminalPair { from: Terminal::SWB, to: Terminal::FUL };
+        let schedule = Schedule {
+            date_range: DateRange { from: date!(2022 - 06 - 30), to: date!(2022 - 09 - 05) },
+            items: vec![
+                ScheduleItem {
+                    sailing: Sailing { depart_time: time!(9:00), arrive_time: time!(9:35), stops: vec![] },
+                    weekdays: HashMap::from([
+                        (Weekday::Saturday, DateRestriction::All),
+                        (Weekday::Sunday, DateRestriction::Only(HashSet::from([date!(2022 - 07 - 03)]))),
+                    ]),
+                    notes: HashMap::new(),
+                    note_categories: HashMap::new(),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                },
+                ScheduleItem {
+                    sailing: Sailing {
+                        depart_time: time!(7:00),
+                        arrive_time: time!(7:35),
+                        stops: vec![Stop { type_: StopType::Stop, terminal: Terminal::PVB }],
+                    },
+                    weekdays: iter::successors(Some(Weekday::Monday), |weekday| Some(weekday.next()))
+                        .take(5)
+                        .map(|weekday| (weekday, DateRestriction::Except(HashSet::from([date!(2022 - 08 - 01)]))))
+                        .collect(),
+                    notes: HashMap::from([(Cow::Borrowed("Foot passengers only"), DateRestriction::All)]),
+                    note_categories: HashMap::new(),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                },
+            ],
+            source_url: "https://www.bcferries.com/routes-fares/schedules/southern-gulf-islands".to_string(),
+            ..Schedule::test_fixture(terminal_pair)
+        };
+        let markdown = route_summary_markdown(terminal_pair, &[&schedule]);
+        assert!(markdown.starts_with("# Victoria (Swartz Bay) to Salt Spring Island (Fulford Harbour)\n"));
+        assert!(markdown.contains("\n## Jun 30, 2022 to Sep 5, 2022\n"));
+        assert!(markdown.contains(
+            "\n| 7:00 am | 7:35 am | Mon-Fri | Stops at Village Bay | Not on Aug 1; Foot passengers only |\n| 9:00 am"
+        ));
+        assert!(markdown.contains("\n| 9:00 am | 9:35 am | Sat, Sun | Non-stop | Only on Jul 3 |\n"));
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use crate::prelude::*;
+
+// Schedules of operators other than BC Ferries, such as seasonal water taxis, maintained by hand from the operators'
+// published timetables since there is no schedule page to scrape.  Each entry is like:
+//
+//     {
+//         "operator": "GulfIslandsWaterTaxi",
+//         "terminal_pair": { "from": "PVB", "to": "PST" },
+//         "dates": { "from": "2024-06-29", "to": "2024-09-02" },
+//         "weekdays": ["Saturday", "Sunday"],
+//         "sailings": [["09:15", "09:45"], ["16:30", "17:00"]],
+//         "source_url": "https://..."
+//     }
+//
+// where `sailings` are departure and arrival times.  Entries can be removed once they are over.
+const SUPPLEMENTAL_SCHEDULES_JSON: &str = include_str!("../data/supplemental_schedules.json");
+
+const SAILING_TIME_FORMAT: &TimeFormat = format_description!("[hour]:[minute]");
+
+#[derive(Debug, Deserialize)]
+struct SupplementalSchedule {
+    operator: Operator,
+    terminal_pair: TerminalPair,
+    dates: DateRange,
+    weekdays: Vec<Weekday>,
+    sailings: Vec<(String, String)>,
+    source_url: String,
+}
+
+fn parse_supplemental_schedules(json: &str) -> Result<Vec<SupplementalSchedule>> {
+    serde_json::from_str(json).context("Failed to parse supplemental schedules JSON")
+}
+
+impl SupplementalSchedule {
+    fn to_schedule(&self, refreshed_at: OffsetDateTime) -> Result<Schedule> {
+        ensure!(
+            self.operator.is_supplemental(),
+            "Supplemental schedule for {} is for {}, whose schedules are scraped",
+            self.terminal_pair,
+            self.operator.name()
+        );
+        ensure!(
+            self.dates.from <= self.dates.to,
+            "Supplemental schedule for {} ends before it starts",
+            self.terminal_pair
+        );
+        let parse_time = |text: &str| {
+            Time::parse(text, SAILING_TIME_FORMAT).with_context(|| {
+                format!("Invalid time in supplemental schedule for {}: {:?}", self.terminal_pair, text)
+            })
+        };
+        let items = self
+            .sailings
+            .iter()
+            .map(|(depart_time, arrive_time)| {
+                Ok(ScheduleItem {
+                    sailing: Sailing {
+                        depart_time: parse_time(depart_time)?,
+                        arrive_time: parse_time(arrive_time)?,
+                        stops: vec![],
+                    },
+                    weekdays: self.weekdays.iter().map(|&weekday| (weekday, DateRestriction::All)).collect(),
+                    // Water taxis don't carry vehicles
+                    notes: HashMap::from([(Cow::from(FOOT_PASSENGERS_ONLY_NOTE), DateRestriction::All)]),
+                    note_categories: HashMap::from([(
+                        Cow::from(FOOT_PASSENGERS_ONLY_NOTE),
+                        NoteCategory::FootPassengersOnly,
+                    )]),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Schedule {
+            terminal_pair: self.terminal_pair,
+            date_range: self.dates,
+            items,
+            source_url: self.source_url.clone(),
+            refreshed_at,
+            alerts: vec![],
+            effective_from_time: None,
+            operator: self.operator,
+            header_notes: vec![],
+            previous_items: vec![],
+            suspensions: vec![],
+            provisional_basis: None,
+        })
+    }
+}
+
+// Adds the schedules of other operators, which the front-end shows behind a toggle with the operator's disclaimer
+pub fn add_supplemental_schedules(schedules: &mut Vec<Schedule>, refreshed_at: OffsetDateTime) -> Result<()> {
+    for supplemental in parse_supplemental_schedules(SUPPLEMENTAL_SCHEDULES_JSON)? {
+        schedules.push(supplemental.to_schedule(refreshed_at)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_supplemental_schedules_file() -> Result<()> {
+        for supplemental in parse_supplemental_schedules(SUPPLEMENTAL_SCHEDULES_JSON)? {
+            supplemental.to_schedule(OffsetDateTime::UNIX_EPOCH)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_supplemental_schedule() -> Result<()> {
+        let supplementals = parse_supplemental_schedules(
+            r#"[{
+                "operator": "GulfIslandsWaterTaxi",
+                "terminal_pair": { "from": "PVB", "to": "PST" },
+                "dates": { "from": "2024-06-29", "to": "2024-09-02" },
+                "weekdays": ["Saturday"],
+                "sailings": [["09:15", "09:45"]],
+                "source_url": "https://example.com/water-taxi"
+            }, {
+                "operator": "BcFerries",
+                "terminal_pair": { "from": "PVB", "to": "PST" },
+                "dates": { "from": "2024-06-29", "to": "2024-09-02" },
+                "weekdays": ["Saturday"],
+                "sailings": [],
+                "source_url": "https://example.com/ferry"
+            }]"#,
+        )?;
+        let schedule = supplementals[0].to_schedule(OffsetDateTime::UNIX_EPOCH)?;
+        assert_eq!(schedule.operator, Operator::GulfIslandsWaterTaxi);
+        assert_eq!(schedule.items.len(), 1);
+        let item = &schedule.items[0];
+        assert_eq!((item.sailing.depart_time, item.sailing.arrive_time), (time!(09:15), time!(09:45)));
+        assert!(item.is_on_date(date!(2024 - 07 - 06)));
+        assert!(!item.is_on_date(date!(2024 - 07 - 07)));
+        assert_eq!(item.note_category(FOOT_PASSENGERS_ONLY_NOTE), NoteCategory::FootPassengersOnly);
+        assert!(supplementals[1].to_schedule(OffsetDateTime::UNIX_EPOCH).is_err());
+        Ok(())
+    }
+}
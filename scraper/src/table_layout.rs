@@ -0,0 +1,233 @@
+use crate::macros::*;
+use crate::prelude::*;
+use crate::utils::*;
+
+// Schedule tables are resolved into an explicit layout before any of their text is interpreted as a schedule:
+//
+// - Header detection: a `thead` row with a `data-schedule-day` attribute starts a section of rows for that day, and
+//   other `thead` rows name the columns
+// - Column typing: column names are matched to the kinds of values that schedule interpretation needs
+// - Spans: a cell with `rowspan` or `colspan` is repeated in every row and column it covers, so that every row has one
+//   cell per column
+//
+// Supporting a new layout variant should then only mean recognizing its headers, not changing how rows are parsed.
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColumnKind {
+    Depart,
+    // Either the arrival time or the duration of the sailing
+    Arrive,
+    Stops,
+    Other,
+}
+
+impl ColumnKind {
+    fn from_header(text: &str) -> ColumnKind {
+        let text = text.to_lowercase();
+        if text.contains("depart") {
+            ColumnKind::Depart
+        } else if text.contains("arrive") || text.contains("duration") {
+            ColumnKind::Arrive
+        } else if text.contains("stop") || text.contains("transfer") {
+            ColumnKind::Stops
+        } else {
+            ColumnKind::Other
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LayoutCell {
+    pub texts: Vec<String>,
+}
+
+impl LayoutCell {
+    pub fn text(&self) -> String {
+        self.texts.join(" ")
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct LayoutSection {
+    // Value of the `data-schedule-day` attribute, if the rows follow a day header
+    pub day: Option<String>,
+    pub rows: Vec<Vec<LayoutCell>>,
+}
+
+#[derive(Debug)]
+pub struct TableLayout {
+    pub headers: Vec<String>,
+    pub columns: Vec<ColumnKind>,
+    pub sections: Vec<LayoutSection>,
+}
+
+fn span_attr(elem: &ElementRef, name: &str) -> Result<usize> {
+    match elem.value().attr(name) {
+        None => Ok(1),
+        Some(text) => text
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|&span| span > 0)
+            .ok_or_else(|| anyhow!("Invalid {} in schedule table: {:?}", name, text)),
+    }
+}
+
+// Cells of a row, one per column.  `pending` holds cells whose row spans continue into the following rows, along with
+// how many rows they still cover.
+fn resolve_row(row_elem: ElementRef, pending: &mut Vec<Option<(LayoutCell, usize)>>) -> Result<Vec<LayoutCell>> {
+    let mut cell_elems = row_elem
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|elem| matches!(elem.value().name(), "td" | "th"))
+        .peekable();
+    let mut cells = Vec::new();
+    while cell_elems.peek().is_some() || pending.iter().skip(cells.len()).any(Option::is_some) {
+        let column = cells.len();
+        if let Some((cell, remaining)) = pending.get_mut(column).and_then(Option::as_mut) {
+            cells.push(cell.clone());
+            *remaining -= 1;
+            if *remaining == 0 {
+                pending[column] = None;
+            }
+            continue;
+        }
+        let cell_elem = match cell_elems.next() {
+            Some(cell_elem) => cell_elem,
+            // A row that ends before spans from the rows above it leaves a gap
+            None => {
+                cells.push(LayoutCell::default());
+                continue;
+            }
+        };
+        let cell = LayoutCell { texts: element_texts(&cell_elem) };
+        let rowspan = span_attr(&cell_elem, "rowspan")?;
+        for _ in 0..span_attr(&cell_elem, "colspan")? {
+            if rowspan > 1 {
+                if pending.len() <= cells.len() {
+                    pending.resize(cells.len() + 1, None);
+                }
+                pending[cells.len()] = Some((cell.clone(), rowspan - 1));
+            }
+            cells.push(cell.clone());
+        }
+    }
+    Ok(cells)
+}
+
+impl TableLayout {
+    // `row_selector` picks out the rows that hold sailings; other body rows only count towards row spans
+    pub fn parse(table_elem: ElementRef, row_selector: &Selector) -> Result<TableLayout> {
+        let inner = || {
+            let mut header_rows = Vec::new();
+            let mut header_pending = Vec::new();
+            let mut sections = Vec::new();
+            for group_elem in table_elem.children().filter_map(ElementRef::wrap) {
+                match group_elem.value().name() {
+                    "thead" => {
+                        for row_elem in group_elem.select(selector!("tr")) {
+                            match row_elem.value().attr("data-schedule-day") {
+                                Some(day) => sections.push(LayoutSection { day: Some(day.to_string()), rows: vec![] }),
+                                None => header_rows.push(resolve_row(row_elem, &mut header_pending)?),
+                            }
+                        }
+                    }
+                    "tbody" => {
+                        if sections.is_empty() {
+                            sections.push(LayoutSection { day: None, rows: vec![] });
+                        }
+                        let section = sections.last_mut().expect("Expect a section for table body");
+                        let mut pending = Vec::new();
+                        for row_elem in group_elem.children().filter_map(ElementRef::wrap) {
+                            let cells = resolve_row(row_elem, &mut pending)?;
+                            if row_selector.matches(&row_elem) {
+                                section.rows.push(cells);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            // Header rows stacked above each other name the same columns, so their texts are combined
+            let width = header_rows.iter().map(Vec::len).max().unwrap_or(0);
+            let headers: Vec<_> = (0..width)
+                .map(|column| {
+                    header_rows
+                        .iter()
+                        .filter_map(|row| row.get(column))
+                        .map(LayoutCell::text)
+                        .filter(|text| !text.is_empty())
+                        .dedup()
+                        .join(" ")
+                })
+                .collect();
+            let columns = headers.iter().map(|header| ColumnKind::from_header(header)).collect();
+            Ok(TableLayout { headers, columns, sections }) as Result<_>
+        };
+        inner().context("Failed to resolve schedule table layout")
+    }
+
+    // Column kinds named by the headers, or `default_columns` if the table has no headers that are recognized
+    pub fn typed_columns<'a>(&'a self, default_columns: &'a [ColumnKind]) -> &'a [ColumnKind] {
+        if self.columns.iter().any(|&kind| kind != ColumnKind::Other) {
+            &self.columns
+        } else {
+            default_columns
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(html: &str, row_selector: &Selector) -> Result<TableLayout> {
+        let document = Html::parse_document(html);
+        let table_elem = document.select(selector!("table")).next().expect("Expect table");
+        TableLayout::parse(table_elem, row_selector)
+    }
+
+    fn row_texts(section: &LayoutSection) -> Vec<Vec<String>> {
+        section.rows.iter().map(|row| row.iter().map(LayoutCell::text).collect()).collect()
+    }
+
+    #[test]
+    fn test_resolve_spans() -> Result<()> {
+        let layout = layout(
+            r#"<table>
+            <thead>
+                <tr><th rowspan="2">Depart</th><th colspan="2">Sailing</th></tr>
+                <tr><th>Arrive</th><th>Stops &amp; transfers</th></tr>
+            </thead>
+            <thead><tr data-schedule-day="mon"><th colspan="3">Monday</th></tr></thead>
+            <tbody>
+                <tr class="row"><td rowspan="2">7:00 am</td><td>8:35 am</td><td>Non-stop</td></tr>
+                <tr class="details"><td colspan="2">Details</td></tr>
+                <tr class="row"><td>9:00 am</td><td colspan="2">10:35 am</td></tr>
+            </tbody>
+            </table>"#,
+            selector!("tr.row"),
+        )?;
+        assert_eq!(layout.headers, ["Depart", "Sailing Arrive", "Sailing Stops & transfers"]);
+        assert_eq!(layout.columns, [ColumnKind::Depart, ColumnKind::Arrive, ColumnKind::Stops]);
+        assert_eq!(layout.sections.len(), 1);
+        assert_eq!(layout.sections[0].day.as_deref(), Some("mon"));
+        assert_eq!(
+            row_texts(&layout.sections[0]),
+            [["7:00 am", "8:35 am", "Non-stop"], ["9:00 am", "10:35 am", "10:35 am"]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_typed_columns() -> Result<()> {
+        let default_columns = [ColumnKind::Other, ColumnKind::Depart];
+        let layout = layout(
+            r#"<table><tbody><tr class="row"><td></td><td>7:00 am</td></tr></tbody></table>"#,
+            selector!("tr.row"),
+        )?;
+        assert_eq!(layout.sections[0].day, None);
+        assert_eq!(layout.typed_columns(&default_columns), default_columns);
+        Ok(())
+    }
+}
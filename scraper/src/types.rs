@@ -1,6 +1,15 @@
 use clap::Args;
 
-use crate::imports::*;
+use crate::prelude::*;
+use crate::upload::{UploadTarget, DEFAULT_CACHE_CONTROL};
+
+// Catches typos, which would otherwise silently scrape nothing.  Routes that are not configured are allowed, since they
+// may be discovered from the schedules index.
+fn parse_route_terminal_pair(text: &str) -> Result<TerminalPair> {
+    let terminal_pair: TerminalPair = text.parse()?;
+    ensure!(terminal_pair.from != terminal_pair.to, "Terminal pair {} is not a route", terminal_pair);
+    Ok(terminal_pair)
+}
 
 #[derive(Args, Debug)]
 pub struct Options {
@@ -8,9 +17,13 @@ pub struct Options {
     #[clap(short = 'a', long, value_name = "HOURS", default_value = "12")]
     pub max_cache_age: i64,
 
-    /// Only process schedules for specified terminal pair
-    #[clap(short, long, value_name = "FROM-TO")]
-    pub terminals: Option<TerminalPair>,
+    /// Only process schedules for specified terminal pair (may be repeated)
+    #[clap(short, long, value_name = "FROM-TO", parse(try_from_str = parse_route_terminal_pair))]
+    pub terminals: Vec<TerminalPair>,
+
+    /// Skip schedules for specified terminal pair, even if found in the schedules index (may be repeated)
+    #[clap(long, value_name = "FROM-TO", parse(try_from_str = parse_route_terminal_pair))]
+    pub skip_terminals: Vec<TerminalPair>,
 
     /// Only process schedules whose date range includes this date
     #[clap(short, long, value_name = "YYYY-MM-DD", parse(try_from_str = parse_iso8601_date))]
@@ -20,15 +33,159 @@ pub struct Options {
     #[clap(short, long, value_name = "PATH")]
     pub output_file: Option<PathBuf>,
 
-    /// Upload schedules JSON to this S3 bucket
+    /// Also keep this many copies of the output file beside it, named for when they were written, removing older ones
+    #[clap(long, value_name = "N", default_value = "0", requires = "output-file")]
+    pub keep_output_snapshots: usize,
+
+    /// Also write brotli and gzip compressed copies of the schedules JSON beside it, for the host to serve instead
+    #[clap(long)]
+    pub precompress_output: bool,
+
+    /// Also write each route's schedules JSON to its own file, in a `routes` directory beside the schedules JSON, so
+    /// that the app can load only the route being viewed
+    #[clap(long)]
+    pub route_files: bool,
+
+    /// Publish schedules that start after the current month in a file for each month, in a `partitions` directory
+    /// beside the schedules JSON, so that the app can still show current schedules if a later month's file fails to load
+    #[clap(long)]
+    pub partition_output: bool,
+
+    /// Upload schedules JSON, and any GTFS feed or iCalendar files beside it, to this bucket
     #[clap(short = 'b', long, value_name = "NAME")]
     pub output_s3_bucket: Option<String>,
 
-    /// Upload schedules JSON to this S3 key
+    /// Upload schedules JSON to this key in the bucket
     #[clap(short = 'k', long, value_name = "KEY", default_value = "schedules.json")]
     pub output_s3_key: String,
 
+    /// Storage service that the bucket is in; GCS is uploaded to with HMAC keys in the AWS credential variables
+    #[clap(long, value_name = "SERVICE", default_value = "s3", possible_values = ["s3", "gcs"])]
+    pub upload_target: UploadTarget,
+
+    /// `Cache-Control` header of uploaded files
+    #[clap(long, value_name = "HEADER", default_value = DEFAULT_CACHE_CONTROL)]
+    pub upload_cache_control: String,
+
+    /// Compress uploaded files with gzip, with a matching `Content-Encoding` header
+    #[clap(long)]
+    pub upload_gzip: bool,
+
+    /// Publish to this data channel; preview data is written beside the output file and S3 key, in a `preview`
+    /// subdirectory
+    #[clap(long, value_name = "CHANNEL", default_value = "stable", possible_values = ["stable", "preview"])]
+    pub channel: DataChannel,
+
+    /// Cross-check scraped sailings against this mobile API URL, where {from}, {to}, and {date} are replaced
+    #[clap(long, value_name = "URL TEMPLATE")]
+    pub cross_check_url: Option<String>,
+
+    /// Number of days starting today to cross-check for each route
+    #[clap(long, value_name = "DAYS", default_value = "2")]
+    pub cross_check_days: i64,
+
+    /// Check that schedule source URLs are not broken, and link to the final location of any that redirect
+    #[clap(long)]
+    pub check_source_urls: bool,
+
+    /// Add alerts from BC Ferries' service notices to the schedules of the routes they are for
+    #[clap(long)]
+    pub service_notices: bool,
+
+    /// Compare with previously published schedules JSON in this file, to mark sailings in re-issued schedules
+    #[clap(long, value_name = "PATH")]
+    pub previous_file: Option<PathBuf>,
+
+    /// Save a snapshot of the schedules in this archive directory, for later analysis
+    #[clap(long, value_name = "PATH")]
+    pub archive_dir: Option<PathBuf>,
+
+    /// Experimental: for routes whose next schedule period isn't published yet, add a provisional schedule based on the
+    /// same period last year in the archive directory's snapshots
+    #[clap(long, requires = "archive-dir")]
+    pub provisional_schedules: bool,
+
+    /// Also write the schedules as a GTFS static feed to this zip file
+    #[clap(long, value_name = "PATH")]
+    pub gtfs_file: Option<PathBuf>,
+
+    /// Also write a Markdown summary of each route's schedules to this directory, replacing those from earlier runs
+    #[clap(long, value_name = "PATH")]
+    pub markdown_dir: Option<PathBuf>,
+
+    /// Also write an iCalendar file of each route's sailings to this directory, replacing those from earlier runs
+    #[clap(long, value_name = "PATH")]
+    pub ical_dir: Option<PathBuf>,
+
+    /// Keep scraping past unrecognized annotation texts, noting them on the affected sailings as-is, and exit with an
+    /// error only after writing the output
+    #[clap(long)]
+    pub lenient_annotations: bool,
+
+    /// Write a JSON report of the annotation texts that lenient parsing let through to this file
+    #[clap(long, value_name = "PATH")]
+    pub parse_report_file: Option<PathBuf>,
+
+    /// Instead of scraping, export every sailing in the archive directory's snapshots to this Parquet file
+    #[clap(long, value_name = "PATH", requires = "archive-dir")]
+    pub export_parquet: Option<PathBuf>,
+
+    /// Instead of scraping, print the sailings added, removed and changed between two schedules JSON files
+    #[clap(long, value_names = &["OLD", "NEW"], number_of_values = 2)]
+    pub diff: Option<Vec<PathBuf>>,
+
+    /// Instead of scraping, check a schedules JSON file for sailings, stops and date ranges that can't be right, and
+    /// print them as a JSON list
+    #[clap(long, value_name = "PATH")]
+    pub validate: Option<PathBuf>,
+
+    /// Instead of scraping, check that every annotation text in the bundled corpus still parses
+    #[clap(long)]
+    pub selftest: bool,
+
+    /// Save each schedule page scraped to this fixtures directory, along with a snapshot of what it parses to
+    #[clap(long, value_name = "PATH")]
+    pub record_fixtures: Option<PathBuf>,
+
+    /// Instead of scraping, replay the saved pages in this fixtures directory and compare what they parse to with their
+    /// snapshots
+    #[clap(long, value_name = "PATH")]
+    pub check_fixtures: Option<PathBuf>,
+
+    /// Publish the feature flags in this JSON file in the manifest, to roll features out to a share of clients or to
+    /// some routes
+    #[clap(long, value_name = "PATH")]
+    pub feature_flags_file: Option<PathBuf>,
+
+    /// Number of routes to scrape at once
+    #[clap(long, value_name = "N", default_value = "4")]
+    pub concurrency: usize,
+
+    /// Most times to try fetching a URL, retrying with backoff after timeouts, dropped connections and server errors
+    #[clap(long, value_name = "N", default_value = "6")]
+    pub max_fetch_attempts: usize,
+
+    /// Least time between starting requests to the same host, however many routes are being scraped at once
+    #[clap(long, value_name = "MILLISECONDS", default_value = "250")]
+    pub host_delay_ms: u64,
+
+    /// Save every response fetched, including ones from the cache, to this directory by URL
+    #[clap(long, value_name = "PATH")]
+    pub record_http: Option<PathBuf>,
+
+    /// Fetch nothing, and use the responses saved in this directory by `--record-http` instead; pass the recording's
+    /// `--date` to request the same pages
+    #[clap(long, value_name = "PATH", conflicts_with_all = &["record-http", "check-source-urls"])]
+    pub replay_http: Option<PathBuf>,
+
     /// After uploading schedules JSON, invalidate this CloudFront distribution
     #[clap(short = 'c', long, value_name = "DISTRIBUTION ID")]
     pub invalidate_cloudfront_distribution_id: Option<String>,
 }
+
+impl Options {
+    pub fn includes_route(&self, terminal_pair: TerminalPair) -> bool {
+        (self.terminals.is_empty() || self.terminals.contains(&terminal_pair))
+            && !self.skip_terminals.contains(&terminal_pair)
+    }
+}
@@ -0,0 +1,194 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::compression::compress;
+use crate::prelude::*;
+use crate::types::*;
+
+// Published files are served straight from the bucket (or a CDN in front of it), so the uploads set the headers that
+// browsers cache them by.  Uploading needs the `upload` feature, which brings in the AWS SDK.
+
+pub const DEFAULT_CACHE_CONTROL: &str = "max-age=43200,public";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UploadTarget {
+    S3,
+    Gcs,
+}
+
+impl UploadTarget {
+    fn url(&self, bucket: &str, key: &str) -> String {
+        match self {
+            UploadTarget::S3 => format!("s3://{}/{}", bucket, key),
+            UploadTarget::Gcs => format!("gs://{}/{}", bucket, key),
+        }
+    }
+}
+
+impl FromStr for UploadTarget {
+    type Err = Error;
+    fn from_str(text: &str) -> Result<UploadTarget> {
+        match text {
+            "s3" => Ok(UploadTarget::S3),
+            "gcs" => Ok(UploadTarget::Gcs),
+            _ => bail!("Unknown upload target: {:?}", text),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct UploadObject {
+    pub key: String,
+    pub body: Vec<u8>,
+    pub content_type: &'static str,
+    // Set if the body is already compressed, as pre-compressed copies are
+    pub content_encoding: Option<ContentEncoding>,
+}
+
+impl UploadObject {
+    // A file that was written locally, uploaded under the given key
+    pub fn from_file(key: String, path: &Path, content_type: &'static str) -> Result<UploadObject> {
+        let body = fs::read(path).with_context(|| format!("Failed to read file to upload: {:?}", path))?;
+        Ok(UploadObject { key, body, content_type, content_encoding: None })
+    }
+}
+
+// The body as uploaded, and its content encoding if it isn't the identity
+fn encoded_body(object: UploadObject, gzip: bool) -> (Vec<u8>, Option<ContentEncoding>) {
+    match object.content_encoding {
+        Some(content_encoding) => (object.body, Some(content_encoding)),
+        None if gzip => (compress(&object.body, ContentEncoding::Gzip), Some(ContentEncoding::Gzip)),
+        None => (object.body, None),
+    }
+}
+
+// Google Cloud Storage is uploaded to through its S3-compatible API, with HMAC keys in the usual AWS credential variables
+#[cfg(feature = "upload")]
+fn storage_client(aws_config: &aws_types::SdkConfig, target: UploadTarget) -> aws_sdk_s3::Client {
+    let config = aws_sdk_s3::config::Builder::from(aws_config);
+    let config = match target {
+        UploadTarget::S3 => config,
+        UploadTarget::Gcs => config.endpoint_resolver(aws_sdk_s3::Endpoint::immutable(
+            "https://storage.googleapis.com".parse().expect("Expect GCS endpoint to be a valid URI"),
+        )),
+    };
+    aws_sdk_s3::Client::from_conf(config.build())
+}
+
+#[cfg(feature = "upload")]
+async fn put_object(aws_config: &aws_types::SdkConfig, options: &Options, object: UploadObject) -> Result<()> {
+    let bucket = options.output_s3_bucket.as_deref().expect("Expect bucket to upload to");
+    let url = options.upload_target.url(bucket, &object.key);
+    info!("Uploading to: {}", url);
+    let key = object.key.clone();
+    let content_type = object.content_type;
+    let (body, content_encoding) = encoded_body(object, options.upload_gzip);
+    storage_client(aws_config, options.upload_target)
+        .put_object()
+        .bucket(bucket)
+        .key(key)
+        .content_type(content_type)
+        .set_content_encoding(content_encoding.map(|content_encoding| content_encoding.to_string()))
+        .acl(aws_sdk_s3::model::ObjectCannedAcl::PublicRead)
+        .cache_control(&options.upload_cache_control)
+        .body(aws_sdk_s3::types::ByteStream::from(body))
+        .send()
+        .await
+        .with_context(|| format!("Failed to upload: {}", url))?;
+    Ok(())
+}
+
+#[cfg(feature = "upload")]
+async fn invalidate_cloudfront_distribution(
+    aws_config: &aws_types::SdkConfig,
+    distribution_id: &str,
+    s3_keys: &[&str],
+) -> Result<()> {
+    let cloudfront_client = aws_sdk_cloudfront::Client::new(aws_config);
+    let paths: Vec<_> = s3_keys.iter().map(|key| format!("/{}", key)).collect();
+    info!("Invalidating CloudFront distribution {:?} for paths: {:?}", distribution_id, paths);
+    cloudfront_client
+        .create_invalidation()
+        .distribution_id(distribution_id)
+        .invalidation_batch(
+            aws_sdk_cloudfront::model::InvalidationBatch::builder()
+                .caller_reference(OffsetDateTime::now_utc().unix_timestamp_nanos().to_string())
+                .paths(
+                    aws_sdk_cloudfront::model::Paths::builder()
+                        .quantity(paths.len() as i32)
+                        .set_items(Some(paths))
+                        .build(),
+                )
+                .build(),
+        )
+        .send()
+        .await
+        .with_context(|| {
+            format!("Failed to create CloudFront invalidation for distribution ID: {:?}", distribution_id)
+        })?;
+    Ok(())
+}
+
+// Uploads the objects in order, so that callers can put anything that refers to other objects last
+#[cfg(feature = "upload")]
+pub async fn upload_objects(options: &Options, objects: Vec<UploadObject>) -> Result<()> {
+    ensure!(
+        options.invalidate_cloudfront_distribution_id.is_none() || options.upload_target == UploadTarget::S3,
+        "CloudFront distributions can only be invalidated after uploading to S3"
+    );
+    let aws_config = aws_config::from_env().load().await;
+    let keys: Vec<_> = objects.iter().map(|object| object.key.clone()).collect();
+    for object in objects {
+        put_object(&aws_config, options, object).await?;
+    }
+    if let Some(distribution_id) = &options.invalidate_cloudfront_distribution_id {
+        let keys: Vec<_> = keys.iter().map(String::as_str).collect();
+        invalidate_cloudfront_distribution(&aws_config, distribution_id, &keys).await?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "upload"))]
+pub async fn upload_objects(_options: &Options, _objects: Vec<UploadObject>) -> Result<()> {
+    bail!("Uploading requires the scraper to be built with the `upload` feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+
+    #[test]
+    fn test_encoded_body() -> Result<()> {
+        let body = br#"[{"terminal_pair":"SWB-TSA"}]"#.to_vec();
+        let object = |content_encoding| UploadObject {
+            key: "schedules.json".to_string(),
+            body: body.clone(),
+            content_type: "application/json",
+            content_encoding,
+        };
+        assert_eq!(encoded_body(object(None), false), (body.clone(), None));
+        let (gzipped, content_encoding) = encoded_body(object(None), true);
+        assert_eq!(content_encoding, Some(ContentEncoding::Gzip));
+        let mut decoded = Vec::new();
+        io::Read::read_to_end(&mut GzDecoder::new(gzipped.as_slice()), &mut decoded)?;
+        assert_eq!(decoded, body);
+        // Pre-compressed bodies aren't compressed again
+        assert_eq!(
+            encoded_body(object(Some(ContentEncoding::Brotli)), true),
+            (body.clone(), Some(ContentEncoding::Brotli))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_upload_target() -> Result<()> {
+        assert_eq!(
+            "gcs".parse::<UploadTarget>()?.url("ferries", "data/schedules.json"),
+            "gs://ferries/data/schedules.json"
+        );
+        assert_eq!("s3".parse::<UploadTarget>()?.url("ferries", "schedules.json"), "s3://ferries/schedules.json");
+        assert!("azure".parse::<UploadTarget>().is_err());
+        Ok(())
+    }
+}
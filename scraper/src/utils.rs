@@ -1,9 +1,11 @@
 use scraper::ElementRef;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::Hasher;
+use std::path::Path;
+use tempfile::NamedTempFile;
 
-use crate::imports::*;
 use crate::macros::*;
+use crate::prelude::*;
 
 pub fn element_text(elem: &ElementRef) -> String {
     element_texts(elem).join(" ")
@@ -13,6 +15,42 @@ pub fn element_texts(elem: &ElementRef) -> Vec<String> {
     elem.text().map(|s| regex!(r"\s+").replace_all(s, " ").trim().to_string()).filter(|s| !s.is_empty()).collect()
 }
 
+// Writes to a temporary file next to the path and renames it into place once complete, so that a crash part way
+// through never leaves a truncated file behind
+pub fn write_file_atomically<F: FnOnce(&mut fs::File) -> Result<()>>(path: &Path, write: F) -> Result<()> {
+    let inner = || {
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => std::env::current_dir().context("Failed to get current directory")?,
+        };
+        let mut temp_file = NamedTempFile::new_in(&dir).context("Failed to create temporary file")?;
+        write(temp_file.as_file_mut())?;
+        temp_file.as_file().sync_all().context("Failed to flush temporary file")?;
+        temp_file.persist(path).context("Failed to rename temporary file")?;
+        Ok(()) as Result<_>
+    };
+    inner().with_context(|| format!("Failed to write file: {:?}", path))
+}
+
+// Removes files in the directory that are named after a terminal pair with the extension, except those for routes that
+// are still current, so per-route outputs always match the latest run
+pub fn remove_stale_route_files<F: Fn(TerminalPair) -> bool>(dir: &Path, extension: &str, is_current: F) -> Result<()> {
+    let suffix = format!(".{}", extension);
+    for entry in fs::read_dir(dir).context("Failed to list directory")? {
+        let path = entry.context("Failed to read directory entry")?.path();
+        let stale_terminal_pair = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(&suffix))
+            .and_then(|stem| stem.parse::<TerminalPair>().ok())
+            .filter(|tp| !is_current(*tp));
+        if stale_terminal_pair.is_some() {
+            fs::remove_file(&path).with_context(|| format!("Failed to remove stale file: {:?}", path))?;
+        }
+    }
+    Ok(())
+}
+
 pub fn calculate_hash<T: Hash>(t: &T) -> u64 {
     let mut s = DefaultHasher::new();
     t.hash(&mut s);
@@ -56,13 +94,31 @@ pub fn parse_duration(duration_text: &str) -> Result<Duration> {
     inner().with_context(|| format!("Failed to parse duration: {:?}", duration_text))
 }
 
+// Time between departure and arrival, allowing for sailings that arrive after midnight
+pub fn sailing_duration(depart_time: Time, arrive_time: Time) -> Duration {
+    let duration = arrive_time - depart_time;
+    if duration.is_negative() {
+        duration + Duration::DAY
+    } else {
+        duration
+    }
+}
+
+// Overnight sailings on the northern routes mark their arrival as being on the next day, which an arrive time earlier
+// than the depart time already implies
+pub fn strip_next_day_marker(text: &str) -> &str {
+    let marker = regex!(r"(?i)\s*(\(\s*next day\s*\)|next day|\+\s*1(\s*day)?)$").find(text);
+    marker.map(|m| &text[..m.start()]).unwrap_or(text)
+}
+
 pub fn parse_arrive_time_or_duration(depart_time: Time, text: &str) -> Result<Time> {
+    let text = strip_next_day_marker(text);
     parse_schedule_time(text)
         .or_else(|time_err| parse_duration(text).map(|dur| depart_time + dur).context(time_err))
         .with_context(|| format!("Failed to parse arrive time or duration: {:?}", text))
 }
 
-fn terminal_from_schedule_stop_text(stop_text: &str) -> Result<Terminal> {
+pub fn terminal_from_schedule_stop_text(stop_text: &str) -> Result<Terminal> {
     let stop_text = stop_text.to_lowercase();
     match &stop_text[..] {
         "chemainus" => Ok(Terminal::CHM),
@@ -73,12 +129,19 @@ fn terminal_from_schedule_stop_text(stop_text: &str) -> Result<Terminal> {
         | "mayne island {village bay)"
         | "mayne island (village bay)except on oct 9" => Ok(Terminal::PVB),
         "pender" | "pender island (otter bay)" => Ok(Terminal::POB),
+        "port hardy" | "port hardy (bear cove)" => Ok(Terminal::PPH),
+        "prince rupert" => Ok(Terminal::PPR),
+        "skidegate" | "haida gwaii (skidegate)" => Ok(Terminal::PSK),
         "penelakut island (telegraph harbour)" | "enelakut island (telegraph harbour)" => Ok(Terminal::PEN),
         "salt spring" | "salt spring island (long harbour)" => Ok(Terminal::PLH),
         "saturna" | "saturna island (lyall harbour)" | "saturna island (lyall harbour" => Ok(Terminal::PST),
         "thetis island (preedy harbour)" => Ok(Terminal::THT),
+        "tsawwassen" | "vancouver (tsawwassen)" => Ok(Terminal::TSA),
         "victoria (swartz bay)" | "swartz bay" => Ok(Terminal::SWB),
-        _ => Err(anyhow!("Unknown schedule stop name: {:?}", stop_text)),
+        // Footnotes name terminals by their own names, like "Otter Bay"
+        _ => Terminal::iter()
+            .find(|terminal| terminal.name().to_lowercase() == stop_text)
+            .ok_or_else(|| anyhow!("Unknown schedule stop name: {:?}", stop_text)),
     }
 }
 
@@ -0,0 +1,291 @@
+use std::path::Path;
+
+use crate::connections::leg_sailings;
+use crate::prelude::*;
+use crate::reissue::read_schedules_file;
+use crate::scraper::max_plausible_sailing_duration;
+use crate::utils::*;
+
+// Gaps between a route's schedules up to this long are more likely a schedule that failed to scrape than a break
+// between seasons
+const MAX_GAP_WITHIN_SEASON: Duration = Duration::days(14);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ViolationKind {
+    ImplausibleDuration,
+    DuplicateSailing,
+    DateRangeGap,
+    InvalidStop,
+    MissingThrufareLeg,
+    ImpossibleThrufareConnection,
+}
+
+// Something in the schedules output that can't be right, found by checking it as a whole after scraping
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    pub terminal_pair: TerminalPair,
+    pub date_range: DateRange,
+    pub detail: String,
+}
+
+impl Violation {
+    fn new(kind: ViolationKind, schedule: &Schedule, detail: String) -> Violation {
+        Violation { kind, terminal_pair: schedule.terminal_pair, date_range: schedule.date_range, detail }
+    }
+}
+
+// Zero durations are as wrong as long ones, since no sailing arrives when it departs
+fn duration_violations(schedule: &Schedule) -> Vec<Violation> {
+    let max_duration = max_plausible_sailing_duration(schedule.terminal_pair);
+    schedule
+        .items
+        .iter()
+        .filter_map(|item| {
+            let duration = sailing_duration(item.sailing.depart_time, item.sailing.arrive_time);
+            (duration.is_zero() || duration > max_duration).then(|| {
+                Violation::new(
+                    ViolationKind::ImplausibleDuration,
+                    schedule,
+                    format!(
+                        "Sailing at {} arrives at {}, {} minutes later",
+                        item.sailing.depart_time,
+                        item.sailing.arrive_time,
+                        duration.whole_minutes()
+                    ),
+                )
+            })
+        })
+        .collect()
+}
+
+fn stop_violations(schedule: &Schedule) -> Vec<Violation> {
+    let terminal_pair = schedule.terminal_pair;
+    schedule
+        .items
+        .iter()
+        .flat_map(|item| {
+            item.sailing
+                .stops
+                .iter()
+                .chain(item.conditional_stops.iter().map(|cs| &cs.stop))
+                .map(move |stop| (item, stop))
+        })
+        .filter_map(|(item, stop)| {
+            let problem = if stop.terminal == terminal_pair.from || stop.terminal == terminal_pair.to {
+                "is one of the route's own terminals"
+            } else if stop.type_ == StopType::Thrufare && !is_thrufare_eligible(terminal_pair, stop.terminal) {
+                "is not a thru-fare connection for the route"
+            } else {
+                return None;
+            };
+            Some(Violation::new(
+                ViolationKind::InvalidStop,
+                schedule,
+                format!("Sailing at {} stops at {}, which {}", item.sailing.depart_time, stop.terminal, problem),
+            ))
+        })
+        .collect()
+}
+
+// Legs are only checked if their route is in the output at all, so that validating a scrape of some routes doesn't
+// report every thru-fare
+fn thrufare_violations(schedules: &[Schedule], schedule: &Schedule) -> Vec<Violation> {
+    let has_route = |terminal_pair| schedules.iter().any(|s| s.terminal_pair == terminal_pair);
+    let mut violations = Vec::new();
+    for item in &schedule.items {
+        let via = match item.sailing.thrufare_via(schedule.terminal_pair) {
+            Some(via) => via,
+            None => continue,
+        };
+        let weekdays: HashSet<_> = item.weekdays.keys().copied().collect();
+        let first_leg_terminal_pair = TerminalPair { from: schedule.terminal_pair.from, to: via };
+        let second_leg_terminal_pair = TerminalPair { from: via, to: schedule.terminal_pair.to };
+        let first_leg_sailings = leg_sailings(schedules, schedule, first_leg_terminal_pair, &weekdays);
+        let second_leg_sailings = leg_sailings(schedules, schedule, second_leg_terminal_pair, &weekdays);
+        let missing_legs = [
+            (first_leg_terminal_pair, "departing", &first_leg_sailings, item.sailing.depart_time, true),
+            (second_leg_terminal_pair, "arriving", &second_leg_sailings, item.sailing.arrive_time, false),
+        ];
+        let mut is_missing_leg = false;
+        for (leg_terminal_pair, verb, sailings, time, is_first_leg) in missing_legs {
+            let is_found =
+                sailings.iter().any(|s| if is_first_leg { s.depart_time == time } else { s.arrive_time == time });
+            if has_route(leg_terminal_pair) && !is_found {
+                is_missing_leg = true;
+                violations.push(Violation::new(
+                    ViolationKind::MissingThrufareLeg,
+                    schedule,
+                    format!(
+                        "Thru-fare sailing at {} has no {} sailing {} at {}",
+                        item.sailing.depart_time, leg_terminal_pair, verb, time
+                    ),
+                ));
+            }
+        }
+        if !is_missing_leg && is_impossible_connection(&item.sailing, first_leg_sailings, second_leg_sailings) {
+            violations.push(Violation::new(
+                ViolationKind::ImpossibleThrufareConnection,
+                schedule,
+                format!("Thru-fare sailing at {} can't connect at {}", item.sailing.depart_time, via),
+            ));
+        }
+    }
+    violations
+}
+
+// Gaps within a season and duplicate sailings are between schedules, so they are checked for each route and operator
+fn route_violations(schedules: &[&Schedule]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let sorted_schedules: Vec<_> = schedules.iter().sorted_by_key(|s| (s.date_range.from, s.date_range.to)).collect();
+    for (previous, next) in sorted_schedules.iter().tuple_windows() {
+        let gap = next.date_range.from - previous.date_range.to - Duration::DAY;
+        if gap.is_positive() && gap <= MAX_GAP_WITHIN_SEASON {
+            violations.push(Violation {
+                kind: ViolationKind::DateRangeGap,
+                terminal_pair: previous.terminal_pair,
+                date_range: DateRange {
+                    from: previous.date_range.to + Duration::DAY,
+                    to: next.date_range.from - Duration::DAY,
+                },
+                detail: format!(
+                    "No schedule for {} days between {} and {}",
+                    gap.whole_days(),
+                    previous.date_range,
+                    next.date_range
+                ),
+            });
+        }
+    }
+    // Reported once for each sailing time, with every date it is duplicated on
+    let mut duplicate_dates: HashMap<Time, Vec<Date>> = HashMap::new();
+    let dates = schedules.iter().flat_map(|s| s.date_range.iter_days()).unique().sorted();
+    for date in dates {
+        let depart_times = schedules
+            .iter()
+            .flat_map(|s| s.items.iter().map(move |item| (s, item)))
+            .filter(|(s, item)| item.is_on_date(date) && s.is_effective_at(date, item.sailing.depart_time))
+            .map(|(_, item)| item.sailing.depart_time);
+        for depart_time in depart_times.duplicates() {
+            duplicate_dates.entry(depart_time).or_default().push(date);
+        }
+    }
+    for (depart_time, dates) in duplicate_dates.into_iter().sorted_by_key(|(depart_time, _)| *depart_time) {
+        let (first_date, last_date) = (dates[0], dates[dates.len() - 1]);
+        violations.push(Violation {
+            kind: ViolationKind::DuplicateSailing,
+            terminal_pair: schedules[0].terminal_pair,
+            date_range: DateRange { from: first_date, to: last_date },
+            detail: format!(
+                "More than one sailing at {} on {} dates from {} to {}",
+                depart_time,
+                dates.len(),
+                first_date,
+                last_date
+            ),
+        });
+    }
+    violations
+}
+
+pub fn validate_schedules(schedules: &[Schedule]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for schedule in schedules {
+        violations.extend(duration_violations(schedule));
+        violations.extend(stop_violations(schedule));
+        violations.extend(thrufare_violations(schedules, schedule));
+    }
+    let routes = into_vec_group_map(schedules.iter(), |s| (s.terminal_pair, s.operator));
+    for (_, route_schedules) in
+        routes.into_iter().sorted_by_key(|((terminal_pair, operator), _)| (*terminal_pair, operator.is_supplemental()))
+    {
+        violations.extend(route_violations(&route_schedules));
+    }
+    violations
+}
+
+// Prints the violations in the schedules JSON file as a JSON list, failing if there are any
+pub fn validate_schedules_file(path: &Path) -> Result<()> {
+    let schedules =
+        read_schedules_file(path).with_context(|| format!("Failed to read schedules to validate: {:?}", path))?;
+    let violations = validate_schedules(&schedules);
+    let mut stdout = io::stdout();
+    serde_json::to_writer_pretty(&mut stdout, &violations).context("Failed to write violations")?;
+    io::Write::write_all(&mut stdout, b"\n").context("Failed to write violations")?;
+    info!("Validated {} schedules and found {} violations", schedules.len(), violations.len());
+    ensure!(violations.is_empty(), "{} violations found in schedules: {:?}", violations.len(), path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(terminal_pair: TerminalPair, date_range: DateRange, sailings: &[(Time, Time, &[Stop])]) -> Schedule {
+        Schedule {
+            date_range,
+            items: sailings
+                .iter()
+                .map(|&(depart_time, arrive_time, stops)| ScheduleItem {
+                    sailing: Sailing { depart_time, arrive_time, stops: stops.to_vec() },
+                    weekdays: HashMap::from([(Weekday::Monday, DateRestriction::All)]),
+                    notes: HashMap::new(),
+                    note_categories: HashMap::new(),
+                    vehicle_restrictions: HashMap::new(),
+                    conditional_stops: vec![],
+                })
+                .collect(),
+            ..Schedule::test_fixture(terminal_pair)
+        }
+    }
+
+    #[test]
+    fn test_validate_schedules() -> Result<()> {
+        let june = DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 30) };
+        let july = DateRange { from: date!(2022 - 07 - 04), to: date!(2022 - 07 - 31) };
+        let swb_tsa = TerminalPair { from: Terminal::SWB, to: Terminal::TSA };
+        let tsa_pob = TerminalPair { from: Terminal::TSA, to: Terminal::POB };
+        let tsa_swb = TerminalPair { from: Terminal::TSA, to: Terminal::SWB };
+        let swb_pob = TerminalPair { from: Terminal::SWB, to: Terminal::POB };
+        let thrufare = Stop { type_: StopType::Thrufare, terminal: Terminal::SWB };
+        let valid = vec![
+            schedule(swb_tsa, june, &[(time!(07:00), time!(08:35), &[])]),
+            schedule(tsa_swb, june, &[(time!(07:00), time!(08:35), &[])]),
+            schedule(swb_pob, june, &[(time!(09:10), time!(10:00), &[])]),
+            schedule(tsa_pob, june, &[(time!(07:00), time!(10:00), &[thrufare])]),
+        ];
+        assert_eq!(validate_schedules(&valid), vec![]);
+        let invalid = vec![
+            schedule(swb_tsa, june, &[(time!(07:00), time!(07:00), &[])]),
+            // Not a gap within a season, since a new season starting in September is likely
+            schedule(swb_tsa, DateRange { from: date!(2022 - 09 - 06), to: date!(2022 - 09 - 30) }, &[]),
+            schedule(swb_tsa, july, &[(time!(09:00), time!(10:35), &[])]),
+            schedule(swb_tsa, july, &[(time!(09:00), time!(10:35), &[])]),
+            schedule(
+                tsa_swb,
+                june,
+                &[(time!(07:00), time!(08:35), &[Stop { type_: StopType::Stop, terminal: Terminal::TSA }])],
+            ),
+            schedule(swb_pob, june, &[(time!(08:40), time!(10:00), &[])]),
+            schedule(
+                tsa_pob,
+                june,
+                &[(time!(07:00), time!(10:00), &[thrufare]), (time!(06:00), time!(10:00), &[thrufare])],
+            ),
+        ];
+        let kinds: Vec<_> = validate_schedules(&invalid).into_iter().map(|v| (v.kind, v.date_range)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (ViolationKind::ImplausibleDuration, june),
+                (ViolationKind::InvalidStop, june),
+                (ViolationKind::ImpossibleThrufareConnection, june),
+                (ViolationKind::MissingThrufareLeg, june),
+                (ViolationKind::DateRangeGap, DateRange { from: date!(2022 - 07 - 01), to: date!(2022 - 07 - 03) }),
+                (ViolationKind::DuplicateSailing, DateRange { from: date!(2022 - 07 - 04), to: date!(2022 - 07 - 25) }),
+            ]
+        );
+        Ok(())
+    }
+}
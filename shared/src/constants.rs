@@ -1,6 +1,4 @@
-use crate::imports::*;
-use crate::types::*;
-use crate::utils::*;
+use crate::prelude::*;
 
 pub const BCFERRIES_BASE_URL: &str = "https://www.bcferries.com";
 pub const BCFERRIES_HOME_URL: &str = concatcp!(BCFERRIES_BASE_URL, "/");
@@ -20,6 +18,30 @@ pub const TSA_SGI_SERVICE_NOTICES_URL: &str =
     concatcp!(ALL_SERVICE_NOTICES_URL, "#Metro%20Vancouver%20-%20Southern%20Gulf%20Islands");
 pub const THRU_FARE_INFORMATION_URL: &str = concatcp!(BCFERRIES_BASE_URL, "/routes-fares/ferry-fares/thru-fare");
 
+pub const DATA_SOURCE_NAME: &str = "British Columbia Ferry Services Inc.";
+pub const DATA_ATTRIBUTION: &str =
+    "Schedule information is collected from the schedules published on the BC Ferries web site and remains the property of British Columbia Ferry Services Inc.";
+pub const DATA_DISCLAIMER: &str = "This data is scraped and processed automatically, and may be out of date or incorrect. It is provided as is, without warranty of any kind, and is not an official source of schedule information.";
+
+// Note for sailings that don't take vehicles, written by the scraper
+pub const FOOT_PASSENGERS_ONLY_NOTE: &str = "Foot passengers only";
+// Note for sailings where a passenger-only vessel replaces the car ferry on some dates, written by the scraper
+pub const PASSENGER_ONLY_VESSEL_NOTE: &str =
+    "Passenger-only vessel replaces the car ferry; no vehicles on this sailing";
+// Notes for sailings from small terminals that only run in suitable conditions, written by the scraper for
+// `SailingCondition`s
+pub const DAYLIGHT_LOADING_NOTE: &str = "Vehicles only load in daylight; this sailing may not run after dark";
+pub const TIDE_RESTRICTION_NOTE: &str = "Depends on the tide; this sailing may be delayed or cancelled at low water";
+// Start and end of notes for sailings with a `VehicleRestriction`, written by the scraper
+pub const VEHICLE_RESTRICTION_NOTE_PREFIX: &str = "No vehicles over ";
+pub const VEHICLE_RESTRICTION_NOTE_SUFFIX: &str = " on this sailing";
+// Largest vehicles that pay the standard vehicle fare, in feet
+pub const STANDARD_VEHICLE_MAX_HEIGHT_FEET: u32 = 7;
+pub const STANDARD_VEHICLE_MAX_LENGTH_FEET: u32 = 20;
+// Sailings departing before this time belong to the previous day's service, since travellers think of a sailing just
+// after midnight as the last one of the evening before
+pub const SERVICE_DAY_START: Time = time!(3:00);
+
 pub static ROUTE_5_AND_9_GULF_ISLAND_TERMINALS: Lazy<HashSet<Terminal>> =
     Lazy::new(|| HashSet::from_iter([Terminal::PLH, Terminal::POB, Terminal::PSB, Terminal::PST, Terminal::PVB]));
 
@@ -39,13 +61,109 @@ pub static ALL_TERMINAL_PAIRS: Lazy<HashSet<TerminalPair>> = Lazy::new(|| {
         vec![Terminal::BTW, Terminal::MIL],
         // Route 20 (Chemainus/Thetis/Penelakut)
         vec![Terminal::CHM, Terminal::THT, Terminal::PEN],
+        // Route 10 (Port Hardy/Prince Rupert, the Inside Passage)
+        vec![Terminal::PPH, Terminal::PPR],
+        // Route 11 (Prince Rupert/Haida Gwaii)
+        vec![Terminal::PPR, Terminal::PSK],
     ];
     routes.iter().flat_map(|terminals| Terminal::combinations(terminals)).collect()
 });
 
+const NO_RESERVATIONS_NOTE: &str =
+    "Reservations are not accepted on this route; vehicles load in the order they arrive";
+
+const RESERVATIONS_RECOMMENDED_NOTE: &str =
+    "Vehicle reservations are recommended on this route, especially on weekends and holidays";
+
+const SOUTHERN_GULF_ISLAND_AND_SWARTZ_BAY_TERMINALS: &[Terminal] =
+    &[Terminal::SWB, Terminal::PLH, Terminal::POB, Terminal::PSB, Terminal::PST, Terminal::PVB];
+
+// Informational notes about each route that do not appear in the scraped schedules, applying to travel between any of
+// the first terminals and any of the second terminals (in either direction)
+pub static ROUTE_NOTES: Lazy<HashMap<TerminalPair, Vec<&'static str>>> = Lazy::new(|| {
+    let routes: [(&[Terminal], &[Terminal], &[&'static str]); 9] = [
+        // Route 1 (Tsawwassen/Swartz Bay)
+        (&[Terminal::TSA], &[Terminal::SWB], &[RESERVATIONS_RECOMMENDED_NOTE]),
+        // Route 4 (Fulford Harbour/Swartz Bay)
+        (&[Terminal::FUL], &[Terminal::SWB], &[NO_RESERVATIONS_NOTE]),
+        // Route 5 (Swartz Bay/Southern Gulf Islands)
+        (
+            SOUTHERN_GULF_ISLAND_AND_SWARTZ_BAY_TERMINALS,
+            SOUTHERN_GULF_ISLAND_AND_SWARTZ_BAY_TERMINALS,
+            &[NO_RESERVATIONS_NOTE],
+        ),
+        // Route 6 (Vesuvius/Crofton)
+        (&[Terminal::VES], &[Terminal::CFT], &[NO_RESERVATIONS_NOTE]),
+        // Route 9 (Tsawwassen/Southern Gulf Islands)
+        (
+            &[Terminal::TSA],
+            &[Terminal::PLH, Terminal::POB, Terminal::PSB, Terminal::PST, Terminal::PVB],
+            &[RESERVATIONS_RECOMMENDED_NOTE],
+        ),
+        // Route 12 (Brentwood/Mill Bay)
+        (
+            &[Terminal::BTW],
+            &[Terminal::MIL],
+            &[NO_RESERVATIONS_NOTE, "Short crossing of Saanich Inlet on a small vessel"],
+        ),
+        // Route 20 (Chemainus/Thetis/Penelakut)
+        (
+            &[Terminal::CHM, Terminal::THT, Terminal::PEN],
+            &[Terminal::CHM, Terminal::THT, Terminal::PEN],
+            &[NO_RESERVATIONS_NOTE],
+        ),
+        // Route 10 (Port Hardy/Prince Rupert, the Inside Passage)
+        (
+            &[Terminal::PPH],
+            &[Terminal::PPR],
+            &[RESERVATIONS_RECOMMENDED_NOTE, "Day-long crossing of the Inside Passage"],
+        ),
+        // Route 11 (Prince Rupert/Haida Gwaii)
+        (
+            &[Terminal::PPR],
+            &[Terminal::PSK],
+            &[RESERVATIONS_RECOMMENDED_NOTE, "Crossing of Hecate Strait, overnight on some sailings"],
+        ),
+    ];
+    routes
+        .iter()
+        .flat_map(|&(from_terminals, to_terminals, notes)| {
+            from_terminals
+                .iter()
+                .cartesian_product(to_terminals)
+                .filter(|(from, to)| from != to)
+                .flat_map(|(&from, &to)| [TerminalPair { from, to }, TerminalPair { from: to, to: from }])
+                .map(move |tp| (tp, notes))
+        })
+        .unique_by(|(tp, _)| *tp)
+        .map(|(tp, notes)| (tp, notes.to_vec()))
+        .collect()
+});
+
 pub static ALL_AREA_PAIRS: Lazy<HashSet<AreaPair>> =
     Lazy::new(|| HashSet::from_iter(ALL_TERMINAL_PAIRS.iter().map(|tp| tp.area_pair())));
 
+// Area pairs that make the same journey from a different terminal, such as reaching Salt Spring Island from southern or
+// central Vancouver Island.  Each group applies in both directions.
+pub static ALTERNATE_AREA_PAIRS: Lazy<HashMap<AreaPair, Vec<AreaPair>>> = Lazy::new(|| {
+    let groups: &[&[(Area, Area)]] = &[&[(Area::Victoria, Area::SaltSpring), (Area::Crofton, Area::SaltSpring)]];
+    groups
+        .iter()
+        .flat_map(|group| {
+            [false, true].into_iter().flat_map(move |reverse| {
+                let area_pairs: Vec<_> = group
+                    .iter()
+                    .map(|&(a, b)| if reverse { AreaPair { from: b, to: a } } else { AreaPair { from: a, to: b } })
+                    .collect();
+                area_pairs
+                    .iter()
+                    .map(|&ap| (ap, area_pairs.iter().filter(|&&other| other != ap).cloned().collect()))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect()
+});
+
 pub static AREA_TERMINALS: Lazy<HashMap<Area, HashSet<Terminal>>> =
     Lazy::new(|| into_hashset_group_map(Terminal::iter(), |t| t.area()));
 
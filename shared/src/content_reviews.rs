@@ -0,0 +1,72 @@
+use crate::prelude::*;
+
+// Hand-maintained content that encodes BC Ferries policies, which change without anything in the scraped schedules
+// saying so.  Each needs checking against BC Ferries' site by its review date, after which the scraper and debug builds
+// of the front-end warn about it.  Move the date forward after reviewing the content, even if nothing changed.
+#[derive(Debug)]
+pub struct ContentReview {
+    pub description: &'static str,
+    // Where the content is maintained, relative to the repository root
+    pub location: &'static str,
+    pub review_by: Date,
+}
+
+pub const CONTENT_REVIEWS: &[ContentReview] = &[
+    ContentReview {
+        description: "Reservation opening lead times",
+        location: "frontend/src/reservation_hints.rs",
+        review_by: date!(2027 - 03 - 01),
+    },
+    ContentReview {
+        description: "Thru-fare connection rules",
+        location: "shared/src/thrufares.rs",
+        review_by: date!(2027 - 04 - 01),
+    },
+    ContentReview {
+        description: "Terminal check-in and arrival deadlines",
+        location: "frontend/src/checklist.rs",
+        review_by: date!(2027 - 04 - 01),
+    },
+    ContentReview {
+        description: "Standard vehicle size limits for fares",
+        location: "shared/src/types.rs",
+        review_by: date!(2027 - 04 - 01),
+    },
+    ContentReview {
+        description: "Help pages on fares, reservations and thru-fares",
+        location: "frontend/content/help.toml",
+        review_by: date!(2027 - 04 - 01),
+    },
+];
+
+impl ContentReview {
+    pub fn warning(&self) -> String {
+        format!(
+            "{} ({}) were due for review by {}; check them against BC Ferries' site and update the review date",
+            self.description,
+            self.location,
+            format_iso8601_date(self.review_by)
+        )
+    }
+}
+
+pub fn overdue_content_reviews(today: Date) -> impl Iterator<Item = &'static ContentReview> {
+    CONTENT_REVIEWS.iter().filter(move |review| review.review_by < today)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overdue_content_reviews() -> Result<()> {
+        let latest_review_by = CONTENT_REVIEWS.iter().map(|review| review.review_by).max().expect("Expect reviews");
+        assert_eq!(overdue_content_reviews(date!(2022 - 01 - 01)).count(), 0);
+        assert_eq!(overdue_content_reviews(latest_review_by).filter(|r| r.review_by == latest_review_by).count(), 0);
+        assert_eq!(
+            overdue_content_reviews(latest_review_by.next_day().expect("Expect next day")).count(),
+            CONTENT_REVIEWS.len()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use crate::prelude::*;
+
+// Easter Sunday in the Gregorian calendar (anonymous Gregorian algorithm)
+fn easter_sunday(year: i32) -> Date {
+    let (a, b, c) = (year % 19, year / 100, year % 100);
+    let (d, e) = (b / 4, b % 4);
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let (i, k) = (c / 4, c % 4);
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = Month::try_from(u8::try_from((h + l - 7 * m + 114) / 31).expect("Expect Easter month to fit"))
+        .expect("Expect Easter month to be valid");
+    let day = u8::try_from((h + l - 7 * m + 114) % 31 + 1).expect("Expect Easter day to fit");
+    Date::from_calendar_date(year, month, day).expect("Expect Easter date to be valid")
+}
+
+fn nth_weekday(year: i32, month: Month, n: u8, weekday: Weekday) -> Date {
+    let first = Date::from_calendar_date(year, month, 1).expect("Expect first of month to be valid");
+    let offset = (weekday.number_days_from_monday() + 7 - first.weekday().number_days_from_monday()) % 7;
+    first + Duration::days((offset + 7 * (n - 1)).into())
+}
+
+fn fixed_date(year: i32, month: Month, day: u8) -> Date {
+    Date::from_calendar_date(year, month, day).expect("Expect holiday date to be valid")
+}
+
+// Statutory holidays in British Columbia with their names, which "holiday" in annotations refers to.  Holidays on
+// fixed dates that fall on a weekend are also observed on the following Monday.
+pub fn bc_holidays(year: i32) -> Vec<(Date, &'static str)> {
+    let easter = easter_sunday(year);
+    let fixed_holidays = [
+        (fixed_date(year, Month::January, 1), "New Year's Day"),
+        (fixed_date(year, Month::July, 1), "Canada Day"),
+        (fixed_date(year, Month::September, 30), "National Day for Truth and Reconciliation"),
+        (fixed_date(year, Month::November, 11), "Remembrance Day"),
+        (fixed_date(year, Month::December, 25), "Christmas Day"),
+    ];
+    let observed_holidays = fixed_holidays.iter().filter_map(|&(date, name)| match date.weekday() {
+        Weekday::Saturday => Some((date + Duration::days(2), name)),
+        Weekday::Sunday => Some((date + Duration::days(1), name)),
+        _ => None,
+    });
+    let victoria_day = (18..=24)
+        .map(|day| fixed_date(year, Month::May, day))
+        .find(|date| date.weekday() == Weekday::Monday)
+        .expect("Expect a Monday in a week");
+    fixed_holidays
+        .iter()
+        .copied()
+        .chain(observed_holidays)
+        .chain([
+            (nth_weekday(year, Month::February, 3, Weekday::Monday), "Family Day"),
+            (easter - Duration::days(2), "Good Friday"),
+            (easter + Duration::days(1), "Easter Monday"),
+            (victoria_day, "Victoria Day"),
+            (nth_weekday(year, Month::August, 1, Weekday::Monday), "B.C. Day"),
+            (nth_weekday(year, Month::September, 1, Weekday::Monday), "Labour Day"),
+            (nth_weekday(year, Month::October, 2, Weekday::Monday), "Thanksgiving"),
+        ])
+        .sorted()
+        .dedup_by(|a, b| a.0 == b.0)
+        .collect()
+}
+
+pub fn bc_holiday_name(date: Date) -> Option<&'static str> {
+    bc_holidays(date.year()).into_iter().find(|(d, _)| *d == date).map(|(_, name)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bc_holidays() -> Result<()> {
+        assert_eq!(easter_sunday(2023), date!(2023 - 04 - 09));
+        assert_eq!(easter_sunday(2024), date!(2024 - 03 - 31));
+        let mondays: Vec<_> =
+            bc_holidays(2023).into_iter().map(|(d, _)| d).filter(|d| d.weekday() == Weekday::Monday).collect();
+        assert_eq!(
+            mondays,
+            [
+                date!(2023 - 01 - 02),
+                date!(2023 - 02 - 20),
+                date!(2023 - 04 - 10),
+                date!(2023 - 05 - 22),
+                date!(2023 - 07 - 03),
+                date!(2023 - 08 - 07),
+                date!(2023 - 09 - 04),
+                date!(2023 - 10 - 02),
+                date!(2023 - 10 - 09),
+                date!(2023 - 11 - 13),
+                date!(2023 - 12 - 25),
+            ]
+        );
+        assert_eq!(bc_holiday_name(date!(2023 - 07 - 03)), Some("Canada Day"));
+        assert_eq!(bc_holiday_name(date!(2023 - 07 - 04)), None);
+        Ok(())
+    }
+}
@@ -1,4 +1,8 @@
 pub mod constants;
-pub mod imports;
+pub mod content_reviews;
+pub mod holidays;
+pub mod prelude;
+pub mod sun;
+pub mod thrufares;
 pub mod types;
 pub mod utils;
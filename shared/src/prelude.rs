@@ -1,10 +1,14 @@
-pub use anyhow::{anyhow, bail, ensure, Context, Result};
+// Everything the scraper and front-end need from the shared crate and its dependencies, which each crate's own prelude
+// extends with its own
+pub use anyhow::{anyhow, bail, ensure, Context, Error, Result};
 pub use const_format::concatcp;
 pub use itertools::{Combinations, Itertools};
 pub use log;
 pub use log::{debug, error, info, warn};
 pub use once_cell::sync::{Lazy, OnceCell};
+#[cfg(feature = "serde")]
 pub use serde::de::DeserializeOwned;
+#[cfg(feature = "serde")]
 pub use serde::{Deserialize, Serialize};
 pub use std::borrow::Cow;
 pub use std::cmp::{max, min};
@@ -17,7 +21,15 @@ pub use std::rc::Rc;
 pub use std::str::FromStr;
 pub use strum::IntoEnumIterator;
 pub use strum_macros::{Display, EnumIter, EnumString};
-pub use time::macros::{date, format_description};
+pub use time::macros::{date, format_description, time};
 pub use time::parsing::Parsable;
 pub use time::{Date, Duration, Month, OffsetDateTime, Time, Weekday};
 pub use time_tz::{timezones, OffsetDateTimeExt};
+
+pub use crate::constants::*;
+pub use crate::content_reviews::*;
+pub use crate::holidays::*;
+pub use crate::sun::*;
+pub use crate::thrufares::*;
+pub use crate::types::*;
+pub use crate::utils::*;
@@ -0,0 +1,80 @@
+use crate::prelude::*;
+
+// Zenith angles (in degrees) of the sun at sunset (accounting for refraction and the sun's radius) and at the end of
+// nautical twilight, after which it is too dark to see the horizon
+const SUNSET_ZENITH: f64 = 90.833;
+const NAUTICAL_DUSK_ZENITH: f64 = 102.0;
+
+fn normalize(value: f64, max: f64) -> f64 {
+    value.rem_euclid(max)
+}
+
+// Time (in hours past midnight UTC) when the sun sets past `zenith`, using the sunrise/sunset algorithm from the
+// Almanac for Computers (accurate to within a couple of minutes at mid-latitudes)
+fn sun_set_utc_hours(date: Date, coordinates: Coordinates, zenith: f64) -> Option<f64> {
+    let longitude_hours = coordinates.longitude / 15.0;
+    let approx_time = f64::from(date.ordinal()) + (18.0 - longitude_hours) / 24.0;
+    let mean_anomaly = 0.9856 * approx_time - 3.289;
+    let true_longitude = normalize(
+        mean_anomaly
+            + 1.916 * mean_anomaly.to_radians().sin()
+            + 0.020 * (2.0 * mean_anomaly).to_radians().sin()
+            + 282.634,
+        360.0,
+    );
+    let mut right_ascension = normalize((0.91764 * true_longitude.to_radians().tan()).atan().to_degrees(), 360.0);
+    // Right ascension must be in the same quadrant as the true longitude
+    right_ascension += (true_longitude / 90.0).floor() * 90.0 - (right_ascension / 90.0).floor() * 90.0;
+    let sin_declination = 0.39782 * true_longitude.to_radians().sin();
+    let cos_declination = sin_declination.asin().cos();
+    let latitude = coordinates.latitude.to_radians();
+    let cos_hour_angle =
+        (zenith.to_radians().cos() - sin_declination * latitude.sin()) / (cos_declination * latitude.cos());
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        // The sun never sets past the zenith on this date at this latitude
+        return None;
+    }
+    let hour_angle = cos_hour_angle.acos().to_degrees() / 15.0;
+    let local_mean_time = hour_angle + right_ascension / 15.0 - 0.06571 * approx_time - 6.622;
+    Some(normalize(local_mean_time - longitude_hours, 24.0))
+}
+
+fn sun_set_vancouver(date: Date, coordinates: Coordinates, zenith: f64) -> Option<Time> {
+    let utc_hours = sun_set_utc_hours(date, coordinates, zenith)?;
+    let utc = date.midnight().assume_utc() + Duration::seconds((utc_hours * 3600.0).round() as i64);
+    let local = utc.to_timezone(timezones::db::america::VANCOUVER);
+    // Evening in Vancouver is usually after midnight UTC, which the algorithm wraps to the start of the same UTC date
+    let local = match local.date().cmp(&date) {
+        std::cmp::Ordering::Less => local + Duration::DAY,
+        std::cmp::Ordering::Equal => local,
+        std::cmp::Ordering::Greater => local - Duration::DAY,
+    };
+    Some(local.time())
+}
+
+pub fn sunset_vancouver(date: Date, coordinates: Coordinates) -> Option<Time> {
+    sun_set_vancouver(date, coordinates, SUNSET_ZENITH)
+}
+
+pub fn nautical_dusk_vancouver(date: Date, coordinates: Coordinates) -> Option<Time> {
+    sun_set_vancouver(date, coordinates, NAUTICAL_DUSK_ZENITH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: Option<Time>, expected: Time) {
+        let actual = actual.expect("Expect sun to set");
+        assert!((actual - expected).abs() <= Duration::minutes(3), "{} is not near {}", actual, expected);
+    }
+
+    #[test]
+    fn test_sunset_vancouver() -> Result<()> {
+        let coordinates = Terminal::SWB.coordinates();
+        assert_near(sunset_vancouver(date!(2021 - 06 - 21), coordinates), time!(21:18));
+        assert_near(sunset_vancouver(date!(2021 - 12 - 21), coordinates), time!(16:18));
+        assert_near(nautical_dusk_vancouver(date!(2021 - 06 - 21), coordinates), time!(23:04));
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use crate::prelude::*;
+
+// A connection pattern that BC Ferries sells as a thru-fare: travelling between any of the `from` terminals and any of
+// the `to` terminals (in either direction) with a connection at the `via` terminal
+#[derive(Debug)]
+pub struct ThrufareRule {
+    pub via: Terminal,
+    pub from: &'static [Terminal],
+    pub to: &'static [Terminal],
+}
+
+const SOUTHERN_GULF_ISLAND_TERMINALS: &[Terminal] =
+    &[Terminal::PLH, Terminal::POB, Terminal::PSB, Terminal::PST, Terminal::PVB];
+
+pub const THRUFARE_RULES: &[ThrufareRule] = &[
+    // Vancouver to the Southern Gulf Islands, connecting at Swartz Bay (routes 1 and 5)
+    ThrufareRule { via: Terminal::SWB, from: &[Terminal::TSA], to: SOUTHERN_GULF_ISLAND_TERMINALS },
+    // Between Southern Gulf Islands, connecting at Swartz Bay (route 5)
+    ThrufareRule { via: Terminal::SWB, from: SOUTHERN_GULF_ISLAND_TERMINALS, to: SOUTHERN_GULF_ISLAND_TERMINALS },
+    // Victoria to Saturna, connecting at Mayne Island (route 5)
+    ThrufareRule { via: Terminal::PVB, from: &[Terminal::SWB], to: &[Terminal::PST] },
+];
+
+impl ThrufareRule {
+    fn matches_one_way(&self, from: Terminal, via: Terminal, to: Terminal) -> bool {
+        via == self.via && self.from.contains(&from) && self.to.contains(&to)
+    }
+
+    pub fn matches(&self, terminal_pair: TerminalPair, via: Terminal) -> bool {
+        terminal_pair.from != terminal_pair.to
+            && (self.matches_one_way(terminal_pair.from, via, terminal_pair.to)
+                || self.matches_one_way(terminal_pair.to, via, terminal_pair.from))
+    }
+}
+
+// Whether travel between the terminal pair with a thru-fare connection at `via` is genuinely eligible for a thru-fare,
+// which requires both legs to be served and a rule to allow it
+pub fn is_thrufare_eligible(terminal_pair: TerminalPair, via: Terminal) -> bool {
+    ALL_TERMINAL_PAIRS.contains(&TerminalPair { from: terminal_pair.from, to: via })
+        && ALL_TERMINAL_PAIRS.contains(&TerminalPair { from: via, to: terminal_pair.to })
+        && THRUFARE_RULES.iter().any(|rule| rule.matches(terminal_pair, via))
+}
+
+// Shortest time between arriving on the first leg of a thru-fare and departing on the second that could be relied on
+pub const MIN_THRUFARE_CONNECTION: Duration = Duration::minutes(10);
+
+// Whether a thru-fare sailing's connection cannot be made.  The first leg is the sailing to the connecting terminal that
+// departs when the thru-fare sailing does, and the second leg is the sailing from it that arrives when the thru-fare
+// sailing does; if either leg is missing there is nothing to check.
+pub fn is_impossible_connection<'a>(
+    sailing: &Sailing,
+    first_leg_sailings: impl IntoIterator<Item = &'a Sailing>,
+    second_leg_sailings: impl IntoIterator<Item = &'a Sailing>,
+) -> bool {
+    let first_leg = first_leg_sailings.into_iter().find(|s| s.depart_time == sailing.depart_time);
+    let second_leg = second_leg_sailings.into_iter().find(|s| s.arrive_time == sailing.arrive_time);
+    match (first_leg, second_leg) {
+        (Some(first_leg), Some(second_leg)) => second_leg.depart_time - first_leg.arrive_time < MIN_THRUFARE_CONNECTION,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_thrufare_eligible() -> Result<()> {
+        assert!(is_thrufare_eligible(TerminalPair { from: Terminal::TSA, to: Terminal::POB }, Terminal::SWB));
+        assert!(is_thrufare_eligible(TerminalPair { from: Terminal::POB, to: Terminal::TSA }, Terminal::SWB));
+        assert!(is_thrufare_eligible(TerminalPair { from: Terminal::PSB, to: Terminal::PLH }, Terminal::SWB));
+        assert!(is_thrufare_eligible(TerminalPair { from: Terminal::PST, to: Terminal::SWB }, Terminal::PVB));
+        assert!(!is_thrufare_eligible(TerminalPair { from: Terminal::TSA, to: Terminal::POB }, Terminal::PVB));
+        assert!(!is_thrufare_eligible(TerminalPair { from: Terminal::TSA, to: Terminal::TSA }, Terminal::SWB));
+        assert!(!is_thrufare_eligible(TerminalPair { from: Terminal::CHM, to: Terminal::PEN }, Terminal::THT));
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_impossible_connection() -> Result<()> {
+        let sailing = |depart_time, arrive_time| Sailing { depart_time, arrive_time, stops: vec![] };
+        let thrufare = sailing(time!(7:00), time!(10:15));
+        let first_legs = [sailing(time!(7:00), time!(8:35))];
+        assert!(!is_impossible_connection(&thrufare, &first_legs, &[sailing(time!(9:00), time!(10:15))]));
+        assert!(is_impossible_connection(&thrufare, &first_legs, &[sailing(time!(8:40), time!(10:15))]));
+        // Legs that cannot be found are not checked
+        assert!(!is_impossible_connection(&thrufare, &first_legs, &[sailing(time!(8:00), time!(9:15))]));
+        Ok(())
+    }
+}
@@ -1,38 +1,48 @@
-use crate::constants::*;
-use crate::imports::*;
+use crate::prelude::*;
 
 pub type TimeFormat = [time::format_description::FormatItem<'static>];
 
-#[derive(
-    Copy, Clone, Debug, Deserialize, Display, EnumString, Eq, EnumIter, Hash, Ord, PartialEq, PartialOrd, Serialize,
-)]
+#[derive(Copy, Clone, Debug, Display, EnumString, Eq, EnumIter, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Area {
     Brentwood,
     Chemainus,
     Crofton,
     // Gulf Islands terminals have aliases for backward compatibility when parsing URL query
-    #[serde(alias = "PSB")]
+    #[cfg_attr(feature = "serde", serde(alias = "PSB"))]
     Galiano,
-    #[serde(alias = "PVB")]
+    #[cfg_attr(feature = "serde", serde(alias = "PVB"))]
     Mayne,
     MillBay,
-    #[serde(alias = "POB")]
+    #[cfg_attr(feature = "serde", serde(alias = "POB"))]
     Pender,
     Penelakut,
-    #[serde(alias = "PLH")]
+    #[cfg_attr(feature = "serde", serde(alias = "PLH"))]
     SaltSpring,
-    #[serde(alias = "PST")]
+    #[cfg_attr(feature = "serde", serde(alias = "PST"))]
     Saturna,
     Thetis,
-    #[serde(alias = "TSA")]
+    #[cfg_attr(feature = "serde", serde(alias = "TSA"))]
     Vancouver,
-    #[serde(alias = "SWB")]
+    #[cfg_attr(feature = "serde", serde(alias = "SWB"))]
     Victoria,
+    // Shared views store areas by their position, so these are after the original areas (see `ViewState`)
+    HaidaGwaii,
+    PortHardy,
+    PrinceRupert,
 }
 
-#[derive(
-    Copy, Clone, Debug, Deserialize, Display, EnumString, Eq, EnumIter, Hash, Ord, PartialEq, PartialOrd, Serialize,
-)]
+// Regions that group areas for selection, in the order they are listed
+#[derive(Copy, Clone, Debug, Eq, EnumIter, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Region {
+    Mainland,
+    VancouverIsland,
+    GulfIslands,
+    NorthCoast,
+}
+
+#[derive(Copy, Clone, Debug, Display, EnumString, Eq, EnumIter, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Terminal {
     BTW, // Brentwood Bay
     CFT, // Crofton
@@ -42,7 +52,10 @@ pub enum Terminal {
     PEN, // Penelakut Island (Telegraph Harbour)
     PLH, // Salt Spring Island (Long Harbour)
     POB, // Pender Island (Otter Bay)
+    PPH, // Port Hardy (Bear Cove)
+    PPR, // Prince Rupert
     PSB, // Galiano Island (Sturdies Bay)
+    PSK, // Haida Gwaii (Skidegate)
     PST, // Saturna Island (Lyall Harbour)
     PVB, // Mayne Island (Village Bay)
     SWB, // Victoria (Swartz Bay)
@@ -51,32 +64,83 @@ pub enum Terminal {
     VES, // Salt Spring Island (Vesuvius Bay)
 }
 
-#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct AreaPair {
     pub from: Area,
     pub to: Area,
 }
 
-#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct TerminalPair {
     pub from: Terminal,
     pub to: Terminal,
 }
 
-#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+// Why a pair of terminals or areas is not a route
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InvalidRoute {
+    SameEndpoints,
+    NoService,
+}
+
+// Conditions that a sailing from a small terminal depends on, as given in schedule footnotes
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SailingCondition {
+    DaylightLoading,
+    Tide,
+}
+
+// Size of vehicle that a deck restriction in schedule footnotes limits
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum VehicleDimension {
+    Height,
+    Length,
+}
+
+// Vehicles larger than `max_feet` in the dimension cannot take the sailing, such as when the vessel has no room for
+// tall vehicles
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct VehicleRestriction {
+    pub dimension: VehicleDimension,
+    pub max_feet: u32,
+}
+
+// Classes of vehicle by the fare they pay, which decide whether a deck restriction applies to them
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum VehicleClass {
+    // Up to `STANDARD_VEHICLE_MAX_HEIGHT_FEET` tall and `STANDARD_VEHICLE_MAX_LENGTH_FEET` long
+    Standard,
+    // Campers, trailers, and anything else larger than a standard vehicle
+    Oversize,
+}
+
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum StopType {
     Stop,
     Transfer,
     Thrufare,
 }
 
-#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Stop {
     pub type_: StopType,
     pub terminal: Terminal,
 }
 
-#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Sailing {
     pub depart_time: Time,
     pub arrive_time: Time,
@@ -88,51 +152,309 @@ struct DateDaysIterator {
     date: Option<Date>,
 }
 
-#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct DateRange {
     pub from: Date,
     pub to: Date,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum DateRestriction {
     All,
     Only(HashSet<Date>),
     Except(HashSet<Date>),
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+// A stop the sailing only makes on some dates, such as when a footnote reroutes it "via Otter Bay on Jul 1, 8"
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ConditionalStop {
+    pub stop: Stop,
+    pub dates: DateRestriction,
+}
+
+// What kind of note a sailing note is, so that it can be shown with an icon and severity without matching its text.
+// The scraper sets it on the notes it recognizes, and the frontend on the notes it adds itself.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum NoteCategory {
+    FootPassengersOnly,
+    // A passenger-only vessel replaces the car ferry on the note's dates
+    PassengerOnlyVessel,
+    // Vehicles over a height or length cannot take the sailing
+    VehicleRestriction,
+    VehiclePriority,
+    NearMidnight,
+    // The sailing may not run if conditions at a small terminal are unsuitable
+    DaylightLoading,
+    Tide,
+    // Sailings listed with the previous day's service
+    NextCalendarDay,
+    // Thru-fare sailings whose connection cannot be made according to the schedules
+    ImpossibleConnection,
+    // Notes without a category, such as annotations the scraper passed through as-is, which are shown as plain text
+    Other,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ScheduleItem {
     pub sailing: Sailing,
     pub weekdays: HashMap<Weekday, DateRestriction>,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
-    #[serde(default)]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "HashMap::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub notes: HashMap<Cow<'static, str>, DateRestriction>,
+    // Categories of the notes that have one; notes not listed are `NoteCategory::Other`
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "HashMap::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub note_categories: HashMap<Cow<'static, str>, NoteCategory>,
+    // Size limits behind the notes that are `NoteCategory::VehicleRestriction`
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "HashMap::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub vehicle_restrictions: HashMap<Cow<'static, str>, VehicleRestriction>,
+    // Stops in addition to the sailing's own on some of its dates
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub conditional_stops: Vec<ConditionalStop>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum AlertLevel {
     Info,
     Warning,
     Danger,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+// Kind of disruption described by a service notice, most severe first
+#[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum AlertSeverity {
+    Cancellation,
+    Delay,
+    Advisory,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Alert {
     pub message: String,
     pub level: AlertLevel,
+    // Only set for alerts from service notices
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub severity: Option<AlertSeverity>,
+    // Dates the alert is relevant to, each mentioned date or range separately; if empty, it applies to the whole schedule
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub relevant_date_ranges: Vec<DateRange>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, Default, EnumIter, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Operator {
+    #[default]
+    BcFerries,
+    GulfIslandsWaterTaxi,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Schedule {
     pub terminal_pair: TerminalPair,
     pub date_range: DateRange,
     pub items: Vec<ScheduleItem>,
     pub source_url: String,
     pub refreshed_at: OffsetDateTime,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    #[serde(default)]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub alerts: Vec<Alert>,
+    // Time of day on `date_range.from` that the schedule takes effect, if it replaces another schedule mid-day
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub effective_from_time: Option<Time>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Operator::is_bc_ferries"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub operator: Operator,
+    // Informational text from the header of the schedule page, such as the seasonal service window
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub header_notes: Vec<String>,
+    // Items of the previously published schedule for the same period, if it was re-issued with changes
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub previous_items: Vec<ScheduleItem>,
+    // Dates when the schedule says there is no service on the route at all, such as a seasonal suspension
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub suspensions: Vec<DateRange>,
+    // Dates of last year's schedule that this one was extrapolated from, if it is a provisional schedule for a period
+    // that BC Ferries has not published yet
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub provisional_basis: Option<DateRange>,
+}
+
+// Where the published schedules came from and the terms they are published under, shown on the "About this data" page
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct DataMetadata {
+    pub source_name: String,
+    pub source_url: String,
+    pub attribution: String,
+    pub disclaimer: String,
+    pub scraped_at: OffsetDateTime,
+    // Version of the scraper that generated the data
+    pub generator_version: String,
+}
+
+// Contents of the published schedules JSON file.  Deserialized by its shape (an object or a list) rather than by trying
+// each variant, so that an error in the data is reported as-is instead of as no variant matching.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum PublishedData {
+    WithMetadata { metadata: DataMetadata, schedules: Vec<Schedule> },
+    // Files published before the metadata was embedded, which may still be cached or downloaded as previous data
+    SchedulesOnly(Vec<Schedule>),
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct PublishedDataWithMetadata {
+    metadata: DataMetadata,
+    schedules: Vec<Schedule>,
+}
+
+#[cfg(feature = "serde")]
+struct PublishedDataVisitor;
+
+// Which copy of the published data to use.  Preview data comes from scraper changes that are not released yet, so they
+// can be checked against real schedules before the stable data is replaced.
+#[derive(Copy, Clone, Debug, Default, Display, EnumIter, EnumString, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[strum(serialize_all = "lowercase")]
+pub enum DataChannel {
+    #[default]
+    Stable,
+    Preview,
+}
+
+// Published after the data files it lists, so that it never refers to a data file that is still being written
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct DataManifest {
+    pub channel: DataChannel,
+    // Relative to the manifest's location
+    pub schedules_file: String,
+    pub schedules_bytes: usize,
+    pub published_at: OffsetDateTime,
+    // Further schedules files, loaded after the main one so that a failure to load one only affects its dates
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub partitions: Vec<DataPartition>,
+    // Features being rolled out gradually, which the app enables without needing a separate build
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub feature_flags: Vec<FeatureFlag>,
+    // Pre-compressed copies of the schedules file that are published beside it, named by `ContentEncoding::file_name`
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub schedules_encodings: Vec<ContentEncoding>,
+    // Each route's schedules in a file of its own, besides being in the schedules file, so that the app can start with
+    // just the route being viewed.  This also makes the manifest the index of routes.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub route_files: Vec<DataRouteFile>,
+}
+
+// Compression of a pre-compressed data file, which is served with a matching `Content-Encoding` header so that
+// browsers decompress it as it downloads
+#[derive(Copy, Clone, Debug, Display, EnumIter, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ContentEncoding {
+    #[cfg_attr(feature = "serde", serde(rename = "br"))]
+    #[strum(serialize = "br")]
+    Brotli,
+    #[cfg_attr(feature = "serde", serde(rename = "gzip"))]
+    #[strum(serialize = "gzip")]
+    Gzip,
+}
+
+// Each client is in a feature's rollout or not depending on its own ID, so raising the percentage only adds clients and
+// nobody sees a feature come and go between visits
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct FeatureFlag {
+    pub name: String,
+    // Share of clients the feature is enabled for, from 0 to 100
+    pub rollout_percent: u8,
+    // If not empty, the feature is only enabled on these routes, in either direction
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub routes: Vec<AreaPair>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct DataPartition {
+    // Relative to the manifest's location, like `schedules_file`
+    pub file: String,
+    pub bytes: usize,
+    // Dates covered by the partition's schedules
+    pub date_range: DateRange,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct DataRouteFile {
+    pub terminal_pair: TerminalPair,
+    // Relative to the manifest's location, like `schedules_file`
+    pub file: String,
+    pub bytes: usize,
+}
+
+impl ContentEncoding {
+    // The compressed copy of a file, like `schedules.json.br`
+    pub fn file_name(&self, file_name: &str) -> String {
+        let extension = match self {
+            ContentEncoding::Brotli => "br",
+            ContentEncoding::Gzip => "gz",
+        };
+        format!("{}.{}", file_name, extension)
+    }
+}
+
+impl DataChannel {
+    // Location of the channel's files relative to the stable channel's files
+    pub fn subdirectory(&self) -> Option<&'static str> {
+        match *self {
+            DataChannel::Stable => None,
+            DataChannel::Preview => Some("preview"),
+        }
+    }
+
+    // Moves a stable channel path, like `data/schedules.json`, to the channel's subdirectory
+    pub fn path(&self, stable_path: &str) -> String {
+        match (self.subdirectory(), stable_path.rsplit_once('/')) {
+            (None, _) => stable_path.to_string(),
+            (Some(subdirectory), Some((dir, file_name))) => format!("{}/{}/{}", dir, subdirectory, file_name),
+            (Some(subdirectory), None) => format!("{}/{}", subdirectory, stable_path),
+        }
+    }
+}
+
+impl Region {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Region::Mainland => "Mainland",
+            Region::VancouverIsland => "Vancouver Island",
+            Region::GulfIslands => "Gulf Islands",
+            Region::NorthCoast => "North Coast",
+        }
+    }
 }
 
 impl Area {
@@ -142,10 +464,13 @@ impl Area {
             Area::Chemainus => "Chemainus",
             Area::Crofton => "Crofton",
             Area::Galiano => "Galiano Island",
+            Area::HaidaGwaii => "Haida Gwaii",
             Area::Mayne => "Mayne Island",
             Area::MillBay => "Mill Bay",
             Area::Pender => "Pender Island",
             Area::Penelakut => "Penelakut Island",
+            Area::PortHardy => "Port Hardy",
+            Area::PrinceRupert => "Prince Rupert",
             Area::SaltSpring => "Salt Spring Island",
             Area::Saturna => "Saturna Island",
             Area::Thetis => "Thetis Island",
@@ -154,16 +479,40 @@ impl Area {
         }
     }
 
+    pub fn region(&self) -> Region {
+        match *self {
+            Area::Brentwood => Region::VancouverIsland,
+            Area::Chemainus => Region::VancouverIsland,
+            Area::Crofton => Region::VancouverIsland,
+            Area::Galiano => Region::GulfIslands,
+            Area::HaidaGwaii => Region::NorthCoast,
+            Area::Mayne => Region::GulfIslands,
+            Area::MillBay => Region::VancouverIsland,
+            Area::Pender => Region::GulfIslands,
+            Area::Penelakut => Region::GulfIslands,
+            Area::PortHardy => Region::NorthCoast,
+            Area::PrinceRupert => Region::NorthCoast,
+            Area::SaltSpring => Region::GulfIslands,
+            Area::Saturna => Region::GulfIslands,
+            Area::Thetis => Region::GulfIslands,
+            Area::Vancouver => Region::Mainland,
+            Area::Victoria => Region::VancouverIsland,
+        }
+    }
+
     pub fn short_name(&self) -> &'static str {
         match *self {
             Area::Brentwood => "Brentwood",
             Area::Chemainus => "Chemainus",
             Area::Crofton => "Crofton",
             Area::Galiano => "Galiano",
+            Area::HaidaGwaii => "Haida Gwaii",
             Area::Mayne => "Mayne",
             Area::MillBay => "Mill Bay",
             Area::Pender => "Pender",
             Area::Penelakut => "Penelakut",
+            Area::PortHardy => "Port Hardy",
+            Area::PrinceRupert => "Prince Rupert",
             Area::SaltSpring => "Salt Spring",
             Area::Saturna => "Saturna",
             Area::Thetis => "Thetis",
@@ -192,7 +541,10 @@ impl Terminal {
             Terminal::PEN => "Telegraph Harbour",
             Terminal::PLH => "Long Harbour",
             Terminal::POB => "Otter Bay",
+            Terminal::PPH => "Bear Cove",
+            Terminal::PPR => "Prince Rupert",
             Terminal::PSB => "Sturdies Bay",
+            Terminal::PSK => "Skidegate",
             Terminal::PST => "Lyall Harbour",
             Terminal::PVB => "Village Bay",
             Terminal::SWB => "Swartz Bay",
@@ -202,6 +554,36 @@ impl Terminal {
         }
     }
 
+    pub fn coordinates(&self) -> Coordinates {
+        let (latitude, longitude) = match *self {
+            Terminal::BTW => (48.5745, -123.4640),
+            Terminal::CFT => (48.8653, -123.6383),
+            Terminal::CHM => (48.9236, -123.7131),
+            Terminal::FUL => (48.7686, -123.4508),
+            Terminal::MIL => (48.6406, -123.5525),
+            Terminal::PEN => (48.9706, -123.6589),
+            Terminal::PLH => (48.8508, -123.4486),
+            Terminal::POB => (48.7997, -123.3158),
+            Terminal::PPH => (50.7236, -127.4961),
+            Terminal::PPR => (54.3006, -130.3261),
+            Terminal::PSB => (48.8775, -123.3153),
+            Terminal::PSK => (53.2417, -132.0125),
+            Terminal::PST => (48.7953, -123.2025),
+            Terminal::PVB => (48.8444, -123.3242),
+            Terminal::SWB => (48.6889, -123.4108),
+            Terminal::THT => (48.9775, -123.6703),
+            Terminal::TSA => (49.0072, -123.1303),
+            Terminal::VES => (48.8831, -123.5722),
+        };
+        Coordinates { latitude, longitude }
+    }
+
+    // Whether the terminal is staffed and lit for sailings after dark, rather than being a small terminal that mostly
+    // operates in daylight
+    pub fn has_night_operations(&self) -> bool {
+        matches!(*self, Terminal::SWB | Terminal::TSA | Terminal::PPH | Terminal::PPR | Terminal::PSK)
+    }
+
     pub fn area(&self) -> Area {
         match *self {
             Terminal::BTW => Area::Brentwood,
@@ -212,7 +594,10 @@ impl Terminal {
             Terminal::PEN => Area::Penelakut,
             Terminal::PLH => Area::SaltSpring,
             Terminal::POB => Area::Pender,
+            Terminal::PPH => Area::PortHardy,
+            Terminal::PPR => Area::PrinceRupert,
             Terminal::PSB => Area::Galiano,
+            Terminal::PSK => Area::HaidaGwaii,
             Terminal::PST => Area::Saturna,
             Terminal::PVB => Area::Mayne,
             Terminal::SWB => Area::Victoria,
@@ -230,11 +615,73 @@ impl Terminal {
     }
 }
 
+impl Coordinates {
+    // Approximate, but accurate enough to compare distances between nearby terminals
+    pub fn distance_km(&self, other: Coordinates) -> f64 {
+        const EARTH_RADIUS_KM: f64 = 6371.0;
+        let mean_latitude = ((self.latitude + other.latitude) / 2.0).to_radians();
+        let x = (other.longitude - self.longitude).to_radians() * mean_latitude.cos();
+        let y = (other.latitude - self.latitude).to_radians();
+        (x * x + y * y).sqrt() * EARTH_RADIUS_KM
+    }
+}
+
+impl Display for InvalidRoute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            InvalidRoute::SameEndpoints => "the departure and arrival are the same",
+            InvalidRoute::NoService => "there is no ferry service between them",
+        })
+    }
+}
+
+impl std::error::Error for InvalidRoute {}
+
 impl AreaPair {
     pub fn swapped(&self) -> AreaPair {
         AreaPair { from: self.to, to: self.from }
     }
 
+    // An area pair is a route if any pair of their terminals is
+    pub fn validate(&self) -> std::result::Result<(), InvalidRoute> {
+        if self.from == self.to {
+            return Err(InvalidRoute::SameEndpoints);
+        }
+        let from_terminals = AREA_TERMINALS.get(&self.from).into_iter().flatten();
+        let mut terminal_pairs = from_terminals.cartesian_product(AREA_TERMINALS.get(&self.to).into_iter().flatten());
+        if terminal_pairs.any(|(&from, &to)| TerminalPair { from, to }.validate().is_ok()) {
+            Ok(())
+        } else {
+            Err(InvalidRoute::NoService)
+        }
+    }
+
+    // Routes that keep one end of this pair, with the other end as close as possible to what was asked for
+    pub fn nearest_valid_alternatives(&self, count: usize) -> Vec<AreaPair> {
+        let area_distance = |a: Area, b: Area| {
+            let terminals = |area| AREA_TERMINALS.get(&area).into_iter().flatten();
+            terminals(a)
+                .cartesian_product(terminals(b))
+                .map(|(ta, tb)| ta.coordinates().distance_km(tb.coordinates()))
+                .fold(f64::INFINITY, f64::min)
+        };
+        ALL_AREA_PAIRS
+            .iter()
+            .filter(|ap| ap != &self && (ap.from == self.from || ap.to == self.to))
+            .map(|&ap| {
+                let distance = if ap.from == self.from {
+                    area_distance(ap.to, self.to)
+                } else {
+                    area_distance(ap.from, self.from)
+                };
+                (ap, distance)
+            })
+            .sorted_by(|(ap1, d1), (ap2, d2)| d1.total_cmp(d2).then_with(|| ap1.cmp(ap2)))
+            .map(|(ap, _)| ap)
+            .take(count)
+            .collect()
+    }
+
     pub fn includes_terminal(&self, terminal: Terminal) -> bool {
         self.from.includes_terminal(terminal) || self.to.includes_terminal(terminal)
     }
@@ -263,6 +710,16 @@ impl TerminalPair {
         inner().with_context(|| format!("Failed to parse terminal code pair: {:?}", code_pair))
     }
 
+    pub fn validate(&self) -> std::result::Result<(), InvalidRoute> {
+        if self.from == self.to {
+            Err(InvalidRoute::SameEndpoints)
+        } else if !ALL_TERMINAL_PAIRS.contains(self) {
+            Err(InvalidRoute::NoService)
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn to_schedule_code_pair(self) -> String {
         format!("{}-{}", self.from, self.to)
     }
@@ -298,8 +755,74 @@ impl FromStr for TerminalPair {
 }
 
 impl Sailing {
-    pub fn is_thrufare(&self) -> bool {
-        self.stops.iter().any(|s| s.type_ == StopType::Thrufare)
+    // Only thru-fare stops that the rules allow for this terminal pair count, since the source data is not always right
+    pub fn is_thrufare(&self, terminal_pair: TerminalPair) -> bool {
+        self.thrufare_via(terminal_pair).is_some()
+    }
+
+    // Terminal where a thru-fare sailing connects to another vessel
+    pub fn thrufare_via(&self, terminal_pair: TerminalPair) -> Option<Terminal> {
+        self.stops
+            .iter()
+            .find(|s| s.type_ == StopType::Thrufare && is_thrufare_eligible(terminal_pair, s.terminal))
+            .map(|s| s.terminal)
+    }
+
+    // The day of service that the sailing belongs to when it departs on the date
+    pub fn service_date(&self, depart_date: Date) -> Date {
+        if self.depart_time < SERVICE_DAY_START {
+            depart_date.previous_day().unwrap_or(depart_date)
+        } else {
+            depart_date
+        }
+    }
+}
+
+impl SailingCondition {
+    pub fn note(&self) -> &'static str {
+        match self {
+            SailingCondition::DaylightLoading => DAYLIGHT_LOADING_NOTE,
+            SailingCondition::Tide => TIDE_RESTRICTION_NOTE,
+        }
+    }
+
+    pub fn note_category(&self) -> NoteCategory {
+        match self {
+            SailingCondition::DaylightLoading => NoteCategory::DaylightLoading,
+            SailingCondition::Tide => NoteCategory::Tide,
+        }
+    }
+}
+
+impl VehicleDimension {
+    fn adjective(&self) -> &'static str {
+        match self {
+            VehicleDimension::Height => "tall",
+            VehicleDimension::Length => "long",
+        }
+    }
+}
+
+impl VehicleRestriction {
+    // Note written by the scraper for the restriction
+    pub fn note(&self) -> String {
+        format!(
+            "{}{} ft {}{}",
+            VEHICLE_RESTRICTION_NOTE_PREFIX,
+            self.max_feet,
+            self.dimension.adjective(),
+            VEHICLE_RESTRICTION_NOTE_SUFFIX
+        )
+    }
+
+    // Whether vehicles of the class may be too large for the sailing.  Oversize vehicles have no upper limit, so every
+    // restriction applies to them.
+    pub fn applies_to(&self, vehicle_class: VehicleClass) -> bool {
+        match (vehicle_class, self.dimension) {
+            (VehicleClass::Standard, VehicleDimension::Height) => self.max_feet < STANDARD_VEHICLE_MAX_HEIGHT_FEET,
+            (VehicleClass::Standard, VehicleDimension::Length) => self.max_feet < STANDARD_VEHICLE_MAX_LENGTH_FEET,
+            (VehicleClass::Oversize, _) => true,
+        }
     }
 }
 
@@ -341,20 +864,20 @@ impl DateRange {
                     format_description!("[month repr:short case_sensitive:false] [day padding:none] [year]"),
                 )
             })?;
-            match Date::from_calendar_date(self.from.year(), parsed_date.month(), parsed_date.day()) {
-                Ok(from_year_date) if self.includes_date_inclusive(from_year_date) => Ok(Some(from_year_date)),
-                _ if self.from.year() == self.to.year() => Ok(None) as Result<_>,
-                _ => match Date::from_calendar_date(self.to.year(), parsed_date.month(), parsed_date.day()) {
-                    Ok(to_year_date) if self.includes_date_inclusive(to_year_date) => {
-                        Ok(Some(to_year_date)) as Result<_>
-                    }
-                    _ => Ok(None) as Result<_>,
-                },
-            }
+            Ok(self.date_within(parsed_date.month(), parsed_date.day())) as Result<_>
         };
         inner().with_context(|| format!("Failed to parse date within range {}: {:?}", self, text))
     }
 
+    // The date with the month and day in either year of the range, if it is within the range
+    pub fn date_within(&self, month: Month, day: u8) -> Option<Date> {
+        [self.from.year(), self.to.year()]
+            .into_iter()
+            .dedup()
+            .filter_map(|year| Date::from_calendar_date(year, month, day).ok())
+            .find(|date| self.includes_date_inclusive(*date))
+    }
+
     pub fn parse(text: &str, date_format: &TimeFormat, separator: &str) -> Result<DateRange> {
         let inner = || {
             let parts: Vec<_> = text.split(separator).collect();
@@ -423,6 +946,24 @@ impl DateRestriction {
 }
 
 impl ScheduleItem {
+    pub fn is_on_date(&self, date: Date) -> bool {
+        self.weekdays.get(&date.weekday()).map(|dr| dr.includes_date(date)).unwrap_or(false)
+    }
+
+    pub fn note_category(&self, text: &str) -> NoteCategory {
+        self.note_categories.get(text).copied().unwrap_or(NoteCategory::Other)
+    }
+
+    // The sailing's stops on the date, including the conditional stops it makes that day
+    pub fn stops_on_date(&self, date: Date) -> Vec<Stop> {
+        self.sailing
+            .stops
+            .iter()
+            .copied()
+            .chain(self.conditional_stops.iter().filter(|cs| cs.dates.includes_date(date)).map(|cs| cs.stop))
+            .collect()
+    }
+
     pub fn merge_items(items: Vec<ScheduleItem>) -> Result<Vec<ScheduleItem>> {
         let mut map: HashMap<Sailing, ScheduleItem> = HashMap::new();
         for new_item in items {
@@ -431,6 +972,20 @@ impl ScheduleItem {
                     .context("Failed to merge weekdays of schedule items")?;
                 DateRestriction::merge_map(&mut existing_item.notes, new_item.notes)
                     .context("Failed to merge notes of schedule items")?;
+                existing_item.note_categories.extend(new_item.note_categories);
+                existing_item.vehicle_restrictions.extend(new_item.vehicle_restrictions);
+                let mut conditional_stops: HashMap<_, _> =
+                    existing_item.conditional_stops.drain(..).map(|cs| (cs.stop, cs.dates)).collect();
+                DateRestriction::merge_map(
+                    &mut conditional_stops,
+                    new_item.conditional_stops.into_iter().map(|cs| (cs.stop, cs.dates)).collect(),
+                )
+                .context("Failed to merge conditional stops of schedule items")?;
+                existing_item.conditional_stops = conditional_stops
+                    .into_iter()
+                    .map(|(stop, dates)| ConditionalStop { stop, dates })
+                    .sorted_by_key(|cs| cs.stop)
+                    .collect();
             } else {
                 map.insert(new_item.sailing.clone(), new_item);
             }
@@ -441,6 +996,176 @@ impl ScheduleItem {
     }
 }
 
+impl AlertSeverity {
+    pub fn level(&self) -> AlertLevel {
+        match self {
+            AlertSeverity::Cancellation => AlertLevel::Danger,
+            AlertSeverity::Delay => AlertLevel::Warning,
+            AlertSeverity::Advisory => AlertLevel::Info,
+        }
+    }
+}
+
+impl Alert {
+    pub fn is_relevant_on(&self, date: Date) -> bool {
+        self.relevant_date_ranges.is_empty()
+            || self.relevant_date_ranges.iter().any(|dates| dates.includes_date_inclusive(date))
+    }
+}
+
+impl Operator {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Operator::BcFerries => "BC Ferries",
+            Operator::GulfIslandsWaterTaxi => "Gulf Islands Water Taxi",
+        }
+    }
+
+    pub fn is_bc_ferries(&self) -> bool {
+        *self == Operator::BcFerries
+    }
+
+    // Supplemental services are not run by BC Ferries, so they are only shown when asked for
+    pub fn is_supplemental(&self) -> bool {
+        !self.is_bc_ferries()
+    }
+
+    pub fn disclaimer(&self) -> Option<&'static str> {
+        match *self {
+            Operator::BcFerries => None,
+            Operator::GulfIslandsWaterTaxi => Some(
+                "Foot passengers only.  This seasonal water taxi is not operated by BC Ferries; confirm sailings with the operator, who may require booking ahead.",
+            ),
+        }
+    }
+}
+
+impl Schedule {
+    pub fn switchover_time(&self, date: Date) -> Option<Time> {
+        self.effective_from_time.filter(|_| date == self.date_range.from)
+    }
+
+    pub fn is_effective_at(&self, date: Date, time: Time) -> bool {
+        self.date_range.includes_date_inclusive(date)
+            && self.switchover_time(date).map(|switchover_time| time >= switchover_time).unwrap_or(true)
+    }
+
+    // Route-level notes from the routes metadata followed by those scraped from the schedule page
+    pub fn route_notes(&self) -> Vec<&str> {
+        ROUTE_NOTES
+            .get(&self.terminal_pair)
+            .into_iter()
+            .flatten()
+            .copied()
+            .chain(self.header_notes.iter().map(String::as_str))
+            .collect()
+    }
+}
+
+// Other crates enable the `test-fixtures` feature in their dev-dependencies to use this
+#[cfg(any(test, feature = "test-fixtures"))]
+impl Schedule {
+    // A June 2022 BC Ferries schedule with no sailings, for tests to fill in the fields they are about
+    pub fn test_fixture(terminal_pair: TerminalPair) -> Schedule {
+        Schedule {
+            terminal_pair,
+            date_range: DateRange { from: date!(2022 - 06 - 01), to: date!(2022 - 06 - 30) },
+            items: vec![],
+            source_url: String::new(),
+            refreshed_at: OffsetDateTime::UNIX_EPOCH,
+            alerts: vec![],
+            effective_from_time: None,
+            operator: Operator::BcFerries,
+            header_notes: vec![],
+            previous_items: vec![],
+            suspensions: vec![],
+            provisional_basis: None,
+        }
+    }
+}
+
+#[cfg(any(test, feature = "test-fixtures"))]
+impl ScheduleItem {
+    // The sailing on every date of the weekdays, with no notes or conditional stops
+    pub fn test_fixture(sailing: Sailing, weekdays: &[Weekday]) -> ScheduleItem {
+        ScheduleItem {
+            sailing,
+            weekdays: weekdays.iter().map(|&weekday| (weekday, DateRestriction::All)).collect(),
+            notes: HashMap::new(),
+            note_categories: HashMap::new(),
+            vehicle_restrictions: HashMap::new(),
+            conditional_stops: vec![],
+        }
+    }
+}
+
+impl DataMetadata {
+    pub fn new(scraped_at: OffsetDateTime, generator_version: &str) -> DataMetadata {
+        DataMetadata {
+            source_name: DATA_SOURCE_NAME.to_string(),
+            source_url: ALL_SCHEDULES_URL.to_string(),
+            attribution: DATA_ATTRIBUTION.to_string(),
+            disclaimer: DATA_DISCLAIMER.to_string(),
+            scraped_at,
+            generator_version: generator_version.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for PublishedData {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<PublishedData, D::Error> {
+        deserializer.deserialize_any(PublishedDataVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for PublishedDataVisitor {
+    type Value = PublishedData;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an object with metadata and schedules, or a list of schedules")
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<PublishedData, A::Error> {
+        let data = PublishedDataWithMetadata::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+        Ok(PublishedData::WithMetadata { metadata: data.metadata, schedules: data.schedules })
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, seq: A) -> Result<PublishedData, A::Error> {
+        Vec::deserialize(serde::de::value::SeqAccessDeserializer::new(seq)).map(PublishedData::SchedulesOnly)
+    }
+}
+
+impl PublishedData {
+    pub fn metadata(&self) -> Option<&DataMetadata> {
+        match self {
+            PublishedData::WithMetadata { metadata, .. } => Some(metadata),
+            PublishedData::SchedulesOnly(_) => None,
+        }
+    }
+
+    pub fn schedules(&self) -> &[Schedule] {
+        match self {
+            PublishedData::WithMetadata { schedules, .. } | PublishedData::SchedulesOnly(schedules) => schedules,
+        }
+    }
+
+    pub fn into_schedules(self) -> Vec<Schedule> {
+        match self {
+            PublishedData::WithMetadata { schedules, .. } | PublishedData::SchedulesOnly(schedules) => schedules,
+        }
+    }
+
+    // Other schedules published the same way, as for a file that holds part of the data
+    pub fn with_schedules(&self, schedules: Vec<Schedule>) -> PublishedData {
+        match self.metadata() {
+            Some(metadata) => PublishedData::WithMetadata { metadata: metadata.clone(), schedules },
+            None => PublishedData::SchedulesOnly(schedules),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -475,6 +1200,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_route_validate() -> Result<()> {
+        assert_eq!(TerminalPair { from: Terminal::SWB, to: Terminal::TSA }.validate(), Ok(()));
+        assert_eq!(
+            TerminalPair { from: Terminal::SWB, to: Terminal::SWB }.validate(),
+            Err(InvalidRoute::SameEndpoints)
+        );
+        assert_eq!(TerminalPair { from: Terminal::BTW, to: Terminal::TSA }.validate(), Err(InvalidRoute::NoService));
+        assert_eq!(AreaPair { from: Area::Victoria, to: Area::Galiano }.validate(), Ok(()));
+        assert_eq!(
+            AreaPair { from: Area::SaltSpring, to: Area::SaltSpring }.validate(),
+            Err(InvalidRoute::SameEndpoints)
+        );
+        let area_pair = AreaPair { from: Area::Brentwood, to: Area::Vancouver };
+        assert_eq!(area_pair.validate(), Err(InvalidRoute::NoService));
+        let alternatives = area_pair.nearest_valid_alternatives(3);
+        assert_eq!(alternatives[0], AreaPair { from: Area::Victoria, to: Area::Vancouver });
+        assert!(alternatives.iter().all(|ap| ap.validate().is_ok()));
+        Ok(())
+    }
+
     #[test]
     fn test_date_range_iter_days() -> Result<()> {
         assert_eq!(
@@ -484,6 +1230,71 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_schedule_is_effective_at() -> Result<()> {
+        let schedule = Schedule {
+            date_range: DateRange { from: date!(2021 - 03 - 30), to: date!(2021 - 04 - 01) },
+            effective_from_time: Some(time!(13:00)),
+            ..Schedule::test_fixture(TerminalPair { from: Terminal::SWB, to: Terminal::TSA })
+        };
+        assert!(!schedule.is_effective_at(date!(2021 - 03 - 29), time!(14:00)));
+        assert!(!schedule.is_effective_at(date!(2021 - 03 - 30), time!(12:59)));
+        assert!(schedule.is_effective_at(date!(2021 - 03 - 30), time!(13:00)));
+        assert!(schedule.is_effective_at(date!(2021 - 03 - 31), time!(07:00)));
+        assert!(schedule.is_effective_at(date!(2021 - 04 - 01), time!(23:00)));
+        assert_eq!(schedule.switchover_time(date!(2021 - 03 - 30)), Some(time!(13:00)));
+        assert_eq!(schedule.switchover_time(date!(2021 - 03 - 31)), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedule_route_notes() -> Result<()> {
+        let schedule = Schedule {
+            header_notes: vec!["Seasonal service".to_string()],
+            ..Schedule::test_fixture(TerminalPair { from: Terminal::PSB, to: Terminal::TSA })
+        };
+        let notes = schedule.route_notes();
+        assert_eq!(notes.len(), 2);
+        assert!(notes[0].contains("reservations are recommended"));
+        assert_eq!(notes[1], "Seasonal service");
+        let island_schedule = Schedule {
+            terminal_pair: TerminalPair { from: Terminal::PSB, to: Terminal::POB },
+            header_notes: vec![],
+            ..schedule
+        };
+        assert!(island_schedule.route_notes()[0].contains("not accepted"));
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_published_data_formats() -> Result<()> {
+        let metadata = DataMetadata::new(OffsetDateTime::UNIX_EPOCH, "1.2.3");
+        let json =
+            serde_json::to_string(&PublishedData::WithMetadata { metadata: metadata.clone(), schedules: vec![] })?;
+        let data: PublishedData = serde_json::from_str(&json)?;
+        assert_eq!(data.metadata(), Some(&metadata));
+        let legacy_data: PublishedData = serde_json::from_str("[]")?;
+        assert_eq!(legacy_data.metadata(), None);
+        assert!(legacy_data.into_schedules().is_empty());
+        let err = serde_json::from_str::<PublishedData>(r#"{"version":999,"schedules":[]}"#).unwrap_err();
+        assert!(err.to_string().contains("missing field `metadata`"), "{}", err);
+        // The error is the one from the schedule that failed to parse, not that neither format matched
+        let err = serde_json::from_str::<PublishedData>(r#"[{"terminal_pair":1}]"#).unwrap_err();
+        assert!(err.to_string().contains("expected struct TerminalPair"), "{}", err);
+        assert!(serde_json::from_str::<PublishedData>("1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_data_channel_path() -> Result<()> {
+        assert_eq!(DataChannel::Stable.path("data/schedules.json"), "data/schedules.json");
+        assert_eq!(DataChannel::Preview.path("data/schedules.json"), "data/preview/schedules.json");
+        assert_eq!(DataChannel::Preview.path("schedules.json"), "preview/schedules.json");
+        assert_eq!("preview".parse::<DataChannel>()?, DataChannel::Preview);
+        Ok(())
+    }
+
     #[test]
     fn test_terminal_combinations() -> Result<()> {
         assert_eq!(
@@ -499,4 +1310,29 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_alternate_area_pairs() -> Result<()> {
+        assert_eq!(
+            ALTERNATE_AREA_PAIRS.get(&AreaPair { from: Area::SaltSpring, to: Area::Victoria }),
+            Some(&vec![AreaPair { from: Area::SaltSpring, to: Area::Crofton }])
+        );
+        assert_eq!(
+            ALTERNATE_AREA_PAIRS.get(&AreaPair { from: Area::Crofton, to: Area::SaltSpring }),
+            Some(&vec![AreaPair { from: Area::Victoria, to: Area::SaltSpring }])
+        );
+        assert_eq!(ALTERNATE_AREA_PAIRS.get(&AreaPair { from: Area::Victoria, to: Area::Vancouver }), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_vehicle_restriction_note() -> Result<()> {
+        let restriction = VehicleRestriction { dimension: VehicleDimension::Height, max_feet: 7 };
+        assert_eq!(restriction.note(), "No vehicles over 7 ft tall on this sailing");
+        assert!(!restriction.applies_to(VehicleClass::Standard));
+        assert!(restriction.applies_to(VehicleClass::Oversize));
+        let restriction = VehicleRestriction { dimension: VehicleDimension::Length, max_feet: 18 };
+        assert!(restriction.applies_to(VehicleClass::Standard));
+        Ok(())
+    }
 }
@@ -1,21 +1,70 @@
-use crate::imports::*;
-use crate::types::*;
+use crate::prelude::*;
+use time::{PrimitiveDateTime, UtcOffset};
+use time_tz::Tz;
 
 const ISO8601_DATE_FORMAT: &TimeFormat = format_description!("[year]-[month]-[day]");
 
+const VANCOUVER: &Tz = timezones::db::america::VANCOUVER;
+
 #[cfg(not(all(target_arch = "wasm32", not(target_os = "wasi"), feature = "wasmbind")))]
-pub fn now_utc() -> OffsetDateTime {
+fn system_now_utc() -> OffsetDateTime {
     OffsetDateTime::now_utc()
 }
 
 #[cfg(all(target_arch = "wasm32", not(target_os = "wasi"), feature = "wasmbind"))]
-pub fn now_utc() -> OffsetDateTime {
+fn system_now_utc() -> OffsetDateTime {
     OffsetDateTime::from_unix_timestamp_nanos(1000000i128 * (stdweb::web::Date::now() as i128))
         .expect("Expect current timestamp to convert to offset date/time")
 }
 
+// Source of the current time, which can be shifted to simulate another day (for testing schedule boundaries and
+// expiry without changing the system clock)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimeProvider {
+    System,
+    Simulated { today: Date },
+}
+
+static TIME_PROVIDER: OnceCell<TimeProvider> = OnceCell::new();
+
+impl TimeProvider {
+    // Pretend today is `today` in Vancouver, with the time of day continuing to follow the system clock
+    pub fn simulating_today(today: Date) -> TimeProvider {
+        TimeProvider::Simulated { today }
+    }
+
+    pub fn now_utc(&self) -> OffsetDateTime {
+        self.now_utc_at(system_now_utc())
+    }
+
+    fn now_utc_at(&self, system_now: OffsetDateTime) -> OffsetDateTime {
+        match *self {
+            TimeProvider::System => system_now,
+            TimeProvider::Simulated { today } => {
+                // Keep the Vancouver time of day, using the UTC offset in effect on the simulated day
+                let local = PrimitiveDateTime::new(today, system_now.to_timezone(VANCOUVER).time());
+                let guess = local.assume_offset(system_now.to_timezone(VANCOUVER).offset());
+                local.assume_offset(guess.to_timezone(VANCOUVER).offset()).to_offset(UtcOffset::UTC)
+            }
+        }
+    }
+}
+
+// Can only be set once, before the current time is used
+pub fn set_time_provider(time_provider: TimeProvider) -> Result<()> {
+    TIME_PROVIDER.set(time_provider).map_err(|_| anyhow!("Time provider is already set"))
+}
+
+pub fn time_provider() -> TimeProvider {
+    TIME_PROVIDER.get().copied().unwrap_or(TimeProvider::System)
+}
+
+pub fn now_utc() -> OffsetDateTime {
+    time_provider().now_utc()
+}
+
 pub fn now_vancouver() -> OffsetDateTime {
-    now_utc().to_timezone(timezones::db::america::VANCOUVER)
+    now_utc().to_timezone(VANCOUVER)
 }
 
 pub fn today_vancouver() -> Date {
@@ -69,6 +118,21 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_time_provider_simulating_today() -> Result<()> {
+        let provider = TimeProvider::simulating_today(date!(2021 - 12 - 01));
+        // 1:00 pm PDT, simulated on a day in PST
+        let now = provider.now_utc_at(datetime!(2021 - 07 - 15 20:00 UTC));
+        assert_eq!(now, datetime!(2021 - 12 - 01 21:00 UTC));
+        assert_eq!(now.to_timezone(VANCOUVER).time(), time!(13:00));
+        // Late evening in Vancouver is already the next day in UTC
+        let now = provider.now_utc_at(datetime!(2021 - 07 - 16 06:30 UTC));
+        assert_eq!(now.to_timezone(VANCOUVER).date(), date!(2021 - 12 - 01));
+        assert_eq!(now.to_timezone(VANCOUVER).time(), time!(23:30));
+        Ok(())
+    }
 
     #[test]
     fn test_format_iso8601_date() -> Result<()> {